@@ -1,8 +1,10 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, ImplItemMethod};
+use syn::{parse_macro_input, FnArg, ImplItemMethod, ReturnType};
 
-use crate::common::{arg_is_mutable_receiver, assert_reference_type};
+use crate::common::{
+    arg_is_immutable_receiver, arg_is_mutable_receiver, assert_reference_type, assert_type,
+};
 
 pub fn verify_hook(item: TokenStream) -> TokenStream {
     let method_item = parse_macro_input!(item as ImplItemMethod);
@@ -22,3 +24,54 @@ pub fn verify_hook(item: TokenStream) -> TokenStream {
 
     TokenStream::from(quote! {#method_item})
 }
+
+// A tx hook before may return nothing, or a `ServiceResponse<()>` whose error
+// rejects the transaction.
+pub fn verify_tx_hook_before(item: TokenStream) -> TokenStream {
+    let method_item = parse_macro_input!(item as ImplItemMethod);
+
+    let inputs = &method_item.sig.inputs;
+    assert_eq!(inputs.len(), 2);
+
+    assert!(arg_is_mutable_receiver(&inputs[0]));
+
+    match &inputs[1] {
+        FnArg::Typed(pt) => {
+            let ty = pt.ty.as_ref();
+            assert_type(ty, "ServiceContext")
+        }
+        _ => panic!("The second parameter type should be `ServiceContext`."),
+    }
+
+    if let ReturnType::Type(_, ty) = &method_item.sig.output {
+        assert_type(ty.as_ref(), "ServiceResponse")
+    }
+
+    TokenStream::from(quote! {#method_item})
+}
+
+// A write hook only reads states, it returns a `ServiceResponse<()>` whose
+// error rejects the write.
+pub fn verify_write_hook(item: TokenStream) -> TokenStream {
+    let method_item = parse_macro_input!(item as ImplItemMethod);
+
+    let inputs = &method_item.sig.inputs;
+    assert_eq!(inputs.len(), 2);
+
+    assert!(arg_is_immutable_receiver(&inputs[0]));
+
+    match &inputs[1] {
+        FnArg::Typed(pt) => {
+            let ty = pt.ty.as_ref();
+            assert_type(ty, "ServiceContext")
+        }
+        _ => panic!("The second parameter type should be `ServiceContext`."),
+    }
+
+    match &method_item.sig.output {
+        ReturnType::Type(_, ty) => assert_type(ty.as_ref(), "ServiceResponse"),
+        ReturnType::Default => panic!("The return type should be `ServiceResponse<()>`."),
+    }
+
+    TokenStream::from(quote! {#method_item})
+}
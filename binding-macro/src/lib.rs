@@ -9,7 +9,7 @@ mod service;
 use proc_macro::TokenStream;
 
 use crate::cycles::gen_cycles_code;
-use crate::hooks::verify_hook;
+use crate::hooks::{verify_hook, verify_tx_hook_before, verify_write_hook};
 use crate::read_write::verify_read_or_write;
 use crate::service::gen_service_code;
 
@@ -53,9 +53,12 @@ pub fn genesis(_: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// Marks a method so that it executes before every transaction. It is either
+/// `(&mut self, ctx: ServiceContext)`, or returns `ServiceResponse<()>` to
+/// reject the transaction with an error.
 #[proc_macro_attribute]
 pub fn tx_hook_before(_: TokenStream, item: TokenStream) -> TokenStream {
-    item
+    verify_tx_hook_before(item)
 }
 
 #[proc_macro_attribute]
@@ -63,6 +66,15 @@ pub fn tx_hook_after(_: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// Marks a method so that it executes before every write of any service, by
+/// a transaction or nested, e.g. to check permissions of the caller. It is
+/// `(&self, ctx: ServiceContext) -> ServiceResponse<()>`, `ctx` is the
+/// context of the write and an error response rejects it.
+#[proc_macro_attribute]
+pub fn write_hook(_: TokenStream, item: TokenStream) -> TokenStream {
+    verify_write_hook(item)
+}
+
 #[rustfmt::skip]
 /// `#[read]` marks a service method as readable.
 ///
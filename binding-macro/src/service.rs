@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, Type};
+use syn::{parse_macro_input, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, ReturnType, Type};
 
 const READ_ATTRIBUTE: &str = "read";
 const WRITE_ATTRIBUTE: &str = "write";
@@ -9,6 +9,7 @@ const HOOK_BEFORE_ATTRIBUTE: &str = "hook_before";
 const HOOK_AFTER_ATTRIBUTE: &str = "hook_after";
 const TX_HOOK_BEFORE_ATTRIBUTE: &str = "tx_hook_before";
 const TX_HOOK_AFTER_ATTRIBUTE: &str = "tx_hook_after";
+const WRITE_HOOK_ATTRIBUTE: &str = "write_hook";
 
 enum ServiceMethod {
     Read(ImplItemMethod),
@@ -16,10 +17,13 @@ enum ServiceMethod {
 }
 
 struct Hooks {
-    before:    Option<Ident>,
-    after:     Option<Ident>,
-    tx_before: Option<Ident>,
-    tx_after:  Option<Ident>,
+    before:             Option<Ident>,
+    after:              Option<Ident>,
+    tx_before:          Option<Ident>,
+    tx_after:           Option<Ident>,
+    write:              Option<Ident>,
+    // Whether the tx before hook returns a `ServiceResponse<()>`
+    tx_before_response: bool,
 }
 
 struct MethodMeta {
@@ -64,14 +68,24 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
     };
     let tx_hook_before = &hooks.tx_before;
     let tx_hook_before_body = match tx_hook_before {
-        Some(tx_hook_before) => quote! { self.#tx_hook_before(_ctx) },
-        None => quote! {()},
+        Some(tx_hook_before) if hooks.tx_before_response => quote! { self.#tx_hook_before(_ctx) },
+        Some(tx_hook_before) => quote! {
+            self.#tx_hook_before(_ctx);
+            ServiceResponse::<()>::from_succeed(())
+        },
+        None => quote! { ServiceResponse::<()>::from_succeed(()) },
     };
     let tx_hook_after = &hooks.tx_after;
     let tx_hook_after_body = match tx_hook_after {
         Some(tx_hook_after) => quote! { self.#tx_hook_after(_ctx) },
         None => quote! {()},
     };
+    let write_hook = &hooks.write;
+    let write_hook_body = match write_hook {
+        Some(write_hook) => quote! { self.#write_hook(_ctx) },
+        None => quote! { ServiceResponse::<()>::from_succeed(()) },
+    };
+    let has_write_hook = write_hook.is_some();
 
     let list_method_meta: Vec<MethodMeta> = methods.into_iter().map(extract_method_meta).collect();
 
@@ -99,7 +113,7 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
                 #hook_after_body
             }
 
-            fn tx_hook_before_(&mut self, _ctx: ServiceContext) {
+            fn tx_hook_before_(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
                 #tx_hook_before_body
             }
 
@@ -107,6 +121,14 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
                 #tx_hook_after_body
             }
 
+            fn write_hook_(&self, _ctx: ServiceContext) -> ServiceResponse<()> {
+                #write_hook_body
+            }
+
+            fn has_write_hook_(&self) -> bool {
+                #has_write_hook
+            }
+
             fn read_(&self, ctx: protocol::types::ServiceContext) -> ServiceResponse<String> {
                 let service = ctx.get_service_name();
                 let method = ctx.get_service_method();
@@ -305,16 +327,19 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
     let methods: Vec<ImplItemMethod> = find_list_for_item_method(items);
 
     let mut hooks = Hooks {
-        before:    None,
-        after:     None,
-        tx_before: None,
-        tx_after:  None,
+        before:             None,
+        after:              None,
+        tx_before:          None,
+        tx_after:           None,
+        write:              None,
+        tx_before_response: false,
     };
 
     let mut before_count = 0;
     let mut after_count = 0;
     let mut tx_before_count = 0;
     let mut tx_after_count = 0;
+    let mut write_count = 0;
 
     for method in methods {
         for attr in &method.attrs {
@@ -336,6 +361,10 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
                 } else if segment.ident == TX_HOOK_BEFORE_ATTRIBUTE {
                     if tx_before_count == 0 {
                         hooks.tx_before = Some(method.sig.ident.clone());
+                        hooks.tx_before_response = match method.sig.output {
+                            ReturnType::Default => false,
+                            ReturnType::Type(..) => true,
+                        };
                         tx_before_count = 1;
                     } else {
                         panic!("The tx before hook can only have one")
@@ -347,6 +376,13 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
                     } else {
                         panic!("The tx after hook can only have one")
                     }
+                } else if segment.ident == WRITE_HOOK_ATTRIBUTE {
+                    if write_count == 0 {
+                        hooks.write = Some(method.sig.ident.clone());
+                        write_count = 1;
+                    } else {
+                        panic!("The write hook can only have one")
+                    }
                 }
             }
         }
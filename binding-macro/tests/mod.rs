@@ -11,8 +11,8 @@ use serde::{Deserialize, Serialize};
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    ExecutorParams, Service, ServiceResponse, ServiceSDK, StoreArray, StoreBool, StoreMap,
-    StoreString, StoreUint64,
+    ExecutorParams, Service, ServiceResponse, ServiceSDK, StoreArray, StoreBool, StoreDeque,
+    StoreMap, StoreOrderedMap, StoreSet, StoreString, StoreUint128, StoreUint64,
 };
 use protocol::types::{
     Address, Block, Hash, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
@@ -392,6 +392,105 @@ fn test_service_none_response() {
     assert_eq!(test_service.hook_after, true);
 }
 
+#[test]
+fn test_tx_hooks() {
+    struct Tests<SDK: ServiceSDK> {
+        _sdk:     SDK,
+        tx_after: bool,
+    }
+
+    #[service]
+    impl<SDK: ServiceSDK> Tests<SDK> {
+        #[tx_hook_before]
+        fn custom_tx_hook_before(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+            if ctx.get_service_method() == "frozen" {
+                return ServiceResponse::<()>::from_error(2, "frozen".to_owned());
+            }
+            ServiceResponse::<()>::from_succeed(())
+        }
+
+        #[tx_hook_after]
+        fn custom_tx_hook_after(&mut self, _ctx: ServiceContext) {
+            self.tx_after = true;
+        }
+
+        #[write]
+        fn test_write(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
+            ServiceResponse::<()>::from_succeed(())
+        }
+    }
+
+    let mut test_service = Tests {
+        _sdk:     MockServiceSDK {},
+        tx_after: false,
+    };
+
+    let context = get_context(1024 * 1024, "", "test_write", "");
+    assert_eq!(
+        test_service.tx_hook_before_(context.clone()).is_error(),
+        false
+    );
+
+    let context = get_context(1024 * 1024, "", "frozen", "");
+    let resp = test_service.tx_hook_before_(context.clone());
+    assert_eq!((resp.code, resp.error_message.as_str()), (2, "frozen"));
+
+    test_service.tx_hook_after_(context);
+    assert_eq!(test_service.tx_after, true);
+}
+
+#[test]
+fn test_write_hook() {
+    struct Tests<SDK: ServiceSDK> {
+        _sdk: SDK,
+    }
+
+    #[service]
+    impl<SDK: ServiceSDK> Tests<SDK> {
+        #[write_hook]
+        fn custom_write_hook(&self, ctx: ServiceContext) -> ServiceResponse<()> {
+            if ctx.get_service_method() == "frozen" {
+                return ServiceResponse::<()>::from_error(2, "frozen".to_owned());
+            }
+            ServiceResponse::<()>::from_succeed(())
+        }
+
+        #[write]
+        fn test_write(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
+            ServiceResponse::<()>::from_succeed(())
+        }
+    }
+
+    struct NoHookTests<SDK: ServiceSDK> {
+        _sdk: SDK,
+    }
+
+    #[service]
+    impl<SDK: ServiceSDK> NoHookTests<SDK> {
+        #[write]
+        fn test_write(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
+            ServiceResponse::<()>::from_succeed(())
+        }
+    }
+
+    let test_service = Tests {
+        _sdk: MockServiceSDK {},
+    };
+    assert_eq!(test_service.has_write_hook_(), true);
+
+    let context = get_context(1024 * 1024, "", "test_write", "");
+    assert_eq!(test_service.write_hook_(context).is_error(), false);
+
+    let context = get_context(1024 * 1024, "", "frozen", "");
+    let resp = test_service.write_hook_(context);
+    assert_eq!((resp.code, resp.error_message.as_str()), (2, "frozen"));
+
+    let no_hook_service = NoHookTests {
+        _sdk: MockServiceSDK {},
+    };
+    assert_eq!(no_hook_service.has_write_hook_(), false);
+}
+
 fn get_context(cycles_limit: u64, service: &str, method: &str, payload: &str) -> ServiceContext {
     let params = ServiceContextParams {
         tx_hash: None,
@@ -441,11 +540,43 @@ impl ServiceSDK for MockServiceSDK {
         unimplemented!()
     }
 
+    // Alloc or recover a `OrderedMap` by` var_name`
+    fn alloc_or_recover_ordered_map<
+        Key: 'static + Send + FixedCodec + Clone + Ord,
+        Val: 'static + FixedCodec,
+    >(
+        &mut self,
+        _var_name: &str,
+    ) -> Box<dyn StoreOrderedMap<Key, Val>> {
+        unimplemented!()
+    }
+
+    // Alloc or recover a `Set` by` var_name`
+    fn alloc_or_recover_set<Elm: 'static + Send + FixedCodec + Clone + PartialEq>(
+        &mut self,
+        _var_name: &str,
+    ) -> Box<dyn StoreSet<Elm>> {
+        unimplemented!()
+    }
+
+    // Alloc or recover a `Deque` by` var_name`
+    fn alloc_or_recover_deque<Elm: 'static + FixedCodec>(
+        &mut self,
+        _var_name: &str,
+    ) -> Box<dyn StoreDeque<Elm>> {
+        unimplemented!()
+    }
+
     // Alloc or recover a `Uint64` by` var_name`
     fn alloc_or_recover_uint64(&mut self, _var_name: &str) -> Box<dyn StoreUint64> {
         unimplemented!()
     }
 
+    // Alloc or recover a `Uint128` by` var_name`
+    fn alloc_or_recover_uint128(&mut self, _var_name: &str) -> Box<dyn StoreUint128> {
+        unimplemented!()
+    }
+
     // Alloc or recover a `String` by` var_name`
     fn alloc_or_recover_string(&mut self, _var_name: &str) -> Box<dyn StoreString> {
         unimplemented!()
@@ -466,6 +597,11 @@ impl ServiceSDK for MockServiceSDK {
         unimplemented!()
     }
 
+    // Remove a value from the service state by key
+    fn remove_value<Key: FixedCodec>(&mut self, _key: &Key) {
+        unimplemented!()
+    }
+
     // Get a value from the specified address by key
     fn get_account_value<Key: FixedCodec, Ret: FixedCodec>(
         &self,
@@ -485,6 +621,11 @@ impl ServiceSDK for MockServiceSDK {
         unimplemented!()
     }
 
+    // Remove a value from the specified address by key
+    fn remove_account_value<Key: FixedCodec>(&mut self, _address: &Address, _key: &Key) {
+        unimplemented!()
+    }
+
     // Get a signed transaction by `tx_hash`
     // if not found on the chain, return None
     fn get_transaction_by_hash(&self, _tx_hash: &Hash) -> Option<SignedTransaction> {
@@ -6,37 +6,58 @@ pub mod types;
 
 use std::collections::BTreeMap;
 
-use binding_macro::{cycles, genesis, service};
+use binding_macro::{cycles, genesis, hook_after, service};
 use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
 use protocol::types::{Address, Bytes, Hash, ServiceContext};
 
 use crate::types::{
-    ApproveEvent, ApprovePayload, Asset, AssetBalance, CreateAssetPayload, GetAllowancePayload,
+    Amount, ApproveEvent, ApprovePayload, Asset, AssetBalance, BatchTransferPayload, BurnEvent,
+    BurnPayload, CreateAssetPayload, FreezeEvent, FreezePayload, GetAllowancePayload,
     GetAllowanceResponse, GetAssetPayload, GetBalancePayload, GetBalanceResponse,
-    InitGenesisPayload, TransferEvent, TransferFromEvent, TransferFromPayload, TransferPayload,
+    GetFreezeStatusResponse, GetPauseStatusResponse, InitGenesisPayload, MintEvent, MintPayload,
+    PauseEvent, PausePayload, TransferEvent, TransferFromEvent, TransferFromPayload,
+    TransferIssuerEvent, TransferIssuerPayload, TransferPayload, UnfreezePayload, UnpausePayload,
 };
 
+// Cycles charged for each leg of batch transfer, on top of the base cost
+const BATCH_TRANSFER_LEG_CYCLES: u64 = 100_00;
+const MAP_MIGRATION_BATCH: u32 = 1000;
+
 pub struct AssetService<SDK> {
-    sdk:    SDK,
-    assets: Box<dyn StoreMap<Hash, Asset>>,
+    sdk:             SDK,
+    assets:          Box<dyn StoreMap<Hash, Asset>>,
+    paused_assets:   Box<dyn StoreMap<Hash, bool>>,
+    // Key is digest of asset id and account address
+    frozen_accounts: Box<dyn StoreMap<Hash, bool>>,
 }
 
 #[service]
 impl<SDK: ServiceSDK> AssetService<SDK> {
     pub fn new(mut sdk: SDK) -> Self {
         let assets: Box<dyn StoreMap<Hash, Asset>> = sdk.alloc_or_recover_map("assets");
-
-        Self { sdk, assets }
+        let paused_assets: Box<dyn StoreMap<Hash, bool>> =
+            sdk.alloc_or_recover_map("paused_assets");
+        let frozen_accounts: Box<dyn StoreMap<Hash, bool>> =
+            sdk.alloc_or_recover_map("frozen_accounts");
+
+        Self {
+            sdk,
+            assets,
+            paused_assets,
+            frozen_accounts,
+        }
     }
 
     #[genesis]
     fn init_genesis(&mut self, payload: InitGenesisPayload) {
         let asset = Asset {
-            id:     payload.id,
-            name:   payload.name,
-            symbol: payload.symbol,
-            supply: payload.supply,
-            issuer: payload.issuer.clone(),
+            id:         payload.id,
+            name:       payload.name,
+            symbol:     payload.symbol,
+            supply:     payload.supply,
+            issuer:     payload.issuer.clone(),
+            max_supply: payload.max_supply,
+            decimals:   payload.decimals,
         };
 
         self.assets.insert(asset.id.clone(), asset.clone());
@@ -50,6 +71,13 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             .set_account_value(&asset.issuer, asset.id, asset_balance)
     }
 
+    // Maps written in the bucket layout are moved a batch per block, so no
+    // transaction pays for it. Maps added since are never in that layout.
+    #[hook_after]
+    fn migrate_maps(&mut self, _params: &ExecutorParams) {
+        self.assets.migrate(MAP_MIGRATION_BATCH);
+    }
+
     #[cycles(100_00)]
     #[read]
     fn get_asset(&self, ctx: ServiceContext, payload: GetAssetPayload) -> ServiceResponse<Asset> {
@@ -78,7 +106,7 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             .sdk
             .get_account_value(&payload.user, &payload.asset_id)
             .unwrap_or(AssetBalance {
-                value:     0,
+                value:     Amount::default(),
                 allowance: BTreeMap::new(),
             });
 
@@ -110,13 +138,17 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             .get_account_value(&payload.grantor, &payload.asset_id);
 
         if let Some(v) = opt_asset_balance {
-            let allowance = v.allowance.get(&payload.grantee).unwrap_or(&0);
+            let allowance = v
+                .allowance
+                .get(&payload.grantee)
+                .copied()
+                .unwrap_or_default();
 
             let res = GetAllowanceResponse {
                 asset_id: payload.asset_id,
                 grantor:  payload.grantor,
                 grantee:  payload.grantee,
-                value:    *allowance,
+                value:    allowance,
             };
             ServiceResponse::<GetAllowanceResponse>::from_succeed(res)
         } else {
@@ -124,12 +156,55 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
                 asset_id: payload.asset_id,
                 grantor:  payload.grantor,
                 grantee:  payload.grantee,
-                value:    0,
+                value:    Amount::default(),
             };
             ServiceResponse::<GetAllowanceResponse>::from_succeed(res)
         }
     }
 
+    #[cycles(100_00)]
+    #[read]
+    fn get_pause_status(
+        &self,
+        ctx: ServiceContext,
+        payload: GetAssetPayload,
+    ) -> ServiceResponse<GetPauseStatusResponse> {
+        if !self.assets.contains(&payload.id) {
+            return ServiceResponse::<GetPauseStatusResponse>::from_error(
+                101,
+                "asset id not existed".to_owned(),
+            );
+        }
+
+        let res = GetPauseStatusResponse {
+            paused:   self._is_paused(&payload.id),
+            asset_id: payload.id,
+        };
+        ServiceResponse::<GetPauseStatusResponse>::from_succeed(res)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_freeze_status(
+        &self,
+        ctx: ServiceContext,
+        payload: FreezePayload,
+    ) -> ServiceResponse<GetFreezeStatusResponse> {
+        if !self.assets.contains(&payload.asset_id) {
+            return ServiceResponse::<GetFreezeStatusResponse>::from_error(
+                101,
+                "asset id not existed".to_owned(),
+            );
+        }
+
+        let res = GetFreezeStatusResponse {
+            frozen:   self._is_frozen(&payload.asset_id, &payload.account),
+            asset_id: payload.asset_id,
+            account:  payload.account,
+        };
+        ServiceResponse::<GetFreezeStatusResponse>::from_succeed(res)
+    }
+
     #[cycles(210_00)]
     #[write]
     fn create_asset(
@@ -150,12 +225,20 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         if self.assets.contains(&id) {
             return ServiceResponse::<Asset>::from_error(102, "asset id existed".to_owned());
         }
+        if !payload.max_supply.is_zero() && payload.supply > payload.max_supply {
+            return ServiceResponse::<Asset>::from_error(
+                108,
+                "supply exceeds max supply".to_owned(),
+            );
+        }
         let asset = Asset {
-            id:     id.clone(),
-            name:   payload.name,
-            symbol: payload.symbol,
-            supply: payload.supply,
-            issuer: caller,
+            id:         id.clone(),
+            name:       payload.name,
+            symbol:     payload.symbol,
+            supply:     payload.supply,
+            issuer:     caller,
+            max_supply: payload.max_supply,
+            decimals:   payload.decimals,
         };
         self.assets.insert(id, asset.clone());
 
@@ -190,8 +273,10 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             return ServiceResponse::<()>::from_error(101, "asset id not existed".to_owned());
         }
 
-        if let Err(e) = self._transfer(caller.clone(), to.clone(), asset_id.clone(), value) {
-            return ServiceResponse::<()>::from_error(106, format!("{:?}", e));
+        if let Err((code, msg)) =
+            self._transfer(caller.clone(), to.clone(), asset_id.clone(), value)
+        {
+            return ServiceResponse::<()>::from_error(code, msg);
         };
 
         let event = TransferEvent {
@@ -211,6 +296,93 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         ServiceResponse::<()>::from_succeed(())
     }
 
+    #[cycles(210_00)]
+    #[write]
+    fn batch_transfer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: BatchTransferPayload,
+    ) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+        let legs = payload.transfers;
+
+        let leg_cycles = BATCH_TRANSFER_LEG_CYCLES.saturating_mul(legs.len() as u64);
+        if !ctx.sub_cycles(leg_cycles) {
+            return ServiceResponse::<()>::from_error(3, "out of cycles".to_owned());
+        }
+
+        // Apply every leg on a snapshot of balances first, nothing is written
+        // unless all legs succeed.
+        let mut balances: BTreeMap<(Hash, Address), Amount> = BTreeMap::new();
+        for leg in legs.iter() {
+            if !self.assets.contains(&leg.asset_id) {
+                return ServiceResponse::<()>::from_error(101, "asset id not existed".to_owned());
+            }
+            if caller == leg.to {
+                return ServiceResponse::<()>::from_error(
+                    106,
+                    "cann't send value to yourself".to_owned(),
+                );
+            }
+            if let Err((code, msg)) = self._check_transferable(&leg.asset_id, &[&caller, &leg.to]) {
+                return ServiceResponse::<()>::from_error(code, msg);
+            }
+
+            let sender_key = (leg.asset_id.clone(), caller.clone());
+            let sender_balance = self._snapshot_balance(&balances, &sender_key);
+            let sender_balance = match sender_balance.checked_sub(leg.value) {
+                Some(v) => v,
+                None => {
+                    return ServiceResponse::<()>::from_error(
+                        106,
+                        "insufficient balance".to_owned(),
+                    )
+                }
+            };
+            balances.insert(sender_key, sender_balance);
+
+            let recipient_key = (leg.asset_id.clone(), leg.to.clone());
+            let recipient_balance = self._snapshot_balance(&balances, &recipient_key);
+            let recipient_balance = match recipient_balance.checked_add(leg.value) {
+                Some(v) => v,
+                None => return ServiceResponse::<()>::from_error(106, "u128 overflow".to_owned()),
+            };
+            balances.insert(recipient_key, recipient_balance);
+        }
+
+        for ((asset_id, account), value) in balances.into_iter() {
+            let mut asset_balance: AssetBalance = self
+                .sdk
+                .get_account_value(&account, &asset_id)
+                .unwrap_or(AssetBalance {
+                    value:     Amount::default(),
+                    allowance: BTreeMap::new(),
+                });
+            asset_balance.value = value;
+
+            self.sdk
+                .set_account_value(&account, asset_id, asset_balance);
+        }
+
+        for leg in legs.into_iter() {
+            let event = TransferEvent {
+                asset_id: leg.asset_id,
+                from:     caller.clone(),
+                to:       leg.to,
+                value:    leg.value,
+            };
+            let event_res = serde_json::to_string(&event);
+
+            if let Err(e) = event_res {
+                return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+            };
+            let event_str = event_res.unwrap();
+            ctx.emit_event(event_str);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
     #[cycles(210_00)]
     #[write]
     fn approve(&mut self, ctx: ServiceContext, payload: ApprovePayload) -> ServiceResponse<()> {
@@ -227,11 +399,15 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             return ServiceResponse::<()>::from_error(101, "asset id not existed".to_owned());
         }
 
+        if let Err((code, msg)) = self._check_transferable(&asset_id, &[&caller, &to]) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
         let mut caller_asset_balance: AssetBalance = self
             .sdk
             .get_account_value(&caller, &asset_id)
             .unwrap_or(AssetBalance {
-                value:     0,
+                value:     Amount::default(),
                 allowance: BTreeMap::new(),
             });
         caller_asset_balance
@@ -277,21 +453,29 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             return ServiceResponse::<()>::from_error(101, "asset id not existed".to_owned());
         }
 
+        if let Err((code, msg)) =
+            self._check_transferable(&asset_id, &[&caller, &sender, &recipient])
+        {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
         let mut sender_asset_balance: AssetBalance = self
             .sdk
             .get_account_value(&sender, &asset_id)
             .unwrap_or(AssetBalance {
-                value:     0,
+                value:     Amount::default(),
                 allowance: BTreeMap::new(),
             });
         let sender_allowance = sender_asset_balance
             .allowance
             .entry(caller.clone())
-            .or_insert(0);
-        if *sender_allowance < value {
-            return ServiceResponse::<()>::from_error(105, "insufficient balance".to_owned());
-        }
-        let after_sender_allowance = *sender_allowance - value;
+            .or_insert_with(Amount::default);
+        let after_sender_allowance = match sender_allowance.checked_sub(value) {
+            Some(v) => v,
+            None => {
+                return ServiceResponse::<()>::from_error(105, "insufficient balance".to_owned())
+            }
+        };
         sender_asset_balance
             .allowance
             .entry(caller.clone())
@@ -300,8 +484,10 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         self.sdk
             .set_account_value(&sender, asset_id.clone(), sender_asset_balance);
 
-        if let Err(e) = self._transfer(sender.clone(), recipient.clone(), asset_id.clone(), value) {
-            return ServiceResponse::<()>::from_error(106, format!("{:?}", e));
+        if let Err((code, msg)) =
+            self._transfer(sender.clone(), recipient.clone(), asset_id.clone(), value)
+        {
+            return ServiceResponse::<()>::from_error(code, msg);
         };
 
         let event = TransferFromEvent {
@@ -322,55 +508,344 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         ServiceResponse::<()>::from_succeed(())
     }
 
+    #[cycles(210_00)]
+    #[write]
+    fn mint(&mut self, ctx: ServiceContext, payload: MintPayload) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+        let asset_id = payload.asset_id;
+        let value = payload.value;
+        let to = payload.to;
+
+        let mut asset = match self._get_issued_asset(&asset_id, &caller) {
+            Ok(asset) => asset,
+            Err((code, msg)) => return ServiceResponse::<()>::from_error(code, msg),
+        };
+
+        let supply = match asset.supply.checked_add(value) {
+            Some(supply) if asset.max_supply.is_zero() || supply <= asset.max_supply => supply,
+            _ => {
+                return ServiceResponse::<()>::from_error(
+                    108,
+                    "supply exceeds max supply".to_owned(),
+                )
+            }
+        };
+
+        let mut to_asset_balance: AssetBalance = self
+            .sdk
+            .get_account_value(&to, &asset_id)
+            .unwrap_or(AssetBalance {
+                value:     Amount::default(),
+                allowance: BTreeMap::new(),
+            });
+        to_asset_balance.value = match to_asset_balance.value.checked_add(value) {
+            Some(v) => v,
+            None => return ServiceResponse::<()>::from_error(106, "u128 overflow".to_owned()),
+        };
+        self.sdk
+            .set_account_value(&to, asset_id.clone(), to_asset_balance);
+
+        asset.supply = supply;
+        self.assets.insert(asset_id.clone(), asset);
+
+        let event = MintEvent {
+            asset_id,
+            to,
+            value,
+        };
+        let event_res = serde_json::to_string(&event);
+
+        if let Err(e) = event_res {
+            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+        };
+        let event_str = event_res.unwrap();
+        ctx.emit_event(event_str);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn burn(&mut self, ctx: ServiceContext, payload: BurnPayload) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+        let asset_id = payload.asset_id;
+        let value = payload.value;
+
+        let mut asset = match self._get_issued_asset(&asset_id, &caller) {
+            Ok(asset) => asset,
+            Err((code, msg)) => return ServiceResponse::<()>::from_error(code, msg),
+        };
+
+        let mut caller_asset_balance: AssetBalance = self
+            .sdk
+            .get_account_value(&caller, &asset_id)
+            .unwrap_or(AssetBalance {
+                value:     Amount::default(),
+                allowance: BTreeMap::new(),
+            });
+        let (balance, supply) = match (
+            caller_asset_balance.value.checked_sub(value),
+            asset.supply.checked_sub(value),
+        ) {
+            (Some(balance), Some(supply)) => (balance, supply),
+            _ => return ServiceResponse::<()>::from_error(105, "insufficient balance".to_owned()),
+        };
+        caller_asset_balance.value = balance;
+        self.sdk
+            .set_account_value(&caller, asset_id.clone(), caller_asset_balance);
+
+        asset.supply = supply;
+        self.assets.insert(asset_id.clone(), asset);
+
+        let event = BurnEvent {
+            asset_id,
+            from: caller,
+            value,
+        };
+        let event_res = serde_json::to_string(&event);
+
+        if let Err(e) = event_res {
+            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+        };
+        let event_str = event_res.unwrap();
+        ctx.emit_event(event_str);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn transfer_issuer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TransferIssuerPayload,
+    ) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+        let asset_id = payload.asset_id;
+        let new_issuer = payload.new_issuer;
+
+        let mut asset = match self._get_issued_asset(&asset_id, &caller) {
+            Ok(asset) => asset,
+            Err((code, msg)) => return ServiceResponse::<()>::from_error(code, msg),
+        };
+
+        asset.issuer = new_issuer.clone();
+        self.assets.insert(asset_id.clone(), asset);
+
+        let event = TransferIssuerEvent {
+            asset_id,
+            old_issuer: caller,
+            new_issuer,
+        };
+        let event_res = serde_json::to_string(&event);
+
+        if let Err(e) = event_res {
+            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+        };
+        let event_str = event_res.unwrap();
+        ctx.emit_event(event_str);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn pause(&mut self, ctx: ServiceContext, payload: PausePayload) -> ServiceResponse<()> {
+        self._set_paused(ctx, payload.asset_id, true)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn unpause(&mut self, ctx: ServiceContext, payload: UnpausePayload) -> ServiceResponse<()> {
+        self._set_paused(ctx, payload.asset_id, false)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn freeze_account(
+        &mut self,
+        ctx: ServiceContext,
+        payload: FreezePayload,
+    ) -> ServiceResponse<()> {
+        self._set_frozen(ctx, payload.asset_id, payload.account, true)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn unfreeze_account(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UnfreezePayload,
+    ) -> ServiceResponse<()> {
+        self._set_frozen(ctx, payload.asset_id, payload.account, false)
+    }
+
+    fn _set_paused(
+        &mut self,
+        ctx: ServiceContext,
+        asset_id: Hash,
+        paused: bool,
+    ) -> ServiceResponse<()> {
+        if let Err((code, msg)) = self._get_issued_asset(&asset_id, &ctx.get_caller()) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        self.paused_assets.insert(asset_id.clone(), paused);
+
+        let event = PauseEvent { asset_id, paused };
+        let event_res = serde_json::to_string(&event);
+
+        if let Err(e) = event_res {
+            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+        };
+        let event_str = event_res.unwrap();
+        ctx.emit_event(event_str);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    fn _set_frozen(
+        &mut self,
+        ctx: ServiceContext,
+        asset_id: Hash,
+        account: Address,
+        frozen: bool,
+    ) -> ServiceResponse<()> {
+        if let Err((code, msg)) = self._get_issued_asset(&asset_id, &ctx.get_caller()) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        self.frozen_accounts
+            .insert(frozen_key(&asset_id, &account), frozen);
+
+        let event = FreezeEvent {
+            asset_id,
+            account,
+            frozen,
+        };
+        let event_res = serde_json::to_string(&event);
+
+        if let Err(e) = event_res {
+            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+        };
+        let event_str = event_res.unwrap();
+        ctx.emit_event(event_str);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    fn _get_issued_asset(&self, asset_id: &Hash, caller: &Address) -> Result<Asset, (u64, String)> {
+        let asset = match self.assets.get(asset_id) {
+            Some(asset) => asset,
+            None => return Err((101, "asset id not existed".to_owned())),
+        };
+        if &asset.issuer != caller {
+            return Err((107, "caller is not issuer".to_owned()));
+        }
+
+        Ok(asset)
+    }
+
+    fn _snapshot_balance(
+        &self,
+        balances: &BTreeMap<(Hash, Address), Amount>,
+        key: &(Hash, Address),
+    ) -> Amount {
+        if let Some(value) = balances.get(key) {
+            return *value;
+        }
+
+        let (asset_id, account) = key;
+        self.sdk
+            .get_account_value::<_, AssetBalance>(account, asset_id)
+            .map(|b| b.value)
+            .unwrap_or_default()
+    }
+
+    fn _is_paused(&self, asset_id: &Hash) -> bool {
+        self.paused_assets.get(asset_id).unwrap_or(false)
+    }
+
+    fn _is_frozen(&self, asset_id: &Hash, account: &Address) -> bool {
+        self.frozen_accounts
+            .get(&frozen_key(asset_id, account))
+            .unwrap_or(false)
+    }
+
+    fn _check_transferable(
+        &self,
+        asset_id: &Hash,
+        accounts: &[&Address],
+    ) -> Result<(), (u64, String)> {
+        if self._is_paused(asset_id) {
+            return Err((109, "asset is paused".to_owned()));
+        }
+        if accounts
+            .iter()
+            .any(|account| self._is_frozen(asset_id, account))
+        {
+            return Err((110, "account is frozen".to_owned()));
+        }
+
+        Ok(())
+    }
+
     fn _transfer(
         &mut self,
         sender: Address,
         recipient: Address,
         asset_id: Hash,
-        value: u64,
-    ) -> Result<(), String> {
+        value: Amount,
+    ) -> Result<(), (u64, String)> {
         if recipient == sender {
-            return Err("cann't send value to yourself".to_owned());
+            return Err((106, "cann't send value to yourself".to_owned()));
         }
 
+        self._check_transferable(&asset_id, &[&sender, &recipient])?;
+
         let mut sender_asset_balance: AssetBalance = self
             .sdk
             .get_account_value(&sender, &asset_id)
             .unwrap_or(AssetBalance {
-                value:     0,
+                value:     Amount::default(),
                 allowance: BTreeMap::new(),
             });
         let sender_balance = sender_asset_balance.value;
 
         if sender_balance < value {
-            return Err("insufficient balance".to_owned());
+            return Err((106, "insufficient balance".to_owned()));
         }
 
         let mut to_asset_balance: AssetBalance = self
             .sdk
             .get_account_value(&recipient, &asset_id)
             .unwrap_or(AssetBalance {
-                value:     0,
+                value:     Amount::default(),
                 allowance: BTreeMap::new(),
             });
 
-        let (v, overflow) = to_asset_balance.value.overflowing_add(value);
-        if overflow {
-            return Err("u64 overflow".to_owned());
-        }
-        to_asset_balance.value = v;
+        to_asset_balance.value = match to_asset_balance.value.checked_add(value) {
+            Some(v) => v,
+            None => return Err((106, "u128 overflow".to_owned())),
+        };
 
         self.sdk
             .set_account_value(&recipient, asset_id.clone(), to_asset_balance);
 
-        let (v, overflow) = sender_balance.overflowing_sub(value);
-        if overflow {
-            return Err("u64 overflow".to_owned());
-        }
-        sender_asset_balance.value = v;
+        sender_asset_balance.value = match sender_balance.checked_sub(value) {
+            Some(v) => v,
+            None => return Err((106, "u128 overflow".to_owned())),
+        };
         self.sdk
             .set_account_value(&sender, asset_id, sender_asset_balance);
 
         Ok(())
     }
 }
+
+fn frozen_key(asset_id: &Hash, account: &Address) -> Hash {
+    let mut key = asset_id.as_bytes().to_vec();
+    key.extend_from_slice(account.as_bytes().as_ref());
+
+    Hash::digest(Bytes::from(key))
+}
@@ -7,17 +7,20 @@ use cita_trie::MemoryDB;
 
 use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{Context, NoopDispatcher, Storage};
 use protocol::types::{
-    Address, Block, Hash, Proof, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
+    Address, Block, Evidence, Hash, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
 use crate::types::{
-    ApprovePayload, CreateAssetPayload, GetAllowancePayload, GetAssetPayload, GetBalancePayload,
-    TransferFromPayload, TransferPayload,
+    Amount, ApprovePayload, Asset, AssetBalance, BatchTransferPayload, BurnPayload,
+    CreateAssetPayload, FreezePayload, GetAllowancePayload, GetAssetPayload, GetBalancePayload,
+    MintPayload, PausePayload, TransferFromPayload, TransferIssuerPayload, TransferPayload,
 };
-use crate::AssetService;
+use crate::{AssetService, BATCH_TRANSFER_LEG_CYCLES};
 
 #[test]
 fn test_create_asset() {
@@ -27,13 +30,15 @@ fn test_create_asset() {
 
     let mut service = new_asset_service();
 
-    let supply = 1024 * 1024;
+    let supply = Amount(1024 * 1024);
     // test create_asset
     let asset = service
         .create_asset(context.clone(), CreateAssetPayload {
             name: "test".to_owned(),
             symbol: "test".to_owned(),
             supply,
+            max_supply: Amount(0),
+            decimals: 0,
         })
         .succeed_data;
 
@@ -62,13 +67,15 @@ fn test_transfer() {
 
     let mut service = new_asset_service();
 
-    let supply = 1024 * 1024;
+    let supply = Amount(1024 * 1024);
     // test create_asset
     let asset = service
         .create_asset(context.clone(), CreateAssetPayload {
             name: "test".to_owned(),
             symbol: "test".to_owned(),
             supply,
+            max_supply: Amount(0),
+            decimals: 0,
         })
         .succeed_data;
 
@@ -76,7 +83,7 @@ fn test_transfer() {
     service.transfer(context.clone(), TransferPayload {
         asset_id: asset.id.clone(),
         to:       to_address.clone(),
-        value:    1024,
+        value:    Amount(1024),
     });
 
     let balance_res = service
@@ -85,7 +92,7 @@ fn test_transfer() {
             user:     caller,
         })
         .succeed_data;
-    assert_eq!(balance_res.balance, supply - 1024);
+    assert_eq!(balance_res.balance, Amount(supply.0 - 1024));
 
     let context = mock_context(cycles_limit, to_address.clone());
     let balance_res = service
@@ -94,7 +101,7 @@ fn test_transfer() {
             user:     to_address,
         })
         .succeed_data;
-    assert_eq!(balance_res.balance, 1024);
+    assert_eq!(balance_res.balance, Amount(1024));
 }
 
 #[test]
@@ -105,12 +112,14 @@ fn test_approve() {
 
     let mut service = new_asset_service();
 
-    let supply = 1024 * 1024;
+    let supply = Amount(1024 * 1024);
     let asset = service
         .create_asset(context.clone(), CreateAssetPayload {
             name: "test".to_owned(),
             symbol: "test".to_owned(),
             supply,
+            max_supply: Amount(0),
+            decimals: 0,
         })
         .succeed_data;
 
@@ -118,7 +127,7 @@ fn test_approve() {
     service.approve(context.clone(), ApprovePayload {
         asset_id: asset.id.clone(),
         to:       to_address.clone(),
-        value:    1024,
+        value:    Amount(1024),
     });
 
     let allowance_res = service
@@ -130,7 +139,7 @@ fn test_approve() {
         .succeed_data;
     assert_eq!(allowance_res.asset_id, asset.id);
     assert_eq!(allowance_res.grantee, to_address);
-    assert_eq!(allowance_res.value, 1024);
+    assert_eq!(allowance_res.value, Amount(1024));
 }
 
 #[test]
@@ -141,12 +150,14 @@ fn test_transfer_from() {
 
     let mut service = new_asset_service();
 
-    let supply = 1024 * 1024;
+    let supply = Amount(1024 * 1024);
     let asset = service
         .create_asset(context.clone(), CreateAssetPayload {
             name: "test".to_owned(),
             symbol: "test".to_owned(),
             supply,
+            max_supply: Amount(0),
+            decimals: 0,
         })
         .succeed_data;
 
@@ -154,7 +165,7 @@ fn test_transfer_from() {
     service.approve(context.clone(), ApprovePayload {
         asset_id: asset.id.clone(),
         to:       to_address.clone(),
-        value:    1024,
+        value:    Amount(1024),
     });
 
     let to_context = mock_context(cycles_limit, to_address.clone());
@@ -163,7 +174,7 @@ fn test_transfer_from() {
         asset_id:  asset.id.clone(),
         sender:    caller.clone(),
         recipient: to_address.clone(),
-        value:     24,
+        value:     Amount(24),
     });
 
     let allowance_res = service
@@ -175,7 +186,7 @@ fn test_transfer_from() {
         .succeed_data;
     assert_eq!(allowance_res.asset_id, asset.id);
     assert_eq!(allowance_res.grantee, to_address);
-    assert_eq!(allowance_res.value, 1000);
+    assert_eq!(allowance_res.value, Amount(1000));
 
     let balance_res = service
         .get_balance(context, GetBalancePayload {
@@ -183,7 +194,7 @@ fn test_transfer_from() {
             user:     caller,
         })
         .succeed_data;
-    assert_eq!(balance_res.balance, supply - 24);
+    assert_eq!(balance_res.balance, Amount(supply.0 - 24));
 
     let balance_res = service
         .get_balance(to_context, GetBalancePayload {
@@ -191,7 +202,421 @@ fn test_transfer_from() {
             user:     to_address,
         })
         .succeed_data;
-    assert_eq!(balance_res.balance, 24);
+    assert_eq!(balance_res.balance, Amount(24));
+}
+
+#[test]
+fn test_mint_and_burn() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let supply = Amount(1024);
+    let asset = service
+        .create_asset(context.clone(), CreateAssetPayload {
+            name: "test".to_owned(),
+            symbol: "test".to_owned(),
+            supply,
+            max_supply: Amount(2048),
+            decimals: 0,
+        })
+        .succeed_data;
+
+    let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let res = service.mint(context.clone(), MintPayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(1000),
+    });
+    assert!(!res.is_error());
+    assert_eq!(context.get_events().len(), 2);
+
+    let res = service.mint(context.clone(), MintPayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(25),
+    });
+    assert_eq!(res.code, 108);
+
+    let to_context = mock_context(cycles_limit, to_address.clone());
+    let res = service.mint(to_context.clone(), MintPayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 107);
+
+    let balance_res = service
+        .get_balance(to_context.clone(), GetBalancePayload {
+            asset_id: asset.id.clone(),
+            user:     to_address,
+        })
+        .succeed_data;
+    assert_eq!(balance_res.balance, Amount(1000));
+
+    let res = service.burn(context.clone(), BurnPayload {
+        asset_id: asset.id.clone(),
+        value:    Amount(supply.0 + 1),
+    });
+    assert_eq!(res.code, 105);
+
+    let res = service.burn(context.clone(), BurnPayload {
+        asset_id: asset.id.clone(),
+        value:    Amount(24),
+    });
+    assert!(!res.is_error());
+
+    let res = service.burn(to_context, BurnPayload {
+        asset_id: asset.id.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 107);
+
+    let balance_res = service
+        .get_balance(context.clone(), GetBalancePayload {
+            asset_id: asset.id.clone(),
+            user:     caller,
+        })
+        .succeed_data;
+    assert_eq!(balance_res.balance, Amount(1000));
+
+    let new_asset = service
+        .get_asset(context, GetAssetPayload { id: asset.id })
+        .succeed_data;
+    assert_eq!(new_asset.supply, Amount(2000));
+}
+
+#[test]
+fn test_create_asset_exceeds_max_supply() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller);
+
+    let mut service = new_asset_service();
+
+    let res = service.create_asset(context, CreateAssetPayload {
+        name:       "test".to_owned(),
+        symbol:     "test".to_owned(),
+        supply:     Amount(1025),
+        max_supply: Amount(1024),
+        decimals:   0,
+    });
+    assert_eq!(res.code, 108);
+}
+
+#[test]
+fn test_transfer_issuer() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let asset = service
+        .create_asset(context.clone(), CreateAssetPayload {
+            name:       "test".to_owned(),
+            symbol:     "test".to_owned(),
+            supply:     Amount(1024),
+            max_supply: Amount(0),
+            decimals:   0,
+        })
+        .succeed_data;
+
+    let new_issuer = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let res = service.transfer_issuer(context.clone(), TransferIssuerPayload {
+        asset_id:   asset.id.clone(),
+        new_issuer: new_issuer.clone(),
+    });
+    assert!(!res.is_error());
+
+    // Old issuer loses its power
+    let res = service.mint(context.clone(), MintPayload {
+        asset_id: asset.id.clone(),
+        to:       caller.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 107);
+
+    let new_context = mock_context(cycles_limit, new_issuer.clone());
+    let res = service.mint(new_context, MintPayload {
+        asset_id: asset.id.clone(),
+        to:       caller,
+        value:    Amount(u128::max_value()),
+    });
+    assert_eq!(res.code, 108);
+
+    let new_asset = service
+        .get_asset(context, GetAssetPayload { id: asset.id })
+        .succeed_data;
+    assert_eq!(new_asset.issuer, new_issuer);
+}
+
+#[test]
+fn test_pause() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let asset = service
+        .create_asset(context.clone(), CreateAssetPayload {
+            name:       "test".to_owned(),
+            symbol:     "test".to_owned(),
+            supply:     Amount(1024),
+            max_supply: Amount(0),
+            decimals:   0,
+        })
+        .succeed_data;
+
+    let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let to_context = mock_context(cycles_limit, to_address.clone());
+    let res = service.pause(to_context, PausePayload {
+        asset_id: asset.id.clone(),
+    });
+    assert_eq!(res.code, 107);
+
+    let res = service.pause(context.clone(), PausePayload {
+        asset_id: asset.id.clone(),
+    });
+    assert!(!res.is_error());
+
+    let status = service
+        .get_pause_status(context.clone(), GetAssetPayload {
+            id: asset.id.clone(),
+        })
+        .succeed_data;
+    assert!(status.paused);
+
+    let res = service.transfer(context.clone(), TransferPayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 109);
+
+    let res = service.approve(context.clone(), ApprovePayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 109);
+
+    let res = service.unpause(context.clone(), PausePayload {
+        asset_id: asset.id.clone(),
+    });
+    assert!(!res.is_error());
+
+    let res = service.transfer(context, TransferPayload {
+        asset_id: asset.id,
+        to:       to_address,
+        value:    Amount(1),
+    });
+    assert!(!res.is_error());
+}
+
+#[test]
+fn test_freeze_account() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let asset = service
+        .create_asset(context.clone(), CreateAssetPayload {
+            name:       "test".to_owned(),
+            symbol:     "test".to_owned(),
+            supply:     Amount(1024),
+            max_supply: Amount(0),
+            decimals:   0,
+        })
+        .succeed_data;
+
+    let to_address = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let to_context = mock_context(cycles_limit, to_address.clone());
+    service.transfer(context.clone(), TransferPayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(100),
+    });
+    service.approve(to_context.clone(), ApprovePayload {
+        asset_id: asset.id.clone(),
+        to:       caller.clone(),
+        value:    Amount(100),
+    });
+
+    let res = service.freeze_account(context.clone(), FreezePayload {
+        asset_id: asset.id.clone(),
+        account:  to_address.clone(),
+    });
+    assert!(!res.is_error());
+
+    let status = service
+        .get_freeze_status(context.clone(), FreezePayload {
+            asset_id: asset.id.clone(),
+            account:  to_address.clone(),
+        })
+        .succeed_data;
+    assert!(status.frozen);
+
+    // Frozen account can neither send nor receive
+    let res = service.transfer(to_context.clone(), TransferPayload {
+        asset_id: asset.id.clone(),
+        to:       caller.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 110);
+
+    let res = service.transfer(context.clone(), TransferPayload {
+        asset_id: asset.id.clone(),
+        to:       to_address.clone(),
+        value:    Amount(1),
+    });
+    assert_eq!(res.code, 110);
+
+    let res = service.transfer_from(context.clone(), TransferFromPayload {
+        asset_id:  asset.id.clone(),
+        sender:    to_address.clone(),
+        recipient: caller.clone(),
+        value:     Amount(1),
+    });
+    assert_eq!(res.code, 110);
+
+    let res = service.unfreeze_account(context.clone(), FreezePayload {
+        asset_id: asset.id.clone(),
+        account:  to_address.clone(),
+    });
+    assert!(!res.is_error());
+
+    let res = service.transfer(to_context, TransferPayload {
+        asset_id: asset.id.clone(),
+        to:       caller,
+        value:    Amount(1),
+    });
+    assert!(!res.is_error());
+
+    let balance_res = service
+        .get_balance(context, GetBalancePayload {
+            asset_id: asset.id,
+            user:     to_address,
+        })
+        .succeed_data;
+    assert_eq!(balance_res.balance, Amount(99));
+}
+
+#[test]
+fn test_batch_transfer() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let supply = Amount(1024);
+    let asset = service
+        .create_asset(context.clone(), CreateAssetPayload {
+            name: "test".to_owned(),
+            symbol: "test".to_owned(),
+            supply,
+            max_supply: Amount(0),
+            decimals: 0,
+        })
+        .succeed_data;
+
+    let alice = Address::from_hex("0x666cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let bob = Address::from_hex("0x777cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let leg = |to: &Address, value: u128| TransferPayload {
+        asset_id: asset.id.clone(),
+        to:       to.clone(),
+        value:    Amount(value),
+    };
+
+    // The last leg fails, nothing should be transferred
+    let res = service.batch_transfer(context.clone(), BatchTransferPayload {
+        transfers: vec![leg(&alice, 1000), leg(&bob, 24), leg(&bob, 1)],
+    });
+    assert_eq!(res.code, 106);
+
+    let balance_of = |service: &AssetService<_>, user: &Address| {
+        service
+            .get_balance(context.clone(), GetBalancePayload {
+                asset_id: asset.id.clone(),
+                user:     user.clone(),
+            })
+            .succeed_data
+            .balance
+    };
+    assert_eq!(balance_of(&service, &caller), supply);
+    assert_eq!(balance_of(&service, &alice), Amount(0));
+
+    let events_before = context.get_events().len();
+    let cycles_before = context.get_cycles_used();
+    let res = service.batch_transfer(context.clone(), BatchTransferPayload {
+        transfers: vec![leg(&alice, 1000), leg(&bob, 20), leg(&bob, 4)],
+    });
+    assert!(!res.is_error());
+    assert_eq!(context.get_events().len(), events_before + 3);
+    assert_eq!(
+        context.get_cycles_used() - cycles_before,
+        210_00 + 3 * BATCH_TRANSFER_LEG_CYCLES
+    );
+
+    assert_eq!(balance_of(&service, &caller), Amount(0));
+    assert_eq!(balance_of(&service, &alice), Amount(1000));
+    assert_eq!(balance_of(&service, &bob), Amount(24));
+}
+
+#[test]
+fn test_amount_json() {
+    let max = Amount(u128::max_value());
+    let json = serde_json::to_string(&max).unwrap();
+    assert_eq!(json, format!("\"{}\"", u128::max_value()));
+    assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), max);
+
+    // Json numbers are still accepted
+    assert_eq!(
+        serde_json::from_str::<Amount>("1024").unwrap(),
+        Amount(1024)
+    );
+    assert!(serde_json::from_str::<Amount>("-1").is_err());
+    assert!(serde_json::from_str::<Amount>("\"1.5\"").is_err());
+
+    let payload: CreateAssetPayload =
+        serde_json::from_str(r#"{ "name": "test", "symbol": "test", "supply": 1024 }"#).unwrap();
+    assert_eq!(payload.supply, Amount(1024));
+    assert_eq!(payload.max_supply, Amount(0));
+    assert_eq!(payload.decimals, 0);
+}
+
+#[test]
+fn test_decode_legacy_state() {
+    let issuer = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let id = Hash::digest(Bytes::from("legacy"));
+
+    // Asset and balance encoded with u64 amounts, before max supply and
+    // decimals were introduced.
+    let mut stream = rlp::RlpStream::new_list(5);
+    stream
+        .append(&id)
+        .append(&"test".to_owned())
+        .append(&"TT".to_owned())
+        .append(&1024u64)
+        .append(&issuer);
+    let asset = Asset::decode_fixed(Bytes::from(stream.out())).unwrap();
+    assert_eq!(asset.supply, Amount(1024));
+    assert_eq!(asset.max_supply, Amount(0));
+    assert_eq!(asset.decimals, 0);
+
+    let encoded = asset.encode_fixed().unwrap();
+    assert_eq!(Asset::decode_fixed(encoded).unwrap(), asset);
+
+    let mut stream = rlp::RlpStream::new_list(2);
+    stream.append(&u64::max_value()).begin_list(0);
+    let balance = AssetBalance::decode_fixed(Bytes::from(stream.out())).unwrap();
+    assert_eq!(balance.value, Amount::from(u64::max_value()));
 }
 
 fn new_asset_service() -> AssetService<
@@ -308,4 +733,12 @@ impl Storage for MockStorage {
     async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
         unimplemented!()
     }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
 }
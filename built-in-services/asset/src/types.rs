@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
+use std::fmt;
 
 use muta_codec_derive::RlpFixedCodec;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
 
 use protocol::fixed_codec::{FixedCodec, FixedCodecError};
 use protocol::types::{Address, Bytes, Hash};
@@ -10,18 +12,27 @@ use protocol::ProtocolResult;
 /// Payload
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
 pub struct InitGenesisPayload {
-    pub id:     Hash,
-    pub name:   String,
-    pub symbol: String,
-    pub supply: u64,
-    pub issuer: Address,
+    pub id:         Hash,
+    pub name:       String,
+    pub symbol:     String,
+    pub supply:     Amount,
+    pub issuer:     Address,
+    #[serde(default)]
+    pub max_supply: Amount,
+    #[serde(default)]
+    pub decimals:   u8,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
 pub struct CreateAssetPayload {
-    pub name:   String,
-    pub symbol: String,
-    pub supply: u64,
+    pub name:       String,
+    pub symbol:     String,
+    pub supply:     Amount,
+    /// Cap of total supply, 0 means unlimited.
+    #[serde(default)]
+    pub max_supply: Amount,
+    #[serde(default)]
+    pub decimals:   u8,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -33,7 +44,7 @@ pub struct GetAssetPayload {
 pub struct TransferPayload {
     pub asset_id: Hash,
     pub to:       Address,
-    pub value:    u64,
+    pub value:    Amount,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -41,7 +52,12 @@ pub struct TransferEvent {
     pub asset_id: Hash,
     pub from:     Address,
     pub to:       Address,
-    pub value:    u64,
+    pub value:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct BatchTransferPayload {
+    pub transfers: Vec<TransferPayload>,
 }
 
 pub type ApprovePayload = TransferPayload;
@@ -51,7 +67,7 @@ pub struct ApproveEvent {
     pub asset_id: Hash,
     pub grantor:  Address,
     pub grantee:  Address,
-    pub value:    u64,
+    pub value:    Amount,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -59,7 +75,7 @@ pub struct TransferFromPayload {
     pub asset_id:  Hash,
     pub sender:    Address,
     pub recipient: Address,
-    pub value:     u64,
+    pub value:     Amount,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -68,7 +84,88 @@ pub struct TransferFromEvent {
     pub caller:    Address,
     pub sender:    Address,
     pub recipient: Address,
-    pub value:     u64,
+    pub value:     Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct MintPayload {
+    pub asset_id: Hash,
+    pub to:       Address,
+    pub value:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct MintEvent {
+    pub asset_id: Hash,
+    pub to:       Address,
+    pub value:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct BurnPayload {
+    pub asset_id: Hash,
+    pub value:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct BurnEvent {
+    pub asset_id: Hash,
+    pub from:     Address,
+    pub value:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct TransferIssuerPayload {
+    pub asset_id:   Hash,
+    pub new_issuer: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct TransferIssuerEvent {
+    pub asset_id:   Hash,
+    pub old_issuer: Address,
+    pub new_issuer: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct PausePayload {
+    pub asset_id: Hash,
+}
+
+pub type UnpausePayload = PausePayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct PauseEvent {
+    pub asset_id: Hash,
+    pub paused:   bool,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct FreezePayload {
+    pub asset_id: Hash,
+    pub account:  Address,
+}
+
+pub type UnfreezePayload = FreezePayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct FreezeEvent {
+    pub asset_id: Hash,
+    pub account:  Address,
+    pub frozen:   bool,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GetPauseStatusResponse {
+    pub asset_id: Hash,
+    pub paused:   bool,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GetFreezeStatusResponse {
+    pub asset_id: Hash,
+    pub account:  Address,
+    pub frozen:   bool,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -81,7 +178,7 @@ pub struct GetBalancePayload {
 pub struct GetBalanceResponse {
     pub asset_id: Hash,
     pub user:     Address,
-    pub balance:  u64,
+    pub balance:  Amount,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -96,27 +193,78 @@ pub struct GetAllowanceResponse {
     pub asset_id: Hash,
     pub grantor:  Address,
     pub grantee:  Address,
-    pub value:    u64,
+    pub value:    Amount,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
 pub struct Asset {
-    pub id:     Hash,
-    pub name:   String,
-    pub symbol: String,
-    pub supply: u64,
-    pub issuer: Address,
+    pub id:         Hash,
+    pub name:       String,
+    pub symbol:     String,
+    pub supply:     Amount,
+    pub issuer:     Address,
+    /// Cap of total supply, 0 means unlimited.
+    pub max_supply: Amount,
+    pub decimals:   u8,
+}
+
+impl rlp::Decodable for Asset {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        // Assets created before max supply and decimals were introduced
+        // have fewer fields, missing ones fall back to default.
+        let item_count = rlp.item_count()?;
+        let max_supply = if item_count > 5 {
+            rlp.val_at(5)?
+        } else {
+            Amount::default()
+        };
+        let decimals = if item_count > 6 { rlp.val_at(6)? } else { 0 };
+
+        Ok(Asset {
+            id: rlp.val_at(0)?,
+            name: rlp.val_at(1)?,
+            symbol: rlp.val_at(2)?,
+            supply: rlp.val_at(3)?,
+            issuer: rlp.val_at(4)?,
+            max_supply,
+            decimals,
+        })
+    }
+}
+
+impl rlp::Encodable for Asset {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(7)
+            .append(&self.id)
+            .append(&self.name)
+            .append(&self.symbol)
+            .append(&self.supply)
+            .append(&self.issuer)
+            .append(&self.max_supply)
+            .append(&self.decimals);
+    }
+}
+
+impl FixedCodec for Asset {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
 }
 
+#[derive(Clone)]
 pub struct AssetBalance {
-    pub value:     u64,
-    pub allowance: BTreeMap<Address, u64>,
+    pub value:     Amount,
+    pub allowance: BTreeMap<Address, Amount>,
 }
 
 #[derive(RlpFixedCodec)]
 struct AllowanceCodec {
     pub addr:  Address,
-    pub total: u64,
+    pub total: Amount,
 }
 
 impl rlp::Decodable for AssetBalance {
@@ -161,3 +309,132 @@ impl FixedCodec for AssetBalance {
         Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
     }
 }
+
+/// Amount of asset. Encoded as a decimal string in json, since it may exceed
+/// the safe integer range of javascript clients. Json numbers are still
+/// accepted for compatibility.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(pub u128);
+
+impl Amount {
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(val: u64) -> Self {
+        Amount(u128::from(val))
+    }
+}
+
+impl From<u128> for Amount {
+    fn from(val: u128) -> Self {
+        Amount(val)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+struct AmountVisitor;
+
+impl<'de> Visitor<'de> for AmountVisitor {
+    type Value = Amount;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal string or a non-negative integer")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Amount::from(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if v < 0 {
+            return Err(E::custom("negative amount"));
+        }
+        Ok(Amount(v as u128))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse::<u128>().map(Amount).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+// Same as rlp encoding of unsigned integers, so amounts stored as u64 before
+// can be decoded directly.
+impl rlp::Encodable for Amount {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let bytes = self.0.to_be_bytes();
+        let leading_zeros = (self.0.leading_zeros() / 8) as usize;
+
+        s.append(&bytes[leading_zeros..].to_vec());
+    }
+}
+
+impl rlp::Decodable for Amount {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let bytes = rlp.data()?;
+
+        if bytes.len() > 16 {
+            return Err(rlp::DecoderError::RlpIsTooBig);
+        }
+        if bytes.first() == Some(&0) {
+            return Err(rlp::DecoderError::RlpInvalidIndirection);
+        }
+
+        let mut buf = [0u8; 16];
+        buf[16 - bytes.len()..].copy_from_slice(bytes);
+
+        Ok(Amount(u128::from_be_bytes(buf)))
+    }
+}
+
+impl FixedCodec for Amount {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
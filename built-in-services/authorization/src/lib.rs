@@ -1,20 +1,30 @@
-mod types;
+#[cfg(test)]
+mod tests;
+pub mod types;
 
-use binding_macro::{cycles, genesis, service};
+use binding_macro::{cycles, genesis, hook_after, service, write_hook};
 use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
-use protocol::types::{Address, ServiceContext};
+use protocol::types::{Address, ServiceContext, SignedTransaction};
 
 use crate::types::{
-    AddVerifiedItemPayload, InitGenesisPayload, RemoveVerifiedItemPayload, SetAdminPayload,
+    AddVerifiedItemPayload, CheckMethodPermissionPayload, GetAccountRolesPayload,
+    GetAccountRolesResponse, GetAdminResponse, GetMethodPermissionPayload, GetRolePayload,
+    GrantRolePayload, InitGenesisPayload, MethodPermission, MultiSigAccountPayload,
+    RemoveVerifiedItemPayload, RevokeRolePayload, Role, RolePayload, SetAdminPayload,
+    SetMethodPermissionPayload,
 };
 
 const AUTHORIZATION_ADMIN_KEY: &str = "authotization_admin";
 const MULTI_SIG_SERVICE: &str = "multi_signature";
 const MULTI_SIG_METHOD: &str = "verify_signature";
+const MULTI_SIG_ACCOUNT_METHOD: &str = "get_account_from_address";
+const MAP_MIGRATION_BATCH: u32 = 1000;
 
 pub struct AuthorizationService<SDK> {
-    sdk:          SDK,
-    verified_map: Box<dyn StoreMap<String, String>>,
+    sdk:                SDK,
+    verified_map:       Box<dyn StoreMap<String, String>>,
+    roles:              Box<dyn StoreMap<String, Role>>,
+    method_permissions: Box<dyn StoreMap<String, MethodPermission>>,
 }
 
 #[service]
@@ -25,7 +35,16 @@ impl<SDK: ServiceSDK> AuthorizationService<SDK> {
 
         verified_map.insert(MULTI_SIG_SERVICE.to_owned(), MULTI_SIG_METHOD.to_owned());
 
-        Self { sdk, verified_map }
+        let roles: Box<dyn StoreMap<String, Role>> = sdk.alloc_or_recover_map("roles");
+        let method_permissions: Box<dyn StoreMap<String, MethodPermission>> =
+            sdk.alloc_or_recover_map("method_permissions");
+
+        Self {
+            sdk,
+            verified_map,
+            roles,
+            method_permissions,
+        }
     }
 
     #[genesis]
@@ -40,6 +59,26 @@ impl<SDK: ServiceSDK> AuthorizationService<SDK> {
         for item in service_names.into_iter().zip(function_names.into_iter()) {
             self.verified_map.insert(item.0, item.1);
         }
+
+        for role in payload.roles.into_iter() {
+            self.roles.insert(role.name.clone(), role);
+        }
+
+        for permission in payload.method_permissions.into_iter() {
+            for role in permission.roles.iter() {
+                assert!(self.roles.contains(role), "role {} not existed", role);
+            }
+
+            let key = MethodPermission::key(&permission.service_name, &permission.method_name);
+            self.method_permissions.insert(key, permission);
+        }
+    }
+
+    // Maps written in the bucket layout are moved a batch per block, so no
+    // transaction pays for it. Maps added since are never in that layout.
+    #[hook_after]
+    fn migrate_maps(&mut self, _params: &ExecutorParams) {
+        self.verified_map.migrate(MAP_MIGRATION_BATCH);
     }
 
     #[cycles(210_00)]
@@ -58,7 +97,45 @@ impl<SDK: ServiceSDK> AuthorizationService<SDK> {
             }
         }
 
-        ServiceResponse::from_succeed(())
+        let stx: SignedTransaction = match serde_json::from_str(&payload) {
+            Ok(stx) => stx,
+            Err(e) => {
+                return ServiceResponse::<()>::from_error(
+                    104,
+                    format!("decode transaction error {:?}", e),
+                )
+            }
+        };
+
+        let request = &stx.raw.request;
+        self._check_permission(&ctx.get_caller(), &request.service_name, &request.method)
+    }
+
+    // Every write is checked before it runs, including calls dispatched by
+    // services, e.g. multi-signature proposals or contracts, which don't go
+    // through `check_authorization`.
+    #[cycles(100_00)]
+    #[write_hook]
+    fn check_write_permission(&self, ctx: ServiceContext) -> ServiceResponse<()> {
+        self._check_permission(
+            &ctx.get_caller(),
+            ctx.get_service_name(),
+            ctx.get_service_method(),
+        )
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn check_method_permission(
+        &self,
+        ctx: ServiceContext,
+        payload: CheckMethodPermissionPayload,
+    ) -> ServiceResponse<()> {
+        self._check_permission(
+            &ctx.get_caller(),
+            &payload.service_name,
+            &payload.method_name,
+        )
     }
 
     #[cycles(210_00)]
@@ -99,12 +176,196 @@ impl<SDK: ServiceSDK> AuthorizationService<SDK> {
             return ServiceResponse::<()>::from_error(103, "Invalid caller".to_owned());
         }
 
+        if !self._is_multi_sig_account(&ctx, &payload.new_admin) {
+            return ServiceResponse::<()>::from_error(
+                106,
+                "admin must be a multi-signature account".to_owned(),
+            );
+        }
+
         self.sdk
             .set_value(AUTHORIZATION_ADMIN_KEY.to_string(), payload.new_admin);
 
         ServiceResponse::from_succeed(())
     }
 
+    #[cycles(210_00)]
+    #[write]
+    fn create_role(&mut self, ctx: ServiceContext, payload: RolePayload) -> ServiceResponse<()> {
+        if !self._is_admin(&ctx) {
+            return ServiceResponse::<()>::from_error(103, "Invalid caller".to_owned());
+        }
+
+        if self.roles.contains(&payload.role) {
+            return ServiceResponse::<()>::from_error(107, "role existed".to_owned());
+        }
+
+        self.roles
+            .insert(payload.role.clone(), Role::new(payload.role));
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn delete_role(&mut self, ctx: ServiceContext, payload: RolePayload) -> ServiceResponse<()> {
+        if !self._is_admin(&ctx) {
+            return ServiceResponse::<()>::from_error(103, "Invalid caller".to_owned());
+        }
+
+        if self.roles.remove(&payload.role).is_none() {
+            return ServiceResponse::<()>::from_error(108, "role not existed".to_owned());
+        }
+
+        // Drop the role from every method permission, a permission left
+        // without roles is removed so that the method is open again.
+        let permissions = self
+            .method_permissions
+            .iter()
+            .filter(|(_, permission)| permission.roles.contains(&payload.role))
+            .collect::<Vec<_>>();
+        for (key, mut permission) in permissions.into_iter() {
+            permission.roles.retain(|role| role != &payload.role);
+            if permission.roles.is_empty() {
+                self.method_permissions.remove(&key);
+            } else {
+                self.method_permissions.insert(key, permission);
+            }
+        }
+
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn grant_role(
+        &mut self,
+        ctx: ServiceContext,
+        payload: GrantRolePayload,
+    ) -> ServiceResponse<()> {
+        if !self._is_admin(&ctx) {
+            return ServiceResponse::<()>::from_error(103, "Invalid caller".to_owned());
+        }
+
+        let mut role = match self.roles.get(&payload.role) {
+            Some(role) => role,
+            None => return ServiceResponse::<()>::from_error(108, "role not existed".to_owned()),
+        };
+
+        if !role.has_member(&payload.account) {
+            role.members.push(payload.account);
+            self.roles.insert(payload.role, role);
+        }
+
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn revoke_role(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RevokeRolePayload,
+    ) -> ServiceResponse<()> {
+        if !self._is_admin(&ctx) {
+            return ServiceResponse::<()>::from_error(103, "Invalid caller".to_owned());
+        }
+
+        let mut role = match self.roles.get(&payload.role) {
+            Some(role) => role,
+            None => return ServiceResponse::<()>::from_error(108, "role not existed".to_owned()),
+        };
+
+        role.members.retain(|member| member != &payload.account);
+        self.roles.insert(payload.role, role);
+
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_method_permission(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetMethodPermissionPayload,
+    ) -> ServiceResponse<()> {
+        if !self._is_admin(&ctx) {
+            return ServiceResponse::<()>::from_error(103, "Invalid caller".to_owned());
+        }
+
+        if let Some(role) = payload.roles.iter().find(|role| !self.roles.contains(role)) {
+            return ServiceResponse::<()>::from_error(108, format!("role {} not existed", role));
+        }
+
+        let key = MethodPermission::key(&payload.service_name, &payload.method_name);
+        if payload.roles.is_empty() {
+            self.method_permissions.remove(&key);
+        } else {
+            self.method_permissions.insert(key, MethodPermission {
+                service_name: payload.service_name,
+                method_name:  payload.method_name,
+                roles:        payload.roles,
+            });
+        }
+
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_admin(&self, _ctx: ServiceContext) -> ServiceResponse<GetAdminResponse> {
+        let admin: Address = self
+            .sdk
+            .get_value(&AUTHORIZATION_ADMIN_KEY.to_string())
+            .expect("must have an admin");
+
+        ServiceResponse::from_succeed(GetAdminResponse { admin })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_role(&self, _ctx: ServiceContext, payload: GetRolePayload) -> ServiceResponse<Role> {
+        if let Some(role) = self.roles.get(&payload.role) {
+            ServiceResponse::from_succeed(role)
+        } else {
+            ServiceResponse::<Role>::from_error(108, "role not existed".to_owned())
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_account_roles(
+        &self,
+        _ctx: ServiceContext,
+        payload: GetAccountRolesPayload,
+    ) -> ServiceResponse<GetAccountRolesResponse> {
+        let roles = self
+            .roles
+            .iter()
+            .filter(|(_, role)| role.has_member(&payload.account))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+
+        ServiceResponse::from_succeed(GetAccountRolesResponse { roles })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_method_permission(
+        &self,
+        _ctx: ServiceContext,
+        payload: GetMethodPermissionPayload,
+    ) -> ServiceResponse<MethodPermission> {
+        let key = MethodPermission::key(&payload.service_name, &payload.method_name);
+        if let Some(permission) = self.method_permissions.get(&key) {
+            ServiceResponse::from_succeed(permission)
+        } else {
+            ServiceResponse::<MethodPermission>::from_error(
+                109,
+                "method permission not existed".to_owned(),
+            )
+        }
+    }
+
     fn _do_verify(
         &self,
         ctx: &ServiceContext,
@@ -116,6 +377,60 @@ impl<SDK: ServiceSDK> AuthorizationService<SDK> {
             .read(&ctx, None, service_name, method_name, &payload_json)
     }
 
+    fn _check_permission(
+        &self,
+        caller: &Address,
+        service_name: &str,
+        method_name: &str,
+    ) -> ServiceResponse<()> {
+        if !self._has_permission(caller, service_name, method_name) {
+            return ServiceResponse::<()>::from_error(
+                105,
+                format!(
+                    "caller has no role to call {}",
+                    MethodPermission::key(service_name, method_name)
+                ),
+            );
+        }
+
+        ServiceResponse::from_succeed(())
+    }
+
+    fn _has_permission(&self, caller: &Address, service_name: &str, method_name: &str) -> bool {
+        let key = MethodPermission::key(service_name, method_name);
+        let permission = match self.method_permissions.get(&key) {
+            Some(permission) => permission,
+            None => return true,
+        };
+
+        permission.roles.iter().any(|name| {
+            self.roles
+                .get(name)
+                .map(|role| role.has_member(caller))
+                .unwrap_or(false)
+        })
+    }
+
+    fn _is_multi_sig_account(&self, ctx: &ServiceContext, address: &Address) -> bool {
+        let payload = match serde_json::to_string(&MultiSigAccountPayload {
+            multi_sig_address: address.clone(),
+        }) {
+            Ok(payload) => payload,
+            Err(_) => return false,
+        };
+
+        !self
+            .sdk
+            .read(
+                ctx,
+                None,
+                MULTI_SIG_SERVICE,
+                MULTI_SIG_ACCOUNT_METHOD,
+                &payload,
+            )
+            .is_error()
+    }
+
     fn _is_admin(&self, ctx: &ServiceContext) -> bool {
         let admin: Address = self
             .sdk
@@ -0,0 +1,414 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{Context, Dispatcher, ServiceResponse, ServiceSDK, Storage};
+use protocol::types::{
+    Address, Block, Evidence, Hash, Proof, RawTransaction, Receipt, ServiceContext,
+    ServiceContextParams, SignedTransaction, TransactionRequest,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{
+    CheckMethodPermissionPayload, GetAccountRolesPayload, GetMethodPermissionPayload,
+    GrantRolePayload, InitGenesisPayload, MethodPermission, Role, RolePayload, SetAdminPayload,
+    SetMethodPermissionPayload,
+};
+use crate::AuthorizationService;
+
+const ADMIN: &str = "0x755cdba6ae4f479f7164792b318b2a06c759833b";
+const MULTI_SIG: &str = "0xf8389d774afdad8755ef8e629e5a154fddc6325a";
+const ALICE: &str = "0x0000000000000000000000000000000000000001";
+const BOB: &str = "0x0000000000000000000000000000000000000002";
+
+#[test]
+fn test_role_management() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_hex(ADMIN).unwrap();
+    let alice = Address::from_hex(ALICE).unwrap();
+    let context = mock_context(cycles_limit, admin);
+
+    let mut service = new_authorization_service();
+
+    // test non-admin caller
+    let res = service.create_role(mock_context(cycles_limit, alice.clone()), RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert_eq!(res.code, 103);
+
+    let res = service.create_role(context.clone(), RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert!(!res.is_error());
+    let res = service.create_role(context.clone(), RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert_eq!(res.code, 107);
+
+    let res = service.grant_role(context.clone(), GrantRolePayload {
+        role:    "minter".to_owned(),
+        account: alice.clone(),
+    });
+    assert!(!res.is_error());
+    let res = service.grant_role(context.clone(), GrantRolePayload {
+        role:    "burner".to_owned(),
+        account: alice.clone(),
+    });
+    assert_eq!(res.code, 108);
+
+    let roles = service.get_account_roles(context.clone(), GetAccountRolesPayload {
+        account: alice.clone(),
+    });
+    assert_eq!(roles.succeed_data.roles, vec!["minter".to_owned()]);
+
+    // test method permission with an unknown role
+    let res = service.set_method_permission(context.clone(), SetMethodPermissionPayload {
+        service_name: "asset".to_owned(),
+        method_name:  "mint".to_owned(),
+        roles:        vec!["burner".to_owned()],
+    });
+    assert_eq!(res.code, 108);
+
+    let res = service.set_method_permission(context.clone(), SetMethodPermissionPayload {
+        service_name: "asset".to_owned(),
+        method_name:  "mint".to_owned(),
+        roles:        vec!["minter".to_owned()],
+    });
+    assert!(!res.is_error());
+
+    // test delete role also drops it from method permissions
+    let res = service.delete_role(context.clone(), RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert!(!res.is_error());
+
+    let role = service.get_role(context.clone(), RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert_eq!(role.code, 108);
+    let permission = service.get_method_permission(context, GetMethodPermissionPayload {
+        service_name: "asset".to_owned(),
+        method_name:  "mint".to_owned(),
+    });
+    assert_eq!(permission.code, 109);
+}
+
+#[test]
+fn test_check_authorization() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex(ALICE).unwrap();
+    let bob = Address::from_hex(BOB).unwrap();
+
+    let mut service = new_authorization_service();
+    service.init_genesis(InitGenesisPayload {
+        admin:                  Address::from_hex(ADMIN).unwrap(),
+        register_service_names: vec![],
+        verified_method_names:  vec![],
+        roles:                  vec![Role {
+            name:    "minter".to_owned(),
+            members: vec![alice.clone()],
+        }],
+        method_permissions:     vec![MethodPermission {
+            service_name: "asset".to_owned(),
+            method_name:  "mint".to_owned(),
+            roles:        vec!["minter".to_owned()],
+        }],
+    });
+
+    let res = service.check_authorization(
+        mock_context(cycles_limit, alice.clone()),
+        mock_stx_json("asset", "mint", alice),
+    );
+    assert!(!res.is_error());
+
+    let res = service.check_authorization(
+        mock_context(cycles_limit, bob.clone()),
+        mock_stx_json("asset", "mint", bob.clone()),
+    );
+    assert_eq!(res.code, 105);
+
+    // test method without permission is open
+    let res = service.check_authorization(
+        mock_context(cycles_limit, bob.clone()),
+        mock_stx_json("asset", "transfer", bob.clone()),
+    );
+    assert!(!res.is_error());
+
+    let res = service.check_authorization(mock_context(cycles_limit, bob), "{}".to_owned());
+    assert_eq!(res.code, 104);
+}
+
+#[test]
+fn test_check_method_permission() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex(ALICE).unwrap();
+    let multi_sig = Address::from_hex(MULTI_SIG).unwrap();
+
+    let mut service = new_authorization_service();
+    service.init_genesis(InitGenesisPayload {
+        admin:                  Address::from_hex(ADMIN).unwrap(),
+        register_service_names: vec![],
+        verified_method_names:  vec![],
+        roles:                  vec![Role {
+            name:    "minter".to_owned(),
+            members: vec![alice.clone()],
+        }],
+        method_permissions:     vec![MethodPermission {
+            service_name: "asset".to_owned(),
+            method_name:  "mint".to_owned(),
+            roles:        vec!["minter".to_owned()],
+        }],
+    });
+
+    let mint = CheckMethodPermissionPayload {
+        service_name: "asset".to_owned(),
+        method_name:  "mint".to_owned(),
+    };
+
+    let res = service.check_method_permission(mock_context(cycles_limit, alice), mint.clone());
+    assert!(!res.is_error());
+
+    // test nested call from an account without role, e.g. a multi-signature
+    // account dispatching an approved proposal
+    let res = service.check_method_permission(mock_context(cycles_limit, multi_sig.clone()), mint);
+    assert_eq!(res.code, 105);
+
+    let res = service.check_method_permission(
+        mock_context(cycles_limit, multi_sig),
+        CheckMethodPermissionPayload {
+            service_name: "asset".to_owned(),
+            method_name:  "transfer".to_owned(),
+        },
+    );
+    assert!(!res.is_error());
+
+    // test write hook checks the write it's called for
+    let mint_context = |caller: Address| {
+        ServiceContext::with_context(
+            &mock_context(cycles_limit, caller),
+            None,
+            "asset".to_owned(),
+            "mint".to_owned(),
+            "{}".to_owned(),
+        )
+    };
+
+    let res = service.check_write_permission(mint_context(Address::from_hex(ALICE).unwrap()));
+    assert!(!res.is_error());
+
+    let res = service.check_write_permission(mint_context(Address::from_hex(MULTI_SIG).unwrap()));
+    assert_eq!(res.code, 105);
+}
+
+#[test]
+fn test_set_admin() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_hex(ADMIN).unwrap();
+    let multi_sig = Address::from_hex(MULTI_SIG).unwrap();
+    let context = mock_context(cycles_limit, admin);
+
+    let mut service = new_authorization_service();
+
+    // test admin must be a multi-signature account
+    let res = service.set_admin(context.clone(), SetAdminPayload {
+        new_admin: Address::from_hex(ALICE).unwrap(),
+    });
+    assert_eq!(res.code, 106);
+
+    let res = service.set_admin(context.clone(), SetAdminPayload {
+        new_admin: multi_sig.clone(),
+    });
+    assert!(!res.is_error());
+    assert_eq!(
+        service.get_admin(context.clone()).succeed_data.admin,
+        multi_sig
+    );
+
+    // test old admin lost its power
+    let res = service.create_role(context, RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert_eq!(res.code, 103);
+
+    let res = service.create_role(mock_context(cycles_limit, multi_sig), RolePayload {
+        role: "minter".to_owned(),
+    });
+    assert!(!res.is_error());
+}
+
+// Only `MULTI_SIG` is a multi-signature account, every other read succeeds.
+struct MockDispatcher;
+
+impl Dispatcher for MockDispatcher {
+    fn read(&self, context: ServiceContext) -> ServiceResponse<String> {
+        if context.get_service_name() == "multi_signature"
+            && context.get_service_method() == "get_account_from_address"
+            && !context.get_payload().contains(MULTI_SIG)
+        {
+            return ServiceResponse::<String>::from_error(113, "account not existed".to_owned());
+        }
+
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+
+    fn write(&self, _context: ServiceContext) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+}
+
+fn new_authorization_service() -> AuthorizationService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        MockDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let mut sdk = DefaultServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        MockDispatcher {},
+    );
+    sdk.set_value(
+        "authotization_admin".to_owned(),
+        Address::from_hex(ADMIN).unwrap(),
+    );
+
+    AuthorizationService::new(sdk)
+}
+
+fn mock_stx_json(service_name: &str, method: &str, sender: Address) -> String {
+    let raw = RawTransaction {
+        chain_id: Hash::from_empty(),
+        cycles_price: 1,
+        cycles_limit: 60_000,
+        nonce: Hash::from_empty(),
+        request: TransactionRequest {
+            method:       method.to_owned(),
+            service_name: service_name.to_owned(),
+            payload:      "{}".to_owned(),
+        },
+        timeout: 10,
+        sender,
+    };
+
+    serde_json::to_string(&SignedTransaction {
+        raw,
+        tx_hash: Hash::from_empty(),
+        pubkey: Bytes::new(),
+        signature: Bytes::new(),
+    })
+    .unwrap()
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height: 1,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _ctx: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _ctx: Context, _: u64, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _ctx: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        _: Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block(&self, _ctx: Context, _: u64) -> ProtocolResult<Option<Block>> {
+        unimplemented!()
+    }
+
+    async fn get_receipt_by_hash(&self, _ctx: Context, _: Hash) -> ProtocolResult<Option<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _ctx: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
+}
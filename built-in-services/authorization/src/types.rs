@@ -10,6 +10,10 @@ pub struct InitGenesisPayload {
     pub admin:                  Address,
     pub register_service_names: Vec<String>,
     pub verified_method_names:  Vec<String>,
+    #[serde(default)]
+    pub roles:                  Vec<Role>,
+    #[serde(default)]
+    pub method_permissions:     Vec<MethodPermission>,
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
@@ -27,3 +31,91 @@ pub struct RemoveVerifiedItemPayload {
 pub struct SetAdminPayload {
     pub new_admin: Address,
 }
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Role {
+    pub name:    String,
+    pub members: Vec<Address>,
+}
+
+impl Role {
+    pub fn new(name: String) -> Self {
+        Role {
+            name,
+            members: Vec::new(),
+        }
+    }
+
+    pub fn has_member(&self, account: &Address) -> bool {
+        self.members.contains(account)
+    }
+}
+
+/// Roles allowed to call a (service, method) pair, caller must hold at least
+/// one of them. Pairs without a permission are open to everyone.
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MethodPermission {
+    pub service_name: String,
+    pub method_name:  String,
+    pub roles:        Vec<String>,
+}
+
+impl MethodPermission {
+    pub fn key(service_name: &str, method_name: &str) -> String {
+        format!("{}.{}", service_name, method_name)
+    }
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct RolePayload {
+    pub role: String,
+}
+
+pub type GetRolePayload = RolePayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GrantRolePayload {
+    pub role:    String,
+    pub account: Address,
+}
+
+pub type RevokeRolePayload = GrantRolePayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct SetMethodPermissionPayload {
+    pub service_name: String,
+    pub method_name:  String,
+    pub roles:        Vec<String>,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetMethodPermissionPayload {
+    pub service_name: String,
+    pub method_name:  String,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct CheckMethodPermissionPayload {
+    pub service_name: String,
+    pub method_name:  String,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetAccountRolesPayload {
+    pub account: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GetAccountRolesResponse {
+    pub roles: Vec<String>,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GetAdminResponse {
+    pub admin: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct MultiSigAccountPayload {
+    pub multi_sig_address: Address,
+}
@@ -1,9 +1,15 @@
 #[cfg(test)]
 mod tests;
+pub mod types;
 
 use binding_macro::{cycles, genesis, service};
 use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK};
-use protocol::types::{Metadata, ServiceContext, METADATA_KEY};
+use protocol::types::{Address, Metadata, ServiceContext, METADATA_KEY};
+
+use crate::types::UpdateVerifierListPayload;
+
+/// The only service allowed to replace the verifier list.
+pub const VERIFIER_LIST_UPDATER: &str = "staking";
 
 pub struct MetadataService<SDK> {
     sdk: SDK,
@@ -29,4 +35,29 @@ impl<SDK: ServiceSDK> MetadataService<SDK> {
             .expect("metadata should not be none");
         ServiceResponse::<Metadata>::from_succeed(metadata)
     }
+
+    #[cycles(210_00)]
+    #[write]
+    fn update_verifier_list(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UpdateVerifierListPayload,
+    ) -> ServiceResponse<()> {
+        if ctx.get_caller() != Address::from_service_name(VERIFIER_LIST_UPDATER) {
+            return ServiceResponse::<()>::from_error(101, "invalid caller".to_owned());
+        }
+
+        if payload.verifier_list.is_empty() {
+            return ServiceResponse::<()>::from_error(102, "empty verifier list".to_owned());
+        }
+
+        let mut metadata: Metadata = self
+            .sdk
+            .get_value(&METADATA_KEY.to_owned())
+            .expect("metadata should not be none");
+        metadata.verifier_list = payload.verifier_list;
+        self.sdk.set_value(METADATA_KEY.to_string(), metadata);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
 }
@@ -9,12 +9,13 @@ use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
 use protocol::traits::{Context, NoopDispatcher, ServiceSDK, Storage};
 use protocol::types::{
-    Address, Block, Hash, Hex, Metadata, Proof, Receipt, ServiceContext, ServiceContextParams,
-    SignedTransaction, ValidatorExtend, METADATA_KEY,
+    Address, Block, Evidence, Hash, Hex, Metadata, Proof, Receipt, ServiceContext,
+    ServiceContextParams, SignedTransaction, ValidatorExtend, METADATA_KEY,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
-use crate::MetadataService;
+use crate::types::UpdateVerifierListPayload;
+use crate::{MetadataService, VERIFIER_LIST_UPDATER};
 
 #[test]
 fn test_get_metadata() {
@@ -30,6 +31,44 @@ fn test_get_metadata() {
     assert_eq!(metadata, init_metadata);
 }
 
+#[test]
+fn test_update_verifier_list() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let init_metadata = mock_metadata();
+    let mut service = new_metadata_service_with_metadata(init_metadata.clone());
+
+    let mut verifier = init_metadata.verifier_list[0].clone();
+    verifier.address = caller.clone();
+    verifier.propose_weight = 5;
+    let payload = UpdateVerifierListPayload {
+        verifier_list: vec![verifier.clone()],
+    };
+
+    // test invalid caller
+    let res = service.update_verifier_list(mock_context(cycles_limit, caller), payload.clone());
+    assert_eq!(res.code, 101);
+
+    let updater = Address::from_service_name(VERIFIER_LIST_UPDATER);
+    let res = service.update_verifier_list(
+        mock_context(cycles_limit, updater.clone()),
+        UpdateVerifierListPayload {
+            verifier_list: vec![],
+        },
+    );
+    assert_eq!(res.code, 102);
+
+    let res = service.update_verifier_list(mock_context(cycles_limit, updater.clone()), payload);
+    assert!(!res.is_error());
+
+    let metadata = service
+        .get_metadata(mock_context(cycles_limit, updater))
+        .succeed_data;
+    assert_eq!(metadata.verifier_list, vec![verifier]);
+    assert_eq!(metadata.chain_id, init_metadata.chain_id);
+}
+
 fn new_metadata_service_with_metadata(
     metadata: Metadata,
 ) -> MetadataService<
@@ -172,4 +211,12 @@ impl Storage for MockStorage {
     async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
         unimplemented!()
     }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
 }
@@ -0,0 +1,11 @@
+use muta_codec_derive::RlpFixedCodec;
+use serde::{Deserialize, Serialize};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Bytes, ValidatorExtend};
+use protocol::ProtocolResult;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct UpdateVerifierListPayload {
+    pub verifier_list: Vec<ValidatorExtend>,
+}
@@ -8,30 +8,36 @@ use std::collections::HashMap;
 use std::panic::catch_unwind;
 
 use binding_macro::{cycles, genesis, service};
+use serde::Serialize;
 
 use common_crypto::{Crypto, Secp256k1};
-use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK};
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
 use protocol::types::{Address, Bytes, Hash, ServiceContext, SignedTransaction};
 
 use crate::types::{
-    Account, AddAccountPayload, ChangeMemoPayload, ChangeOwnerPayload,
-    GenerateMultiSigAccountPayload, GenerateMultiSigAccountResponse, GetMultiSigAccountPayload,
-    GetMultiSigAccountResponse, InitGenesisPayload, MultiSigPermission, RemoveAccountPayload,
-    RemoveAccountResult, SetAccountWeightPayload, SetThresholdPayload, SetWeightResult,
-    VerifySignaturePayload, Witness,
+    Account, AddAccountPayload, ApproveProposalPayload, ChangeMemoPayload, ChangeOwnerPayload,
+    ExecuteProposalEvent, GenerateMultiSigAccountPayload, GenerateMultiSigAccountResponse,
+    GetMultiSigAccountPayload, GetMultiSigAccountResponse, GetProposalPayload, GetProposalResponse,
+    InitGenesisPayload, MultiSigPermission, Proposal, ProposalEvent, ProposePayload,
+    ProposeResponse, RemoveAccountPayload, RemoveAccountResult, SetAccountWeightPayload,
+    SetThresholdPayload, SetWeightResult, VerifySignaturePayload, Witness,
 };
 
 const MAX_MULTI_SIGNATURE_RECURSION_DEPTH: u8 = 8;
 const MAX_PERMISSION_ACCOUNTS: u8 = 16;
+const MAX_PROPOSAL_TIMEOUT_GAP: u64 = 20_000;
 
 pub struct MultiSignatureService<SDK> {
-    sdk: SDK,
+    sdk:       SDK,
+    proposals: Box<dyn StoreMap<Hash, Proposal>>,
 }
 
 #[service]
 impl<SDK: ServiceSDK> MultiSignatureService<SDK> {
-    pub fn new(sdk: SDK) -> Self {
-        MultiSignatureService { sdk }
+    pub fn new(mut sdk: SDK) -> Self {
+        let proposals: Box<dyn StoreMap<Hash, Proposal>> = sdk.alloc_or_recover_map("proposals");
+
+        MultiSignatureService { sdk, proposals }
     }
 
     #[genesis]
@@ -459,6 +465,192 @@ impl<SDK: ServiceSDK> MultiSignatureService<SDK> {
         }
     }
 
+    #[cycles(210_00)]
+    #[write]
+    fn propose(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ProposePayload,
+    ) -> ServiceResponse<ProposeResponse> {
+        let caller = ctx.get_caller();
+        let permission = if let Some(permission) = self
+            .sdk
+            .get_account_value::<_, MultiSigPermission>(&payload.multi_sig_address, &0u8)
+        {
+            permission
+        } else {
+            return ServiceResponse::<ProposeResponse>::from_error(
+                113,
+                "account not existed".to_owned(),
+            );
+        };
+
+        if permission.get_account(&caller).is_none() {
+            return ServiceResponse::<ProposeResponse>::from_error(
+                124,
+                "caller is not a member of multi-signature account".to_owned(),
+            );
+        }
+
+        let height = ctx.get_current_height();
+        if payload.timeout <= height || payload.timeout > height + MAX_PROPOSAL_TIMEOUT_GAP {
+            return ServiceResponse::<ProposeResponse>::from_error(
+                129,
+                "invalid proposal timeout".to_owned(),
+            );
+        }
+
+        let mut seed = ctx
+            .get_tx_hash()
+            .expect("Can not get tx hash")
+            .as_bytes()
+            .to_vec();
+        seed.extend_from_slice(payload.multi_sig_address.as_bytes().as_ref());
+        let id = Hash::digest(Bytes::from(seed));
+
+        if self.proposals.contains(&id) {
+            return ServiceResponse::<ProposeResponse>::from_error(
+                130,
+                "proposal existed".to_owned(),
+            );
+        }
+
+        let proposal = Proposal {
+            id:                id.clone(),
+            multi_sig_address: payload.multi_sig_address,
+            proposer:          caller.clone(),
+            service_name:      payload.service_name,
+            method:            payload.method,
+            payload:           payload.payload,
+            timeout:           payload.timeout,
+            approvals:         vec![caller],
+            executed:          false,
+        };
+
+        if let Err((code, msg)) = self._accept_approval(&ctx, proposal, &permission) {
+            return ServiceResponse::<ProposeResponse>::from_error(code, msg);
+        }
+
+        ServiceResponse::<ProposeResponse>::from_succeed(ProposeResponse { proposal_id: id })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn approve(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ApproveProposalPayload,
+    ) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+        let mut proposal = if let Some(proposal) = self.proposals.get(&payload.proposal_id) {
+            proposal
+        } else {
+            return ServiceResponse::<()>::from_error(125, "proposal not existed".to_owned());
+        };
+
+        if proposal.executed {
+            return ServiceResponse::<()>::from_error(128, "proposal executed".to_owned());
+        }
+
+        if proposal.is_expired(ctx.get_current_height()) {
+            return ServiceResponse::<()>::from_error(126, "proposal expired".to_owned());
+        }
+
+        let permission = if let Some(permission) = self
+            .sdk
+            .get_account_value::<_, MultiSigPermission>(&proposal.multi_sig_address, &0u8)
+        {
+            permission
+        } else {
+            return ServiceResponse::<()>::from_error(113, "account not existed".to_owned());
+        };
+
+        if permission.get_account(&caller).is_none() {
+            return ServiceResponse::<()>::from_error(
+                124,
+                "caller is not a member of multi-signature account".to_owned(),
+            );
+        }
+
+        if proposal.approvals.contains(&caller) {
+            return ServiceResponse::<()>::from_error(127, "already approved".to_owned());
+        }
+
+        proposal.approvals.push(caller);
+        if let Err((code, msg)) = self._accept_approval(&ctx, proposal, &permission) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_proposal(
+        &self,
+        ctx: ServiceContext,
+        payload: GetProposalPayload,
+    ) -> ServiceResponse<GetProposalResponse> {
+        if let Some(proposal) = self.proposals.get(&payload.proposal_id) {
+            let expired = !proposal.executed && proposal.is_expired(ctx.get_current_height());
+
+            ServiceResponse::<GetProposalResponse>::from_succeed(GetProposalResponse {
+                proposal,
+                expired,
+            })
+        } else {
+            ServiceResponse::<GetProposalResponse>::from_error(
+                125,
+                "proposal not existed".to_owned(),
+            )
+        }
+    }
+
+    // Save the latest approval of proposal, dispatch the proposed call as the
+    // multi-signature address once approved weight reaches threshold.
+    fn _accept_approval(
+        &mut self,
+        ctx: &ServiceContext,
+        mut proposal: Proposal,
+        permission: &MultiSigPermission,
+    ) -> Result<(), (u64, String)> {
+        let weight = proposal.approved_weight(permission);
+        emit_event(ctx, &ProposalEvent {
+            proposal_id: proposal.id.clone(),
+            approver: ctx.get_caller(),
+            weight,
+        })?;
+
+        if weight < permission.threshold {
+            self.proposals.insert(proposal.id.clone(), proposal);
+            return Ok(());
+        }
+
+        // Mark executed before dispatching, so the call can't be replayed
+        proposal.executed = true;
+        self.proposals.insert(proposal.id.clone(), proposal.clone());
+
+        let sub_ctx = ServiceContext::with_caller(ctx, proposal.multi_sig_address.clone());
+        let resp = self.sdk.write(
+            &sub_ctx,
+            None,
+            &proposal.service_name,
+            &proposal.method,
+            &proposal.payload,
+        );
+
+        let response = if resp.is_error() {
+            resp.error_message
+        } else {
+            resp.succeed_data
+        };
+        emit_event(ctx, &ExecuteProposalEvent {
+            proposal_id: proposal.id,
+            code: resp.code,
+            response,
+        })
+    }
+
     fn _inner_verify_signature(&self, payload: VerifySignaturePayload) -> ServiceResponse<()> {
         let pubkeys = payload.pubkeys.clone();
         let signatures = payload.signatures.clone();
@@ -578,3 +770,10 @@ impl<SDK: ServiceSDK> MultiSignatureService<SDK> {
         }
     }
 }
+
+fn emit_event<E: Serialize>(ctx: &ServiceContext, event: &E) -> Result<(), (u64, String)> {
+    let event_str = serde_json::to_string(event).map_err(|e| (103, format!("{:?}", e)))?;
+    ctx.emit_event(event_str);
+
+    Ok(())
+}
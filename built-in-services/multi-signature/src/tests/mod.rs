@@ -1,4 +1,5 @@
 mod curd_test;
+mod proposal_test;
 mod recursion_test;
 
 use std::cell::RefCell;
@@ -17,7 +18,8 @@ use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
 use protocol::traits::{Context, NoopDispatcher, Storage};
 use protocol::types::{
-    Address, Block, Hash, Proof, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
+    Address, Block, Evidence, Hash, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
@@ -98,6 +100,14 @@ impl Storage for MockStorage {
     async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
         unimplemented!()
     }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
 }
 
 fn new_multi_signature_service() -> MultiSignatureService<
@@ -121,6 +131,10 @@ fn new_multi_signature_service() -> MultiSignatureService<
 }
 
 fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    mock_context_at(cycles_limit, caller, 1)
+}
+
+fn mock_context_at(cycles_limit: u64, caller: Address, height: u64) -> ServiceContext {
     let params = ServiceContextParams {
         tx_hash: Some(mock_hash()),
         nonce: None,
@@ -128,7 +142,7 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         cycles_price: 1,
         cycles_used: Rc::new(RefCell::new(0)),
         caller,
-        height: 1,
+        height,
         timestamp: 0,
         service_name: "service_name".to_owned(),
         service_method: "service_method".to_owned(),
@@ -0,0 +1,211 @@
+use protocol::traits::{Dispatcher, ServiceResponse};
+
+use crate::types::{
+    ApproveProposalPayload, GenerateMultiSigAccountPayload, GetProposalPayload, ProposePayload,
+};
+
+use super::*;
+
+type DispatchedCalls = Rc<RefCell<Vec<(Address, String, String, String)>>>;
+
+#[derive(Clone, Default)]
+struct RecordDispatcher {
+    calls: DispatchedCalls,
+}
+
+impl Dispatcher for RecordDispatcher {
+    fn read(&self, _context: ServiceContext) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        self.calls.borrow_mut().push((
+            context.get_caller(),
+            context.get_service_name().to_owned(),
+            context.get_service_method().to_owned(),
+            context.get_payload().to_owned(),
+        ));
+        ServiceResponse::<String>::from_succeed("ok".to_owned())
+    }
+}
+
+fn new_proposal_service(
+    dispatcher: RecordDispatcher,
+) -> MultiSignatureService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        RecordDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db), dispatcher);
+
+    MultiSignatureService::new(sdk)
+}
+
+#[test]
+fn test_proposal_execute_at_threshold() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let dispatcher = RecordDispatcher::default();
+    let mut service = new_proposal_service(dispatcher.clone());
+
+    let accounts = gen_keypairs(3)
+        .iter()
+        .map(|pair| to_multi_sig_account(pair.1.clone()))
+        .collect::<Vec<_>>();
+    let members = accounts
+        .iter()
+        .map(|account| account.address.clone())
+        .collect::<Vec<_>>();
+    let owner = Address::from_pubkey_bytes(gen_one_keypair().1).unwrap();
+
+    let context = mock_context(cycles_limit, members[0].clone());
+    let multi_sig_address = service
+        .generate_account(context.clone(), GenerateMultiSigAccountPayload {
+            owner,
+            addr_with_weight: accounts,
+            threshold: 2,
+            memo: String::new(),
+        })
+        .succeed_data
+        .address;
+
+    // test propose by a non-member
+    let outsider = Address::from_pubkey_bytes(gen_one_keypair().1).unwrap();
+    let res = service.propose(
+        mock_context(cycles_limit, outsider.clone()),
+        ProposePayload {
+            multi_sig_address: multi_sig_address.clone(),
+            service_name:      "asset".to_owned(),
+            method:            "transfer".to_owned(),
+            payload:           "{}".to_owned(),
+            timeout:           20,
+        },
+    );
+    assert_eq!(res.code, 124);
+
+    // test propose with an invalid timeout
+    let res = service.propose(context.clone(), ProposePayload {
+        multi_sig_address: multi_sig_address.clone(),
+        service_name:      "asset".to_owned(),
+        method:            "transfer".to_owned(),
+        payload:           "{}".to_owned(),
+        timeout:           1,
+    });
+    assert_eq!(res.code, 129);
+
+    let res = service.propose(context, ProposePayload {
+        multi_sig_address: multi_sig_address.clone(),
+        service_name:      "asset".to_owned(),
+        method:            "transfer".to_owned(),
+        payload:           "{}".to_owned(),
+        timeout:           20,
+    });
+    assert!(!res.is_error());
+    assert!(dispatcher.calls.borrow().is_empty());
+    let proposal_id = res.succeed_data.proposal_id;
+
+    // test approve twice and approve by a non-member
+    let res = service.approve(
+        mock_context(cycles_limit, members[0].clone()),
+        ApproveProposalPayload {
+            proposal_id: proposal_id.clone(),
+        },
+    );
+    assert_eq!(res.code, 127);
+    let res = service.approve(
+        mock_context(cycles_limit, outsider),
+        ApproveProposalPayload {
+            proposal_id: proposal_id.clone(),
+        },
+    );
+    assert_eq!(res.code, 124);
+
+    // test execute once threshold reached
+    let res = service.approve(
+        mock_context(cycles_limit, members[1].clone()),
+        ApproveProposalPayload {
+            proposal_id: proposal_id.clone(),
+        },
+    );
+    assert!(!res.is_error());
+    assert_eq!(dispatcher.calls.borrow().clone(), vec![(
+        multi_sig_address,
+        "asset".to_owned(),
+        "transfer".to_owned(),
+        "{}".to_owned()
+    )]);
+
+    let proposal = service
+        .get_proposal(
+            mock_context(cycles_limit, members[2].clone()),
+            GetProposalPayload {
+                proposal_id: proposal_id.clone(),
+            },
+        )
+        .succeed_data
+        .proposal;
+    assert!(proposal.executed);
+    assert_eq!(proposal.approvals, members[0..2].to_vec());
+
+    // test approve an executed proposal
+    let res = service.approve(
+        mock_context(cycles_limit, members[2].clone()),
+        ApproveProposalPayload { proposal_id },
+    );
+    assert_eq!(res.code, 128);
+}
+
+#[test]
+fn test_proposal_expired() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let dispatcher = RecordDispatcher::default();
+    let mut service = new_proposal_service(dispatcher.clone());
+
+    let accounts = gen_keypairs(2)
+        .iter()
+        .map(|pair| to_multi_sig_account(pair.1.clone()))
+        .collect::<Vec<_>>();
+    let members = accounts
+        .iter()
+        .map(|account| account.address.clone())
+        .collect::<Vec<_>>();
+    let owner = Address::from_pubkey_bytes(gen_one_keypair().1).unwrap();
+
+    let context = mock_context(cycles_limit, members[0].clone());
+    let multi_sig_address = service
+        .generate_account(context.clone(), GenerateMultiSigAccountPayload {
+            owner,
+            addr_with_weight: accounts,
+            threshold: 2,
+            memo: String::new(),
+        })
+        .succeed_data
+        .address;
+
+    let proposal_id = service
+        .propose(context, ProposePayload {
+            multi_sig_address,
+            service_name: "asset".to_owned(),
+            method: "transfer".to_owned(),
+            payload: "{}".to_owned(),
+            timeout: 2,
+        })
+        .succeed_data
+        .proposal_id;
+
+    let expired_context = mock_context_at(cycles_limit, members[1].clone(), 3);
+
+    let res = service.get_proposal(expired_context.clone(), GetProposalPayload {
+        proposal_id: proposal_id.clone(),
+    });
+    assert!(res.succeed_data.expired);
+
+    let res = service.approve(expired_context, ApproveProposalPayload { proposal_id });
+    assert_eq!(res.code, 126);
+    assert!(dispatcher.calls.borrow().is_empty());
+}
@@ -104,6 +104,76 @@ pub struct SetThresholdPayload {
     pub new_threshold:     u32,
 }
 
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ProposePayload {
+    pub multi_sig_address: Address,
+    pub service_name:      String,
+    pub method:            String,
+    pub payload:           String,
+    /// Block height after which the proposal can no longer be approved.
+    pub timeout:           u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ProposeResponse {
+    pub proposal_id: Hash,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ApproveProposalPayload {
+    pub proposal_id: Hash,
+}
+
+pub type GetProposalPayload = ApproveProposalPayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GetProposalResponse {
+    pub proposal: Proposal,
+    pub expired:  bool,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Proposal {
+    pub id:                Hash,
+    pub multi_sig_address: Address,
+    pub proposer:          Address,
+    pub service_name:      String,
+    pub method:            String,
+    pub payload:           String,
+    pub timeout:           u64,
+    pub approvals:         Vec<Address>,
+    pub executed:          bool,
+}
+
+impl Proposal {
+    pub fn is_expired(&self, current_height: u64) -> bool {
+        current_height > self.timeout
+    }
+
+    pub fn approved_weight(&self, permission: &MultiSigPermission) -> u32 {
+        permission
+            .accounts
+            .iter()
+            .filter(|account| self.approvals.contains(&account.address))
+            .map(|account| account.weight as u32)
+            .sum::<u32>()
+    }
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ProposalEvent {
+    pub proposal_id: Hash,
+    pub approver:    Address,
+    pub weight:      u32,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ExecuteProposalEvent {
+    pub proposal_id: Hash,
+    pub code:        u64,
+    pub response:    String,
+}
+
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
 pub struct MultiSigPermission {
     pub owner:     Address,
@@ -0,0 +1,329 @@
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+use binding_macro::{cycles, genesis, service};
+use serde::Serialize;
+
+use asset::types::TransferPayload;
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::types::{Address, ServiceContext};
+
+use crate::types::{
+    GetNamePayload, InitGenesisPayload, NameConfig, NameRecord, RegisterEvent, RegisterPayload,
+    RenewEvent, RenewPayload, ResolvePayload, ResolveResponse, ReverseResolvePayload,
+    ReverseResolveResponse, SetAddressPayload, SetReverseNamePayload, TransferNameEvent,
+    TransferNamePayload,
+};
+
+pub const NAME_SERVICE: &str = "name";
+const NAME_CONFIG_KEY: &str = "name_config";
+const MAX_NAME_LEN: usize = 64;
+
+pub struct NameService<SDK> {
+    sdk:           SDK,
+    names:         Box<dyn StoreMap<String, NameRecord>>,
+    reverse_names: Box<dyn StoreMap<Address, String>>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> NameService<SDK> {
+    pub fn new(mut sdk: SDK) -> Self {
+        let names: Box<dyn StoreMap<String, NameRecord>> = sdk.alloc_or_recover_map("names");
+        let reverse_names: Box<dyn StoreMap<Address, String>> =
+            sdk.alloc_or_recover_map("reverse_names");
+
+        Self {
+            sdk,
+            names,
+            reverse_names,
+        }
+    }
+
+    #[genesis]
+    fn init_genesis(&mut self, payload: InitGenesisPayload) {
+        assert!(payload.period > 0, "period should be positive");
+        assert!(
+            payload.suffix.starts_with('.'),
+            "suffix should start with a dot"
+        );
+
+        self.sdk.set_value(NAME_CONFIG_KEY.to_owned(), payload)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_config(&self, ctx: ServiceContext) -> ServiceResponse<NameConfig> {
+        ServiceResponse::<NameConfig>::from_succeed(self._config())
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_name(
+        &self,
+        ctx: ServiceContext,
+        payload: GetNamePayload,
+    ) -> ServiceResponse<NameRecord> {
+        match self._live_record(&ctx, &payload.name) {
+            Some(record) => ServiceResponse::<NameRecord>::from_succeed(record),
+            None => ServiceResponse::<NameRecord>::from_error(103, "name not existed".to_owned()),
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn resolve(
+        &self,
+        ctx: ServiceContext,
+        payload: ResolvePayload,
+    ) -> ServiceResponse<ResolveResponse> {
+        match self._live_record(&ctx, &payload.name) {
+            Some(record) => ServiceResponse::<ResolveResponse>::from_succeed(ResolveResponse {
+                address: record.address,
+            }),
+            None => {
+                ServiceResponse::<ResolveResponse>::from_error(103, "name not existed".to_owned())
+            }
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn reverse_resolve(
+        &self,
+        ctx: ServiceContext,
+        payload: ReverseResolvePayload,
+    ) -> ServiceResponse<ReverseResolveResponse> {
+        // A reverse name is only trusted while the name still resolves back
+        // to the same address.
+        let name = self.reverse_names.get(&payload.address).filter(|name| {
+            match self._live_record(&ctx, name) {
+                Some(record) => record.address == payload.address,
+                None => false,
+            }
+        });
+
+        match name {
+            Some(name) => {
+                ServiceResponse::<ReverseResolveResponse>::from_succeed(ReverseResolveResponse {
+                    name,
+                })
+            }
+            None => ServiceResponse::<ReverseResolveResponse>::from_error(
+                107,
+                "reverse name not set".to_owned(),
+            ),
+        }
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn register(&mut self, ctx: ServiceContext, payload: RegisterPayload) -> ServiceResponse<()> {
+        let config = self._config();
+        if !is_valid_name(&config, &payload.name) {
+            return ServiceResponse::<()>::from_error(101, "name not valid".to_owned());
+        }
+
+        if self._live_record(&ctx, &payload.name).is_some() {
+            return ServiceResponse::<()>::from_error(102, "name registered".to_owned());
+        }
+
+        if let Err((code, msg)) = self._pay_fee(&ctx, &config) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        let record = NameRecord {
+            name:      payload.name.clone(),
+            owner:     ctx.get_caller(),
+            address:   payload.address,
+            expire_at: ctx.get_current_height().saturating_add(config.period),
+        };
+        self.names.insert(payload.name, record.clone());
+
+        if let Err((code, msg)) = emit_event(&ctx, &RegisterEvent {
+            name:      record.name,
+            owner:     record.owner,
+            address:   record.address,
+            expire_at: record.expire_at,
+        }) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn renew(&mut self, ctx: ServiceContext, payload: RenewPayload) -> ServiceResponse<()> {
+        let mut record = match self._owned_record(&ctx, &payload.name) {
+            Ok(record) => record,
+            Err((code, msg)) => return ServiceResponse::<()>::from_error(code, msg),
+        };
+
+        let config = self._config();
+        if let Err((code, msg)) = self._pay_fee(&ctx, &config) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        record.expire_at = record
+            .expire_at
+            .max(ctx.get_current_height())
+            .saturating_add(config.period);
+        self.names.insert(payload.name.clone(), record.clone());
+
+        if let Err((code, msg)) = emit_event(&ctx, &RenewEvent {
+            name:      payload.name,
+            expire_at: record.expire_at,
+        }) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn transfer(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TransferNamePayload,
+    ) -> ServiceResponse<()> {
+        let mut record = match self._owned_record(&ctx, &payload.name) {
+            Ok(record) => record,
+            Err((code, msg)) => return ServiceResponse::<()>::from_error(code, msg),
+        };
+
+        record.owner = payload.to.clone();
+        self.names.insert(payload.name.clone(), record);
+
+        if let Err((code, msg)) = emit_event(&ctx, &TransferNameEvent {
+            name: payload.name,
+            from: ctx.get_caller(),
+            to:   payload.to,
+        }) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn set_address(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetAddressPayload,
+    ) -> ServiceResponse<()> {
+        let mut record = match self._owned_record(&ctx, &payload.name) {
+            Ok(record) => record,
+            Err((code, msg)) => return ServiceResponse::<()>::from_error(code, msg),
+        };
+
+        record.address = payload.address;
+        self.names.insert(payload.name, record);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    /// Only the address a name resolves to can claim it as its reverse name,
+    /// otherwise anyone could label others' addresses.
+    #[cycles(210_00)]
+    #[write]
+    fn set_reverse_name(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetReverseNamePayload,
+    ) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+
+        match self._live_record(&ctx, &payload.name) {
+            Some(record) if record.address == caller => {}
+            Some(_) => {
+                return ServiceResponse::<()>::from_error(
+                    108,
+                    "name not resolved to caller".to_owned(),
+                )
+            }
+            None => return ServiceResponse::<()>::from_error(103, "name not existed".to_owned()),
+        }
+
+        self.reverse_names.insert(caller, payload.name);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    fn _config(&self) -> NameConfig {
+        self.sdk
+            .get_value(&NAME_CONFIG_KEY.to_owned())
+            .expect("name config should not be none")
+    }
+
+    fn _live_record(&self, ctx: &ServiceContext, name: &str) -> Option<NameRecord> {
+        self.names
+            .get(&name.to_owned())
+            .filter(|record| !record.is_expired(ctx.get_current_height()))
+    }
+
+    // An expired name can still be renewed by its owner until someone else
+    // registers it.
+    fn _owned_record(&self, ctx: &ServiceContext, name: &str) -> Result<NameRecord, (u64, String)> {
+        let record = self
+            .names
+            .get(&name.to_owned())
+            .ok_or_else(|| (103, "name not existed".to_owned()))?;
+
+        if record.owner != ctx.get_caller() {
+            return Err((105, "caller is not owner".to_owned()));
+        }
+
+        Ok(record)
+    }
+
+    fn _pay_fee(&mut self, ctx: &ServiceContext, config: &NameConfig) -> Result<(), (u64, String)> {
+        if config.fee.is_zero() {
+            return Ok(());
+        }
+
+        let payload = serde_json::to_string(&TransferPayload {
+            asset_id: config.asset_id.clone(),
+            to:       name_address(),
+            value:    config.fee,
+        })
+        .map_err(|e| (104, format!("{:?}", e)))?;
+
+        let resp = self.sdk.write(ctx, None, "asset", "transfer", &payload);
+        if resp.is_error() {
+            return Err((106, format!("pay fee failed {:?}", resp.error_message)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Address collecting registration and renewal fees.
+pub fn name_address() -> Address {
+    Address::from_service_name(NAME_SERVICE)
+}
+
+// A name is a single label of lowercase letters, digits and hyphens followed
+// by the configured suffix, e.g. `alice.muta`.
+fn is_valid_name(config: &NameConfig, name: &str) -> bool {
+    if name.len() > MAX_NAME_LEN || !name.ends_with(&config.suffix) {
+        return false;
+    }
+
+    let label = &name[..name.len() - config.suffix.len()];
+    label.len() >= (config.min_label_len as usize).max(1)
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn emit_event<E: Serialize>(ctx: &ServiceContext, event: &E) -> Result<(), (u64, String)> {
+    let event_str = serde_json::to_string(event).map_err(|e| (104, format!("{:?}", e)))?;
+    ctx.emit_event(event_str);
+
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use asset::types::{Amount, TransferPayload};
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{Context, Dispatcher, ServiceResponse, Storage};
+use protocol::types::{
+    Address, Block, Evidence, Hash, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{
+    GetNamePayload, InitGenesisPayload, RegisterPayload, RenewPayload, ResolvePayload,
+    ReverseResolvePayload, SetReverseNamePayload, TransferNamePayload,
+};
+use crate::{name_address, NameService};
+
+const PERIOD: u64 = 100;
+
+#[test]
+fn test_register_and_resolve() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let bob = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_name_service(dispatcher.clone());
+
+    for name in &[
+        "al.muta",
+        "Alice.muta",
+        "alice.eth",
+        "-alice.muta",
+        "a.b.muta",
+    ] {
+        let res = service.register(
+            mock_context(cycles_limit, alice.clone(), 1),
+            RegisterPayload {
+                name:    (*name).to_owned(),
+                address: alice.clone(),
+            },
+        );
+        assert_eq!(res.code, 101, "{}", name);
+    }
+
+    let res = service.register(
+        mock_context(cycles_limit, alice.clone(), 1),
+        RegisterPayload {
+            name:    "alice.muta".to_owned(),
+            address: alice.clone(),
+        },
+    );
+    assert!(!res.is_error());
+
+    // test register pays the fee
+    let (caller, method, payload) = dispatcher.calls.borrow().last().cloned().unwrap();
+    let transfer: TransferPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(caller, alice);
+    assert_eq!(method, "asset.transfer");
+    assert_eq!(transfer.to, name_address());
+    assert_eq!(transfer.value, Amount(10));
+
+    let res = service.register(
+        mock_context(cycles_limit, bob.clone(), 2),
+        RegisterPayload {
+            name:    "alice.muta".to_owned(),
+            address: bob.clone(),
+        },
+    );
+    assert_eq!(res.code, 102);
+
+    let res = service.resolve(mock_context(cycles_limit, bob.clone(), 2), ResolvePayload {
+        name: "alice.muta".to_owned(),
+    });
+    assert_eq!(res.succeed_data.address, alice);
+
+    // test failed payment leaves the name free
+    *dispatcher.fail_transfer.borrow_mut() = true;
+    let res = service.register(
+        mock_context(cycles_limit, bob.clone(), 2),
+        RegisterPayload {
+            name:    "bob.muta".to_owned(),
+            address: bob.clone(),
+        },
+    );
+    assert_eq!(res.code, 106);
+    *dispatcher.fail_transfer.borrow_mut() = false;
+
+    let res = service.resolve(mock_context(cycles_limit, bob.clone(), 2), ResolvePayload {
+        name: "bob.muta".to_owned(),
+    });
+    assert_eq!(res.code, 103);
+
+    // test reverse lookup only for the resolved address
+    let res = service.set_reverse_name(
+        mock_context(cycles_limit, bob.clone(), 2),
+        SetReverseNamePayload {
+            name: "alice.muta".to_owned(),
+        },
+    );
+    assert_eq!(res.code, 108);
+
+    let res = service.set_reverse_name(
+        mock_context(cycles_limit, alice.clone(), 2),
+        SetReverseNamePayload {
+            name: "alice.muta".to_owned(),
+        },
+    );
+    assert!(!res.is_error());
+
+    let res = service.reverse_resolve(
+        mock_context(cycles_limit, bob.clone(), 2),
+        ReverseResolvePayload {
+            address: alice.clone(),
+        },
+    );
+    assert_eq!(res.succeed_data.name, "alice.muta".to_owned());
+}
+
+#[test]
+fn test_transfer_and_expire() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let bob = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_name_service(dispatcher);
+
+    let res = service.register(
+        mock_context(cycles_limit, alice.clone(), 1),
+        RegisterPayload {
+            name:    "alice.muta".to_owned(),
+            address: alice.clone(),
+        },
+    );
+    assert!(!res.is_error());
+
+    let res = service.transfer(
+        mock_context(cycles_limit, bob.clone(), 2),
+        TransferNamePayload {
+            name: "alice.muta".to_owned(),
+            to:   bob.clone(),
+        },
+    );
+    assert_eq!(res.code, 105);
+
+    let res = service.transfer(
+        mock_context(cycles_limit, alice.clone(), 2),
+        TransferNamePayload {
+            name: "alice.muta".to_owned(),
+            to:   bob.clone(),
+        },
+    );
+    assert!(!res.is_error());
+
+    // test renew extends from the current expiry
+    let res = service.renew(mock_context(cycles_limit, bob.clone(), 2), RenewPayload {
+        name: "alice.muta".to_owned(),
+    });
+    assert!(!res.is_error());
+
+    let record = service
+        .get_name(mock_context(cycles_limit, bob.clone(), 2), GetNamePayload {
+            name: "alice.muta".to_owned(),
+        })
+        .succeed_data;
+    assert_eq!(record.owner, bob);
+    assert_eq!(record.address, alice);
+    assert_eq!(record.expire_at, 1 + 2 * PERIOD);
+
+    // test expired name can be registered by others
+    let res = service.resolve(
+        mock_context(cycles_limit, bob.clone(), 1 + 2 * PERIOD),
+        ResolvePayload {
+            name: "alice.muta".to_owned(),
+        },
+    );
+    assert_eq!(res.code, 103);
+
+    let res = service.register(
+        mock_context(cycles_limit, alice.clone(), 1 + 2 * PERIOD),
+        RegisterPayload {
+            name:    "alice.muta".to_owned(),
+            address: alice.clone(),
+        },
+    );
+    assert!(!res.is_error());
+}
+
+#[derive(Clone, Default)]
+struct MockDispatcher {
+    calls:         Rc<RefCell<Vec<(Address, String, String)>>>,
+    fail_transfer: Rc<RefCell<bool>>,
+}
+
+impl Dispatcher for MockDispatcher {
+    fn read(&self, _context: ServiceContext) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        let method = format!(
+            "{}.{}",
+            context.get_service_name(),
+            context.get_service_method()
+        );
+        if method == "asset.transfer" && *self.fail_transfer.borrow() {
+            return ServiceResponse::<String>::from_error(105, "insufficient balance".to_owned());
+        }
+
+        self.calls.borrow_mut().push((
+            context.get_caller(),
+            method,
+            context.get_payload().to_owned(),
+        ));
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+}
+
+fn new_name_service(
+    dispatcher: MockDispatcher,
+) -> NameService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        MockDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db), dispatcher);
+
+    let mut service = NameService::new(sdk);
+    service.init_genesis(InitGenesisPayload {
+        asset_id:      Hash::digest(Bytes::from("name")),
+        fee:           Amount(10),
+        period:        PERIOD,
+        suffix:        ".muta".to_owned(),
+        min_label_len: 3,
+    });
+    service
+}
+
+fn mock_context(cycles_limit: u64, caller: Address, height: u64) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _ctx: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _ctx: Context, _: u64, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _ctx: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        _: Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block(&self, _ctx: Context, _: u64) -> ProtocolResult<Option<Block>> {
+        unimplemented!()
+    }
+
+    async fn get_receipt_by_hash(&self, _ctx: Context, _: Hash) -> ProtocolResult<Option<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _ctx: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,100 @@
+use muta_codec_derive::RlpFixedCodec;
+use serde::{Deserialize, Serialize};
+
+use asset::types::Amount;
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Address, Bytes, Hash, ResolveNamePayload, ResolveNameResponse};
+use protocol::ProtocolResult;
+
+/// Payload
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NameConfig {
+    /// Asset in `AssetService` which registration and renewal are paid in.
+    pub asset_id:      Hash,
+    /// Fee of registering or renewing a name for one period.
+    pub fee:           Amount,
+    /// Blocks a name stays valid after it is registered or renewed.
+    pub period:        u64,
+    /// Every name must end with this suffix, e.g. `.muta`.
+    pub suffix:        String,
+    /// Minimum length of a name, suffix excluded.
+    pub min_label_len: u32,
+}
+
+pub type InitGenesisPayload = NameConfig;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct RegisterPayload {
+    pub name:    String,
+    pub address: Address,
+}
+
+pub type SetAddressPayload = RegisterPayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct TransferNamePayload {
+    pub name: String,
+    pub to:   Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct NamePayload {
+    pub name: String,
+}
+
+pub type RenewPayload = NamePayload;
+pub type GetNamePayload = NamePayload;
+pub type SetReverseNamePayload = NamePayload;
+
+// Shared with API, which resolves names in address inputs
+pub type ResolvePayload = ResolveNamePayload;
+pub type ResolveResponse = ResolveNameResponse;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ReverseResolvePayload {
+    pub address: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ReverseResolveResponse {
+    pub name: String,
+}
+
+/// Event
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct RegisterEvent {
+    pub name:      String,
+    pub owner:     Address,
+    pub address:   Address,
+    pub expire_at: u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct TransferNameEvent {
+    pub name: String,
+    pub from: Address,
+    pub to:   Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct RenewEvent {
+    pub name:      String,
+    pub expire_at: u64,
+}
+
+/// Model
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct NameRecord {
+    pub name:      String,
+    pub owner:     Address,
+    /// Address the name resolves to.
+    pub address:   Address,
+    /// The name is released at this height unless it is renewed.
+    pub expire_at: u64,
+}
+
+impl NameRecord {
+    pub fn is_expired(&self, height: u64) -> bool {
+        self.expire_at <= height
+    }
+}
@@ -0,0 +1,428 @@
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use binding_macro::{cycles, genesis, hook_after, service, tx_hook_after, tx_hook_before};
+use serde::Serialize;
+
+use asset::types::{Amount, MintPayload, TransferPayload};
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::types::{
+    Address, Bytes, Metadata, ServiceContext, ServiceContextParams, ValidatorExtend,
+};
+
+use crate::types::{
+    GetCollectedFeesResponse, GetRefundPayload, GetRewardPayload, InitGenesisPayload,
+    ObservedVerifiers, RefundResponse, RewardEvent, RewardsConfig, ValidatorReward,
+};
+
+pub const REWARDS_SERVICE_NAME: &str = "rewards";
+
+const REWARDS_CONFIG_KEY: &str = "rewards_config";
+const COLLECTED_FEES_KEY: &str = "collected_fees";
+const OBSERVED_VERIFIERS_KEY: &str = "observed_verifiers";
+const PREPAID_FEE_KEY: &str = "prepaid_fee";
+
+pub struct RewardsService<SDK> {
+    sdk:     SDK,
+    rewards: Box<dyn StoreMap<u64, RewardEvent>>,
+    // Unused fees which failed to be refunded, claimed by `claim_refund`
+    refunds: Box<dyn StoreMap<Address, Amount>>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> RewardsService<SDK> {
+    pub fn new(mut sdk: SDK) -> Self {
+        let rewards: Box<dyn StoreMap<u64, RewardEvent>> = sdk.alloc_or_recover_map("rewards");
+        let refunds: Box<dyn StoreMap<Address, Amount>> = sdk.alloc_or_recover_map("refunds");
+
+        Self {
+            sdk,
+            rewards,
+            refunds,
+        }
+    }
+
+    #[genesis]
+    fn init_genesis(&mut self, payload: InitGenesisPayload) {
+        assert!(
+            payload.validator_share <= 100,
+            "validator share should not exceed 100"
+        );
+
+        self.sdk.set_value(REWARDS_CONFIG_KEY.to_owned(), payload)
+    }
+
+    // The most a transaction may cost is paid before it runs, a sender who
+    // can't pay it is rejected.
+    #[tx_hook_before]
+    fn prepay_fee(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        let config = match self._config() {
+            Some(config) if config.collect_fee => config,
+            _ => return ServiceResponse::<()>::from_succeed(()),
+        };
+
+        let max_fee =
+            Amount(u128::from(ctx.get_cycles_limit()) * u128::from(ctx.get_cycles_price()));
+        if max_fee.is_zero() {
+            return ServiceResponse::<()>::from_succeed(());
+        }
+
+        if let Err(msg) = self._pay(&ctx, &config, &rewards_address(), max_fee) {
+            return ServiceResponse::<()>::from_error(102, format!("can't pay fee {}", msg));
+        }
+
+        self.sdk.set_value(PREPAID_FEE_KEY.to_owned(), max_fee);
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    // Keeps `cycles_used * cycles_price` of the prepaid fee and refunds the
+    // rest.
+    #[tx_hook_after]
+    fn collect_fee(&mut self, ctx: ServiceContext) {
+        let prepaid: Amount = match self.sdk.get_value(&PREPAID_FEE_KEY.to_owned()) {
+            Some(prepaid) => prepaid,
+            None => return,
+        };
+        self.sdk.remove_value(&PREPAID_FEE_KEY.to_owned());
+
+        let config = self._config().expect("rewards config should not be none");
+        let used = Amount(u128::from(ctx.get_cycles_used()) * u128::from(ctx.get_cycles_price()));
+        let fee = used.min(prepaid);
+        let refund = Amount(prepaid.0 - fee.0);
+
+        if !refund.is_zero() {
+            // Paid by the pool on its own context, the transaction may have
+            // used up its cycles.
+            let refund_ctx = self._service_context(
+                rewards_address(),
+                ctx.get_current_height(),
+                ctx.get_timestamp(),
+                ctx.get_cycles_limit(),
+            );
+            let sender = ctx.get_caller();
+
+            if self._pay(&refund_ctx, &config, &sender, refund).is_err() {
+                let owed = self.refunds.get(&sender).unwrap_or_default();
+                if let Some(owed) = owed.checked_add(refund) {
+                    self.refunds.insert(sender, owed);
+                }
+            }
+        }
+
+        if let Some(fees) = self._collected_fees().checked_add(fee) {
+            self.sdk.set_value(COLLECTED_FEES_KEY.to_owned(), fees);
+        }
+    }
+
+    #[hook_after]
+    fn distribute_rewards(&mut self, params: &ExecutorParams) {
+        let config = match self._config() {
+            Some(config) => config,
+            None => return,
+        };
+
+        let ctx = self._service_context(
+            rewards_address(),
+            params.height,
+            params.timestamp,
+            params.cycles_limit,
+        );
+
+        // Track verifier list of every block, including those without payout
+        let verifiers = self._observe_verifiers(&ctx, params.height);
+
+        // Minted rewards go through the pool as well, then every payout is a
+        // plain transfer.
+        let mut block_reward = config.block_reward(params.height);
+        if config.mint
+            && !block_reward.is_zero()
+            && self
+                ._call_asset(&ctx, "mint", &MintPayload {
+                    asset_id: config.asset_id.clone(),
+                    to:       rewards_address(),
+                    value:    block_reward,
+                })
+                .is_err()
+        {
+            block_reward = Amount(0);
+        }
+
+        let fees = self._collected_fees();
+        let total = match block_reward.checked_add(fees) {
+            Some(total) if !total.is_zero() => total,
+            _ => return,
+        };
+
+        let voters = match verifiers {
+            Some(verifiers) => self._previous_voters(verifiers, params.height),
+            None => vec![],
+        };
+        let total_weight = voters
+            .iter()
+            .map(|(_, weight)| u128::from(*weight))
+            .sum::<u128>();
+        let validator_total = if total_weight == 0 {
+            0
+        } else {
+            share_of(total.0, u128::from(config.validator_share), 100)
+        };
+
+        let mut event = RewardEvent {
+            height: params.height,
+            block_reward,
+            fees,
+            proposer: params.proposer.clone(),
+            ..Default::default()
+        };
+
+        let mut validator_paid = 0u128;
+        for (address, weight) in voters.into_iter() {
+            let amount = Amount(share_of(validator_total, u128::from(weight), total_weight));
+            if amount.is_zero() || self._pay(&ctx, &config, &address, amount).is_err() {
+                continue;
+            }
+
+            validator_paid += amount.0;
+            event
+                .validator_rewards
+                .push(ValidatorReward { address, amount });
+        }
+
+        // Proposer takes the rest, including rounding dust of the validator
+        // share.
+        let proposer_reward = Amount(total.0 - validator_paid);
+        if !proposer_reward.is_zero()
+            && self
+                ._pay(&ctx, &config, &params.proposer, proposer_reward)
+                .is_ok()
+        {
+            event.proposer_reward = proposer_reward;
+        }
+
+        self.sdk.set_value(COLLECTED_FEES_KEY.to_owned(), Amount(0));
+
+        // Events of block hooks have no receipt to go to, `get_reward` is the
+        // record of every payout.
+        self.rewards.insert(params.height, event);
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_config(&self, ctx: ServiceContext) -> ServiceResponse<RewardsConfig> {
+        ServiceResponse::<RewardsConfig>::from_succeed(
+            self._config().expect("rewards config should not be none"),
+        )
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_reward(
+        &self,
+        ctx: ServiceContext,
+        payload: GetRewardPayload,
+    ) -> ServiceResponse<RewardEvent> {
+        if let Some(event) = self.rewards.get(&payload.height) {
+            ServiceResponse::<RewardEvent>::from_succeed(event)
+        } else {
+            ServiceResponse::<RewardEvent>::from_error(101, "reward not existed".to_owned())
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_refund(
+        &self,
+        ctx: ServiceContext,
+        payload: GetRefundPayload,
+    ) -> ServiceResponse<RefundResponse> {
+        ServiceResponse::<RefundResponse>::from_succeed(RefundResponse {
+            amount: self.refunds.get(&payload.address).unwrap_or_default(),
+        })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn claim_refund(&mut self, ctx: ServiceContext) -> ServiceResponse<RefundResponse> {
+        let caller = ctx.get_caller();
+        let amount = match self.refunds.get(&caller) {
+            Some(amount) => amount,
+            None => {
+                return ServiceResponse::<RefundResponse>::from_error(
+                    103,
+                    "refund not existed".to_owned(),
+                )
+            }
+        };
+
+        let config = self._config().expect("rewards config should not be none");
+        let pool_ctx = ServiceContext::with_caller(&ctx, rewards_address());
+        if let Err(msg) = self._pay(&pool_ctx, &config, &caller, amount) {
+            return ServiceResponse::<RefundResponse>::from_error(
+                104,
+                format!("pay refund failed {}", msg),
+            );
+        }
+
+        self.refunds.remove(&caller);
+        ServiceResponse::<RefundResponse>::from_succeed(RefundResponse { amount })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_collected_fees(&self, ctx: ServiceContext) -> ServiceResponse<GetCollectedFeesResponse> {
+        ServiceResponse::<GetCollectedFeesResponse>::from_succeed(GetCollectedFeesResponse {
+            fees: self._collected_fees(),
+        })
+    }
+
+    fn _config(&self) -> Option<RewardsConfig> {
+        self.sdk.get_value(&REWARDS_CONFIG_KEY.to_owned())
+    }
+
+    fn _collected_fees(&self) -> Amount {
+        self.sdk
+            .get_value(&COLLECTED_FEES_KEY.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn _pay(
+        &mut self,
+        ctx: &ServiceContext,
+        config: &RewardsConfig,
+        to: &Address,
+        value: Amount,
+    ) -> Result<(), String> {
+        self._call_asset(ctx, "transfer", &TransferPayload {
+            asset_id: config.asset_id.clone(),
+            to: to.clone(),
+            value,
+        })
+    }
+
+    fn _call_asset<P: Serialize>(
+        &mut self,
+        ctx: &ServiceContext,
+        method: &str,
+        payload: &P,
+    ) -> Result<(), String> {
+        let payload = serde_json::to_string(payload).map_err(|e| format!("{:?}", e))?;
+
+        let resp = self.sdk.write(ctx, None, "asset", method, &payload);
+        if resp.is_error() {
+            return Err(resp.error_message);
+        }
+
+        Ok(())
+    }
+
+    // Current verifier list, the first height it was seen at is updated if it
+    // differs from the one seen by last block.
+    fn _observe_verifiers(
+        &mut self,
+        ctx: &ServiceContext,
+        height: u64,
+    ) -> Option<ObservedVerifiers> {
+        let resp = self.sdk.read(ctx, None, "metadata", "get_metadata", "");
+        if resp.is_error() {
+            return None;
+        }
+
+        let verifier_list = serde_json::from_str::<Metadata>(&resp.succeed_data)
+            .ok()?
+            .verifier_list;
+        let observed: Option<ObservedVerifiers> =
+            self.sdk.get_value(&OBSERVED_VERIFIERS_KEY.to_owned());
+
+        match observed {
+            Some(observed) if observed.verifier_list == verifier_list => Some(observed),
+            _ => {
+                let observed = ObservedVerifiers {
+                    verifier_list,
+                    since: height,
+                };
+                self.sdk
+                    .set_value(OBSERVED_VERIFIERS_KEY.to_owned(), observed.clone());
+                Some(observed)
+            }
+        }
+    }
+
+    // Voters of the previous block, recorded in the bitmap of its proof. The
+    // bitmap indexes the verifier list the proof was signed by, so voters
+    // are unknown until the current list has been seen since before the
+    // proof height.
+    fn _previous_voters(&self, verifiers: ObservedVerifiers, height: u64) -> Vec<(Address, u32)> {
+        if height <= 1 {
+            return vec![];
+        }
+
+        let block = match self.sdk.get_block_by_height(Some(height - 1)) {
+            Some(block) => block,
+            None => return vec![],
+        };
+
+        let proof = &block.header.proof;
+        if verifiers.since >= proof.height {
+            return vec![];
+        }
+
+        extract_voters(verifiers.verifier_list, &proof.bitmap)
+    }
+
+    fn _service_context(
+        &self,
+        caller: Address,
+        height: u64,
+        timestamp: u64,
+        cycles_limit: u64,
+    ) -> ServiceContext {
+        ServiceContext::new(ServiceContextParams {
+            tx_hash: None,
+            nonce: None,
+            cycles_limit,
+            cycles_price: 1,
+            cycles_used: Rc::new(RefCell::new(0)),
+            caller,
+            height,
+            timestamp,
+            service_name: REWARDS_SERVICE_NAME.to_owned(),
+            service_method: String::new(),
+            service_payload: String::new(),
+            extra: None,
+            events: Rc::new(RefCell::new(vec![])),
+        })
+    }
+}
+
+/// Address of the pool holding collected fees, also the payer of rewards.
+pub fn rewards_address() -> Address {
+    Address::from_service_name(REWARDS_SERVICE_NAME)
+}
+
+/// Same order as overlord: verifiers are sorted by address and the i-th bit
+/// of the bitmap, from the most significant bit of the first byte, marks the
+/// i-th verifier.
+pub fn extract_voters(mut verifiers: Vec<ValidatorExtend>, bitmap: &Bytes) -> Vec<(Address, u32)> {
+    verifiers.sort_by(|a, b| a.address.cmp(&b.address));
+
+    verifiers
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            bitmap
+                .get(i / 8)
+                .map(|byte| byte & (0x80 >> (i % 8)) != 0)
+                .unwrap_or(false)
+        })
+        .map(|(_, verifier)| (verifier.address, verifier.vote_weight))
+        .collect()
+}
+
+// `value * numerator / denominator` without overflowing on large values.
+fn share_of(value: u128, numerator: u128, denominator: u128) -> u128 {
+    value / denominator * numerator + value % denominator * numerator / denominator
+}
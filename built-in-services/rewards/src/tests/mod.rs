@@ -0,0 +1,531 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use asset::types::{Amount, MintPayload, TransferPayload};
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{Context, Dispatcher, ExecutorParams, ServiceResponse, Storage};
+use protocol::types::{
+    Address, Block, BlockHeader, Evidence, Hash, Hex, Metadata, Proof, Receipt, ServiceContext,
+    ServiceContextParams, SignedTransaction, ValidatorExtend,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{
+    GetRefundPayload, GetRewardPayload, InitGenesisPayload, RewardsConfig, ValidatorReward,
+};
+use crate::{extract_voters, rewards_address, RewardsService};
+
+const PROPOSER: &str = "0x755cdba6ae4f479f7164792b318b2a06c759833b";
+const VALIDATOR_A: &str = "0x0000000000000000000000000000000000000001";
+const VALIDATOR_B: &str = "0x0000000000000000000000000000000000000002";
+
+#[test]
+fn test_block_reward_halving() {
+    let config = RewardsConfig {
+        initial_reward: Amount(1000),
+        halving_interval: 100,
+        ..Default::default()
+    };
+
+    assert_eq!(config.block_reward(1), Amount(1000));
+    assert_eq!(config.block_reward(100), Amount(500));
+    assert_eq!(config.block_reward(250), Amount(250));
+    assert_eq!(config.block_reward(100 * 200), Amount(0));
+
+    let config = RewardsConfig {
+        initial_reward: Amount(1000),
+        ..Default::default()
+    };
+    assert_eq!(config.block_reward(100 * 200), Amount(1000));
+}
+
+#[test]
+fn test_extract_voters() {
+    let verifiers = vec![
+        mock_verifier("0x0000000000000000000000000000000000000003", 3),
+        mock_verifier(VALIDATOR_A, 1),
+        mock_verifier(VALIDATOR_B, 2),
+    ];
+
+    // Bits of the first and the third verifier ordered by address
+    let voters = extract_voters(verifiers, &Bytes::from(vec![0b1010_0000u8]));
+    assert_eq!(voters, vec![
+        (Address::from_hex(VALIDATOR_A).unwrap(), 1),
+        (
+            Address::from_hex("0x0000000000000000000000000000000000000003").unwrap(),
+            3
+        ),
+    ]);
+}
+
+#[test]
+fn test_distribute_rewards() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_rewards_service(dispatcher.clone(), RewardsConfig {
+        asset_id:         Hash::digest(Bytes::from("rewards")),
+        mint:             false,
+        initial_reward:   Amount(1000),
+        halving_interval: 0,
+        validator_share:  20,
+        collect_fee:      false,
+    });
+
+    // Verifier list is seen since height 2, before the proof of block 4
+    service.distribute_rewards(&mock_executor_params(2));
+    dispatcher.calls.borrow_mut().clear();
+
+    service.distribute_rewards(&mock_executor_params(5));
+
+    let transfers = dispatcher.transfers("asset.transfer");
+    assert_eq!(transfers, vec![
+        (Address::from_hex(VALIDATOR_A).unwrap(), Amount(50)),
+        (Address::from_hex(VALIDATOR_B).unwrap(), Amount(150)),
+        (Address::from_hex(PROPOSER).unwrap(), Amount(800)),
+    ]);
+    assert!(dispatcher
+        .calls
+        .borrow()
+        .iter()
+        .all(|(caller, _, _)| caller == &rewards_address()));
+
+    let event = service
+        .get_reward(
+            mock_context(cycles_limit, rewards_address()),
+            GetRewardPayload { height: 5 },
+        )
+        .succeed_data;
+    assert_eq!(event.block_reward, Amount(1000));
+    assert_eq!(event.proposer_reward, Amount(800));
+    assert_eq!(event.validator_rewards, vec![
+        ValidatorReward {
+            address: Address::from_hex(VALIDATOR_A).unwrap(),
+            amount:  Amount(50),
+        },
+        ValidatorReward {
+            address: Address::from_hex(VALIDATOR_B).unwrap(),
+            amount:  Amount(150),
+        },
+    ]);
+}
+
+#[test]
+fn test_skip_validator_share_after_verifier_list_change() {
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_rewards_service(dispatcher.clone(), RewardsConfig {
+        asset_id:         Hash::digest(Bytes::from("rewards")),
+        mint:             false,
+        initial_reward:   Amount(1000),
+        halving_interval: 0,
+        validator_share:  20,
+        collect_fee:      false,
+    });
+
+    service.distribute_rewards(&mock_executor_params(2));
+
+    // Proofs read by the next blocks may be signed by the old list, their
+    // bitmaps can't be matched against the new one.
+    *dispatcher.verifiers.borrow_mut() = vec![
+        mock_verifier(VALIDATOR_B, 3),
+        mock_verifier("0x0000000000000000000000000000000000000003", 1),
+    ];
+    for height in 5..8 {
+        dispatcher.calls.borrow_mut().clear();
+        service.distribute_rewards(&mock_executor_params(height));
+
+        assert_eq!(dispatcher.transfers("asset.transfer"), vec![(
+            Address::from_hex(PROPOSER).unwrap(),
+            Amount(1000)
+        )]);
+    }
+
+    dispatcher.calls.borrow_mut().clear();
+    service.distribute_rewards(&mock_executor_params(8));
+
+    assert_eq!(dispatcher.transfers("asset.transfer"), vec![
+        (Address::from_hex(VALIDATOR_B).unwrap(), Amount(150)),
+        (
+            Address::from_hex("0x0000000000000000000000000000000000000003").unwrap(),
+            Amount(50)
+        ),
+        (Address::from_hex(PROPOSER).unwrap(), Amount(800)),
+    ]);
+}
+
+#[test]
+fn test_collect_fee_and_mint() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let sender = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_rewards_service(dispatcher.clone(), RewardsConfig {
+        asset_id:         Hash::digest(Bytes::from("rewards")),
+        mint:             true,
+        initial_reward:   Amount(1000),
+        halving_interval: 0,
+        validator_share:  0,
+        collect_fee:      true,
+    });
+
+    // test max fee is prepaid and the unused part refunded
+    let ctx = mock_context(cycles_limit, sender.clone());
+    let res = service.prepay_fee(ctx.clone());
+    assert!(!res.is_error());
+
+    let (caller, method, payload) = dispatcher.calls.borrow()[0].clone();
+    let transfer: TransferPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(caller, sender);
+    assert_eq!(method, "asset.transfer");
+    assert_eq!(transfer.to, rewards_address());
+    assert_eq!(transfer.value, Amount(2 * cycles_limit as u128));
+
+    ctx.sub_cycles(21_000);
+    service.collect_fee(ctx);
+
+    let (caller, _, payload) = dispatcher.calls.borrow()[1].clone();
+    let transfer: TransferPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(caller, rewards_address());
+    assert_eq!(transfer.to, sender);
+    assert_eq!(transfer.value, Amount(2 * cycles_limit as u128 - 42_000));
+
+    let fees = service
+        .get_collected_fees(mock_context(cycles_limit, sender.clone()))
+        .succeed_data
+        .fees;
+    assert_eq!(fees, Amount(42_000));
+    dispatcher.calls.borrow_mut().clear();
+
+    // test block reward is minted into the pool, then paid with fees
+    service.distribute_rewards(&mock_executor_params(1));
+
+    let (_, method, payload) = dispatcher.calls.borrow()[0].clone();
+    let mint: MintPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(method, "asset.mint");
+    assert_eq!(mint.to, rewards_address());
+    assert_eq!(mint.value, Amount(1000));
+
+    assert_eq!(dispatcher.transfers("asset.transfer"), vec![(
+        Address::from_hex(PROPOSER).unwrap(),
+        Amount(43_000)
+    )]);
+
+    let fees = service
+        .get_collected_fees(mock_context(cycles_limit, sender))
+        .succeed_data
+        .fees;
+    assert_eq!(fees, Amount(0));
+}
+
+#[test]
+fn test_reject_unpaid_fee_and_claim_refund() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let sender = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_rewards_service(dispatcher.clone(), RewardsConfig {
+        asset_id:         Hash::digest(Bytes::from("rewards")),
+        mint:             false,
+        initial_reward:   Amount(0),
+        halving_interval: 0,
+        validator_share:  0,
+        collect_fee:      true,
+    });
+
+    // test transaction is rejected if its sender can't prepay
+    *dispatcher.fail_transfer.borrow_mut() = true;
+    let ctx = mock_context(cycles_limit, sender.clone());
+    let res = service.prepay_fee(ctx.clone());
+    assert_eq!(res.code, 102);
+
+    service.collect_fee(ctx);
+    assert!(dispatcher.calls.borrow().is_empty());
+
+    // test failed refund is owed to the sender
+    *dispatcher.fail_transfer.borrow_mut() = false;
+    let ctx = mock_context(cycles_limit, sender.clone());
+    let res = service.prepay_fee(ctx.clone());
+    assert!(!res.is_error());
+
+    *dispatcher.fail_transfer.borrow_mut() = true;
+    ctx.sub_cycles(21_000);
+    service.collect_fee(ctx);
+
+    let refund = Amount(2 * cycles_limit as u128 - 42_000);
+    let owed = service
+        .get_refund(
+            mock_context(cycles_limit, sender.clone()),
+            GetRefundPayload {
+                address: sender.clone(),
+            },
+        )
+        .succeed_data
+        .amount;
+    assert_eq!(owed, refund);
+
+    let res = service.claim_refund(mock_context(cycles_limit, sender.clone()));
+    assert_eq!(res.code, 104);
+
+    *dispatcher.fail_transfer.borrow_mut() = false;
+    let res = service.claim_refund(mock_context(cycles_limit, sender.clone()));
+    assert_eq!(res.succeed_data.amount, refund);
+
+    let (caller, _, payload) = dispatcher.calls.borrow().last().cloned().unwrap();
+    let transfer: TransferPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(caller, rewards_address());
+    assert_eq!(transfer.to, sender);
+    assert_eq!(transfer.value, refund);
+
+    let res = service.claim_refund(mock_context(cycles_limit, sender));
+    assert_eq!(res.code, 103);
+}
+
+// Records every dispatched write as (caller, service.method, payload).
+#[derive(Clone)]
+struct MockDispatcher {
+    calls:         Rc<RefCell<Vec<(Address, String, String)>>>,
+    verifiers:     Rc<RefCell<Vec<ValidatorExtend>>>,
+    fail_transfer: Rc<RefCell<bool>>,
+}
+
+impl Default for MockDispatcher {
+    fn default() -> Self {
+        MockDispatcher {
+            calls:         Default::default(),
+            verifiers:     Rc::new(RefCell::new(vec![
+                mock_verifier(VALIDATOR_B, 3),
+                mock_verifier(VALIDATOR_A, 1),
+            ])),
+            fail_transfer: Default::default(),
+        }
+    }
+}
+
+impl MockDispatcher {
+    fn transfers(&self, method: &str) -> Vec<(Address, Amount)> {
+        self.calls
+            .borrow()
+            .iter()
+            .filter(|(_, m, _)| m == method)
+            .map(|(_, _, payload)| {
+                let transfer: TransferPayload = serde_json::from_str(payload).unwrap();
+                (transfer.to, transfer.value)
+            })
+            .collect()
+    }
+}
+
+impl Dispatcher for MockDispatcher {
+    fn read(&self, context: ServiceContext) -> ServiceResponse<String> {
+        assert_eq!(context.get_service_name(), "metadata");
+
+        let metadata = mock_metadata(self.verifiers.borrow().clone());
+        ServiceResponse::<String>::from_succeed(serde_json::to_string(&metadata).unwrap())
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        let method = format!(
+            "{}.{}",
+            context.get_service_name(),
+            context.get_service_method()
+        );
+        if method == "asset.transfer" && *self.fail_transfer.borrow() {
+            return ServiceResponse::<String>::from_error(105, "insufficient balance".to_owned());
+        }
+
+        self.calls.borrow_mut().push((
+            context.get_caller(),
+            method,
+            context.get_payload().to_owned(),
+        ));
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+}
+
+fn new_rewards_service(
+    dispatcher: MockDispatcher,
+    config: InitGenesisPayload,
+) -> RewardsService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        MockDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {
+        bitmap: Bytes::from(vec![0b1100_0000u8]),
+    }));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db), dispatcher);
+
+    let mut service = RewardsService::new(sdk);
+    service.init_genesis(config);
+    service
+}
+
+fn mock_verifier(address: &str, vote_weight: u32) -> ValidatorExtend {
+    ValidatorExtend {
+        bls_pub_key: Hex::from_string("0x00".to_owned()).unwrap(),
+        address: Address::from_hex(address).unwrap(),
+        propose_weight: 1,
+        vote_weight,
+    }
+}
+
+fn mock_metadata(verifier_list: Vec<ValidatorExtend>) -> Metadata {
+    Metadata {
+        verifier_list,
+        ..Default::default()
+    }
+}
+
+fn mock_block(height: u64, bitmap: Bytes) -> Block {
+    let header = BlockHeader {
+        chain_id: Hash::default(),
+        height,
+        exec_height: height - 1,
+        prev_hash: Hash::default(),
+        timestamp: 0,
+        order_root: Hash::default(),
+        order_signed_transactions_hash: Hash::default(),
+        confirm_root: vec![],
+        state_root: Hash::default(),
+        receipt_root: vec![],
+        cycles_used: vec![],
+        proposer: Address::from_hex(PROPOSER).unwrap(),
+        proof: Proof {
+            height: height - 1,
+            round: 0,
+            block_hash: Hash::default(),
+            signature: Bytes::new(),
+            bitmap,
+        },
+        validator_version: 0,
+        validators: vec![],
+    };
+
+    Block {
+        header,
+        ordered_tx_hashes: vec![],
+    }
+}
+
+fn mock_executor_params(height: u64) -> ExecutorParams {
+    ExecutorParams {
+        state_root: Hash::default(),
+        height,
+        timestamp: 0,
+        cycles_limit: 1024 * 1024,
+        proposer: Address::from_hex(PROPOSER).unwrap(),
+    }
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 2,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height: 1,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage {
+    bitmap: Bytes,
+}
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _ctx: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _ctx: Context, _: u64, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _ctx: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        _: Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block(&self, _ctx: Context, height: u64) -> ProtocolResult<Option<Block>> {
+        Ok(Some(mock_block(height, self.bitmap.clone())))
+    }
+
+    async fn get_receipt_by_hash(&self, _ctx: Context, _: Hash) -> ProtocolResult<Option<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _ctx: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,89 @@
+use muta_codec_derive::RlpFixedCodec;
+use serde::{Deserialize, Serialize};
+
+use asset::types::Amount;
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Address, Bytes, Hash, ValidatorExtend};
+use protocol::ProtocolResult;
+
+/// Payload
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RewardsConfig {
+    /// Asset paid as reward and charged as fee.
+    pub asset_id:         Hash,
+    /// Mint rewards, this service must be the issuer of the asset. Otherwise
+    /// rewards are transferred from the pool owned by this service.
+    pub mint:             bool,
+    pub initial_reward:   Amount,
+    /// Reward halves every `halving_interval` blocks, 0 means never.
+    pub halving_interval: u64,
+    /// Percentage of the payout shared by voters of the previous block.
+    pub validator_share:  u8,
+    /// Charge `cycles_used * cycles_price` of every transaction. The sender
+    /// prepays `cycles_limit * cycles_price` and is refunded the rest, a
+    /// transaction whose sender can't prepay is rejected.
+    pub collect_fee:      bool,
+}
+
+impl RewardsConfig {
+    pub fn block_reward(&self, height: u64) -> Amount {
+        if self.halving_interval == 0 {
+            return self.initial_reward;
+        }
+
+        let halvings = height / self.halving_interval;
+        if halvings >= 128 {
+            Amount(0)
+        } else {
+            Amount(self.initial_reward.0 >> halvings)
+        }
+    }
+}
+
+pub type InitGenesisPayload = RewardsConfig;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetRewardPayload {
+    pub height: u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GetCollectedFeesResponse {
+    pub fees: Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetRefundPayload {
+    pub address: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct RefundResponse {
+    pub amount: Amount,
+}
+
+/// Verifier list seen by the latest `hook_after_`, and the first height it
+/// was seen at.
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObservedVerifiers {
+    pub verifier_list: Vec<ValidatorExtend>,
+    pub since:         u64,
+}
+
+/// Event
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidatorReward {
+    pub address: Address,
+    pub amount:  Amount,
+}
+
+/// Payout of a block, only successful payments are recorded.
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RewardEvent {
+    pub height:            u64,
+    pub block_reward:      Amount,
+    pub fees:              Amount,
+    pub proposer:          Address,
+    pub proposer_reward:   Amount,
+    pub validator_rewards: Vec<ValidatorReward>,
+}
@@ -0,0 +1,585 @@
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use binding_macro::{cycles, genesis, hook_after, service};
+use serde::Serialize;
+
+use asset::types::{Amount, TransferPayload};
+use common_crypto::{
+    BlsCommonReference, BlsPublicKey, BlsSignature, BlsSignatureVerify, HashValue,
+};
+use metadata::types::UpdateVerifierListPayload;
+use metadata::VERIFIER_LIST_UPDATER;
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap, StoreOrderedMap};
+use protocol::types::{
+    Address, Bytes, Hash, Hex, ServiceContext, ServiceContextParams, ValidatorExtend,
+};
+
+use crate::types::{
+    BondEvent, BondPayload, Candidate, CandidateRank, Delegation, GetCandidatePayload,
+    GetDelegationPayload, GetUnbondingsPayload, InitGenesisPayload, RegisterCandidatePayload,
+    StakingConfig, UnbondEvent, UnbondPayload, Unbonding, UnbondingList, ValidatorList,
+    WithdrawEvent, WithdrawResponse,
+};
+
+const STAKING_CONFIG_KEY: &str = "staking_config";
+const VALIDATORS_KEY: &str = "validators";
+/// Weight of the validator with the most stake, the others are scaled down by
+/// their stake.
+const MAX_VALIDATOR_WEIGHT: u128 = 100;
+const BLS_POP_COMMON_REF: &str = "muta-staking";
+
+pub struct StakingService<SDK> {
+    sdk:         SDK,
+    candidates:  Box<dyn StoreMap<Address, Candidate>>,
+    // Eligible candidates only, to their bls public keys, so that an election
+    // reads the top of the ranking instead of every candidate ever registered.
+    ranking:     Box<dyn StoreOrderedMap<CandidateRank, Hex>>,
+    delegations: Box<dyn StoreMap<Hash, Delegation>>,
+    unbondings:  Box<dyn StoreMap<Address, UnbondingList>>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> StakingService<SDK> {
+    pub fn new(mut sdk: SDK) -> Self {
+        let candidates: Box<dyn StoreMap<Address, Candidate>> =
+            sdk.alloc_or_recover_map("candidates");
+        let ranking: Box<dyn StoreOrderedMap<CandidateRank, Hex>> =
+            sdk.alloc_or_recover_ordered_map("ranking");
+        let delegations: Box<dyn StoreMap<Hash, Delegation>> =
+            sdk.alloc_or_recover_map("delegations");
+        let unbondings: Box<dyn StoreMap<Address, UnbondingList>> =
+            sdk.alloc_or_recover_map("unbondings");
+
+        Self {
+            sdk,
+            candidates,
+            ranking,
+            delegations,
+            unbondings,
+        }
+    }
+
+    #[genesis]
+    fn init_genesis(&mut self, payload: InitGenesisPayload) {
+        assert!(payload.epoch_length > 0, "epoch length should be positive");
+        assert!(
+            payload.min_validator_count > 0,
+            "min validator count should be positive"
+        );
+        assert!(
+            payload.min_validator_count <= payload.validator_count,
+            "min validator count should not exceed validator count"
+        );
+
+        let config = payload.config();
+        self.sdk
+            .set_value(STAKING_CONFIG_KEY.to_owned(), config.clone());
+
+        // Genesis candidates bond to themselves, no proof of possession is
+        // checked since the keys come from the chain spec.
+        for genesis in payload.candidates.into_iter() {
+            assert!(
+                decode_bls_pub_key(&genesis.bls_pub_key).is_ok(),
+                "invalid bls public key of genesis candidate"
+            );
+            assert!(
+                !self.candidates.contains(&genesis.address),
+                "duplicate genesis candidate"
+            );
+
+            let key = delegation_key(&genesis.address, &genesis.address);
+            self.delegations.insert(key, Delegation {
+                delegator: genesis.address.clone(),
+                candidate: genesis.address.clone(),
+                amount:    genesis.stake,
+            });
+            self._save_candidate(&config, None, Candidate {
+                address:     genesis.address,
+                bls_pub_key: genesis.bls_pub_key,
+                stake:       genesis.stake,
+                self_stake:  genesis.stake,
+                active:      true,
+            });
+        }
+    }
+
+    #[hook_after]
+    fn elect_validators(&mut self, params: &ExecutorParams) {
+        let config: StakingConfig = match self.sdk.get_value(&STAKING_CONFIG_KEY.to_owned()) {
+            Some(config) => config,
+            None => return,
+        };
+
+        if params.height % config.epoch_length != 0 {
+            return;
+        }
+
+        // Keep the current verifier list until enough candidates are eligible.
+        let validators = self._rank_candidates(config.validator_count);
+        if validators.len() < config.min_validator_count as usize {
+            return;
+        }
+
+        let payload = match serde_json::to_string(&UpdateVerifierListPayload {
+            verifier_list: validators.clone(),
+        }) {
+            Ok(payload) => payload,
+            Err(_) => return,
+        };
+
+        let ctx = self._service_context(params);
+        let resp = self
+            .sdk
+            .write(&ctx, None, "metadata", "update_verifier_list", &payload);
+
+        if !resp.is_error() {
+            self.sdk
+                .set_value(VALIDATORS_KEY.to_owned(), ValidatorList { validators });
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_config(&self, ctx: ServiceContext) -> ServiceResponse<StakingConfig> {
+        ServiceResponse::<StakingConfig>::from_succeed(self._config())
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_validators(&self, ctx: ServiceContext) -> ServiceResponse<ValidatorList> {
+        let validators: ValidatorList = self
+            .sdk
+            .get_value(&VALIDATORS_KEY.to_owned())
+            .unwrap_or_default();
+
+        ServiceResponse::<ValidatorList>::from_succeed(validators)
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_candidate(
+        &self,
+        ctx: ServiceContext,
+        payload: GetCandidatePayload,
+    ) -> ServiceResponse<Candidate> {
+        if let Some(candidate) = self.candidates.get(&payload.address) {
+            ServiceResponse::<Candidate>::from_succeed(candidate)
+        } else {
+            ServiceResponse::<Candidate>::from_error(103, "candidate not existed".to_owned())
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_delegation(
+        &self,
+        ctx: ServiceContext,
+        payload: GetDelegationPayload,
+    ) -> ServiceResponse<Delegation> {
+        let key = delegation_key(&payload.delegator, &payload.candidate);
+
+        if let Some(delegation) = self.delegations.get(&key) {
+            ServiceResponse::<Delegation>::from_succeed(delegation)
+        } else {
+            ServiceResponse::<Delegation>::from_error(107, "delegation not existed".to_owned())
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_unbondings(
+        &self,
+        ctx: ServiceContext,
+        payload: GetUnbondingsPayload,
+    ) -> ServiceResponse<UnbondingList> {
+        let unbondings = self.unbondings.get(&payload.delegator).unwrap_or_default();
+
+        ServiceResponse::<UnbondingList>::from_succeed(unbondings)
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn register_candidate(
+        &mut self,
+        ctx: ServiceContext,
+        payload: RegisterCandidatePayload,
+    ) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+
+        // A deregistered candidate registers again to be elected, it may
+        // rotate its key meanwhile.
+        let old = self.candidates.get(&caller);
+        if old.as_ref().map_or(false, |candidate| candidate.active) {
+            return ServiceResponse::<()>::from_error(101, "candidate existed".to_owned());
+        }
+
+        // An elected key which can't be decoded halts consensus, and one
+        // without proof of possession allows rogue key attack on aggregated
+        // signatures.
+        if let Err((code, msg)) =
+            verify_bls_pub_key(&caller, &payload.bls_pub_key, &payload.bls_pop)
+        {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        let (stake, self_stake) = old.as_ref().map_or((Amount(0), Amount(0)), |candidate| {
+            (candidate.stake, candidate.self_stake)
+        });
+        let config = self._config();
+        self._save_candidate(&config, old, Candidate {
+            address: caller,
+            bls_pub_key: payload.bls_pub_key,
+            stake,
+            self_stake,
+            active: true,
+        });
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn deregister_candidate(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        let caller = ctx.get_caller();
+
+        let old = match self.candidates.get(&caller) {
+            Some(candidate) if candidate.active => candidate,
+            _ => return ServiceResponse::<()>::from_error(103, "candidate not existed".to_owned()),
+        };
+
+        // Stakes stay bonded until each delegator unbonds.
+        let mut candidate = old.clone();
+        candidate.active = false;
+        let config = self._config();
+        self._save_candidate(&config, Some(old), candidate);
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn bond(&mut self, ctx: ServiceContext, payload: BondPayload) -> ServiceResponse<()> {
+        let delegator = ctx.get_caller();
+        let amount = payload.amount;
+
+        if amount.is_zero() {
+            return ServiceResponse::<()>::from_error(102, "amount should be positive".to_owned());
+        }
+
+        let old = match self.candidates.get(&payload.candidate) {
+            Some(candidate) if candidate.active => candidate,
+            _ => return ServiceResponse::<()>::from_error(103, "candidate not existed".to_owned()),
+        };
+        let mut candidate = old.clone();
+
+        let key = delegation_key(&delegator, &payload.candidate);
+        let mut delegation = self.delegations.get(&key).unwrap_or_else(|| Delegation {
+            delegator: delegator.clone(),
+            candidate: payload.candidate.clone(),
+            amount:    Amount(0),
+        });
+
+        match (
+            candidate.stake.checked_add(amount),
+            delegation.amount.checked_add(amount),
+        ) {
+            (Some(stake), Some(bonded)) => {
+                candidate.stake = stake;
+                delegation.amount = bonded;
+            }
+            _ => return ServiceResponse::<()>::from_error(105, "stake overflow".to_owned()),
+        }
+        if delegator == payload.candidate {
+            candidate.self_stake = delegation.amount;
+        }
+
+        // Move stake into the pool owned by this service before touching state.
+        let config = self._config();
+        if let Err((code, msg)) =
+            self._transfer_asset(&ctx, config.asset_id, staking_address(), amount)
+        {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        self._save_candidate(&config, Some(old), candidate);
+        self.delegations.insert(key, delegation);
+
+        if let Err((code, msg)) = emit_event(&ctx, &BondEvent {
+            delegator,
+            candidate: payload.candidate,
+            amount,
+        }) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn unbond(&mut self, ctx: ServiceContext, payload: UnbondPayload) -> ServiceResponse<()> {
+        let delegator = ctx.get_caller();
+        let amount = payload.amount;
+
+        if amount.is_zero() {
+            return ServiceResponse::<()>::from_error(102, "amount should be positive".to_owned());
+        }
+
+        let key = delegation_key(&delegator, &payload.candidate);
+        let mut delegation = if let Some(delegation) = self.delegations.get(&key) {
+            delegation
+        } else {
+            return ServiceResponse::<()>::from_error(107, "delegation not existed".to_owned());
+        };
+        let old = self
+            .candidates
+            .get(&payload.candidate)
+            .expect("candidate of delegation should not be none");
+        let mut candidate = old.clone();
+
+        match (
+            candidate.stake.checked_sub(amount),
+            delegation.amount.checked_sub(amount),
+        ) {
+            (Some(stake), Some(bonded)) => {
+                candidate.stake = stake;
+                delegation.amount = bonded;
+            }
+            _ => {
+                return ServiceResponse::<()>::from_error(
+                    108,
+                    "insufficient bonded amount".to_owned(),
+                )
+            }
+        }
+        if delegator == payload.candidate {
+            candidate.self_stake = delegation.amount;
+        }
+
+        let config = self._config();
+        self._save_candidate(&config, Some(old), candidate);
+        if delegation.amount.is_zero() {
+            self.delegations.remove(&key);
+        } else {
+            self.delegations.insert(key, delegation);
+        }
+
+        let unlock_height = ctx.get_current_height() + config.unbond_period;
+        let mut unbondings = self.unbondings.get(&delegator).unwrap_or_default();
+        unbondings.unbondings.push(Unbonding {
+            candidate: payload.candidate.clone(),
+            amount,
+            unlock_height,
+        });
+        self.unbondings.insert(delegator.clone(), unbondings);
+
+        if let Err((code, msg)) = emit_event(&ctx, &UnbondEvent {
+            delegator,
+            candidate: payload.candidate,
+            amount,
+            unlock_height,
+        }) {
+            return ServiceResponse::<()>::from_error(code, msg);
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn withdraw(&mut self, ctx: ServiceContext) -> ServiceResponse<WithdrawResponse> {
+        let delegator = ctx.get_caller();
+        let height = ctx.get_current_height();
+
+        let (matured, locked): (Vec<Unbonding>, Vec<Unbonding>) = self
+            .unbondings
+            .get(&delegator)
+            .unwrap_or_default()
+            .unbondings
+            .into_iter()
+            .partition(|unbonding| unbonding.unlock_height <= height);
+
+        let mut amount = Amount(0);
+        for unbonding in matured.iter() {
+            amount = match amount.checked_add(unbonding.amount) {
+                Some(amount) => amount,
+                None => {
+                    return ServiceResponse::<WithdrawResponse>::from_error(
+                        105,
+                        "stake overflow".to_owned(),
+                    )
+                }
+            };
+        }
+
+        if amount.is_zero() {
+            return ServiceResponse::<WithdrawResponse>::from_error(
+                109,
+                "no matured unbonding".to_owned(),
+            );
+        }
+
+        let pool_ctx = ServiceContext::with_caller(&ctx, staking_address());
+        let config = self._config();
+        if let Err((code, msg)) =
+            self._transfer_asset(&pool_ctx, config.asset_id, delegator.clone(), amount)
+        {
+            return ServiceResponse::<WithdrawResponse>::from_error(code, msg);
+        }
+
+        if locked.is_empty() {
+            self.unbondings.remove(&delegator);
+        } else {
+            self.unbondings
+                .insert(delegator.clone(), UnbondingList { unbondings: locked });
+        }
+
+        if let Err((code, msg)) = emit_event(&ctx, &WithdrawEvent { delegator, amount }) {
+            return ServiceResponse::<WithdrawResponse>::from_error(code, msg);
+        }
+
+        ServiceResponse::<WithdrawResponse>::from_succeed(WithdrawResponse { amount })
+    }
+
+    fn _config(&self) -> StakingConfig {
+        self.sdk
+            .get_value(&STAKING_CONFIG_KEY.to_owned())
+            .expect("staking config should not be none")
+    }
+
+    fn _transfer_asset(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: Hash,
+        to: Address,
+        value: Amount,
+    ) -> Result<(), (u64, String)> {
+        let payload = serde_json::to_string(&TransferPayload {
+            asset_id,
+            to,
+            value,
+        })
+        .map_err(|e| (104, format!("{:?}", e)))?;
+
+        let resp = self.sdk.write(ctx, None, "asset", "transfer", &payload);
+        if resp.is_error() {
+            return Err((
+                106,
+                format!("transfer stake failed {:?}", resp.error_message),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Keeps the ranking in step with the candidate, `old` is the stored one
+    // whose rank is replaced.
+    fn _save_candidate(
+        &mut self,
+        config: &StakingConfig,
+        old: Option<Candidate>,
+        candidate: Candidate,
+    ) {
+        if let Some(old) = old {
+            self.ranking.remove(&old.rank());
+        }
+        if candidate.eligible(config.min_self_stake) {
+            self.ranking
+                .insert(candidate.rank(), candidate.bls_pub_key.clone());
+        }
+
+        self.candidates.insert(candidate.address.clone(), candidate);
+    }
+
+    // Top eligible candidates, in the order of the ranking.
+    fn _rank_candidates(&self, count: u32) -> Vec<ValidatorExtend> {
+        let ranked = self.ranking.page(0, count);
+
+        let top_stake = match ranked.first() {
+            Some((rank, _)) => rank.stake.0,
+            None => return vec![],
+        };
+
+        ranked
+            .into_iter()
+            .map(|(rank, bls_pub_key)| {
+                let weight =
+                    (rank.stake.0.saturating_mul(MAX_VALIDATOR_WEIGHT) / top_stake).max(1) as u32;
+
+                ValidatorExtend {
+                    bls_pub_key,
+                    address: rank.address,
+                    propose_weight: weight,
+                    vote_weight: weight,
+                }
+            })
+            .collect()
+    }
+
+    fn _service_context(&self, params: &ExecutorParams) -> ServiceContext {
+        ServiceContext::new(ServiceContextParams {
+            tx_hash:         None,
+            nonce:           None,
+            cycles_limit:    params.cycles_limit,
+            cycles_price:    1,
+            cycles_used:     Rc::new(RefCell::new(0)),
+            caller:          staking_address(),
+            height:          params.height,
+            timestamp:       params.timestamp,
+            service_name:    VERIFIER_LIST_UPDATER.to_owned(),
+            service_method:  "elect_validators".to_owned(),
+            service_payload: String::new(),
+            extra:           None,
+            events:          Rc::new(RefCell::new(vec![])),
+        })
+    }
+}
+
+/// Address of the pool holding all stakes, it is also the caller when this
+/// service updates the verifier list in `MetadataService`.
+pub fn staking_address() -> Address {
+    Address::from_service_name(VERIFIER_LIST_UPDATER)
+}
+
+fn delegation_key(delegator: &Address, candidate: &Address) -> Hash {
+    let mut bytes = delegator.as_bytes().to_vec();
+    bytes.extend_from_slice(candidate.as_bytes().as_ref());
+
+    Hash::digest(Bytes::from(bytes))
+}
+
+fn decode_bls_pub_key(pub_key: &Hex) -> Result<BlsPublicKey, (u64, String)> {
+    hex::decode(pub_key.as_string_trim0x())
+        .ok()
+        .and_then(|bytes| BlsPublicKey::try_from(bytes.as_ref()).ok())
+        .ok_or_else(|| (110, "invalid bls public key".to_owned()))
+}
+
+fn verify_bls_pub_key(caller: &Address, pub_key: &Hex, pop: &Hex) -> Result<(), (u64, String)> {
+    let pub_key = decode_bls_pub_key(pub_key)?;
+    let pop = hex::decode(pop.as_string_trim0x())
+        .ok()
+        .and_then(|bytes| BlsSignature::try_from(bytes.as_ref()).ok())
+        .ok_or_else(|| (111, "invalid bls proof of possession".to_owned()))?;
+
+    let caller_hash = Hash::digest(caller.as_bytes());
+    let msg = HashValue::try_from(caller_hash.as_bytes().as_ref())
+        .map_err(|e| (111, format!("{:?}", e)))?;
+    let common_ref: BlsCommonReference = BLS_POP_COMMON_REF.into();
+
+    pop.verify(&msg, &pub_key, &common_ref)
+        .map_err(|_| (111, "invalid bls proof of possession".to_owned()))
+}
+
+fn emit_event<E: Serialize>(ctx: &ServiceContext, event: &E) -> Result<(), (u64, String)> {
+    let event_str = serde_json::to_string(event).map_err(|e| (104, format!("{:?}", e)))?;
+    ctx.emit_event(event_str);
+
+    Ok(())
+}
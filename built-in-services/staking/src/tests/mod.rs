@@ -0,0 +1,556 @@
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use asset::types::{Amount, TransferPayload};
+use common_crypto::{BlsPrivateKey, HashValue, PrivateKey, Signature};
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use metadata::types::UpdateVerifierListPayload;
+use protocol::traits::{Context, Dispatcher, ExecutorParams, ServiceResponse, Storage};
+use protocol::types::{
+    Address, Block, Evidence, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{
+    BondPayload, GenesisCandidate, GetCandidatePayload, GetDelegationPayload, GetUnbondingsPayload,
+    InitGenesisPayload, RegisterCandidatePayload, UnbondPayload, Unbonding,
+};
+use crate::{staking_address, StakingService};
+
+const BLS_PRIV_KEY: &str = "0x000000000000000000000000000000001abd6ffdb44427d9e1fcb6f84e7fe7d98f2b5b205b30a94992ec24d94bb0c970";
+const BLS_PUB_KEY: &str = "0x041054fe9a65be0891094ed37fb3655e3ffb12353bc0a1b4f8673b52ad65d1ca481780cf7e988eb8dcdc05d8352f03605b0d11afb2525b3f1b55ec694509248bcfead39cbb292725d710e2a509c77ed051d1d49e15e429cf6d12b9be7c02179612";
+
+#[test]
+fn test_bond_and_unbond() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let candidate = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let delegator = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_staking_service(dispatcher.clone());
+
+    let res = service.bond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        BondPayload {
+            candidate: candidate.clone(),
+            amount:    Amount(100),
+        },
+    );
+    assert_eq!(res.code, 103);
+
+    register(&mut service, candidate.clone());
+    let res = service.register_candidate(
+        mock_context(cycles_limit, candidate.clone(), 1),
+        register_payload(&candidate),
+    );
+    assert_eq!(res.code, 101);
+
+    // test bond moves stake into the pool
+    let res = service.bond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        BondPayload {
+            candidate: candidate.clone(),
+            amount:    Amount(100),
+        },
+    );
+    assert!(!res.is_error());
+
+    let (caller, _, payload) = dispatcher.calls.borrow().last().cloned().unwrap();
+    let transfer: TransferPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(caller, delegator);
+    assert_eq!(transfer.to, staking_address());
+    assert_eq!(transfer.value, Amount(100));
+
+    // test failed transfer leaves stake untouched
+    *dispatcher.fail_transfer.borrow_mut() = true;
+    let res = service.bond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        BondPayload {
+            candidate: candidate.clone(),
+            amount:    Amount(100),
+        },
+    );
+    assert_eq!(res.code, 106);
+    *dispatcher.fail_transfer.borrow_mut() = false;
+
+    let stake = service
+        .get_candidate(
+            mock_context(cycles_limit, delegator.clone(), 1),
+            GetCandidatePayload {
+                address: candidate.clone(),
+            },
+        )
+        .succeed_data
+        .stake;
+    assert_eq!(stake, Amount(100));
+
+    // test unbond
+    let res = service.unbond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        UnbondPayload {
+            candidate: candidate.clone(),
+            amount:    Amount(101),
+        },
+    );
+    assert_eq!(res.code, 108);
+
+    let res = service.unbond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        UnbondPayload {
+            candidate: candidate.clone(),
+            amount:    Amount(40),
+        },
+    );
+    assert!(!res.is_error());
+
+    let delegation = service.get_delegation(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        GetDelegationPayload {
+            delegator: delegator.clone(),
+            candidate: candidate.clone(),
+        },
+    );
+    assert_eq!(delegation.succeed_data.amount, Amount(60));
+
+    let unbondings = service
+        .get_unbondings(
+            mock_context(cycles_limit, delegator.clone(), 1),
+            GetUnbondingsPayload {
+                delegator: delegator.clone(),
+            },
+        )
+        .succeed_data
+        .unbondings;
+    assert_eq!(unbondings, vec![Unbonding {
+        candidate,
+        amount: Amount(40),
+        unlock_height: 11,
+    }]);
+
+    // test withdraw only after the lock period
+    let res = service.withdraw(mock_context(cycles_limit, delegator.clone(), 10));
+    assert_eq!(res.code, 109);
+
+    let res = service.withdraw(mock_context(cycles_limit, delegator.clone(), 11));
+    assert_eq!(res.succeed_data.amount, Amount(40));
+
+    let (caller, _, payload) = dispatcher.calls.borrow().last().cloned().unwrap();
+    let transfer: TransferPayload = serde_json::from_str(&payload).unwrap();
+    assert_eq!(caller, staking_address());
+    assert_eq!(transfer.to, delegator);
+    assert_eq!(transfer.value, Amount(40));
+
+    let res = service.withdraw(mock_context(cycles_limit, delegator, 12));
+    assert_eq!(res.code, 109);
+}
+
+#[test]
+fn test_register_invalid_bls_pub_key() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let candidate = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let other = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+
+    let mut service = new_staking_service(MockDispatcher::default());
+
+    // test malformed key
+    let mut payload = register_payload(&candidate);
+    payload.bls_pub_key = Hex::from_string("0x0102030405".to_owned()).unwrap();
+    let res = service.register_candidate(mock_context(cycles_limit, candidate.clone(), 1), payload);
+    assert_eq!(res.code, 110);
+
+    // test proof of possession signed for another caller
+    let res = service.register_candidate(
+        mock_context(cycles_limit, candidate.clone(), 1),
+        register_payload(&other),
+    );
+    assert_eq!(res.code, 111);
+
+    let res = service.get_candidate(
+        mock_context(cycles_limit, candidate.clone(), 1),
+        GetCandidatePayload { address: candidate },
+    );
+    assert_eq!(res.code, 103);
+}
+
+#[test]
+fn test_elect_validators() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let candidates = vec![
+        Address::from_hex("0x0000000000000000000000000000000000000001").unwrap(),
+        Address::from_hex("0x0000000000000000000000000000000000000002").unwrap(),
+        Address::from_hex("0x0000000000000000000000000000000000000003").unwrap(),
+    ];
+
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_staking_service(dispatcher.clone());
+
+    // test no update without stake
+    service.elect_validators(&mock_executor_params(10));
+    assert!(dispatcher.calls.borrow().is_empty());
+
+    for (candidate, stake) in candidates.iter().zip([400u128, 100, 200].iter()) {
+        register(&mut service, candidate.clone());
+        let res = service.bond(
+            mock_context(cycles_limit, candidate.clone(), 1),
+            BondPayload {
+                candidate: candidate.clone(),
+                amount:    Amount(*stake),
+            },
+        );
+        assert!(!res.is_error());
+    }
+    dispatcher.calls.borrow_mut().clear();
+
+    // test not an epoch boundary
+    service.elect_validators(&mock_executor_params(11));
+    assert!(dispatcher.calls.borrow().is_empty());
+
+    service.elect_validators(&mock_executor_params(20));
+    let (caller, method, payload) = dispatcher.calls.borrow().last().cloned().unwrap();
+    assert_eq!(caller, staking_address());
+    assert_eq!(method, "metadata.update_verifier_list");
+
+    let verifier_list = serde_json::from_str::<UpdateVerifierListPayload>(&payload)
+        .unwrap()
+        .verifier_list;
+    let elected = verifier_list
+        .iter()
+        .map(|v| (v.address.clone(), v.propose_weight, v.vote_weight))
+        .collect::<Vec<_>>();
+    assert_eq!(elected, vec![
+        (candidates[0].clone(), 100, 100),
+        (candidates[2].clone(), 50, 50)
+    ]);
+
+    let validators = service
+        .get_validators(mock_context(cycles_limit, candidates[1].clone(), 20))
+        .succeed_data
+        .validators;
+    assert_eq!(validators, verifier_list);
+}
+
+#[test]
+fn test_elect_genesis_and_deregistered_candidates() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let candidates = vec![
+        Address::from_hex("0x0000000000000000000000000000000000000001").unwrap(),
+        Address::from_hex("0x0000000000000000000000000000000000000002").unwrap(),
+        Address::from_hex("0x0000000000000000000000000000000000000003").unwrap(),
+    ];
+    let delegator = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_staking_service_with_candidates(dispatcher.clone(), vec![
+        genesis_candidate(candidates[0].clone(), 100),
+        genesis_candidate(candidates[1].clone(), 200),
+    ]);
+    let elect = |service: &mut StakingService<_>, height| {
+        dispatcher.calls.borrow_mut().clear();
+        service.elect_validators(&mock_executor_params(height));
+
+        dispatcher.calls.borrow().last().map(|(_, _, payload)| {
+            serde_json::from_str::<UpdateVerifierListPayload>(payload)
+                .unwrap()
+                .verifier_list
+                .into_iter()
+                .map(|v| (v.address, v.propose_weight))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // test genesis candidates are elected
+    assert_eq!(
+        elect(&mut service, 10),
+        Some(vec![
+            (candidates[1].clone(), 100),
+            (candidates[0].clone(), 50)
+        ])
+    );
+
+    // test candidate without enough self stake is not elected
+    register(&mut service, candidates[2].clone());
+    let res = service.bond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        BondPayload {
+            candidate: candidates[2].clone(),
+            amount:    Amount(1000),
+        },
+    );
+    assert!(!res.is_error());
+    assert_eq!(
+        elect(&mut service, 20),
+        Some(vec![
+            (candidates[1].clone(), 100),
+            (candidates[0].clone(), 50)
+        ])
+    );
+
+    // test too few eligible candidates keeps the current validators
+    let res = service.deregister_candidate(mock_context(cycles_limit, candidates[1].clone(), 1));
+    assert!(!res.is_error());
+    let res = service.deregister_candidate(mock_context(cycles_limit, candidates[1].clone(), 1));
+    assert_eq!(res.code, 103);
+    let res = service.bond(
+        mock_context(cycles_limit, delegator.clone(), 1),
+        BondPayload {
+            candidate: candidates[1].clone(),
+            amount:    Amount(100),
+        },
+    );
+    assert_eq!(res.code, 103);
+    assert_eq!(elect(&mut service, 30), None);
+
+    // test delegators of a deregistered candidate can still unbond
+    let res = service.unbond(
+        mock_context(cycles_limit, candidates[1].clone(), 1),
+        UnbondPayload {
+            candidate: candidates[1].clone(),
+            amount:    Amount(50),
+        },
+    );
+    assert!(!res.is_error());
+
+    // test registering again brings the remaining stake back
+    register(&mut service, candidates[1].clone());
+    let stake = service
+        .get_candidate(
+            mock_context(cycles_limit, delegator, 1),
+            GetCandidatePayload {
+                address: candidates[1].clone(),
+            },
+        )
+        .succeed_data
+        .self_stake;
+    assert_eq!(stake, Amount(150));
+    assert_eq!(
+        elect(&mut service, 40),
+        Some(vec![
+            (candidates[1].clone(), 100),
+            (candidates[0].clone(), 66)
+        ])
+    );
+}
+
+// Records every dispatched write as (caller, service.method, payload).
+#[derive(Clone, Default)]
+struct MockDispatcher {
+    calls:         Rc<RefCell<Vec<(Address, String, String)>>>,
+    fail_transfer: Rc<RefCell<bool>>,
+}
+
+impl Dispatcher for MockDispatcher {
+    fn read(&self, _context: ServiceContext) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        let method = format!(
+            "{}.{}",
+            context.get_service_name(),
+            context.get_service_method()
+        );
+        if method == "asset.transfer" && *self.fail_transfer.borrow() {
+            return ServiceResponse::<String>::from_error(105, "insufficient balance".to_owned());
+        }
+
+        self.calls.borrow_mut().push((
+            context.get_caller(),
+            method,
+            context.get_payload().to_owned(),
+        ));
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+}
+
+fn new_staking_service(
+    dispatcher: MockDispatcher,
+) -> StakingService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        MockDispatcher,
+    >,
+> {
+    new_staking_service_with_candidates(dispatcher, vec![])
+}
+
+fn new_staking_service_with_candidates(
+    dispatcher: MockDispatcher,
+    candidates: Vec<GenesisCandidate>,
+) -> StakingService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        MockDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db), dispatcher);
+
+    let mut service = StakingService::new(sdk);
+    service.init_genesis(InitGenesisPayload {
+        asset_id: Hash::digest(Bytes::from("staking")),
+        epoch_length: 10,
+        validator_count: 2,
+        min_validator_count: 2,
+        min_self_stake: Amount(50),
+        unbond_period: 10,
+        candidates,
+    });
+    service
+}
+
+fn genesis_candidate(address: Address, stake: u128) -> GenesisCandidate {
+    GenesisCandidate {
+        address,
+        bls_pub_key: Hex::from_string(BLS_PUB_KEY.to_owned()).unwrap(),
+        stake: Amount(stake),
+    }
+}
+
+fn register<SDK: protocol::traits::ServiceSDK>(
+    service: &mut StakingService<SDK>,
+    candidate: Address,
+) {
+    let payload = register_payload(&candidate);
+    let res = service.register_candidate(mock_context(1024 * 1024, candidate, 1), payload);
+    assert!(!res.is_error());
+}
+
+fn register_payload(candidate: &Address) -> RegisterCandidatePayload {
+    let priv_key = hex::decode(&BLS_PRIV_KEY[2..]).unwrap();
+    let caller_hash = Hash::digest(candidate.as_bytes());
+    let msg = HashValue::try_from(caller_hash.as_bytes().as_ref()).unwrap();
+    let pop = BlsPrivateKey::try_from(priv_key.as_ref())
+        .unwrap()
+        .sign_message(&msg);
+
+    RegisterCandidatePayload {
+        bls_pub_key: Hex::from_string(BLS_PUB_KEY.to_owned()).unwrap(),
+        bls_pop:     Hex::from_string(format!("0x{}", hex::encode(pop.to_bytes()))).unwrap(),
+    }
+}
+
+fn mock_executor_params(height: u64) -> ExecutorParams {
+    ExecutorParams {
+        state_root: Hash::default(),
+        height,
+        timestamp: 0,
+        cycles_limit: 1024 * 1024,
+        proposer: Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap(),
+    }
+}
+
+fn mock_context(cycles_limit: u64, caller: Address, height: u64) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _ctx: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _ctx: Context, _: u64, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _ctx: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        _: Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block(&self, _ctx: Context, _: u64) -> ProtocolResult<Option<Block>> {
+        unimplemented!()
+    }
+
+    async fn get_receipt_by_hash(&self, _ctx: Context, _: Hash) -> ProtocolResult<Option<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _ctx: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
+}
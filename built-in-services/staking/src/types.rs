@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+
+use muta_codec_derive::RlpFixedCodec;
+use serde::{Deserialize, Serialize};
+
+use asset::types::Amount;
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Address, Bytes, Hash, Hex, ValidatorExtend};
+use protocol::ProtocolResult;
+
+/// Payload
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakingConfig {
+    /// Asset which candidates and delegators stake in `AssetService`.
+    pub asset_id:            Hash,
+    /// Validators are elected every `epoch_length` blocks.
+    pub epoch_length:        u64,
+    /// Maximum number of elected validators.
+    pub validator_count:     u32,
+    /// An election with fewer candidates keeps the current validators.
+    pub min_validator_count: u32,
+    /// Stake a candidate must bond to itself to be elected.
+    pub min_self_stake:      Amount,
+    /// Blocks an unbonded stake stays locked before it can be withdrawn.
+    pub unbond_period:       u64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct InitGenesisPayload {
+    pub asset_id:            Hash,
+    pub epoch_length:        u64,
+    pub validator_count:     u32,
+    pub min_validator_count: u32,
+    pub min_self_stake:      Amount,
+    pub unbond_period:       u64,
+    /// Usually the validators in metadata genesis, so that they are elected
+    /// until others stake more.
+    pub candidates:          Vec<GenesisCandidate>,
+}
+
+impl InitGenesisPayload {
+    pub fn config(&self) -> StakingConfig {
+        StakingConfig {
+            asset_id:            self.asset_id.clone(),
+            epoch_length:        self.epoch_length,
+            validator_count:     self.validator_count,
+            min_validator_count: self.min_validator_count,
+            min_self_stake:      self.min_self_stake,
+            unbond_period:       self.unbond_period,
+        }
+    }
+}
+
+/// A candidate with stake bonded to itself since genesis. Stakes are not
+/// transferred at genesis, the staking address has to be funded with them
+/// before they can be withdrawn.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GenesisCandidate {
+    pub address:     Address,
+    pub bls_pub_key: Hex,
+    pub stake:       Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct RegisterCandidatePayload {
+    pub bls_pub_key: Hex,
+    /// Proof of possession, BLS signature of the hash of caller address.
+    pub bls_pop:     Hex,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct BondPayload {
+    pub candidate: Address,
+    pub amount:    Amount,
+}
+
+pub type UnbondPayload = BondPayload;
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetCandidatePayload {
+    pub address: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetDelegationPayload {
+    pub delegator: Address,
+    pub candidate: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct GetUnbondingsPayload {
+    pub delegator: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct WithdrawResponse {
+    pub amount: Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidatorList {
+    pub validators: Vec<ValidatorExtend>,
+}
+
+/// Event
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct BondEvent {
+    pub delegator: Address,
+    pub candidate: Address,
+    pub amount:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct UnbondEvent {
+    pub delegator:     Address,
+    pub candidate:     Address,
+    pub amount:        Amount,
+    pub unlock_height: u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct WithdrawEvent {
+    pub delegator: Address,
+    pub amount:    Amount,
+}
+
+/// Model
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Candidate {
+    pub address:     Address,
+    pub bls_pub_key: Hex,
+    /// Sum of all stakes bonded to this candidate, including its own.
+    pub stake:       Amount,
+    /// Stake bonded by the candidate itself.
+    pub self_stake:  Amount,
+    /// A deregistered candidate is never elected, but its delegators can
+    /// still unbond.
+    pub active:      bool,
+}
+
+impl Candidate {
+    pub fn eligible(&self, min_self_stake: Amount) -> bool {
+        self.active && !self.stake.is_zero() && self.self_stake >= min_self_stake
+    }
+
+    pub fn rank(&self) -> CandidateRank {
+        CandidateRank {
+            stake:   self.stake,
+            address: self.address.clone(),
+        }
+    }
+}
+
+/// Key of an eligible candidate in the ranking, candidates with more stake
+/// come first and ties are broken by address, so that every node elects the
+/// same list.
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CandidateRank {
+    pub stake:   Amount,
+    pub address: Address,
+}
+
+impl Ord for CandidateRank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .stake
+            .cmp(&self.stake)
+            .then_with(|| self.address.cmp(&other.address))
+    }
+}
+
+impl PartialOrd for CandidateRank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Delegation {
+    pub delegator: Address,
+    pub candidate: Address,
+    pub amount:    Amount,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Unbonding {
+    pub candidate:     Address,
+    pub amount:        Amount,
+    pub unlock_height: u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnbondingList {
+    pub unbondings: Vec<Unbonding>,
+}
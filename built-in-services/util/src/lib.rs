@@ -1,17 +1,36 @@
+use std::convert::TryFrom;
+
 use bytes::Bytes;
 use hasher::{Hasher, HasherKeccak};
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use sha2::{Digest, Sha256};
 
 use binding_macro::{cycles, service};
-use common_crypto::{Crypto, Secp256k1};
+use common_crypto::{
+    BlsCommonReference, BlsPublicKey, BlsSignature, BlsSignatureVerify, Crypto, HashValue,
+    Secp256k1,
+};
+use common_merkle::{Merkle, ProofNode};
 use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK};
-use protocol::types::{Hash, ServiceContext};
+use protocol::types::{Address, Hash, Hex, ServiceContext};
 
-use crate::types::{KeccakPayload, KeccakResponse, SigVerifyPayload, SigVerifyResponse};
+use crate::types::{
+    BlsAggregatedVerifyPayload, HashPayload, HashResponse, KeccakPayload, KeccakResponse,
+    MerkleProofPayload, MessageSigVerifyPayload, RecoverPayload, RecoverResponse, SigVerifyPayload,
+    SigVerifyResponse,
+};
 
 #[cfg(test)]
 mod tests;
 pub mod types;
 
+// Cycles charged for each byte of input, on top of the base cost
+const CYCLES_PER_BYTE: u64 = 10;
+// Cycles charged for each public key of an aggregated BLS signature
+const BLS_CYCLES_PER_PUB_KEY: u64 = 100_00;
+// Cycles charged for each node of a merkle proof
+const CYCLES_PER_PROOF_NODE: u64 = 10_00;
+
 pub struct UtilService<SDK> {
     _sdk: SDK,
 }
@@ -78,4 +97,287 @@ impl<SDK: ServiceSDK> UtilService<SDK> {
 
         ServiceResponse::<SigVerifyResponse>::from_succeed(response)
     }
+
+    #[cycles(100_00)]
+    #[read]
+    fn sha256(&self, ctx: ServiceContext, payload: HashPayload) -> ServiceResponse<HashResponse> {
+        let data = match decode_hex(&payload.hex_str) {
+            Ok(data) => data,
+            Err(_) => {
+                return ServiceResponse::<HashResponse>::from_error(
+                    107,
+                    "data not valid".to_owned(),
+                )
+            }
+        };
+        if !charge_by_len(&ctx, data.len()) {
+            return ServiceResponse::<HashResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let result = to_hash(Sha256::digest(&data).as_slice());
+        ServiceResponse::<HashResponse>::from_succeed(HashResponse { result })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn blake2b(&self, ctx: ServiceContext, payload: HashPayload) -> ServiceResponse<HashResponse> {
+        let data = match decode_hex(&payload.hex_str) {
+            Ok(data) => data,
+            Err(_) => {
+                return ServiceResponse::<HashResponse>::from_error(
+                    107,
+                    "data not valid".to_owned(),
+                )
+            }
+        };
+        if !charge_by_len(&ctx, data.len()) {
+            return ServiceResponse::<HashResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let digest = blake2b_simd::Params::new().hash_length(32).hash(&data);
+        let result = to_hash(digest.as_bytes());
+        ServiceResponse::<HashResponse>::from_succeed(HashResponse { result })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn sm3(&self, ctx: ServiceContext, payload: HashPayload) -> ServiceResponse<HashResponse> {
+        let data = match decode_hex(&payload.hex_str) {
+            Ok(data) => data,
+            Err(_) => {
+                return ServiceResponse::<HashResponse>::from_error(
+                    107,
+                    "data not valid".to_owned(),
+                )
+            }
+        };
+        if !charge_by_len(&ctx, data.len()) {
+            return ServiceResponse::<HashResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let result = to_hash(&libsm::sm3::hash::Sm3Hash::new(&data).get_hash());
+        ServiceResponse::<HashResponse>::from_succeed(HashResponse { result })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn verify_ed25519(
+        &self,
+        ctx: ServiceContext,
+        payload: MessageSigVerifyPayload,
+    ) -> ServiceResponse<SigVerifyResponse> {
+        let (message, sig, pub_key) = match decode_message_sig(&payload) {
+            Ok(decoded) => decoded,
+            Err((code, msg)) => return ServiceResponse::<SigVerifyResponse>::from_error(code, msg),
+        };
+        if !charge_by_len(&ctx, message.len()) {
+            return ServiceResponse::<SigVerifyResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let is_ok = match (
+            ed25519_dalek::PublicKey::from_bytes(&pub_key),
+            ed25519_dalek::Signature::from_bytes(&sig),
+        ) {
+            (Ok(pub_key), Ok(sig)) => pub_key.verify(&message, &sig).is_ok(),
+            _ => false,
+        };
+
+        ServiceResponse::<SigVerifyResponse>::from_succeed(SigVerifyResponse { is_ok })
+    }
+
+    /// Signature is DER encoded and signed with the default user id.
+    #[cycles(100_00)]
+    #[read]
+    fn verify_sm2(
+        &self,
+        ctx: ServiceContext,
+        payload: MessageSigVerifyPayload,
+    ) -> ServiceResponse<SigVerifyResponse> {
+        let (message, sig, pub_key) = match decode_message_sig(&payload) {
+            Ok(decoded) => decoded,
+            Err((code, msg)) => return ServiceResponse::<SigVerifyResponse>::from_error(code, msg),
+        };
+        if !charge_by_len(&ctx, message.len()) {
+            return ServiceResponse::<SigVerifyResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let sig_ctx = libsm::sm2::signature::SigCtx::new();
+        let is_ok = match (
+            sig_ctx.load_pubkey(&pub_key),
+            libsm::sm2::signature::Signature::der_decode(&sig),
+        ) {
+            (Ok(pub_key), Ok(sig)) => sig_ctx.verify(&message, &pub_key, &sig),
+            _ => false,
+        };
+
+        ServiceResponse::<SigVerifyResponse>::from_succeed(SigVerifyResponse { is_ok })
+    }
+
+    /// Aggregated public keys are only as trustworthy as their proofs of
+    /// possession. This method doesn't check any, callers must only pass keys
+    /// whose possession was already verified, e.g. keys of elected
+    /// validators, otherwise a rogue key can forge the aggregated signature.
+    #[cycles(100_00)]
+    #[read]
+    fn verify_bls_aggregated(
+        &self,
+        ctx: ServiceContext,
+        payload: BlsAggregatedVerifyPayload,
+    ) -> ServiceResponse<SigVerifyResponse> {
+        if payload.pub_keys.is_empty() {
+            return ServiceResponse::<SigVerifyResponse>::from_error(
+                109,
+                "public key not valid".to_owned(),
+            );
+        }
+
+        let key_cycles = BLS_CYCLES_PER_PUB_KEY.saturating_mul(payload.pub_keys.len() as u64);
+        if !ctx.sub_cycles(key_cycles) {
+            return ServiceResponse::<SigVerifyResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let sig = match decode_hex(&payload.sig) {
+            Ok(sig) => sig,
+            Err(_) => {
+                return ServiceResponse::<SigVerifyResponse>::from_error(
+                    108,
+                    "signature not valid".to_owned(),
+                )
+            }
+        };
+
+        // The message is hashed to the curve together with the common
+        // reference.
+        let msg_len = payload.hash.as_bytes().len() + payload.common_ref.len();
+        if !charge_by_len(&ctx, sig.len() + msg_len) {
+            return ServiceResponse::<SigVerifyResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let mut pub_keys = Vec::with_capacity(payload.pub_keys.len());
+        for hex_pub_key in payload.pub_keys.iter() {
+            match decode_hex(hex_pub_key)
+                .ok()
+                .and_then(|bytes| BlsPublicKey::try_from(bytes.as_ref()).ok())
+            {
+                Some(pub_key) => pub_keys.push(pub_key),
+                None => {
+                    return ServiceResponse::<SigVerifyResponse>::from_error(
+                        109,
+                        "public key not valid".to_owned(),
+                    )
+                }
+            }
+        }
+
+        let hash = match HashValue::try_from(payload.hash.as_bytes().as_ref()) {
+            Ok(hash) => hash,
+            Err(_) => {
+                return ServiceResponse::<SigVerifyResponse>::from_error(
+                    107,
+                    "data not valid".to_owned(),
+                )
+            }
+        };
+
+        let aggregated_key = BlsPublicKey::aggregate(pub_keys);
+        let common_ref: BlsCommonReference = payload.common_ref.as_str().into();
+        let is_ok = match BlsSignature::try_from(sig.as_ref()) {
+            Ok(sig) => sig.verify(&hash, &aggregated_key, &common_ref).is_ok(),
+            Err(_) => false,
+        };
+
+        ServiceResponse::<SigVerifyResponse>::from_succeed(SigVerifyResponse { is_ok })
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn recover_secp256k1(
+        &self,
+        ctx: ServiceContext,
+        payload: RecoverPayload,
+    ) -> ServiceResponse<RecoverResponse> {
+        let sig = match decode_hex(&payload.sig) {
+            Ok(sig) if sig.len() == 65 => sig,
+            _ => {
+                return ServiceResponse::<RecoverResponse>::from_error(
+                    108,
+                    "signature not valid".to_owned(),
+                )
+            }
+        };
+
+        let secp = secp256k1::Secp256k1::verification_only();
+        let recovered = RecoveryId::from_i32(i32::from(sig[64]))
+            .and_then(|recovery_id| RecoverableSignature::from_compact(&sig[..64], recovery_id))
+            .and_then(|sig| {
+                let msg = secp256k1::Message::from_slice(payload.hash.as_bytes().as_ref())?;
+                secp.recover(&msg, &sig)
+            });
+
+        let pub_key = match recovered {
+            Ok(pub_key) => Bytes::from(pub_key.serialize().to_vec()),
+            Err(_) => {
+                return ServiceResponse::<RecoverResponse>::from_error(
+                    108,
+                    "signature not valid".to_owned(),
+                )
+            }
+        };
+
+        let address = Address::from_pubkey_bytes(pub_key.clone())
+            .expect("address from public key should not fail");
+        let pub_key = Hex::from_string(format!("0x{}", hex::encode(pub_key)))
+            .expect("hex with prefix should not fail");
+
+        ServiceResponse::<RecoverResponse>::from_succeed(RecoverResponse { pub_key, address })
+    }
+
+    /// Verify a proof of `common_merkle::Merkle`.
+    #[cycles(100_00)]
+    #[read]
+    fn verify_merkle_proof(
+        &self,
+        ctx: ServiceContext,
+        payload: MerkleProofPayload,
+    ) -> ServiceResponse<SigVerifyResponse> {
+        let node_cycles = CYCLES_PER_PROOF_NODE.saturating_mul(payload.proof.len() as u64);
+        if !ctx.sub_cycles(node_cycles) {
+            return ServiceResponse::<SigVerifyResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let proof = payload
+            .proof
+            .into_iter()
+            .map(|node| ProofNode {
+                is_right: node.is_right,
+                hash:     node.hash,
+            })
+            .collect::<Vec<_>>();
+
+        let is_ok = Merkle::verify_proof(&payload.root, payload.leaf, &proof);
+        ServiceResponse::<SigVerifyResponse>::from_succeed(SigVerifyResponse { is_ok })
+    }
+}
+
+fn decode_hex(hex_str: &Hex) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(hex_str.as_string_trim0x())
+}
+
+fn decode_message_sig(
+    payload: &MessageSigVerifyPayload,
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), (u64, String)> {
+    let message = decode_hex(&payload.message).map_err(|_| (107, "data not valid".to_owned()))?;
+    let sig = decode_hex(&payload.sig).map_err(|_| (108, "signature not valid".to_owned()))?;
+    let pub_key =
+        decode_hex(&payload.pub_key).map_err(|_| (109, "public key not valid".to_owned()))?;
+
+    Ok((message, sig, pub_key))
+}
+
+fn charge_by_len(ctx: &ServiceContext, len: usize) -> bool {
+    ctx.sub_cycles(CYCLES_PER_BYTE.saturating_mul(len as u64))
+}
+
+fn to_hash(digest: &[u8]) -> Hash {
+    Hash::from_bytes(Bytes::from(digest.to_vec())).expect("digest should be 32 bytes")
 }
@@ -13,12 +13,15 @@ use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
 use protocol::traits::{Context, NoopDispatcher, Storage};
 use protocol::types::{
-    Address, Block, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
+    Address, Block, Evidence, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
     SignedTransaction,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
-use crate::types::{KeccakPayload, SigVerifyPayload};
+use crate::types::{
+    BlsAggregatedVerifyPayload, HashPayload, KeccakPayload, MerkleProofNode, MerkleProofPayload,
+    RecoverPayload, SigVerifyPayload,
+};
 use crate::UtilService;
 
 #[test]
@@ -81,6 +84,139 @@ fn test_verify() {
     assert_eq!(res.is_ok, true)
 }
 
+#[test]
+fn test_sha256() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller);
+
+    let service = new_util_service();
+
+    let res = service
+        .sha256(context, HashPayload {
+            hex_str: Hex::from_string("0x1234".to_string()).unwrap(),
+        })
+        .succeed_data;
+
+    assert_eq!(
+        res.result.as_hex(),
+        "0x3a103a4e5729ad68c02a678ae39accfbc0ae208096437401b7ceab63cca0622f".to_string()
+    )
+}
+
+#[test]
+fn test_hash_out_of_cycles() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(100_00, caller);
+
+    let service = new_util_service();
+
+    let res = service.blake2b(context, HashPayload {
+        hex_str: Hex::from_string("0x1234".to_string()).unwrap(),
+    });
+
+    assert_eq!(res.code, 3);
+}
+
+#[test]
+fn test_bls_aggregated_charge_message() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let service = new_util_service();
+    let payload = BlsAggregatedVerifyPayload {
+        hash:       Hash::digest(Bytes::from("muta")),
+        sig:        Hex::from_string("0x00".to_string()).unwrap(),
+        pub_keys:   vec![Hex::from_string("0x00".to_string()).unwrap()],
+        common_ref: "muta".repeat(100),
+    };
+
+    // base and key cost only
+    let res = service.verify_bls_aggregated(mock_context(200_00, caller.clone()), payload.clone());
+    assert_eq!(res.code, 3);
+
+    // test paid message reaches key decoding
+    let res = service.verify_bls_aggregated(mock_context(300_00, caller), payload);
+    assert_eq!(res.code, 109);
+}
+
+#[test]
+fn test_recover_secp256k1() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller);
+
+    let service = new_util_service();
+
+    let secp = secp256k1::Secp256k1::new();
+    let secret_key = secp256k1::SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let pub_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+    let hash = Hash::from_hex("0x56570de287d73cd1cb6092bb8fdee6173974955fdef345ae579ee9f475ea7432")
+        .unwrap();
+    let msg = secp256k1::Message::from_slice(hash.as_bytes().as_ref()).unwrap();
+    let (recovery_id, sig) = secp.sign_recoverable(&msg, &secret_key).serialize_compact();
+
+    let mut sig_bytes = sig.to_vec();
+    sig_bytes.push(recovery_id.to_i32() as u8);
+    let sig = Hex::from_string(format!("0x{}", hex::encode(sig_bytes))).unwrap();
+
+    let res = service
+        .recover_secp256k1(context, RecoverPayload { hash, sig })
+        .succeed_data;
+
+    let expect_pub_key = pub_key.serialize().to_vec();
+    assert_eq!(
+        res.pub_key.as_string_trim0x(),
+        hex::encode(expect_pub_key.clone())
+    );
+    assert_eq!(
+        res.address,
+        Address::from_pubkey_bytes(Bytes::from(expect_pub_key)).unwrap()
+    );
+}
+
+#[test]
+fn test_verify_merkle_proof() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let service = new_util_service();
+
+    let hashes = (0..5u8)
+        .map(|i| Hash::digest(Bytes::from(vec![i])))
+        .collect::<Vec<_>>();
+    let merkle = common_merkle::Merkle::from_hashes(hashes.clone());
+    let root = merkle.get_root_hash().unwrap();
+    let proof = merkle
+        .get_proof_by_input_index(2)
+        .unwrap()
+        .into_iter()
+        .map(|node| MerkleProofNode {
+            is_right: node.is_right,
+            hash:     node.hash,
+        })
+        .collect::<Vec<_>>();
+
+    let res = service
+        .verify_merkle_proof(
+            mock_context(cycles_limit, caller.clone()),
+            MerkleProofPayload {
+                root:  root.clone(),
+                leaf:  hashes[2].clone(),
+                proof: proof.clone(),
+            },
+        )
+        .succeed_data;
+    assert_eq!(res.is_ok, true);
+
+    let res = service
+        .verify_merkle_proof(mock_context(cycles_limit, caller), MerkleProofPayload {
+            root,
+            leaf: hashes[3].clone(),
+            proof,
+        })
+        .succeed_data;
+    assert_eq!(res.is_ok, false);
+}
+
 fn new_util_service() -> UtilService<
     DefaultServiceSDK<
         GeneralServiceState<MemoryDB>,
@@ -200,4 +336,12 @@ impl Storage for MockStorage {
     async fn load_overlord_wal(&self, _: Context) -> ProtocolResult<Bytes> {
         unimplemented!()
     }
+
+    async fn insert_evidence(&self, _: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
 }
@@ -1,4 +1,4 @@
-use protocol::types::{Hash, Hex};
+use protocol::types::{Address, Hash, Hex};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -22,3 +22,50 @@ pub struct SigVerifyPayload {
 pub struct SigVerifyResponse {
     pub is_ok: bool,
 }
+
+pub type HashPayload = KeccakPayload;
+
+pub type HashResponse = KeccakResponse;
+
+/// Signature over a raw message, for schemes hashing the message themselves.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MessageSigVerifyPayload {
+    pub message: Hex,
+    pub sig:     Hex,
+    pub pub_key: Hex,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BlsAggregatedVerifyPayload {
+    pub hash:       Hash,
+    pub sig:        Hex,
+    pub pub_keys:   Vec<Hex>,
+    pub common_ref: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RecoverPayload {
+    pub hash: Hash,
+    /// 64 bytes compact signature followed by 1 byte recovery id.
+    pub sig:  Hex,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct RecoverResponse {
+    /// Compressed public key
+    pub pub_key: Hex,
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MerkleProofNode {
+    pub is_right: bool,
+    pub hash:     Hash,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MerkleProofPayload {
+    pub root:  Hash,
+    pub leaf:  Hash,
+    pub proof: Vec<MerkleProofNode>,
+}
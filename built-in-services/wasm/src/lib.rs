@@ -0,0 +1,220 @@
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+mod runtime;
+
+use binding_macro::{cycles, service};
+use serde::Serialize;
+
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap, StoreUint64};
+use protocol::types::{Address, Bytes, Hash, ServiceContext};
+
+use crate::runtime::{SdkAccess, WasmError};
+use crate::types::{
+    CallPayload, Contract, ContractResponse, DeployEvent, DeployPayload, DeployResponse,
+    GetContractPayload, QueryPayload,
+};
+
+pub const WASM_SERVICE: &str = "wasm";
+const INIT_METHOD: &str = "init";
+const DEPLOY_BYTE_CYCLES: u64 = 10;
+
+/// Runs contracts uploaded as WebAssembly. A contract exports functions
+/// taking and returning nothing, arguments and results are passed through
+/// host functions imported from the `env` module.
+///
+/// Contracts are not services of their own, they are only reached through
+/// `call` and `query` of this service with the contract address, and are not
+/// routed by the executor as a (service, method) pair. Calls made by a
+/// contract go through the dispatcher like those of any service, so they
+/// share its limit on call depth.
+pub struct WasmService<SDK> {
+    sdk:          SDK,
+    contracts:    Box<dyn StoreMap<Address, Contract>>,
+    deploy_nonce: Box<dyn StoreUint64>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> WasmService<SDK> {
+    pub fn new(mut sdk: SDK) -> Self {
+        let contracts: Box<dyn StoreMap<Address, Contract>> = sdk.alloc_or_recover_map("contracts");
+        let deploy_nonce = sdk.alloc_or_recover_uint64("deploy_nonce");
+
+        Self {
+            sdk,
+            contracts,
+            deploy_nonce,
+        }
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn deploy(
+        &mut self,
+        ctx: ServiceContext,
+        payload: DeployPayload,
+    ) -> ServiceResponse<DeployResponse> {
+        let raw_code = match hex::decode(payload.code.as_string_trim0x()) {
+            Ok(code) => code,
+            Err(e) => {
+                return ServiceResponse::<DeployResponse>::from_error(101, format!("{:?}", e))
+            }
+        };
+
+        if !ctx.sub_cycles(raw_code.len() as u64 * DEPLOY_BYTE_CYCLES) {
+            let e = WasmError::OutOfCycles;
+            return ServiceResponse::<DeployResponse>::from_error(e.code(), e.to_string());
+        }
+
+        let code = match runtime::prepare_code(&raw_code) {
+            Ok(code) => code,
+            Err(e) => {
+                return ServiceResponse::<DeployResponse>::from_error(e.code(), e.to_string())
+            }
+        };
+
+        let address = self._contract_address(&ctx);
+        let contract = Contract {
+            deployer:  ctx.get_caller(),
+            code_hash: Hash::digest(Bytes::from(raw_code)),
+            code:      Bytes::from(code),
+        };
+        self.contracts.insert(address.clone(), contract.clone());
+
+        // `init` is optional, a failed one aborts the deployment
+        match runtime::invoke(
+            SdkAccess::Writable(&mut self.sdk),
+            &ctx,
+            &address,
+            &contract.code,
+            INIT_METHOD,
+            payload.init_args.into_bytes(),
+        ) {
+            Ok(_) | Err(WasmError::MethodNotFound(_)) => {}
+            Err(e) => {
+                return ServiceResponse::<DeployResponse>::from_error(e.code(), e.to_string())
+            }
+        }
+
+        if let Err((code, msg)) = emit_event(&ctx, &DeployEvent {
+            address:   address.clone(),
+            deployer:  contract.deployer,
+            code_hash: contract.code_hash,
+        }) {
+            return ServiceResponse::<DeployResponse>::from_error(code, msg);
+        }
+
+        ServiceResponse::<DeployResponse>::from_succeed(DeployResponse { address })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn call(&mut self, ctx: ServiceContext, payload: CallPayload) -> ServiceResponse<String> {
+        if payload.method == INIT_METHOD {
+            return ServiceResponse::<String>::from_error(
+                104,
+                "init is only run on deploy".to_owned(),
+            );
+        }
+
+        let contract = match self.contracts.get(&payload.address) {
+            Some(contract) => contract,
+            None => {
+                return ServiceResponse::<String>::from_error(
+                    103,
+                    "contract not existed".to_owned(),
+                )
+            }
+        };
+
+        to_response(runtime::invoke(
+            SdkAccess::Writable(&mut self.sdk),
+            &ctx,
+            &payload.address,
+            &contract.code,
+            &payload.method,
+            payload.args.into_bytes(),
+        ))
+    }
+
+    /// Same as `call`, but the contract traps once it tries to change state.
+    #[cycles(100_00)]
+    #[read]
+    fn query(&self, ctx: ServiceContext, payload: QueryPayload) -> ServiceResponse<String> {
+        if payload.method == INIT_METHOD {
+            return ServiceResponse::<String>::from_error(
+                104,
+                "init is only run on deploy".to_owned(),
+            );
+        }
+
+        let contract = match self.contracts.get(&payload.address) {
+            Some(contract) => contract,
+            None => {
+                return ServiceResponse::<String>::from_error(
+                    103,
+                    "contract not existed".to_owned(),
+                )
+            }
+        };
+
+        to_response(runtime::invoke(
+            SdkAccess::ReadOnly(&self.sdk),
+            &ctx,
+            &payload.address,
+            &contract.code,
+            &payload.method,
+            payload.args.into_bytes(),
+        ))
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_contract(
+        &self,
+        ctx: ServiceContext,
+        payload: GetContractPayload,
+    ) -> ServiceResponse<ContractResponse> {
+        match self.contracts.get(&payload.address) {
+            Some(contract) => ServiceResponse::<ContractResponse>::from_succeed(ContractResponse {
+                address:   payload.address,
+                deployer:  contract.deployer,
+                code_hash: contract.code_hash,
+            }),
+            None => ServiceResponse::<ContractResponse>::from_error(
+                103,
+                "contract not existed".to_owned(),
+            ),
+        }
+    }
+
+    // Addresses never repeat, even when the same code is deployed twice
+    fn _contract_address(&mut self, ctx: &ServiceContext) -> Address {
+        let nonce = self.deploy_nonce.get();
+        self.deploy_nonce.set(nonce + 1);
+
+        let mut seed = ctx.get_caller().as_bytes().to_vec();
+        seed.extend_from_slice(&nonce.to_be_bytes());
+
+        Address::from_hash(Hash::digest(Bytes::from(seed)))
+            .expect("contract address should be valid")
+    }
+}
+
+fn to_response(result: Result<Vec<u8>, WasmError>) -> ServiceResponse<String> {
+    match result {
+        Ok(output) => match String::from_utf8(output) {
+            Ok(output) => ServiceResponse::<String>::from_succeed(output),
+            Err(_) => ServiceResponse::<String>::from_error(109, "output is not utf-8".to_owned()),
+        },
+        Err(e) => ServiceResponse::<String>::from_error(e.code(), e.to_string()),
+    }
+}
+
+fn emit_event<E: Serialize>(ctx: &ServiceContext, event: &E) -> Result<(), (u64, String)> {
+    let event_str = serde_json::to_string(event).map_err(|e| (110, format!("{:?}", e)))?;
+    ctx.emit_event(event_str);
+
+    Ok(())
+}
@@ -0,0 +1,442 @@
+use derive_more::Display;
+use parity_wasm::elements;
+use pwasm_utils::rules;
+use wasmi::{
+    Error as InterpreterError, ExternVal, Externals, FuncInstance, FuncRef, HostError,
+    ImportsBuilder, MemoryRef, Module, ModuleImportResolver, ModuleInstance, RuntimeArgs,
+    RuntimeValue, Signature, Trap, ValueType,
+};
+
+use protocol::traits::ServiceSDK;
+use protocol::types::{Address, Bytes, ServiceContext};
+
+const MAX_CODE_SIZE: usize = 512 * 1024;
+// 64 KiB each, contracts must declare a maximum no larger than this
+const MAX_MEMORY_PAGES: u32 = 16;
+
+const HOST_CALL_CYCLES: u64 = 100;
+const MEMORY_BYTE_CYCLES: u64 = 1;
+const STORAGE_BYTE_CYCLES: u64 = 10;
+
+const GAS: usize = 0;
+const INPUT_LEN: usize = 1;
+const INPUT: usize = 2;
+const SET_OUTPUT: usize = 3;
+const REVERT: usize = 4;
+const GET_STORAGE: usize = 5;
+const SET_STORAGE: usize = 6;
+const REMOVE_STORAGE: usize = 7;
+const EMIT_EVENT: usize = 8;
+const SERVICE_READ: usize = 9;
+const SERVICE_WRITE: usize = 10;
+const RET_LEN: usize = 11;
+const RET_COPY: usize = 12;
+const CALLER: usize = 13;
+const ADDRESS: usize = 14;
+const HEIGHT: usize = 15;
+
+#[derive(Debug, Display)]
+pub enum WasmError {
+    #[display(fmt = "invalid code: {}", _0)]
+    InvalidCode(String),
+
+    #[display(fmt = "method {:?} not exported", _0)]
+    MethodNotFound(String),
+
+    #[display(fmt = "out of cycles")]
+    OutOfCycles,
+
+    #[display(fmt = "reverted: {}", _0)]
+    Revert(String),
+
+    #[display(fmt = "trapped: {}", _0)]
+    Trap(String),
+}
+
+impl WasmError {
+    pub fn code(&self) -> u64 {
+        match self {
+            WasmError::InvalidCode(_) => 102,
+            WasmError::MethodNotFound(_) => 105,
+            WasmError::OutOfCycles => 106,
+            WasmError::Revert(_) => 107,
+            WasmError::Trap(_) => 108,
+        }
+    }
+}
+
+// Errors raised by host functions, they abort the contract as a trap
+#[derive(Debug, Display)]
+enum HostTrap {
+    #[display(fmt = "out of cycles")]
+    OutOfCycles,
+
+    #[display(fmt = "{}", _0)]
+    Revert(String),
+
+    #[display(fmt = "state is read only")]
+    ReadOnly,
+
+    #[display(fmt = "memory not exported")]
+    NoMemory,
+
+    #[display(fmt = "memory access out of bounds")]
+    MemoryAccess,
+
+    #[display(fmt = "string is not utf-8")]
+    InvalidUtf8,
+
+    #[display(fmt = "unknown host function {}", _0)]
+    UnknownFunction(usize),
+}
+
+impl HostError for HostTrap {}
+
+/// The SDK a contract runs with, `query` must not change any state.
+pub enum SdkAccess<'a, SDK> {
+    ReadOnly(&'a SDK),
+    Writable(&'a mut SDK),
+}
+
+impl<'a, SDK> SdkAccess<'a, SDK> {
+    fn get(&self) -> &SDK {
+        match self {
+            SdkAccess::ReadOnly(sdk) => *sdk,
+            SdkAccess::Writable(sdk) => &**sdk,
+        }
+    }
+
+    fn get_mut(&mut self) -> Result<&mut SDK, Trap> {
+        match self {
+            SdkAccess::ReadOnly(_) => Err(HostTrap::ReadOnly.into()),
+            SdkAccess::Writable(sdk) => Ok(&mut **sdk),
+        }
+    }
+}
+
+// Check the uploaded code and inject cycles metering, every block of
+// instructions then calls the `gas` host function before it runs.
+pub fn prepare_code(code: &[u8]) -> Result<Vec<u8>, WasmError> {
+    if code.len() > MAX_CODE_SIZE {
+        return Err(WasmError::InvalidCode("code too large".to_owned()));
+    }
+
+    let module: elements::Module =
+        parity_wasm::deserialize_buffer(code).map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+
+    if module.start_section().is_some() {
+        return Err(WasmError::InvalidCode(
+            "start function not allowed".to_owned(),
+        ));
+    }
+
+    if let Some(section) = module.memory_section() {
+        for memory in section.entries() {
+            match memory.limits().maximum() {
+                Some(maximum) if maximum <= MAX_MEMORY_PAGES => {}
+                _ => {
+                    return Err(WasmError::InvalidCode(format!(
+                        "memory maximum should be at most {} pages",
+                        MAX_MEMORY_PAGES
+                    )))
+                }
+            }
+        }
+    }
+
+    // Floats are not deterministic across platforms
+    let rules = rules::Set::default().with_forbidden_floats();
+    let module = pwasm_utils::inject_gas_counter(module, &rules)
+        .map_err(|_| WasmError::InvalidCode("float instructions not allowed".to_owned()))?;
+    let code = parity_wasm::serialize(module).map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+
+    // Imports are resolved on instantiation
+    instantiate(&code)?;
+    Ok(code)
+}
+
+// Run the `method` export of a contract with `input`, return its output
+pub fn invoke<SDK: ServiceSDK>(
+    sdk: SdkAccess<SDK>,
+    ctx: &ServiceContext,
+    address: &Address,
+    code: &[u8],
+    method: &str,
+    input: Vec<u8>,
+) -> Result<Vec<u8>, WasmError> {
+    let instance = instantiate(code)?;
+
+    match instance.export_by_name(method) {
+        Some(ExternVal::Func(_)) => {}
+        _ => return Err(WasmError::MethodNotFound(method.to_owned())),
+    }
+
+    let memory = instance
+        .export_by_name("memory")
+        .and_then(|export| export.as_memory().cloned());
+
+    let mut runtime = Runtime {
+        sdk,
+        ctx,
+        address: address.clone(),
+        memory,
+        input,
+        output: vec![],
+        ret: vec![],
+    };
+
+    match instance.invoke_export(method, &[], &mut runtime) {
+        Ok(_) => Ok(runtime.output),
+        Err(e) => Err(to_wasm_error(e)),
+    }
+}
+
+fn instantiate(code: &[u8]) -> Result<wasmi::ModuleRef, WasmError> {
+    let module = Module::from_buffer(code).map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+    let imports = ImportsBuilder::new().with_resolver("env", &HostResolver);
+
+    // Start functions are rejected in `prepare_code`
+    let instance = ModuleInstance::new(&module, &imports)
+        .map_err(|e| WasmError::InvalidCode(e.to_string()))?
+        .assert_no_start();
+
+    Ok(instance)
+}
+
+fn to_wasm_error(e: InterpreterError) -> WasmError {
+    match e.as_host_error().and_then(|e| e.downcast_ref::<HostTrap>()) {
+        Some(HostTrap::OutOfCycles) => WasmError::OutOfCycles,
+        Some(HostTrap::Revert(msg)) => WasmError::Revert(msg.to_owned()),
+        Some(trap) => WasmError::Trap(trap.to_string()),
+        None => WasmError::Trap(e.to_string()),
+    }
+}
+
+struct HostResolver;
+
+impl ModuleImportResolver for HostResolver {
+    fn resolve_func(
+        &self,
+        field_name: &str,
+        signature: &Signature,
+    ) -> Result<FuncRef, InterpreterError> {
+        let (index, expected) = match host_function(field_name) {
+            Some(function) => function,
+            None => {
+                return Err(InterpreterError::Instantiation(format!(
+                    "host function {} not found",
+                    field_name
+                )))
+            }
+        };
+
+        if signature != &expected {
+            return Err(InterpreterError::Instantiation(format!(
+                "host function {} should be {:?}",
+                field_name, expected
+            )));
+        }
+
+        Ok(FuncInstance::alloc_host(expected, index))
+    }
+}
+
+fn host_function(name: &str) -> Option<(usize, Signature)> {
+    use ValueType::{I32, I64};
+
+    let function = match name {
+        "gas" => (GAS, sig(&[I32], None)),
+        "input_len" => (INPUT_LEN, sig(&[], Some(I32))),
+        "input" => (INPUT, sig(&[I32], None)),
+        "set_output" => (SET_OUTPUT, sig(&[I32, I32], None)),
+        "revert" => (REVERT, sig(&[I32, I32], None)),
+        "get_storage" => (GET_STORAGE, sig(&[I32, I32], Some(I32))),
+        "set_storage" => (SET_STORAGE, sig(&[I32, I32, I32, I32], None)),
+        "remove_storage" => (REMOVE_STORAGE, sig(&[I32, I32], None)),
+        "emit_event" => (EMIT_EVENT, sig(&[I32, I32], None)),
+        "service_read" => (
+            SERVICE_READ,
+            sig(&[I32, I32, I32, I32, I32, I32], Some(I64)),
+        ),
+        "service_write" => (
+            SERVICE_WRITE,
+            sig(&[I32, I32, I32, I32, I32, I32], Some(I64)),
+        ),
+        "ret_len" => (RET_LEN, sig(&[], Some(I32))),
+        "ret_copy" => (RET_COPY, sig(&[I32], None)),
+        "caller" => (CALLER, sig(&[I32], None)),
+        "address" => (ADDRESS, sig(&[I32], None)),
+        "height" => (HEIGHT, sig(&[], Some(I64))),
+        _ => return None,
+    };
+
+    Some(function)
+}
+
+fn sig(params: &'static [ValueType], ret: Option<ValueType>) -> Signature {
+    Signature::new(params, ret)
+}
+
+/// Host side of a running contract. Data is passed through the contract's
+/// exported memory as pointer and length pairs, results of variable length
+/// are kept in `ret` until the contract copies them with `ret_copy`.
+struct Runtime<'a, SDK> {
+    sdk:     SdkAccess<'a, SDK>,
+    ctx:     &'a ServiceContext,
+    address: Address,
+    memory:  Option<MemoryRef>,
+    input:   Vec<u8>,
+    output:  Vec<u8>,
+    ret:     Vec<u8>,
+}
+
+impl<'a, SDK: ServiceSDK> Runtime<'a, SDK> {
+    fn charge(&self, cycles: u64) -> Result<(), Trap> {
+        if self.ctx.sub_cycles(cycles) {
+            Ok(())
+        } else {
+            Err(HostTrap::OutOfCycles.into())
+        }
+    }
+
+    fn memory(&self) -> Result<&MemoryRef, Trap> {
+        self.memory
+            .as_ref()
+            .ok_or_else(|| HostTrap::NoMemory.into())
+    }
+
+    fn read_memory(&self, ptr: u32, len: u32) -> Result<Vec<u8>, Trap> {
+        self.charge(u64::from(len) * MEMORY_BYTE_CYCLES)?;
+        self.memory()?
+            .get(ptr, len as usize)
+            .map_err(|_| HostTrap::MemoryAccess.into())
+    }
+
+    fn read_string(&self, ptr: u32, len: u32) -> Result<String, Trap> {
+        String::from_utf8(self.read_memory(ptr, len)?).map_err(|_| HostTrap::InvalidUtf8.into())
+    }
+
+    fn write_memory(&self, ptr: u32, data: &[u8]) -> Result<(), Trap> {
+        self.charge(data.len() as u64 * MEMORY_BYTE_CYCLES)?;
+        self.memory()?
+            .set(ptr, data)
+            .map_err(|_| HostTrap::MemoryAccess.into())
+    }
+
+    // Calls made by a contract are made in its own name, a contract never
+    // spends the assets of whoever called it.
+    fn call_service(&mut self, args: &RuntimeArgs, write: bool) -> Result<RuntimeValue, Trap> {
+        let service = self.read_string(args.nth_checked(0)?, args.nth_checked(1)?)?;
+        let method = self.read_string(args.nth_checked(2)?, args.nth_checked(3)?)?;
+        let payload = self.read_string(args.nth_checked(4)?, args.nth_checked(5)?)?;
+
+        let ctx = ServiceContext::with_caller(self.ctx, self.address.clone());
+        let resp = if write {
+            self.sdk
+                .get_mut()?
+                .write(&ctx, None, &service, &method, &payload)
+        } else {
+            self.sdk.get().read(&ctx, None, &service, &method, &payload)
+        };
+
+        let code = resp.code;
+        self.ret = if resp.is_error() {
+            resp.error_message.into_bytes()
+        } else {
+            resp.succeed_data.into_bytes()
+        };
+
+        Ok(RuntimeValue::I64(code as i64))
+    }
+}
+
+impl<'a, SDK: ServiceSDK> Externals for Runtime<'a, SDK> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        if index != GAS {
+            self.charge(HOST_CALL_CYCLES)?;
+        }
+
+        match index {
+            GAS => {
+                let cycles: u32 = args.nth_checked(0)?;
+                self.charge(u64::from(cycles))?;
+                Ok(None)
+            }
+            INPUT_LEN => Ok(Some(RuntimeValue::I32(self.input.len() as i32))),
+            INPUT => {
+                self.write_memory(args.nth_checked(0)?, &self.input)?;
+                Ok(None)
+            }
+            SET_OUTPUT => {
+                self.output = self.read_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                Ok(None)
+            }
+            REVERT => {
+                let msg = self.read_string(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                Err(HostTrap::Revert(msg).into())
+            }
+            GET_STORAGE => {
+                let key = self.read_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                let value: Option<Bytes> = self
+                    .sdk
+                    .get()
+                    .get_account_value(&self.address, &Bytes::from(key));
+
+                let found = value.is_some();
+                self.ret = value.map(|v| v.to_vec()).unwrap_or_default();
+                Ok(Some(RuntimeValue::I32(found as i32)))
+            }
+            SET_STORAGE => {
+                let key = self.read_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                let value = self.read_memory(args.nth_checked(2)?, args.nth_checked(3)?)?;
+                self.charge((key.len() + value.len()) as u64 * STORAGE_BYTE_CYCLES)?;
+
+                let address = self.address.clone();
+                self.sdk.get_mut()?.set_account_value(
+                    &address,
+                    Bytes::from(key),
+                    Bytes::from(value),
+                );
+                Ok(None)
+            }
+            REMOVE_STORAGE => {
+                let key = self.read_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+
+                let address = self.address.clone();
+                self.sdk
+                    .get_mut()?
+                    .remove_account_value(&address, &Bytes::from(key));
+                Ok(None)
+            }
+            EMIT_EVENT => {
+                self.sdk.get_mut()?;
+                let data = self.read_string(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                self.ctx.emit_event(data);
+                Ok(None)
+            }
+            SERVICE_READ => Ok(Some(self.call_service(&args, false)?)),
+            SERVICE_WRITE => Ok(Some(self.call_service(&args, true)?)),
+            RET_LEN => Ok(Some(RuntimeValue::I32(self.ret.len() as i32))),
+            RET_COPY => {
+                self.write_memory(args.nth_checked(0)?, &self.ret)?;
+                Ok(None)
+            }
+            CALLER => {
+                self.write_memory(args.nth_checked(0)?, &self.ctx.get_caller().as_bytes())?;
+                Ok(None)
+            }
+            ADDRESS => {
+                self.write_memory(args.nth_checked(0)?, &self.address.as_bytes())?;
+                Ok(None)
+            }
+            HEIGHT => Ok(Some(
+                RuntimeValue::I64(self.ctx.get_current_height() as i64),
+            )),
+            _ => Err(HostTrap::UnknownFunction(index).into()),
+        }
+    }
+}
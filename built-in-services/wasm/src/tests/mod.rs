@@ -0,0 +1,368 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{Context, Dispatcher, ServiceResponse, Storage};
+use protocol::types::{
+    Address, Block, Evidence, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{CallPayload, DeployEvent, DeployPayload, GetContractPayload};
+use crate::WasmService;
+
+const COUNTER: &str = r#"
+(module
+  (import "env" "input_len" (func $input_len (result i32)))
+  (import "env" "input" (func $input (param i32)))
+  (import "env" "set_output" (func $set_output (param i32 i32)))
+  (import "env" "revert" (func $revert (param i32 i32)))
+  (import "env" "get_storage" (func $get_storage (param i32 i32) (result i32)))
+  (import "env" "set_storage" (func $set_storage (param i32 i32 i32 i32)))
+  (import "env" "ret_len" (func $ret_len (result i32)))
+  (import "env" "ret_copy" (func $ret_copy (param i32)))
+  (import "env" "service_write"
+    (func $service_write (param i32 i32 i32 i32 i32 i32) (result i64)))
+  (memory (export "memory") 1 1)
+  (data (i32.const 0) "count")
+  (data (i32.const 16) "bad args")
+  (data (i32.const 32) "asset")
+  (data (i32.const 48) "transfer")
+  (data (i32.const 96) "{}")
+
+  (func (export "init")
+    (call $input (i32.const 64))
+    (call $set_storage (i32.const 0) (i32.const 5) (i32.const 64) (call $input_len)))
+
+  (func (export "get")
+    (if (call $get_storage (i32.const 0) (i32.const 5))
+      (then
+        (call $ret_copy (i32.const 64))
+        (call $set_output (i32.const 64) (call $ret_len)))))
+
+  ;; count is a single ascii digit
+  (func (export "incr")
+    (drop (call $get_storage (i32.const 0) (i32.const 5)))
+    (call $ret_copy (i32.const 64))
+    (i32.store8 (i32.const 64) (i32.add (i32.load8_u (i32.const 64)) (i32.const 1)))
+    (call $set_storage (i32.const 0) (i32.const 5) (i32.const 64) (i32.const 1)))
+
+  (func (export "fail")
+    (call $revert (i32.const 16) (i32.const 8)))
+
+  (func (export "spin")
+    (loop $spin (br $spin)))
+
+  (func (export "pay")
+    (drop (call $service_write
+      (i32.const 32) (i32.const 5) (i32.const 48) (i32.const 8) (i32.const 96) (i32.const 2)))))
+"#;
+
+#[test]
+fn test_deploy_and_call() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let dispatcher = MockDispatcher::default();
+    let mut service = new_wasm_service(dispatcher.clone());
+
+    let ctx = mock_context(cycles_limit, alice.clone());
+    let res = service.deploy(ctx.clone(), DeployPayload {
+        code:      to_hex(COUNTER),
+        init_args: "7".to_owned(),
+    });
+    assert!(!res.is_error(), "{}", res.error_message);
+    let address = res.succeed_data.address;
+
+    let event: DeployEvent = serde_json::from_str(&ctx.get_events()[0].data).unwrap();
+    assert_eq!(event.address, address);
+    assert_eq!(event.deployer, alice);
+
+    let contract = service
+        .get_contract(
+            mock_context(cycles_limit, alice.clone()),
+            GetContractPayload {
+                address: address.clone(),
+            },
+        )
+        .succeed_data;
+    assert_eq!(contract.deployer, alice);
+    assert_eq!(
+        contract.code_hash,
+        Hash::digest(Bytes::from(wat::parse_str(COUNTER).unwrap()))
+    );
+
+    let res = service.call(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "incr"),
+    );
+    assert!(!res.is_error(), "{}", res.error_message);
+
+    let res = service.query(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "get"),
+    );
+    assert_eq!(res.succeed_data, "8".to_owned());
+
+    // test query never changes state
+    let res = service.query(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "incr"),
+    );
+    assert_eq!(res.code, 108);
+
+    let res = service.call(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "init"),
+    );
+    assert_eq!(res.code, 104);
+
+    let res = service.call(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "missing"),
+    );
+    assert_eq!(res.code, 105);
+
+    let res = service.call(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&alice, "get"),
+    );
+    assert_eq!(res.code, 103);
+
+    // test contract calls services in its own name
+    let res = service.call(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "pay"),
+    );
+    assert!(!res.is_error(), "{}", res.error_message);
+
+    let (caller, method, payload) = dispatcher.calls.borrow().last().cloned().unwrap();
+    assert_eq!(caller, address);
+    assert_eq!(method, "asset.transfer");
+    assert_eq!(payload, "{}".to_owned());
+}
+
+#[test]
+fn test_call_failures() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let mut service = new_wasm_service(MockDispatcher::default());
+
+    let address = service
+        .deploy(mock_context(cycles_limit, alice.clone()), DeployPayload {
+            code:      to_hex(COUNTER),
+            init_args: "0".to_owned(),
+        })
+        .succeed_data
+        .address;
+
+    let res = service.call(
+        mock_context(cycles_limit, alice.clone()),
+        call_payload(&address, "fail"),
+    );
+    assert_eq!(res.code, 107);
+    assert_eq!(res.error_message, "reverted: bad args".to_owned());
+
+    // test endless loop is stopped by cycles metering
+    let ctx = mock_context(1_000_000, alice.clone());
+    let res = service.call(ctx.clone(), call_payload(&address, "spin"));
+    assert_eq!(res.code, 106);
+    assert!(ctx.get_cycles_used() <= 1_000_000);
+}
+
+#[test]
+fn test_deploy_invalid_code() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let alice = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let mut service = new_wasm_service(MockDispatcher::default());
+
+    for code in &[
+        // floats are not deterministic
+        r#"(module (func (export "f") (drop (f32.add (f32.const 1) (f32.const 2)))))"#,
+        // memory without a maximum
+        r#"(module (memory (export "memory") 1))"#,
+        // start functions run on every instantiation
+        r#"(module (func $f) (start $f))"#,
+        // unknown host function
+        r#"(module (import "env" "exit" (func)))"#,
+    ] {
+        let res = service.deploy(mock_context(cycles_limit, alice.clone()), DeployPayload {
+            code:      to_hex(code),
+            init_args: String::new(),
+        });
+        assert_eq!(res.code, 102, "{}", code);
+    }
+
+    let res = service.deploy(mock_context(cycles_limit, alice.clone()), DeployPayload {
+        code:      Hex::from_string("0xzz".to_owned()).unwrap(),
+        init_args: String::new(),
+    });
+    assert_eq!(res.code, 101);
+}
+
+#[derive(Clone, Default)]
+struct MockDispatcher {
+    calls: Rc<RefCell<Vec<(Address, String, String)>>>,
+}
+
+impl Dispatcher for MockDispatcher {
+    fn read(&self, _context: ServiceContext) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        self.calls.borrow_mut().push((
+            context.get_caller(),
+            format!(
+                "{}.{}",
+                context.get_service_name(),
+                context.get_service_method()
+            ),
+            context.get_payload().to_owned(),
+        ));
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+}
+
+fn new_wasm_service(
+    dispatcher: MockDispatcher,
+) -> WasmService<
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        MockDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db), dispatcher);
+
+    WasmService::new(sdk)
+}
+
+fn to_hex(wat: &str) -> Hex {
+    let code = wat::parse_str(wat).unwrap();
+    Hex::from_string(format!("0x{}", hex::encode(code))).unwrap()
+}
+
+fn call_payload(address: &Address, method: &str) -> CallPayload {
+    CallPayload {
+        address: address.clone(),
+        method:  method.to_owned(),
+        args:    String::new(),
+    }
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height: 1,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _ctx: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _ctx: Context, _: u64, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _ctx: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        _: Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block(&self, _ctx: Context, _: u64) -> ProtocolResult<Option<Block>> {
+        unimplemented!()
+    }
+
+    async fn get_receipt_by_hash(&self, _ctx: Context, _: Hash) -> ProtocolResult<Option<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _ctx: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,61 @@
+use muta_codec_derive::RlpFixedCodec;
+use serde::{Deserialize, Serialize};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Address, Bytes, Hash, Hex};
+use protocol::ProtocolResult;
+
+/// Payload
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DeployPayload {
+    /// Wasm binary of the contract
+    pub code:      Hex,
+    /// Input of the contract's `init` export, if it has one
+    pub init_args: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct DeployResponse {
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CallPayload {
+    pub address: Address,
+    /// Name of a function exported by the contract, taking and returning
+    /// nothing
+    pub method:  String,
+    pub args:    String,
+}
+
+pub type QueryPayload = CallPayload;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GetContractPayload {
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ContractResponse {
+    pub address:   Address,
+    pub deployer:  Address,
+    pub code_hash: Hash,
+}
+
+/// Event
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DeployEvent {
+    pub address:   Address,
+    pub deployer:  Address,
+    pub code_hash: Hash,
+}
+
+/// State
+#[derive(RlpFixedCodec, Clone, Debug)]
+pub struct Contract {
+    pub deployer:  Address,
+    /// Hash of the uploaded code
+    pub code_hash: Hash,
+    /// Code with cycles metering injected, ready to be instantiated
+    pub code:      Bytes,
+}
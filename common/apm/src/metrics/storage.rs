@@ -15,6 +15,7 @@ make_auto_flush_static_metric! {
     signed_tx,
     wal,
     hash_height,
+    evidence,
   }
 
   pub struct StoragePutCfTimeUsageVec: LocalCounter {
@@ -96,6 +97,10 @@ pub fn on_storage_get_cf(sc: StorageCategory, duration: Duration, keys: i64) {
             STORAGE_GET_CF_TIME_USAGE.hash_height.inc_by(seconds);
             STORAGE_GET_CF_COUNTER.hash_height.inc_by(keys);
         }
+        StorageCategory::Evidence => {
+            STORAGE_GET_CF_TIME_USAGE.evidence.inc_by(seconds);
+            STORAGE_GET_CF_COUNTER.evidence.inc_by(keys);
+        }
     }
 }
 
@@ -123,5 +128,9 @@ pub fn on_storage_put_cf(sc: StorageCategory, duration: Duration, size: i64) {
             STORAGE_PUT_CF_TIME_USAGE.hash_height.inc_by(seconds);
             STORAGE_PUT_CF_BYTES_COUNTER.hash_height.inc_by(size);
         }
+        StorageCategory::Evidence => {
+            STORAGE_PUT_CF_TIME_USAGE.evidence.inc_by(seconds);
+            STORAGE_PUT_CF_BYTES_COUNTER.evidence.inc_by(size);
+        }
     }
 }
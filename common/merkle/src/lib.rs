@@ -41,6 +41,20 @@ impl Merkle {
                     .collect()
             })
     }
+
+    /// Verify a proof returned by `get_proof_by_input_index`, `is_right` of a
+    /// proof node means the node is the right sibling.
+    pub fn verify_proof(root: &Hash, leaf: Hash, proof: &[ProofNode]) -> bool {
+        let computed = proof.iter().fold(leaf, |hash, node| {
+            if node.is_right {
+                merge(&hash, &node.hash)
+            } else {
+                merge(&node.hash, &hash)
+            }
+        });
+
+        &computed == root
+    }
 }
 
 fn merge(left: &Hash, right: &Hash) -> Hash {
@@ -53,6 +67,28 @@ fn merge(left: &Hash, right: &Hash) -> Hash {
     Hash::digest(Bytes::from(root))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_proof() {
+        let hashes = (0..7u8)
+            .map(|i| Hash::digest(Bytes::from(vec![i])))
+            .collect::<Vec<_>>();
+        let merkle = Merkle::from_hashes(hashes.clone());
+        let root = merkle.get_root_hash().unwrap();
+
+        for (index, leaf) in hashes.iter().enumerate() {
+            let proof = merkle.get_proof_by_input_index(index).unwrap();
+            assert!(Merkle::verify_proof(&root, leaf.clone(), &proof));
+        }
+
+        let proof = merkle.get_proof_by_input_index(0).unwrap();
+        assert!(!Merkle::verify_proof(&root, hashes[1].clone(), &proof));
+    }
+}
+
 #[rustfmt::skip]
 /// Bench in Intel(R) Core(TM) i7-4770HQ CPU @2.20GHz (8 x 2200):
 /// test benches::bench_merkle_1000_hashes  ... bench:   1,167,080 ns/iter (+/- 108,462)
@@ -6,9 +6,13 @@ use derive_more::Display;
 
 use protocol::traits::ExecutorFactory;
 use protocol::traits::{
-    APIAdapter, Context, ExecutorParams, MemPool, ServiceMapping, ServiceResponse, Storage,
+    APIAdapter, Context, Executor, ExecutorParams, MemPool, ServiceMapping, ServiceResponse,
+    SimulateResp, Storage, TraceFrame,
+};
+use protocol::types::{
+    Address, Block, Evidence, Hash, MerkleRoot, Metadata, Receipt, SignedTransaction,
+    TransactionRequest,
 };
-use protocol::types::{Address, Block, Hash, Receipt, SignedTransaction, TransactionRequest};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 #[derive(Debug, Display)]
@@ -22,6 +26,12 @@ pub enum APIError {
 
     #[display(fmt = "not found")]
     NotFound,
+
+    #[display(fmt = "state root after executing height {:?} was not found", height)]
+    StateRootNotFound { height: u64 },
+
+    #[display(fmt = "decode metadata failed {:?}", _0)]
+    DecodeMetadata(serde_json::Error),
 }
 
 impl std::error::Error for APIError {}
@@ -63,6 +73,40 @@ impl<
             pin_ef: PhantomData,
         }
     }
+
+    // Block headers carry the latest state root when they were proposed,
+    // together with the height it was executed to. Execution lags behind
+    // consensus, so the header is searched from `exec_height` onwards.
+    async fn get_executed_state_root(
+        &self,
+        ctx: Context,
+        exec_height: u64,
+    ) -> ProtocolResult<MerkleRoot> {
+        let latest_height = self
+            .storage
+            .get_latest_block(ctx.clone())
+            .await?
+            .header
+            .height;
+
+        for height in exec_height..=latest_height {
+            let header = match self.storage.get_block(ctx.clone(), height).await? {
+                Some(block) => block.header,
+                None => break,
+            };
+
+            if header.exec_height == exec_height {
+                return Ok(header.state_root);
+            } else if header.exec_height > exec_height {
+                break;
+            }
+        }
+
+        Err(APIError::StateRootNotFound {
+            height: exec_height,
+        }
+        .into())
+    }
 }
 
 #[async_trait]
@@ -161,4 +205,104 @@ impl<
             payload,
         })
     }
+
+    async fn simulate_transaction(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+        cycles_price: u64,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+    ) -> ProtocolResult<SimulateResp> {
+        let block = self.get_block_by_height(ctx.clone(), Some(height)).await?;
+
+        let mut executor = EF::from_root(
+            block.header.state_root.clone(),
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            Arc::clone(&self.service_mapping),
+        )?;
+
+        let mut params = ExecutorParams {
+            state_root: block.header.state_root,
+            height,
+            timestamp: block.header.timestamp,
+            cycles_limit: u64::max_value(),
+            proposer: block.header.proposer,
+        };
+        params.cycles_limit = get_metadata(executor.as_ref(), &params)?.cycles_limit;
+
+        executor.simulate(
+            &params,
+            &caller,
+            cycles_price,
+            &TransactionRequest {
+                service_name,
+                method,
+                payload,
+            },
+            cycles_limit.min(params.cycles_limit),
+        )
+    }
+
+    async fn get_evidences(&self, ctx: Context, height: u64) -> ProtocolResult<Vec<Evidence>> {
+        self.storage.get_evidences(ctx, height).await
+    }
+
+    async fn trace_transaction(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<TraceFrame> {
+        let receipt = self
+            .get_receipt_by_tx_hash(ctx.clone(), tx_hash.clone())
+            .await?;
+        let block = self
+            .get_block_by_height(ctx.clone(), Some(receipt.height))
+            .await?;
+        let state_root = self
+            .get_executed_state_root(ctx.clone(), block.header.height - 1)
+            .await?;
+
+        let txs = self
+            .storage
+            .get_transactions(ctx.clone(), block.header.height, block.ordered_tx_hashes)
+            .await?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| APIError::NotFound)?;
+
+        let mut executor = EF::from_root(
+            state_root.clone(),
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            Arc::clone(&self.service_mapping),
+        )?;
+
+        let mut params = ExecutorParams {
+            state_root,
+            height: block.header.height,
+            timestamp: block.header.timestamp,
+            cycles_limit: u64::max_value(),
+            proposer: block.header.proposer,
+        };
+
+        // The block was executed with the cycles limit of its parent state
+        params.cycles_limit = get_metadata(executor.as_ref(), &params)?.cycles_limit;
+
+        executor
+            .trace(ctx, &params, &txs, &tx_hash)?
+            .ok_or_else(|| APIError::NotFound.into())
+    }
+}
+
+// Metadata of the chain at the state of `params`
+fn get_metadata(executor: &dyn Executor, params: &ExecutorParams) -> ProtocolResult<Metadata> {
+    let caller = Address::from_hex("0x0000000000000000000000000000000000000000")?;
+    let metadata = executor.read(params, &caller, 1, &TransactionRequest {
+        service_name: "metadata".to_owned(),
+        method:       "get_metadata".to_owned(),
+        payload:      "".to_owned(),
+    })?;
+
+    Ok(serde_json::from_str(&metadata.succeed_data).map_err(APIError::DecodeMetadata)?)
 }
@@ -18,6 +18,10 @@ pub struct GraphQLConfig {
     // It is used to prevent DOS attacking through memory exhaustion.
     // The default value is 1024 * 1024, which is 1MB.
     pub max_payload_size: usize,
+
+    // Service resolving names given as caller of queries, names are rejected
+    // if it's none.
+    pub name_service: Option<String>,
 }
 
 impl Default for GraphQLConfig {
@@ -32,6 +36,7 @@ impl Default for GraphQLConfig {
             workers:          num_cpus::get(),
             maxconn:          25000,
             max_payload_size: 1024 * 1024, // 1MB
+            name_service:     None,
         }
     }
 }
@@ -19,11 +19,13 @@ use common_crypto::{
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{APIAdapter, Context};
+use protocol::types::{ResolveNamePayload, ResolveNameResponse};
 
 use crate::config::GraphQLConfig;
 use crate::schema::{
-    to_signed_transaction, to_transaction, Address, Block, Bytes, Hash, InputRawTransaction,
-    InputTransactionEncryption, Receipt, ServiceResponse, SignedTransaction, Uint64,
+    to_signed_transaction, to_transaction, Address, Block, Bytes, Evidence, Hash,
+    InputRawTransaction, InputTransactionEncryption, Receipt, SchemaError, ServiceResponse,
+    SignedTransaction, SimulateResp, TraceFrame, Uint64,
 };
 
 lazy_static! {
@@ -33,8 +35,9 @@ lazy_static! {
 // This is accessible as state in Tide, and as executor context in Juniper.
 #[derive(Clone)]
 struct State {
-    adapter: Arc<Box<dyn APIAdapter>>,
-    schema:  Arc<Schema>,
+    adapter:      Arc<Box<dyn APIAdapter>>,
+    schema:       Arc<Schema>,
+    name_service: Option<String>,
 }
 
 // We define `Query` unit struct here. GraphQL queries will refer to this
@@ -126,6 +129,23 @@ impl Query {
         Ok(Receipt::from(receipt))
     }
 
+    #[graphql(
+        name = "traceTransaction",
+        description = "Execute the transaction again at the state its block started from, return its service calls"
+    )]
+    async fn trace_transaction(state_ctx: &State, tx_hash: Hash) -> FieldResult<TraceFrame> {
+        let ctx = Context::new();
+
+        let hash = protocol::types::Hash::from_hex(&tx_hash.as_hex())?;
+
+        let trace = state_ctx
+            .adapter
+            .trace_transaction(ctx.clone(), hash)
+            .await?;
+
+        Ok(TraceFrame::from(trace))
+    }
+
     #[graphql(name = "queryService", description = "query service")]
     async fn query_service(
         state_ctx: &State,
@@ -157,7 +177,7 @@ impl Query {
             None => 1,
         };
 
-        let address = protocol::types::Address::from_hex(&caller.as_hex())?;
+        let address = resolve_address(state_ctx, height, caller).await?;
 
         let exec_resp = state_ctx
             .adapter
@@ -174,6 +194,196 @@ impl Query {
             .await?;
         Ok(ServiceResponse::from(exec_resp))
     }
+
+    #[graphql(
+        name = "simulateTransaction",
+        description = "Execute a write request with tx hooks on the state at the given height without committing"
+    )]
+    async fn simulate_transaction(
+        state_ctx: &State,
+        height: Option<Uint64>,
+        cycles_limit: Option<Uint64>,
+        cycles_price: Option<Uint64>,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+    ) -> FieldResult<SimulateResp> {
+        let resp = simulate(
+            state_ctx,
+            height,
+            cycles_limit,
+            cycles_price,
+            caller,
+            service_name,
+            method,
+            payload,
+        )
+        .await?;
+
+        Ok(SimulateResp::from(resp))
+    }
+
+    #[graphql(
+        name = "estimateCycles",
+        description = "Cycles used by a write request, fails if the request fails"
+    )]
+    async fn estimate_cycles(
+        state_ctx: &State,
+        height: Option<Uint64>,
+        cycles_limit: Option<Uint64>,
+        cycles_price: Option<Uint64>,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+    ) -> FieldResult<Uint64> {
+        let resp = simulate(
+            state_ctx,
+            height,
+            cycles_limit,
+            cycles_price,
+            caller,
+            service_name,
+            method,
+            payload,
+        )
+        .await?;
+
+        if resp.response.is_error() {
+            return Err(SchemaError::SimulationFailed {
+                code:    resp.response.code,
+                message: resp.response.error_message,
+            }
+            .into());
+        }
+
+        Ok(Uint64::from(resp.cycles_used))
+    }
+
+    #[graphql(
+        name = "getEvidences",
+        description = "Get evidences of validators who signed conflicting consensus messages"
+    )]
+    async fn get_evidences(
+        state_ctx: &State,
+        height: Option<Uint64>,
+    ) -> FieldResult<Vec<Evidence>> {
+        let ctx = Context::new();
+
+        let height = match height {
+            Some(id) => id.try_into_u64()?,
+            None => {
+                block_on(state_ctx.adapter.get_block_by_height(Context::new(), None))?
+                    .header
+                    .height
+            }
+        };
+
+        let evidences = state_ctx.adapter.get_evidences(ctx.clone(), height).await?;
+
+        Ok(evidences.into_iter().map(Evidence::from).collect())
+    }
+}
+
+async fn simulate(
+    state_ctx: &State,
+    height: Option<Uint64>,
+    cycles_limit: Option<Uint64>,
+    cycles_price: Option<Uint64>,
+    caller: Address,
+    service_name: String,
+    method: String,
+    payload: String,
+) -> FieldResult<protocol::traits::SimulateResp> {
+    let height = match height {
+        Some(id) => id.try_into_u64()?,
+        None => {
+            state_ctx
+                .adapter
+                .get_block_by_height(Context::new(), None)
+                .await?
+                .header
+                .height
+        }
+    };
+    // Capped at the cycles limit of a block
+    let cycles_limit = match cycles_limit {
+        Some(cycles_limit) => cycles_limit.try_into_u64()?,
+        None => std::u64::MAX,
+    };
+
+    let cycles_price = match cycles_price {
+        Some(cycles_price) => cycles_price.try_into_u64()?,
+        None => 1,
+    };
+
+    let address = resolve_address(state_ctx, height, caller).await?;
+
+    let resp = state_ctx
+        .adapter
+        .simulate_transaction(
+            Context::new(),
+            height,
+            cycles_limit,
+            cycles_price,
+            address,
+            service_name,
+            method,
+            payload,
+        )
+        .await?;
+    Ok(resp)
+}
+
+// Only callers of queries and simulations are resolved, the `Address` scalar
+// is parsed without access to the chain. Names are resolved through `resolve`
+// of the configured name service at the given height, so a name always means
+// the same address in one query.
+async fn resolve_address(
+    state_ctx: &State,
+    height: u64,
+    address: Address,
+) -> FieldResult<protocol::types::Address> {
+    let name = match address.as_name() {
+        Some(name) => name.to_owned(),
+        None => return Ok(protocol::types::Address::from_hex(&address.as_hex())?),
+    };
+    let name_service = match state_ctx.name_service.as_ref() {
+        Some(name_service) => name_service,
+        None => {
+            return Err(SchemaError::UnresolvedName {
+                name,
+                reason: "name service not configured".to_owned(),
+            }
+            .into())
+        }
+    };
+
+    let payload = serde_json::to_string(&ResolveNamePayload { name: name.clone() })?;
+    let resp = state_ctx
+        .adapter
+        .query_service(
+            Context::new(),
+            height,
+            std::u64::MAX,
+            1,
+            protocol::types::Address::from_service_name(name_service),
+            name_service.to_owned(),
+            "resolve".to_owned(),
+            payload,
+        )
+        .await?;
+    if resp.is_error() {
+        return Err(SchemaError::UnresolvedName {
+            name,
+            reason: resp.error_message,
+        }
+        .into());
+    }
+
+    let resolved: ResolveNameResponse = serde_json::from_str(&resp.succeed_data)?;
+    Ok(resolved.address)
 }
 
 struct Mutation;
@@ -286,8 +496,9 @@ pub async fn start_graphql<Adapter: APIAdapter + 'static>(cfg: GraphQLConfig, ad
     let schema = Schema::new(Query, Mutation);
 
     let state = State {
-        adapter: Arc::new(Box::new(adapter)),
-        schema:  Arc::new(schema),
+        adapter:      Arc::new(Box::new(adapter)),
+        schema:       Arc::new(schema),
+        name_service: cfg.name_service.clone(),
     };
 
     let path_graphql_uri = cfg.graphql_uri.to_owned();
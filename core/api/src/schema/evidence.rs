@@ -0,0 +1,35 @@
+use crate::schema::{Address, Bytes, Hash, Uint64};
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(
+    description = "Evidence proves that a validator signed two conflicting messages \
+                   of the same type at the same height and round."
+)]
+pub struct Evidence {
+    #[graphql(description = "Hash of the evidence")]
+    pub hash:          Hash,
+    #[graphql(description = "Duplicate proposal, duplicate prevote or duplicate precommit")]
+    pub evidence_type: String,
+    pub height:        Uint64,
+    pub round:         Uint64,
+    #[graphql(description = "The validator who signed conflicting messages")]
+    pub offender:      Address,
+    #[graphql(description = "First signed message in overlord rlp encoding")]
+    pub first:         Bytes,
+    #[graphql(description = "Second signed message in overlord rlp encoding")]
+    pub second:        Bytes,
+}
+
+impl From<protocol::types::Evidence> for Evidence {
+    fn from(evidence: protocol::types::Evidence) -> Self {
+        Evidence {
+            hash:          Hash::from(evidence.hash()),
+            evidence_type: evidence.evidence_type.to_string(),
+            height:        Uint64::from(evidence.height),
+            round:         Uint64::from(evidence.round),
+            offender:      Address::from(evidence.offender),
+            first:         Bytes::from(evidence.first),
+            second:        Bytes::from(evidence.second),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 mod block;
+mod evidence;
 mod receipt;
+mod trace;
 mod transaction;
 
 use std::convert::From;
@@ -10,7 +12,9 @@ use std::num::ParseIntError;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 pub use block::{Block, BlockHeader};
-pub use receipt::{Event, Receipt, ReceiptResponse};
+pub use evidence::Evidence;
+pub use receipt::{Event, Receipt, ReceiptResponse, SimulateResp};
+pub use trace::{TraceFrame, TraceStateKey};
 pub use transaction::{
     to_signed_transaction, to_transaction, InputRawTransaction, InputTransactionEncryption,
     SignedTransaction,
@@ -39,7 +43,9 @@ pub struct Hash(String);
 pub type MerkleRoot = Hash;
 
 #[derive(juniper::GraphQLScalarValue, Clone)]
-#[graphql(description = "20 bytes of account address")]
+#[graphql(
+    description = "20 bytes of account address. A caller of queries may also be a name registered in the configured name service, e.g. alice.muta"
+)]
 pub struct Address(String);
 
 #[derive(juniper::GraphQLScalarValue, Clone)]
@@ -60,6 +66,15 @@ impl Address {
     pub fn as_hex(&self) -> String {
         self.0.to_uppercase()
     }
+
+    /// Names are all inputs not starting with 0x.
+    pub fn as_name(&self) -> Option<&str> {
+        if self.0.starts_with("0x") || self.0.starts_with("0X") {
+            None
+        } else {
+            Some(&self.0)
+        }
+    }
 }
 
 impl Uint64 {
@@ -126,6 +141,12 @@ pub enum SchemaError {
 
     #[display(fmt = "hex should start with 0x")]
     HexPrefix,
+
+    #[display(fmt = "name {:?} not resolved: {}", name, reason)]
+    UnresolvedName { name: String, reason: String },
+
+    #[display(fmt = "simulation failed with code {}: {}", code, message)]
+    SimulationFailed { code: u64, message: String },
 }
 
 impl std::error::Error for SchemaError {}
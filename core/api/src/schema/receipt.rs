@@ -16,6 +16,13 @@ pub struct Event {
     pub data:    String,
 }
 
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct SimulateResp {
+    pub cycles_used: Uint64,
+    pub events:      Vec<Event>,
+    pub response:    ServiceResponse,
+}
+
 #[derive(juniper::GraphQLObject, Clone)]
 pub struct ReceiptResponse {
     pub service_name: String,
@@ -36,6 +43,16 @@ impl From<protocol::types::Receipt> for Receipt {
     }
 }
 
+impl From<protocol::traits::SimulateResp> for SimulateResp {
+    fn from(resp: protocol::traits::SimulateResp) -> Self {
+        Self {
+            cycles_used: Uint64::from(resp.cycles_used),
+            events:      resp.events.into_iter().map(Event::from).collect(),
+            response:    ServiceResponse::from(resp.response),
+        }
+    }
+}
+
 impl From<protocol::types::Event> for Event {
     fn from(event: protocol::types::Event) -> Self {
         Self {
@@ -0,0 +1,48 @@
+use crate::schema::{Bytes, Event, ServiceResponse, Uint64};
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(description = "A service call made by the traced transaction, nested calls included")]
+pub struct TraceFrame {
+    pub service:     String,
+    pub method:      String,
+    pub payload:     String,
+    pub cycles_used: Uint64,
+    pub reads:       Vec<TraceStateKey>,
+    pub writes:      Vec<TraceStateKey>,
+    pub events:      Vec<Event>,
+    pub response:    Option<ServiceResponse>,
+    pub panic:       Option<String>,
+    pub calls:       Vec<TraceFrame>,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct TraceStateKey {
+    pub service: String,
+    pub key:     Bytes,
+}
+
+impl From<protocol::traits::TraceFrame> for TraceFrame {
+    fn from(frame: protocol::traits::TraceFrame) -> Self {
+        Self {
+            service:     frame.service,
+            method:      frame.method,
+            payload:     frame.payload,
+            cycles_used: Uint64::from(frame.cycles_used),
+            reads:       frame.reads.into_iter().map(TraceStateKey::from).collect(),
+            writes:      frame.writes.into_iter().map(TraceStateKey::from).collect(),
+            events:      frame.events.into_iter().map(Event::from).collect(),
+            response:    frame.response.map(ServiceResponse::from),
+            panic:       frame.panic,
+            calls:       frame.calls.into_iter().map(TraceFrame::from).collect(),
+        }
+    }
+}
+
+impl From<protocol::traits::TraceStateKey> for TraceStateKey {
+    fn from(key: protocol::traits::TraceStateKey) -> Self {
+        Self {
+            service: key.service,
+            key:     Bytes::from(key.key),
+        }
+    }
+}
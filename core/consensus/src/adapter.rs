@@ -20,17 +20,18 @@ use protocol::traits::{
     ServiceMapping, Storage, SynchronizationAdapter, TrustFeedback,
 };
 use protocol::types::{
-    Address, Block, Bytes, Hash, Hex, MerkleRoot, Metadata, Proof, Receipt, SignedTransaction,
-    TransactionRequest, Validator,
+    Address, Block, Bytes, Evidence, Hash, Hex, MerkleRoot, Metadata, Proof, Receipt,
+    SignedTransaction, TransactionRequest, Validator,
 };
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
 use crate::consensus::gen_overlord_status;
 use crate::fixed_types::{
-    FixedBlock, FixedHeight, FixedPill, FixedProof, FixedSignedTxs, PullTxsRequest,
+    BlockPart, FixedBlock, FixedHeight, FixedPill, FixedProof, FixedSignedTxs, PullTxsRequest,
 };
 use crate::message::{
-    BROADCAST_HEIGHT, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS,
+    BROADCAST_HEIGHT, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_BLOCK_STREAM, RPC_SYNC_PULL_PROOF,
+    RPC_SYNC_PULL_TXS, RPC_SYNC_PULL_TXS_STREAM,
 };
 use crate::status::{ExecutedInfo, StatusAgent};
 use crate::util::{convert_hex_to_bls_pubkeys, ExecuteInfo, OverlordCrypto};
@@ -39,6 +40,8 @@ use crate::BlockProofField::{BitMap, HashMismatch, HeightMismatch, Signature, We
 use crate::{BlockHeaderField, BlockProofField, ConsensusError};
 
 const OVERLORD_GAP: usize = 10;
+// Txs pulled in one request from nodes without chunked sync
+const SYNC_TXS_CHUNK_SIZE: usize = 5000;
 
 pub struct OverlordConsensusAdapter<
     EF: ExecutorFactory<DB, S, Mapping>,
@@ -55,9 +58,10 @@ pub struct OverlordConsensusAdapter<
     service_mapping:  Arc<Mapping>,
     overlord_handler: RwLock<Option<OverlordHandler<FixedPill>>>,
 
-    exec_queue:  Sender<ExecuteInfo>,
-    exec_demons: Option<ExecDemons<S, DB, EF, Mapping>>,
-    crypto:      Arc<OverlordCrypto>,
+    exec_queue:    Sender<ExecuteInfo>,
+    exec_demons:   Option<ExecDemons<S, DB, EF, Mapping>>,
+    crypto:        Arc<OverlordCrypto>,
+    parallel_exec: bool,
 }
 
 #[async_trait]
@@ -178,14 +182,24 @@ where
         self.storage.load_overlord_wal(ctx).await
     }
 
+    #[muta_apm::derive::tracing_span(kind = "consensus.adapter")]
+    async fn save_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.storage.insert_evidence(ctx, evidence).await
+    }
+
     #[muta_apm::derive::tracing_span(kind = "consensus.adapter")]
     async fn pull_block(&self, ctx: Context, height: u64, end: &str) -> ProtocolResult<Block> {
         log::debug!("consensus: send rpc pull block {}", height);
-        let res = self
+        let parts = self
             .network
-            .call::<FixedHeight, FixedBlock>(ctx, end, FixedHeight::new(height), Priority::High)
+            .call_stream::<FixedHeight, BlockPart>(
+                ctx,
+                end,
+                FixedHeight::new(height),
+                Priority::High,
+            )
             .await?;
-        Ok(res.inner)
+        BlockPart::join(parts)
     }
 
     /// Get the current height from storage.
@@ -260,11 +274,12 @@ where
         params: &ExecutorParams,
         txs: &[SignedTransaction],
     ) -> ProtocolResult<ExecutorResp> {
-        let mut executor = EF::from_root(
+        let mut executor = EF::from_root_with_parallel(
             params.state_root.clone(),
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             Arc::clone(&self.service_mapping),
+            self.parallel_exec,
         )?;
         let inst = Instant::now();
         let resp = executor.exec(ctx, params, txs)?;
@@ -277,22 +292,33 @@ where
     /// Pull some blocks from other nodes from `begin` to `end`.
     #[muta_apm::derive::tracing_span(kind = "consensus.adapter")]
     async fn get_block_from_remote(&self, ctx: Context, height: u64) -> ProtocolResult<Block> {
-        let res = self
-            .network
-            .call::<FixedHeight, FixedBlock>(
-                ctx,
-                RPC_SYNC_PULL_BLOCK,
-                FixedHeight::new(height),
-                Priority::High,
-            )
-            .await;
+        let res = match self
+            .pull_block(ctx.clone(), height, RPC_SYNC_PULL_BLOCK_STREAM)
+            .await
+        {
+            Ok(block) => Ok(block),
+            Err(e) => {
+                // Remote may not serve chunked form yet, fallback to old one
+                log::debug!("consensus: pull block stream {}, fallback", e);
+
+                self.network
+                    .call::<FixedHeight, FixedBlock>(
+                        ctx,
+                        RPC_SYNC_PULL_BLOCK,
+                        FixedHeight::new(height),
+                        Priority::High,
+                    )
+                    .await
+                    .map(|data| data.inner)
+            }
+        };
         match res {
-            Ok(data) => {
+            Ok(block) => {
                 common_apm::metrics::consensus::CONSENSUS_RESULT_COUNTER_VEC_STATIC
                     .get_block_from_remote
                     .success
                     .inc();
-                Ok(data.inner)
+                Ok(block)
             }
             Err(err) => {
                 common_apm::metrics::consensus::CONSENSUS_RESULT_COUNTER_VEC_STATIC
@@ -318,14 +344,38 @@ where
     ) -> ProtocolResult<Vec<SignedTransaction>> {
         let res = self
             .network
-            .call::<PullTxsRequest, FixedSignedTxs>(
-                ctx,
-                RPC_SYNC_PULL_TXS,
+            .call_stream::<PullTxsRequest, SignedTransaction>(
+                ctx.clone(),
+                RPC_SYNC_PULL_TXS_STREAM,
                 PullTxsRequest::new(height, hashes.to_vec()),
                 Priority::High,
             )
-            .await?;
-        Ok(res.inner)
+            .await;
+
+        let e = match res {
+            Ok(txs) => return Ok(txs),
+            Err(e) => e,
+        };
+
+        // Remote may not serve chunked form yet, fallback to old one, which
+        // must be pulled in chunks to fit in a frame.
+        log::debug!("consensus: pull txs stream {}, fallback", e);
+        let mut txs = Vec::with_capacity(hashes.len());
+        for tx_hashes in hashes.chunks(SYNC_TXS_CHUNK_SIZE) {
+            let res = self
+                .network
+                .call::<PullTxsRequest, FixedSignedTxs>(
+                    ctx.clone(),
+                    RPC_SYNC_PULL_TXS,
+                    PullTxsRequest::new(height, tx_hashes.to_vec()),
+                    Priority::High,
+                )
+                .await?;
+
+            txs.extend(res.inner);
+        }
+
+        Ok(txs)
     }
 
     /// Pull a proof of certain block from other nodes
@@ -812,6 +862,7 @@ where
         service_mapping: Arc<Mapping>,
         status_agent: StatusAgent,
         crypto: Arc<OverlordCrypto>,
+        parallel_exec: bool,
     ) -> ProtocolResult<Self> {
         let (exec_queue, rx) = channel(OVERLORD_GAP);
         let exec_demons = Some(ExecDemons::new(
@@ -820,6 +871,7 @@ where
             Arc::clone(&service_mapping),
             rx,
             status_agent,
+            parallel_exec,
         ));
 
         let adapter = OverlordConsensusAdapter {
@@ -832,6 +884,7 @@ where
             exec_queue,
             exec_demons,
             crypto,
+            parallel_exec,
         };
 
         Ok(adapter)
@@ -853,9 +906,10 @@ pub struct ExecDemons<S, DB, EF, Mapping> {
     trie_db:         Arc<DB>,
     service_mapping: Arc<Mapping>,
 
-    pin_ef: PhantomData<EF>,
-    queue:  Receiver<ExecuteInfo>,
-    status: StatusAgent,
+    pin_ef:        PhantomData<EF>,
+    queue:         Receiver<ExecuteInfo>,
+    status:        StatusAgent,
+    parallel_exec: bool,
 }
 
 impl<S, DB, EF, Mapping> ExecDemons<S, DB, EF, Mapping>
@@ -871,6 +925,7 @@ where
         service_mapping: Arc<Mapping>,
         rx: Receiver<ExecuteInfo>,
         status_agent: StatusAgent,
+        parallel_exec: bool,
     ) -> Self {
         ExecDemons {
             storage,
@@ -879,6 +934,7 @@ where
             queue: rx,
             pin_ef: PhantomData,
             status: status_agent,
+            parallel_exec,
         }
     }
 
@@ -913,11 +969,12 @@ where
         let state_root = self.status.to_inner().get_latest_state_root();
 
         let now = Instant::now();
-        let mut executor = EF::from_root(
+        let mut executor = EF::from_root_with_parallel(
             state_root.clone(),
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             Arc::clone(&self.service_mapping),
+            self.parallel_exec,
         )?;
         let exec_params = ExecutorParams {
             state_root: state_root.clone(),
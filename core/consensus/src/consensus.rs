@@ -8,14 +8,18 @@ use overlord::types::{
 };
 use overlord::{DurationConfig, Overlord, OverlordHandler};
 
+use log::{error, warn};
+
 use common_apm::muta_apm;
 
-use protocol::traits::{Consensus, ConsensusAdapter, NodeInfo};
-use protocol::types::Validator;
+use protocol::traits::{Consensus, ConsensusAdapter, MessageTarget, NodeInfo};
+use protocol::types::{Evidence, Validator};
 use protocol::ProtocolResult;
 
 use crate::engine::ConsensusEngine;
+use crate::evidence::EvidencePool;
 use crate::fixed_types::FixedPill;
+use crate::message::{self, END_GOSSIP_EVIDENCE};
 use crate::status::StatusAgent;
 use crate::util::OverlordCrypto;
 use crate::wal::SignedTxsWAL;
@@ -28,7 +32,12 @@ pub struct OverlordConsensus<Adapter: ConsensusAdapter + 'static> {
         Overlord<FixedPill, ConsensusEngine<Adapter>, OverlordCrypto, ConsensusEngine<Adapter>>,
     >,
     /// An overlord consensus protocol handler.
-    handler: OverlordHandler<FixedPill>,
+    handler:       OverlordHandler<FixedPill>,
+    /// Detect validators who sign conflicting proposals or votes.
+    evidence_pool: EvidencePool,
+
+    status_agent: StatusAgent,
+    adapter:      Arc<Adapter>,
 }
 
 #[async_trait]
@@ -38,6 +47,14 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
         let signed_proposal: SignedProposal<FixedPill> = rlp::decode(&proposal)
             .map_err(|_| ConsensusError::DecodeErr(ConsensusType::SignedProposal))?;
 
+        let latest_height = self.status_agent.latest_committed_height();
+        if let Some(evidence) = self
+            .evidence_pool
+            .check_proposal(&signed_proposal, latest_height)
+        {
+            self.report_evidence(ctx.clone(), evidence).await;
+        }
+
         let msg = OverlordMsg::SignedProposal(signed_proposal);
         tracing_overlord_message(ctx.clone(), &msg);
 
@@ -63,6 +80,11 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
         let signed_vote: SignedVote =
             rlp::decode(&vote).map_err(|_| ConsensusError::DecodeErr(ConsensusType::SignedVote))?;
 
+        let latest_height = self.status_agent.latest_committed_height();
+        if let Some(evidence) = self.evidence_pool.check_vote(&signed_vote, latest_height) {
+            self.report_evidence(ctx.clone(), evidence).await;
+        }
+
         let msg = OverlordMsg::SignedVote(signed_vote);
         tracing_overlord_message(ctx.clone(), &msg);
 
@@ -99,6 +121,17 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
             .expect("Overlord handler disconnect");
         Ok(())
     }
+
+    #[muta_apm::derive::tracing_span(kind = "consensus")]
+    async fn set_evidence(&self, ctx: Context, evidence: Vec<u8>) -> ProtocolResult<()> {
+        let evidence: Evidence = bincode::deserialize(&evidence)
+            .map_err(|_| ConsensusError::DecodeErr(ConsensusType::Evidence))?;
+
+        if self.evidence_pool.verify_evidence(&evidence)? {
+            self.report_evidence(ctx, evidence).await;
+        }
+        Ok(())
+    }
 }
 
 impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
@@ -118,6 +151,7 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
             Arc::clone(&crypto),
             lock,
         ));
+        let evidence_pool = EvidencePool::new(Arc::clone(&crypto));
 
         let overlord = Overlord::new(
             node_info.self_address.as_bytes(),
@@ -146,8 +180,11 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
         }
 
         Self {
-            inner:   Arc::new(overlord),
+            inner: Arc::new(overlord),
             handler: overlord_handler,
+            evidence_pool,
+            status_agent,
+            adapter,
         }
     }
 
@@ -155,6 +192,33 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
         self.handler.clone()
     }
 
+    // Evidence is saved for governance or slashing services, then gossiped
+    // so that every node gets it even if offender only sent conflicting
+    // messages to part of the network.
+    async fn report_evidence(&self, ctx: Context, evidence: Evidence) {
+        warn!(
+            "consensus: {} from {:?} at height {} round {}",
+            evidence.evidence_type, evidence.offender, evidence.height, evidence.round
+        );
+
+        if let Err(e) = self
+            .adapter
+            .save_evidence(ctx.clone(), evidence.clone())
+            .await
+        {
+            error!("consensus: save evidence {}", e);
+        }
+
+        let msg = message::Evidence::from(evidence).0;
+        if let Err(e) = self
+            .adapter
+            .transmit(ctx, msg, END_GOSSIP_EVIDENCE, MessageTarget::Broadcast)
+            .await
+        {
+            error!("consensus: broadcast evidence {}", e);
+        }
+    }
+
     pub async fn run(
         &self,
         interval: u64,
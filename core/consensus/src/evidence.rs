@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use overlord::types::{SignedProposal, SignedVote, VoteType};
+use overlord::Crypto;
+use parking_lot::Mutex;
+use rlp::Encodable;
+
+use protocol::types::{Address, Evidence, EvidenceType, Hash};
+use protocol::{Bytes, ProtocolResult};
+
+use crate::fixed_types::FixedPill;
+use crate::util::OverlordCrypto;
+use crate::{ConsensusError, ConsensusType};
+
+/// Signed messages too far away from latest committed height are ignored,
+/// so that memory used by evidence pool is bounded.
+pub const MAX_EVIDENCE_HEIGHT_GAP: u64 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SignedMsgKey {
+    evidence_type: EvidenceType,
+    height:        u64,
+    round:         u64,
+    signer:        Bytes,
+}
+
+#[derive(Clone, Debug)]
+struct SignedMsg {
+    block_hash: Bytes,
+    signature:  Bytes,
+    // Hash of the message which is signed
+    hash:       Bytes,
+    // Overlord rlp encoded signed message
+    raw:        Bytes,
+}
+
+#[derive(Default)]
+struct Book {
+    msgs:  HashMap<SignedMsgKey, SignedMsg>,
+    // Evidence hash to height, for deduplication
+    known: HashMap<Hash, u64>,
+}
+
+impl Book {
+    fn prune(&mut self, latest_height: u64) {
+        let is_recent = |height: u64| height + MAX_EVIDENCE_HEIGHT_GAP > latest_height;
+
+        self.msgs.retain(|key, _| is_recent(key.height));
+        self.known.retain(|_, height| is_recent(*height));
+    }
+}
+
+/// Remember signed votes and proposals per (height, round, signer), detect
+/// validators who sign two different blocks in the same round.
+pub struct EvidencePool {
+    crypto: Arc<OverlordCrypto>,
+    book:   Mutex<Book>,
+}
+
+impl EvidencePool {
+    pub fn new(crypto: Arc<OverlordCrypto>) -> Self {
+        EvidencePool {
+            crypto,
+            book: Mutex::new(Book::default()),
+        }
+    }
+
+    /// Return an evidence if voter already voted a different block in the
+    /// same height, round and vote type.
+    pub fn check_vote(&self, signed_vote: &SignedVote, latest_height: u64) -> Option<Evidence> {
+        let (key, msg) = self.vote_msg(signed_vote);
+        self.check(key, msg, latest_height)
+    }
+
+    /// Return an evidence if proposer already proposed a different block in
+    /// the same height and round.
+    pub fn check_proposal(
+        &self,
+        signed_proposal: &SignedProposal<FixedPill>,
+        latest_height: u64,
+    ) -> Option<Evidence> {
+        let (key, msg) = self.proposal_msg(signed_proposal);
+        self.check(key, msg, latest_height)
+    }
+
+    /// Verify evidence from other nodes. Return false if this evidence is
+    /// already known.
+    pub fn verify_evidence(&self, evidence: &Evidence) -> ProtocolResult<bool> {
+        let invalid = |reason: &str| ConsensusError::InvalidEvidence(reason.to_owned());
+
+        let (first_key, first) = self.decode_msg(evidence.evidence_type, &evidence.first)?;
+        let (second_key, second) = self.decode_msg(evidence.evidence_type, &evidence.second)?;
+
+        if first_key != second_key {
+            return Err(invalid("messages from different height, round or signer").into());
+        }
+        if first_key.height != evidence.height
+            || first_key.round != evidence.round
+            || first_key.signer != evidence.offender.as_bytes()
+        {
+            return Err(invalid("messages mismatch evidence").into());
+        }
+        if first.block_hash == second.block_hash {
+            return Err(invalid("messages sign same block").into());
+        }
+        if !self.verify_msg(&first_key.signer, &first)
+            || !self.verify_msg(&first_key.signer, &second)
+        {
+            return Err(invalid("bad signature").into());
+        }
+
+        let mut book = self.book.lock();
+        Ok(book
+            .known
+            .insert(evidence.hash(), evidence.height)
+            .is_none())
+    }
+
+    fn check(&self, key: SignedMsgKey, msg: SignedMsg, latest_height: u64) -> Option<Evidence> {
+        if key.height + MAX_EVIDENCE_HEIGHT_GAP <= latest_height
+            || key.height > latest_height + MAX_EVIDENCE_HEIGHT_GAP
+        {
+            return None;
+        }
+
+        let mut book = self.book.lock();
+        book.prune(latest_height);
+
+        let exist = match book.msgs.get(&key) {
+            Some(exist) if exist.block_hash != msg.block_hash => exist.clone(),
+            Some(_) => return None,
+            None => {
+                book.msgs.insert(key, msg);
+                return None;
+            }
+        };
+
+        // Signatures are only verified on conflict, overlord will verify
+        // them anyway. A forged message should never frame an honest signer.
+        if !self.verify_msg(&key.signer, &msg) {
+            return None;
+        }
+        if !self.verify_msg(&key.signer, &exist) {
+            book.msgs.insert(key, msg);
+            return None;
+        }
+
+        let offender = Address::from_bytes(key.signer.clone()).ok()?;
+        let evidence = Evidence::new(
+            key.evidence_type,
+            key.height,
+            key.round,
+            offender,
+            exist.raw,
+            msg.raw,
+        );
+
+        if book.known.insert(evidence.hash(), key.height).is_some() {
+            return None;
+        }
+
+        Some(evidence)
+    }
+
+    fn verify_msg(&self, signer: &Bytes, msg: &SignedMsg) -> bool {
+        self.crypto
+            .verify_signature(msg.signature.clone(), msg.hash.clone(), signer.clone())
+            .is_ok()
+    }
+
+    fn vote_msg(&self, signed_vote: &SignedVote) -> (SignedMsgKey, SignedMsg) {
+        let vote = &signed_vote.vote;
+        let evidence_type = match vote.vote_type {
+            VoteType::Prevote => EvidenceType::DuplicatePrevote,
+            VoteType::Precommit => EvidenceType::DuplicatePrecommit,
+        };
+
+        let key = SignedMsgKey {
+            evidence_type,
+            height: vote.height,
+            round: vote.round,
+            signer: signed_vote.voter.clone(),
+        };
+        let msg = SignedMsg {
+            block_hash: vote.block_hash.clone(),
+            signature:  signed_vote.signature.clone(),
+            hash:       self.crypto.hash(Bytes::from(rlp::encode(vote))),
+            raw:        Bytes::from(signed_vote.rlp_bytes()),
+        };
+
+        (key, msg)
+    }
+
+    fn proposal_msg(
+        &self,
+        signed_proposal: &SignedProposal<FixedPill>,
+    ) -> (SignedMsgKey, SignedMsg) {
+        let proposal = &signed_proposal.proposal;
+
+        let key = SignedMsgKey {
+            evidence_type: EvidenceType::DuplicateProposal,
+            height:        proposal.height,
+            round:         proposal.round,
+            signer:        proposal.proposer.clone(),
+        };
+        let msg = SignedMsg {
+            block_hash: proposal.block_hash.clone(),
+            signature:  signed_proposal.signature.clone(),
+            hash:       self.crypto.hash(Bytes::from(rlp::encode(proposal))),
+            raw:        Bytes::from(signed_proposal.rlp_bytes()),
+        };
+
+        (key, msg)
+    }
+
+    fn decode_msg(
+        &self,
+        evidence_type: EvidenceType,
+        raw: &Bytes,
+    ) -> ProtocolResult<(SignedMsgKey, SignedMsg)> {
+        let (key, msg) = match evidence_type {
+            EvidenceType::DuplicateProposal => {
+                let signed_proposal: SignedProposal<FixedPill> = rlp::decode(raw)
+                    .map_err(|_| ConsensusError::DecodeErr(ConsensusType::SignedProposal))?;
+                self.proposal_msg(&signed_proposal)
+            }
+            EvidenceType::DuplicatePrevote | EvidenceType::DuplicatePrecommit => {
+                let signed_vote: SignedVote = rlp::decode(raw)
+                    .map_err(|_| ConsensusError::DecodeErr(ConsensusType::SignedVote))?;
+                self.vote_msg(&signed_vote)
+            }
+        };
+
+        if key.evidence_type != evidence_type {
+            return Err(
+                ConsensusError::InvalidEvidence("evidence type mismatch".to_owned()).into(),
+            );
+        }
+
+        Ok((key, msg))
+    }
+}
@@ -5,7 +5,7 @@ use overlord::Codec;
 
 use protocol::codec::{Deserialize, ProtocolCodecSync, Serialize};
 use protocol::fixed_codec::FixedCodec;
-use protocol::types::{Block, Hash, Pill, Proof, SignedTransaction};
+use protocol::types::{Block, BlockHeader, Hash, Pill, Proof, SignedTransaction};
 use protocol::{traits::MessageCodec, Bytes, BytesMut, ProtocolResult};
 
 use crate::{ConsensusError, ConsensusType};
@@ -149,6 +149,46 @@ impl FixedHeight {
     }
 }
 
+// Streamed block, header comes first, then ordered tx hashes, so block
+// size isn't limited by max frame length.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum BlockPart {
+    Header(#[serde(with = "core_network::serde")] BlockHeader),
+    TxHash(#[serde(with = "core_network::serde")] Hash),
+}
+
+impl BlockPart {
+    pub fn split(block: Block) -> Vec<BlockPart> {
+        let mut parts = Vec::with_capacity(block.ordered_tx_hashes.len() + 1);
+        parts.push(BlockPart::Header(block.header));
+        parts.extend(block.ordered_tx_hashes.into_iter().map(BlockPart::TxHash));
+
+        parts
+    }
+
+    pub fn join(parts: Vec<BlockPart>) -> ProtocolResult<Block> {
+        let mut parts = parts.into_iter();
+        let header = match parts.next() {
+            Some(BlockPart::Header(header)) => header,
+            _ => return Err(ConsensusError::DecodeErr(ConsensusType::RpcPullBlocks).into()),
+        };
+
+        let ordered_tx_hashes = parts
+            .map(|part| match part {
+                BlockPart::TxHash(hash) => Ok(hash),
+                BlockPart::Header(_) => {
+                    Err(ConsensusError::DecodeErr(ConsensusType::RpcPullBlocks).into())
+                }
+            })
+            .collect::<ProtocolResult<Vec<_>>>()?;
+
+        Ok(Block {
+            header,
+            ordered_tx_hashes,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PullTxsRequest {
     pub height: u64,
@@ -187,7 +227,7 @@ mod test {
     };
     use protocol::Bytes;
 
-    use super::{FixedBlock, FixedSignedTxs};
+    use super::{BlockPart, FixedBlock, FixedSignedTxs};
 
     const PUB_KEY_STR: &str = "031288a6788678c25952eba8693b2f278f66e2187004b64ac09416d07f83f96d5b";
 
@@ -288,4 +328,25 @@ mod test {
         let res: FixedBlock = MessageCodec::decode(bytes).await.unwrap();
         assert_eq!(res.inner, block);
     }
+
+    #[tokio::test]
+    async fn test_block_parts() {
+        use super::MessageCodec;
+
+        let mut block = gen_block(random::<u64>(), Hash::from_empty());
+        block.ordered_tx_hashes = (0..100)
+            .map(|_| Hash::digest(Bytes::from(gen_random_bytes(10))))
+            .collect();
+
+        let mut parts = Vec::new();
+        for mut part in BlockPart::split(block.clone()) {
+            let bytes = part.encode().await.unwrap();
+            parts.push(<BlockPart as MessageCodec>::decode(bytes).await.unwrap());
+        }
+        assert_eq!(BlockPart::join(parts.clone()).unwrap(), block);
+
+        // Header should come first
+        parts.rotate_left(1);
+        assert!(BlockPart::join(parts).is_err());
+    }
 }
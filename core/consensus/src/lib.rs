@@ -8,6 +8,7 @@
 pub mod adapter;
 pub mod consensus;
 mod engine;
+pub mod evidence;
 pub mod fixed_types;
 pub mod message;
 pub mod status;
@@ -29,6 +30,7 @@ use protocol::{ProtocolError, ProtocolErrorKind};
 
 pub use crate::adapter::OverlordConsensusAdapter;
 pub use crate::consensus::OverlordConsensus;
+pub use crate::evidence::EvidencePool;
 pub use crate::synchronization::{OverlordSynchronization, RichBlock};
 pub use crate::wal::SignedTxsWAL;
 pub use overlord::{types::Node, DurationConfig};
@@ -58,6 +60,9 @@ pub enum ConsensusType {
 
     #[display(fmt = "WAL Signed Transactions")]
     WALSignedTxs,
+
+    #[display(fmt = "Evidence")]
+    Evidence,
 }
 
 /// Consensus errors defines here.
@@ -130,6 +135,10 @@ pub enum ConsensusError {
     #[display(fmt = "Storage item not found")]
     StorageItemNotFound,
 
+    /// Evidence of equivocation is invalid.
+    #[display(fmt = "Invalid evidence {}", _0)]
+    InvalidEvidence(String),
+
     /// Other error used for very few errors.
     #[display(fmt = "{:?}", _0)]
     Other(String),
@@ -18,12 +18,15 @@ use protocol::ProtocolError;
 
 use core_storage::StorageError;
 
-pub use crate::fixed_types::{FixedBlock, FixedHeight, FixedProof, FixedSignedTxs, PullTxsRequest};
+pub use crate::fixed_types::{
+    BlockPart, FixedBlock, FixedHeight, FixedProof, FixedSignedTxs, PullTxsRequest,
+};
 
 pub const END_GOSSIP_SIGNED_PROPOSAL: &str = "/gossip/consensus/signed_proposal";
 pub const END_GOSSIP_SIGNED_VOTE: &str = "/gossip/consensus/signed_vote";
 pub const END_GOSSIP_AGGREGATED_VOTE: &str = "/gossip/consensus/qc";
 pub const END_GOSSIP_SIGNED_CHOKE: &str = "/gossip/consensus/signed_choke";
+pub const END_GOSSIP_EVIDENCE: &str = "/gossip/consensus/evidence";
 pub const RPC_SYNC_PULL_BLOCK: &str = "/rpc_call/consensus/sync_pull_block";
 pub const RPC_RESP_SYNC_PULL_BLOCK: &str = "/rpc_resp/consensus/sync_pull_block";
 pub const RPC_SYNC_PULL_TXS: &str = "/rpc_call/consensus/sync_pull_txs";
@@ -31,6 +34,11 @@ pub const RPC_RESP_SYNC_PULL_TXS: &str = "/rpc_resp/consensus/sync_pull_txs";
 pub const BROADCAST_HEIGHT: &str = "/gossip/consensus/broadcast_height";
 pub const RPC_SYNC_PULL_PROOF: &str = "/rpc_call/consensus/sync_pull_proof";
 pub const RPC_RESP_SYNC_PULL_PROOF: &str = "/rpc_resp/consensus/sync_pull_proof";
+// Chunked forms of block sync, nodes before them only serve the one-shot ones
+pub const RPC_SYNC_PULL_BLOCK_STREAM: &str = "/rpc_call/consensus/sync_pull_block_stream";
+pub const RPC_RESP_SYNC_PULL_BLOCK_STREAM: &str = "/rpc_resp/consensus/sync_pull_block_stream";
+pub const RPC_SYNC_PULL_TXS_STREAM: &str = "/rpc_call/consensus/sync_pull_txs_stream";
+pub const RPC_RESP_SYNC_PULL_TXS_STREAM: &str = "/rpc_resp/consensus/sync_pull_txs_stream";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Proposal(pub Vec<u8>);
@@ -77,6 +85,15 @@ impl From<SignedChoke> for Choke {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Evidence(pub Vec<u8>);
+
+impl From<protocol::types::Evidence> for Evidence {
+    fn from(evidence: protocol::types::Evidence) -> Self {
+        Evidence(serialize(&evidence).unwrap())
+    }
+}
+
 pub struct ProposalMessageHandler<C> {
     consensus: Arc<C>,
 }
@@ -177,6 +194,31 @@ impl<C: Consensus + 'static> MessageHandler for ChokeMessageHandler<C> {
     }
 }
 
+pub struct EvidenceMessageHandler<C> {
+    consensus: Arc<C>,
+}
+
+impl<C: Consensus + 'static> EvidenceMessageHandler<C> {
+    pub fn new(consensus: Arc<C>) -> Self {
+        Self { consensus }
+    }
+}
+
+#[async_trait]
+impl<C: Consensus + 'static> MessageHandler for EvidenceMessageHandler<C> {
+    type Message = Evidence;
+
+    #[muta_apm::derive::tracing_span(name = "handle_evidence", kind = "consensus.message")]
+    async fn process(&self, ctx: Context, msg: Self::Message) -> TrustFeedback {
+        if let Err(e) = self.consensus.set_evidence(ctx, msg.0).await {
+            warn!("set evidence {}", e);
+            return TrustFeedback::Worse(e.to_string());
+        }
+
+        TrustFeedback::Good
+    }
+}
+
 pub struct RemoteHeightMessageHandler<Sy> {
     synchronization: Arc<Sy>,
 }
@@ -215,6 +257,7 @@ impl<Sy: Synchronization + 'static> MessageHandler for RemoteHeightMessageHandle
 pub struct PullBlockRpcHandler<R, S> {
     rpc:     Arc<R>,
     storage: Arc<S>,
+    stream:  bool,
 }
 
 impl<R, S> PullBlockRpcHandler<R, S>
@@ -223,7 +266,20 @@ where
     S: Storage + 'static,
 {
     pub fn new(rpc: Arc<R>, storage: Arc<S>) -> Self {
-        PullBlockRpcHandler { rpc, storage }
+        PullBlockRpcHandler {
+            rpc,
+            storage,
+            stream: false,
+        }
+    }
+
+    // Handler of `RPC_SYNC_PULL_BLOCK_STREAM`, respond block parts in chunks
+    pub fn streamed(rpc: Arc<R>, storage: Arc<S>) -> Self {
+        PullBlockRpcHandler {
+            rpc,
+            storage,
+            stream: true,
+        }
     }
 }
 
@@ -235,14 +291,32 @@ impl<R: Rpc + 'static, S: Storage + 'static> MessageHandler for PullBlockRpcHand
     async fn process(&self, ctx: Context, msg: FixedHeight) -> TrustFeedback {
         let id = msg.inner;
         let ret = match self.storage.get_block(ctx.clone(), id).await {
-            Ok(Some(block)) => Ok(FixedBlock::new(block)),
+            Ok(Some(block)) => Ok(block),
             Ok(None) => Err(StorageError::GetNone.into()),
             Err(e) => Err(e),
         };
-        self.rpc
-            .response(ctx, RPC_RESP_SYNC_PULL_BLOCK, ret, Priority::High)
-            .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push block {}", e))
-            .await;
+
+        if self.stream {
+            self.rpc
+                .response_stream(
+                    ctx,
+                    RPC_RESP_SYNC_PULL_BLOCK_STREAM,
+                    ret.map(BlockPart::split),
+                    Priority::High,
+                )
+                .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push block {}", e))
+                .await;
+        } else {
+            self.rpc
+                .response(
+                    ctx,
+                    RPC_RESP_SYNC_PULL_BLOCK,
+                    ret.map(FixedBlock::new),
+                    Priority::High,
+                )
+                .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push block {}", e))
+                .await;
+        }
 
         TrustFeedback::Neutral
     }
@@ -306,6 +380,7 @@ impl<R: Rpc + 'static, S: Storage + 'static> MessageHandler for PullProofRpcHand
 pub struct PullTxsRpcHandler<R, S> {
     rpc:     Arc<R>,
     storage: Arc<S>,
+    stream:  bool,
 }
 
 impl<R, S> PullTxsRpcHandler<R, S>
@@ -314,7 +389,20 @@ where
     S: Storage + 'static,
 {
     pub fn new(rpc: Arc<R>, storage: Arc<S>) -> Self {
-        PullTxsRpcHandler { rpc, storage }
+        PullTxsRpcHandler {
+            rpc,
+            storage,
+            stream: false,
+        }
+    }
+
+    // Handler of `RPC_SYNC_PULL_TXS_STREAM`, respond in chunks
+    pub fn streamed(rpc: Arc<R>, storage: Arc<S>) -> Self {
+        PullTxsRpcHandler {
+            rpc,
+            storage,
+            stream: true,
+        }
     }
 }
 
@@ -334,13 +422,24 @@ impl<R: Rpc + 'static, S: Storage + 'static> MessageHandler for PullTxsRpcHandle
                 txs.into_iter()
                     .filter_map(|opt_tx| opt_tx)
                     .collect::<Vec<_>>()
-            })
-            .map(FixedSignedTxs::new);
-
-        self.rpc
-            .response(ctx, RPC_RESP_SYNC_PULL_TXS, ret, Priority::High)
-            .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push txs {}", e))
-            .await;
+            });
+
+        if self.stream {
+            self.rpc
+                .response_stream(ctx, RPC_RESP_SYNC_PULL_TXS_STREAM, ret, Priority::High)
+                .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push txs {}", e))
+                .await;
+        } else {
+            self.rpc
+                .response(
+                    ctx,
+                    RPC_RESP_SYNC_PULL_TXS,
+                    ret.map(FixedSignedTxs::new),
+                    Priority::High,
+                )
+                .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push txs {}", e))
+                .await;
+        }
 
         TrustFeedback::Neutral
     }
@@ -60,6 +60,10 @@ impl StatusAgent {
     pub fn to_inner(&self) -> CurrentConsensusStatus {
         self.status.read().clone()
     }
+
+    pub fn latest_committed_height(&self) -> u64 {
+        self.status.read().latest_committed_height
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Display)]
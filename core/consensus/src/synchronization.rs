@@ -34,8 +34,6 @@ pub struct OverlordSynchronization<Adapter: SynchronizationAdapter> {
     crypto:  Arc<OverlordCrypto>,
     lock:    Arc<Mutex<()>>,
     syncing: Mutex<()>,
-
-    sync_txs_chunk_size: usize,
 }
 
 #[async_trait]
@@ -123,7 +121,6 @@ impl<Adapter: SynchronizationAdapter> Synchronization for OverlordSynchronizatio
 
 impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
     pub fn new(
-        sync_txs_chunk_size: usize,
         adapter: Arc<Adapter>,
         status: StatusAgent,
         crypto: Arc<OverlordCrypto>,
@@ -137,8 +134,6 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
             crypto,
             lock,
             syncing,
-
-            sync_txs_chunk_size,
         }
     }
 
@@ -375,17 +370,10 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
         height: u64,
     ) -> ProtocolResult<RichBlock> {
         let block = self.get_block_from_remote(ctx.clone(), height).await?;
-
-        let mut txs = Vec::with_capacity(block.ordered_tx_hashes.len());
-
-        for tx_hashes in block.ordered_tx_hashes.chunks(self.sync_txs_chunk_size) {
-            let remote_txs = self
-                .adapter
-                .get_txs_from_remote(ctx.clone(), height, &tx_hashes)
-                .await?;
-
-            txs.extend(remote_txs);
-        }
+        let txs = self
+            .adapter
+            .get_txs_from_remote(ctx.clone(), height, &block.ordered_tx_hashes)
+            .await?;
 
         Ok(RichBlock { block, txs })
     }
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use overlord::types::{SignedVote, Vote, VoteType};
+use overlord::Crypto;
+
+use common_crypto::{BlsCommonReference, BlsPrivateKey, ToBlsPublicKey};
+
+use protocol::types::{Address, EvidenceType, Hash};
+use protocol::Bytes;
+
+use crate::evidence::{EvidencePool, MAX_EVIDENCE_HEIGHT_GAP};
+use crate::util::OverlordCrypto;
+
+const LATEST_HEIGHT: u64 = 10;
+const COMMON_REF: &str = "muta-evidence";
+
+struct Validator {
+    address: Bytes,
+    crypto:  Arc<OverlordCrypto>,
+}
+
+impl Validator {
+    fn sign_vote(&self, height: u64, round: u64, vote_type: VoteType, block: &str) -> SignedVote {
+        let vote = Vote {
+            height,
+            round,
+            vote_type,
+            block_hash: Hash::digest(Bytes::from(block.to_owned())).as_bytes(),
+        };
+        let hash = self.crypto.hash(Bytes::from(rlp::encode(&vote)));
+
+        SignedVote {
+            signature: self.crypto.sign(hash).unwrap(),
+            vote,
+            voter: self.address.clone(),
+        }
+    }
+}
+
+fn mock_validators(num: u8) -> Vec<Validator> {
+    let common_ref = || -> BlsCommonReference { COMMON_REF.into() };
+
+    let priv_keys = (1..=num)
+        .map(|i| {
+            let seed = Hash::digest(Bytes::from(vec![i])).as_bytes();
+            BlsPrivateKey::try_from([&[0u8; 16], seed.as_ref()].concat().as_ref()).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let addresses = (1..=num)
+        .map(|i| {
+            Address::from_bytes(Bytes::from(vec![i; 20]))
+                .unwrap()
+                .as_bytes()
+        })
+        .collect::<Vec<_>>();
+
+    let addr_pubkey = addresses
+        .iter()
+        .cloned()
+        .zip(priv_keys.iter().map(|k| k.pub_key(&common_ref())))
+        .collect::<HashMap<_, _>>();
+
+    priv_keys
+        .into_iter()
+        .zip(addresses.into_iter())
+        .map(|(priv_key, address)| Validator {
+            address,
+            crypto: Arc::new(OverlordCrypto::new(
+                priv_key,
+                addr_pubkey.clone(),
+                common_ref(),
+            )),
+        })
+        .collect()
+}
+
+#[test]
+fn test_detect_duplicate_vote() {
+    let validators = mock_validators(2);
+    let pool = EvidencePool::new(Arc::clone(&validators[1].crypto));
+    let offender = &validators[0];
+
+    let first = offender.sign_vote(11, 0, VoteType::Prevote, "a");
+    let second = offender.sign_vote(11, 0, VoteType::Prevote, "b");
+
+    assert!(pool.check_vote(&first, LATEST_HEIGHT).is_none());
+    // Same vote received twice is not an equivocation
+    assert!(pool.check_vote(&first, LATEST_HEIGHT).is_none());
+
+    let evidence = pool.check_vote(&second, LATEST_HEIGHT).unwrap();
+    assert_eq!(evidence.evidence_type, EvidenceType::DuplicatePrevote);
+    assert_eq!(evidence.height, 11);
+    assert_eq!(evidence.round, 0);
+    assert_eq!(evidence.offender.as_bytes(), offender.address);
+
+    // Report only once
+    assert!(pool.check_vote(&second, LATEST_HEIGHT).is_none());
+}
+
+#[test]
+fn test_different_round_or_vote_type_is_not_evidence() {
+    let validators = mock_validators(2);
+    let pool = EvidencePool::new(Arc::clone(&validators[1].crypto));
+    let offender = &validators[0];
+
+    let votes = vec![
+        offender.sign_vote(11, 0, VoteType::Prevote, "a"),
+        offender.sign_vote(11, 1, VoteType::Prevote, "b"),
+        offender.sign_vote(11, 0, VoteType::Precommit, "c"),
+        offender.sign_vote(12, 0, VoteType::Prevote, "d"),
+    ];
+
+    for vote in votes.iter() {
+        assert!(pool.check_vote(vote, LATEST_HEIGHT).is_none());
+    }
+}
+
+#[test]
+fn test_forged_vote_is_not_evidence() {
+    let validators = mock_validators(2);
+    let pool = EvidencePool::new(Arc::clone(&validators[1].crypto));
+    let honest = &validators[0];
+
+    let vote = honest.sign_vote(11, 0, VoteType::Precommit, "a");
+    let mut forged = validators[1].sign_vote(11, 0, VoteType::Precommit, "b");
+    forged.voter = honest.address.clone();
+
+    assert!(pool.check_vote(&vote, LATEST_HEIGHT).is_none());
+    assert!(pool.check_vote(&forged, LATEST_HEIGHT).is_none());
+}
+
+#[test]
+fn test_ignore_vote_out_of_height_gap() {
+    let validators = mock_validators(2);
+    let pool = EvidencePool::new(Arc::clone(&validators[1].crypto));
+    let offender = &validators[0];
+
+    let height = LATEST_HEIGHT + MAX_EVIDENCE_HEIGHT_GAP + 1;
+    let first = offender.sign_vote(height, 0, VoteType::Prevote, "a");
+    let second = offender.sign_vote(height, 0, VoteType::Prevote, "b");
+
+    assert!(pool.check_vote(&first, LATEST_HEIGHT).is_none());
+    assert!(pool.check_vote(&second, LATEST_HEIGHT).is_none());
+}
+
+#[test]
+fn test_verify_evidence() {
+    let validators = mock_validators(3);
+    let offender = &validators[0];
+
+    let reporter = EvidencePool::new(Arc::clone(&validators[1].crypto));
+    let first = offender.sign_vote(11, 2, VoteType::Precommit, "a");
+    let second = offender.sign_vote(11, 2, VoteType::Precommit, "b");
+    assert!(reporter.check_vote(&first, LATEST_HEIGHT).is_none());
+    let evidence = reporter.check_vote(&second, LATEST_HEIGHT).unwrap();
+
+    let receiver = EvidencePool::new(Arc::clone(&validators[2].crypto));
+    assert!(receiver.verify_evidence(&evidence).unwrap());
+    // Already known, no need to broadcast again
+    assert!(!receiver.verify_evidence(&evidence).unwrap());
+
+    let mut bad_evidence = evidence.clone();
+    bad_evidence.second = bad_evidence.first.clone();
+    assert!(receiver.verify_evidence(&bad_evidence).is_err());
+
+    let mut bad_evidence = evidence;
+    bad_evidence.offender = Address::from_bytes(validators[1].address.clone()).unwrap();
+    assert!(receiver.verify_evidence(&bad_evidence).is_err());
+}
@@ -1 +1,2 @@
+mod evidence;
 mod synchronization;
@@ -91,7 +91,6 @@ fn sync_gap_test() {
         let status_agent = StatusAgent::new(status);
         let lock = Arc::new(Mutex::new(()));
         let sync = OverlordSynchronization::<_>::new(
-            5000,
             Arc::clone(&adapter),
             status_agent.clone(),
             Arc::new(mock_crypto()),
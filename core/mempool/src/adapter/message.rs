@@ -15,6 +15,9 @@ pub const END_GOSSIP_NEW_TXS: &str = "/gossip/mempool/new_txs";
 pub const RPC_PULL_TXS: &str = "/rpc_call/mempool/pull_txs";
 pub const RPC_RESP_PULL_TXS: &str = "/rpc_resp/mempool/pull_txs";
 pub const RPC_RESP_PULL_TXS_SYNC: &str = "/rpc_resp/mempool/pull_txs_sync";
+// Chunked form of pull txs, nodes before it only serve `RPC_PULL_TXS`
+pub const RPC_PULL_TXS_STREAM: &str = "/rpc_call/mempool/pull_txs_stream";
+pub const RPC_RESP_PULL_TXS_STREAM: &str = "/rpc_resp/mempool/pull_txs_stream";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MsgNewTxs {
@@ -104,6 +107,7 @@ pub struct MsgPushTxs {
 pub struct PullTxsHandler<N, M> {
     network:  Arc<N>,
     mem_pool: Arc<M>,
+    stream:   bool,
 }
 
 impl<N, M> PullTxsHandler<N, M>
@@ -112,7 +116,20 @@ where
     M: MemPool + 'static,
 {
     pub fn new(network: Arc<N>, mem_pool: Arc<M>) -> Self {
-        PullTxsHandler { network, mem_pool }
+        PullTxsHandler {
+            network,
+            mem_pool,
+            stream: false,
+        }
+    }
+
+    // Handler of `RPC_PULL_TXS_STREAM`, respond in chunks
+    pub fn streamed(network: Arc<N>, mem_pool: Arc<M>) -> Self {
+        PullTxsHandler {
+            network,
+            mem_pool,
+            stream: true,
+        }
     }
 }
 
@@ -129,12 +146,24 @@ where
             let ret = self
                 .mem_pool
                 .get_full_txs(ctx.clone(), msg.height, msg.hashes)
-                .await
-                .map(|sig_txs| MsgPushTxs { sig_txs });
-
-            self.network
-                .response::<MsgPushTxs>(ctx, RPC_RESP_PULL_TXS, ret, Priority::High)
-                .await
+                .await;
+
+            if self.stream {
+                self.network
+                    .response_stream::<SignedTransaction>(
+                        ctx,
+                        RPC_RESP_PULL_TXS_STREAM,
+                        ret,
+                        Priority::High,
+                    )
+                    .await
+            } else {
+                let ret = ret.map(|sig_txs| MsgPushTxs { sig_txs });
+
+                self.network
+                    .response::<MsgPushTxs>(ctx, RPC_RESP_PULL_TXS, ret, Priority::High)
+                    .await
+            }
         };
 
         push_txs
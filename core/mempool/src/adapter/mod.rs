@@ -30,12 +30,12 @@ use protocol::{
         Context, ExecutorFactory, ExecutorParams, Gossip, MemPoolAdapter, PeerTrust, Priority, Rpc,
         ServiceMapping, Storage, TrustFeedback,
     },
-    types::{Address, Hash, SignedTransaction, TransactionRequest},
+    types::{Hash, SignedTransaction, TransactionRequest},
     ProtocolError, ProtocolErrorKind, ProtocolResult,
 };
 
 use crate::adapter::message::{
-    MsgNewTxs, MsgPullTxs, MsgPushTxs, END_GOSSIP_NEW_TXS, RPC_PULL_TXS,
+    MsgNewTxs, MsgPullTxs, MsgPushTxs, END_GOSSIP_NEW_TXS, RPC_PULL_TXS, RPC_PULL_TXS_STREAM,
 };
 use crate::MemPoolError;
 
@@ -227,15 +227,37 @@ where
     ) -> ProtocolResult<Vec<SignedTransaction>> {
         let pull_msg = MsgPullTxs {
             height,
-            hashes: tx_hashes,
+            hashes: tx_hashes.clone(),
         };
 
-        let resp_msg = self
+        let ret = self
             .network
-            .call::<MsgPullTxs, MsgPushTxs>(ctx, RPC_PULL_TXS, pull_msg, Priority::High)
-            .await?;
+            .call_stream::<MsgPullTxs, SignedTransaction>(
+                ctx.clone(),
+                RPC_PULL_TXS_STREAM,
+                pull_msg,
+                Priority::High,
+            )
+            .await;
+
+        match ret {
+            Ok(sig_txs) => Ok(sig_txs),
+            Err(e) => {
+                // Remote may not serve chunked form yet, fallback to old one
+                debug!("[core_mempool] pull txs stream {}, fallback", e);
+
+                let pull_msg = MsgPullTxs {
+                    height,
+                    hashes: tx_hashes,
+                };
+                let resp_msg = self
+                    .network
+                    .call::<MsgPullTxs, MsgPushTxs>(ctx, RPC_PULL_TXS, pull_msg, Priority::High)
+                    .await?;
 
-        Ok(resp_msg.sig_txs)
+                Ok(resp_msg.sig_txs)
+            }
+        }
     }
 
     async fn broadcast_tx(&self, _ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
@@ -302,7 +324,7 @@ where
             serde_json::to_string(&stx_json).map_err(|_| MemPoolError::EncodeJson)?;
 
         let block = self.storage.get_latest_block(ctx_clone.clone()).await?;
-        let caller = Address::from_hex("0x0000000000000000000000000000000000000000")?;
+        let caller = tx_clone.raw.sender.clone();
         let executor = EF::from_root(
             block.header.state_root.clone(),
             Arc::clone(&self.trie_db),
@@ -46,9 +46,8 @@ const DEFAULT_PEER_FATAL_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
 const DEFAULT_PEER_SOFT_BAN_DURATION: Duration = Duration::from_secs(60 * 10); // 10 minutes
 
 // Default peer data persistent path
-pub const DEFAULT_PEER_FILE_NAME: &str = "peers";
-pub const DEFAULT_PEER_FILE_EXT: &str = "dat";
-pub const DEFAULT_PEER_DAT_FILE: &str = "./peers.dat";
+pub const DEFAULT_PEER_STORE_DIR_NAME: &str = "peers";
+pub const DEFAULT_PEER_STORE_DIR: &str = "./peers";
 
 pub const DEFAULT_PING_INTERVAL: u64 = 15;
 pub const DEFAULT_PING_TIMEOUT: u64 = 30;
@@ -58,6 +57,13 @@ pub const DEFAULT_PEER_MANAGER_HEART_BEAT_INTERVAL: u64 = 30;
 pub const DEFAULT_SELF_HEART_BEAT_INTERVAL: u64 = 35;
 
 pub const DEFAULT_RPC_TIMEOUT: u64 = 10;
+pub const DEFAULT_RPC_MAX_CHUNK_SIZE: usize = 1024 * 1024; // 1 Mib
+pub const DEFAULT_RPC_STREAM_WINDOW: usize = 4;
+pub const DEFAULT_RPC_STREAM_TIMEOUT: u64 = 60; // seconds, whole stream
+pub const DEFAULT_RPC_STREAM_MAX_ITEMS: usize = 100_000;
+
+// Relay is disabled by default
+pub const DEFAULT_RELAY_MAX_HOPS: u8 = 0;
 
 // Selfcheck
 pub const DEFAULT_SELF_CHECK_INTERVAL: u64 = 30;
@@ -117,8 +123,9 @@ pub struct NetworkConfig {
     pub bootstraps:             Vec<ArcPeer>,
     pub whitelist:              Vec<Address>,
     pub whitelist_peers_only:   bool,
+    pub private_peers:          Vec<Address>,
     pub enable_save_restore:    bool,
-    pub peer_dat_file:          PathBuf,
+    pub peer_store_dir:         PathBuf,
     pub peer_trust_interval:    Duration,
     pub peer_trust_max_history: Duration,
     pub peer_fatal_ban:         Duration,
@@ -137,7 +144,14 @@ pub struct NetworkConfig {
     pub heart_beat_interval:              Duration,
 
     // rpc
-    pub rpc_timeout: Duration,
+    pub rpc_timeout:          Duration,
+    pub rpc_max_chunk_size:   usize,
+    pub rpc_stream_window:    usize,
+    pub rpc_stream_timeout:   Duration,
+    pub rpc_stream_max_items: usize,
+
+    // relay
+    pub relay_max_hops: u8,
 
     // self check
     pub selfcheck_interval: Duration,
@@ -163,8 +177,9 @@ impl NetworkConfig {
             bootstraps:             Default::default(),
             whitelist:              Default::default(),
             whitelist_peers_only:   false,
+            private_peers:          Default::default(),
             enable_save_restore:    false,
-            peer_dat_file:          PathBuf::from(DEFAULT_PEER_DAT_FILE.to_owned()),
+            peer_store_dir:         PathBuf::from(DEFAULT_PEER_STORE_DIR.to_owned()),
             peer_trust_interval:    DEFAULT_PEER_TRUST_INTERVAL_DURATION,
             peer_trust_max_history: DEFAULT_PEER_TRUST_MAX_HISTORY_DURATION,
             peer_fatal_ban:         DEFAULT_PEER_FATAL_BAN_DURATION,
@@ -179,7 +194,13 @@ impl NetworkConfig {
             peer_manager_heart_beat_interval: peer_manager_hb_interval,
             heart_beat_interval:              Duration::from_secs(DEFAULT_SELF_HEART_BEAT_INTERVAL),
 
-            rpc_timeout: Duration::from_secs(DEFAULT_RPC_TIMEOUT),
+            rpc_timeout:          Duration::from_secs(DEFAULT_RPC_TIMEOUT),
+            rpc_max_chunk_size:   DEFAULT_RPC_MAX_CHUNK_SIZE,
+            rpc_stream_window:    DEFAULT_RPC_STREAM_WINDOW,
+            rpc_stream_timeout:   Duration::from_secs(DEFAULT_RPC_STREAM_TIMEOUT),
+            rpc_stream_max_items: DEFAULT_RPC_STREAM_MAX_ITEMS,
+
+            relay_max_hops: DEFAULT_RELAY_MAX_HOPS,
 
             selfcheck_interval: Duration::from_secs(DEFAULT_SELF_CHECK_INTERVAL),
         }
@@ -286,12 +307,31 @@ impl NetworkConfig {
         self
     }
 
-    pub fn peer_dat_file<P: AsRef<Path>>(mut self, path: P) -> Self {
-        let mut path = path.as_ref().to_owned();
-        path.push(DEFAULT_PEER_FILE_NAME);
-        path.set_extension(DEFAULT_PEER_FILE_EXT);
+    // Addresses of these peers are never shared through discovery, for
+    // example validators behind sentry nodes.
+    pub fn private_peers(mut self, chain_addr_strs: Vec<String>) -> ProtocolResult<Self> {
+        let chain_addrs = chain_addr_strs
+            .into_iter()
+            .map(|s| Address::from_hex(&s))
+            .collect::<ProtocolResult<Vec<_>>>()?;
+
+        self.private_peers = chain_addrs;
+        Ok(self)
+    }
+
+    pub fn enable_save_restore(mut self, flag: Option<bool>) -> Self {
+        if let Some(flag) = flag {
+            self.enable_save_restore = flag;
+        }
+        self
+    }
+
+    // Peers are saved under given data path if save/restore is enabled
+    pub fn peer_store_dir<P: AsRef<Path>>(mut self, data_path: P) -> Self {
+        let mut path = data_path.as_ref().to_owned();
+        path.push(DEFAULT_PEER_STORE_DIR_NAME);
 
-        self.peer_dat_file = path;
+        self.peer_store_dir = path;
 
         self
     }
@@ -384,6 +424,46 @@ impl NetworkConfig {
         self
     }
 
+    pub fn rpc_max_chunk_size(mut self, size: Option<usize>) -> Self {
+        if let Some(size) = size {
+            self.rpc_max_chunk_size = size;
+        }
+
+        self
+    }
+
+    pub fn rpc_stream_window(mut self, window: Option<usize>) -> Self {
+        if let Some(window) = window {
+            self.rpc_stream_window = window;
+        }
+
+        self
+    }
+
+    pub fn rpc_stream_timeout(mut self, timeout: Option<u64>) -> Self {
+        if let Some(timeout) = timeout {
+            self.rpc_stream_timeout = Duration::from_secs(timeout);
+        }
+
+        self
+    }
+
+    pub fn rpc_stream_max_items(mut self, max: Option<usize>) -> Self {
+        if let Some(max) = max {
+            self.rpc_stream_max_items = max;
+        }
+
+        self
+    }
+
+    pub fn relay_max_hops(mut self, hops: Option<u8>) -> Self {
+        if let Some(hops) = hops {
+            self.relay_max_hops = hops;
+        }
+
+        self
+    }
+
     pub fn selfcheck_interval(mut self, interval: Option<u64>) -> Self {
         if let Some(interval) = interval {
             self.selfcheck_interval = Duration::from_secs(interval);
@@ -433,12 +513,13 @@ impl From<&NetworkConfig> for PeerManagerConfig {
             bootstraps:               config.bootstraps.clone(),
             whitelist_by_chain_addrs: config.whitelist.clone(),
             whitelist_peers_only:     config.whitelist_peers_only,
+            private_by_chain_addrs:   config.private_peers.clone(),
             peer_trust_config:        Arc::new(peer_trust_config),
             peer_fatal_ban:           config.peer_fatal_ban,
             peer_soft_ban:            config.peer_soft_ban,
             max_connections:          config.max_connections,
             routine_interval:         config.peer_manager_heart_beat_interval,
-            peer_dat_file:            config.peer_dat_file.clone(),
+            peer_store_dir:           config.peer_store_dir.clone(),
         }
     }
 }
@@ -456,6 +537,29 @@ impl From<&NetworkConfig> for TimeoutConfig {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct RpcStreamConfig {
+    pub max_chunk_size: usize,
+    pub window:         usize,
+    // Deadline for the whole stream, per chunk timeout still applies
+    pub timeout:        Duration,
+    pub max_items:      usize,
+}
+
+impl From<&NetworkConfig> for RpcStreamConfig {
+    fn from(config: &NetworkConfig) -> RpcStreamConfig {
+        // Leave room for message headers and compression overhead
+        let max_chunk_size = config.rpc_max_chunk_size.min(config.max_frame_length / 2);
+
+        RpcStreamConfig {
+            max_chunk_size: max_chunk_size.max(1),
+            window:         config.rpc_stream_window.max(1),
+            timeout:        config.rpc_stream_timeout,
+            max_items:      config.rpc_stream_max_items,
+        }
+    }
+}
+
 impl From<&NetworkConfig> for SelfCheckConfig {
     fn from(config: &NetworkConfig) -> SelfCheckConfig {
         SelfCheckConfig {
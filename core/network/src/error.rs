@@ -55,6 +55,15 @@ pub enum ErrorKind {
     #[display(fmt = "kind: rpc timeout {:?}", _0)]
     RpcTimeout(Option<ConnectedAddr>),
 
+    #[display(fmt = "kind: rpc stream expect chunk {} but got {}", expect, got)]
+    UnexpectedRpcChunk { expect: u64, got: u64 },
+
+    #[display(fmt = "kind: rpc stream exceed max {} items", max)]
+    TooManyRpcItems { max: usize },
+
+    #[display(fmt = "kind: rpc stream expect chunk but got one-shot response")]
+    UnexpectedRpcResponse,
+
     #[display(fmt = "kind: not reactor register for {}", _0)]
     NoReactor(String),
 
@@ -5,12 +5,14 @@ mod connection;
 mod endpoint;
 mod error;
 mod event;
+mod memory;
 mod message;
 mod metrics;
 mod outbound;
 mod peer_manager;
 mod protocols;
 mod reactor;
+mod relay;
 mod rpc;
 mod rpc_map;
 mod selfcheck;
@@ -20,5 +22,6 @@ mod test;
 mod traits;
 
 pub use config::NetworkConfig;
+pub use memory::{LinkCondition, MemoryNetwork};
 pub use message::{serde, serde_multi};
 pub use service::{NetworkService, NetworkServiceHandle};
@@ -0,0 +1,743 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{
+    channel::mpsc::{UnboundedReceiver, UnboundedSender},
+    pin_mut,
+    stream::Stream,
+};
+use futures_timer::Delay;
+use log::{debug, warn};
+use parking_lot::RwLock;
+use protocol::{
+    traits::{Priority, TrustFeedback},
+    types::Address,
+    Bytes, ProtocolResult,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tentacle::{
+    secio::{PeerId, PublicKey},
+    service::TargetSession,
+    SessionId,
+};
+
+use crate::{
+    common::ConnectedAddr,
+    error::{ErrorKind, NetworkError},
+    event::PeerManagerEvent,
+    message::RawSessionMessage,
+    traits::{MessageSender, SessionBook},
+};
+
+const MEMORY_HOST: &str = "memory";
+
+/// Condition applied to messages sent from one node to another.
+///
+/// Each message is delayed by `latency` plus a random duration in
+/// `[0, jitter)`, so a non-zero jitter reorders messages on the link.
+/// `drop_rate` is the probability that a message is lost.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinkCondition {
+    pub latency:   Duration,
+    pub jitter:    Duration,
+    pub drop_rate: f64,
+}
+
+impl LinkCondition {
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn drop_rate(mut self, rate: f64) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+}
+
+struct MemoryNode {
+    pid:        PeerId,
+    chain_addr: Address,
+    raw_msg_tx: UnboundedSender<RawSessionMessage>,
+}
+
+struct Hub {
+    nodes:    HashMap<SessionId, MemoryNode>,
+    by_chain: HashMap<Address, SessionId>,
+    by_peer:  HashMap<PeerId, SessionId>,
+    next_sid: usize,
+
+    default_link: LinkCondition,
+    links:        HashMap<(SessionId, SessionId), LinkCondition>,
+    cuts:         HashSet<(SessionId, SessionId)>,
+    rng:          StdRng,
+
+    // Trust feedback reported by node, keyed by reporter
+    feedbacks: HashMap<SessionId, Vec<(Address, TrustFeedback)>>,
+}
+
+impl Hub {
+    fn reachable(&self, from: SessionId, to: SessionId) -> bool {
+        from != to && self.nodes.contains_key(&to) && !self.cuts.contains(&(from, to))
+    }
+
+    fn sids_of(&self, addrs: &[Address]) -> Vec<SessionId> {
+        addrs
+            .iter()
+            .filter_map(|addr| self.by_chain.get(addr).copied())
+            .collect()
+    }
+}
+
+/// In-process network hub. Every `NetworkService` created by
+/// `NetworkService::in_memory` on the same hub can talk to each other
+/// through channels, no socket is opened.
+///
+/// Randomness used for drops and jitter comes from a seeded rng, so runs
+/// with the same seed and the same message order are reproducible.
+#[derive(Clone)]
+pub struct MemoryNetwork {
+    hub: Arc<RwLock<Hub>>,
+}
+
+impl MemoryNetwork {
+    pub fn new(seed: u64) -> Self {
+        let hub = Hub {
+            nodes:    Default::default(),
+            by_chain: Default::default(),
+            by_peer:  Default::default(),
+            next_sid: 1,
+
+            default_link: LinkCondition::default(),
+            links:        Default::default(),
+            cuts:         Default::default(),
+            rng:          StdRng::seed_from_u64(seed),
+
+            feedbacks: Default::default(),
+        };
+
+        MemoryNetwork {
+            hub: Arc::new(RwLock::new(hub)),
+        }
+    }
+
+    pub fn set_default_link(&self, cond: LinkCondition) {
+        self.hub.write().default_link = cond;
+    }
+
+    pub fn set_link(&self, from: &Address, to: &Address, cond: LinkCondition) {
+        let mut hub = self.hub.write();
+        let from = hub.by_chain.get(from).copied();
+        let to = hub.by_chain.get(to).copied();
+
+        if let (Some(from), Some(to)) = (from, to) {
+            hub.links.insert((from, to), cond);
+        }
+    }
+
+    /// Cut all links between two groups of nodes, in both directions.
+    pub fn partition(&self, left: &[Address], right: &[Address]) {
+        let mut hub = self.hub.write();
+        let left = hub.sids_of(left);
+        let right = hub.sids_of(right);
+
+        for l in left.iter() {
+            for r in right.iter() {
+                hub.cuts.insert((*l, *r));
+                hub.cuts.insert((*r, *l));
+            }
+        }
+    }
+
+    /// Remove all partitions, link conditions are kept.
+    pub fn heal(&self) {
+        self.hub.write().cuts.clear();
+    }
+
+    /// Take trust feedback reported by given node, paired with the chain
+    /// address of the peer it was reported on.
+    pub fn take_trust_feedback(&self, reporter: &Address) -> Vec<(Address, TrustFeedback)> {
+        let mut hub = self.hub.write();
+        let opt_sid = hub.by_chain.get(reporter).copied();
+
+        match opt_sid {
+            Some(sid) => hub.feedbacks.remove(&sid).unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn join(
+        &self,
+        pubkey: &PublicKey,
+        raw_msg_tx: UnboundedSender<RawSessionMessage>,
+    ) -> ProtocolResult<MemoryTransport> {
+        let pubkey_bytes = Bytes::from(pubkey.inner_ref().clone());
+        let chain_addr = Address::from_pubkey_bytes(pubkey_bytes.clone()).map_err(|e| {
+            NetworkError::from(ErrorKind::NoChainAddress {
+                pubkey: pubkey_bytes,
+                cause:  Box::new(e),
+            })
+        })?;
+        let pid = pubkey.peer_id();
+
+        let mut hub = self.hub.write();
+        let sid = SessionId::new(hub.next_sid);
+        hub.next_sid += 1;
+
+        let node = MemoryNode {
+            pid: pid.clone(),
+            chain_addr: chain_addr.clone(),
+            raw_msg_tx,
+        };
+        hub.nodes.insert(sid, node);
+        hub.by_chain.insert(chain_addr, sid);
+        hub.by_peer.insert(pid, sid);
+
+        Ok(MemoryTransport {
+            sid,
+            network: self.clone(),
+        })
+    }
+
+    fn deliver(&self, from: SessionId, to: Vec<SessionId>, msg: Bytes) {
+        let mut hub = self.hub.write();
+        let pid = match hub.nodes.get(&from) {
+            Some(node) => node.pid.clone(),
+            None => return,
+        };
+
+        for to in to {
+            if !hub.reachable(from, to) {
+                continue;
+            }
+
+            let cond = hub
+                .links
+                .get(&(from, to))
+                .copied()
+                .unwrap_or(hub.default_link);
+
+            let drop_rate = cond.drop_rate.max(0.0).min(1.0);
+            if drop_rate > 0.0 && hub.rng.gen_bool(drop_rate) {
+                debug!("network: memory: drop message from {} to {}", from, to);
+                continue;
+            }
+
+            let mut delay = cond.latency;
+            let jitter = cond.jitter.as_nanos() as u64;
+            if jitter > 0 {
+                delay += Duration::from_nanos(hub.rng.gen_range(0, jitter));
+            }
+
+            let raw_msg_tx = hub.nodes[&to].raw_msg_tx.clone();
+            let raw_msg = RawSessionMessage::new(from, pid.clone(), msg.clone());
+
+            if delay == Duration::from_secs(0) {
+                if raw_msg_tx.unbounded_send(raw_msg).is_err() {
+                    warn!("network: memory: node {} offline", to);
+                }
+            } else {
+                tokio::spawn(async move {
+                    Delay::new(delay).await;
+
+                    if raw_msg_tx.unbounded_send(raw_msg).is_err() {
+                        warn!("network: memory: node {} offline", to);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Per node handle to `MemoryNetwork`, it is both the message sender and
+/// the session book of that node. Session id of a peer is the id assigned
+/// to it when joining the network.
+#[derive(Clone)]
+pub struct MemoryTransport {
+    sid:     SessionId,
+    network: MemoryNetwork,
+}
+
+impl MemoryTransport {
+    pub(crate) fn trust_recorder(
+        &self,
+        mgr_rx: UnboundedReceiver<PeerManagerEvent>,
+    ) -> TrustRecorder {
+        TrustRecorder {
+            sid: self.sid,
+            network: self.network.clone(),
+            mgr_rx,
+        }
+    }
+}
+
+#[async_trait]
+impl MessageSender for MemoryTransport {
+    fn send(&self, tar: TargetSession, msg: Bytes, _pri: Priority) -> Result<(), NetworkError> {
+        let to = match tar {
+            TargetSession::Single(sid) => vec![sid],
+            TargetSession::Multi(sids) => sids,
+            TargetSession::All => self.all_sendable(),
+        };
+
+        self.network.deliver(self.sid, to, msg);
+        Ok(())
+    }
+
+    async fn users_send(
+        &self,
+        chain_addrs: Vec<Address>,
+        msg: Bytes,
+        pri: Priority,
+    ) -> Result<(), NetworkError> {
+        let (connected, unconnected) = self.by_chain(chain_addrs);
+        self.send(TargetSession::Multi(connected), msg, pri)?;
+
+        if unconnected.is_empty() {
+            return Ok(());
+        }
+
+        let (_, unknown) = self.peers_by_chain(unconnected.clone());
+        let unconnected = unconnected
+            .into_iter()
+            .filter(|a| !unknown.contains(a))
+            .collect::<Vec<_>>();
+
+        if unconnected.is_empty() && unknown.is_empty() {
+            return Ok(());
+        }
+
+        Err(NetworkError::UserSend {
+            unconnected: Some(unconnected).filter(|u| !u.is_empty()),
+            unknown:     Some(unknown).filter(|u| !u.is_empty()),
+            other:       None,
+        })
+    }
+}
+
+impl SessionBook for MemoryTransport {
+    fn all_sendable(&self) -> Vec<SessionId> {
+        self.all()
+    }
+
+    fn all_blocked(&self) -> Vec<SessionId> {
+        Vec::new()
+    }
+
+    fn refresh_blocked(&self) {}
+
+    fn by_chain(&self, addrs: Vec<Address>) -> (Vec<SessionId>, Vec<Address>) {
+        let hub = self.network.hub.read();
+
+        let mut connected = Vec::new();
+        let mut unconnected = Vec::new();
+        for addr in addrs {
+            match hub.by_chain.get(&addr) {
+                Some(sid) if hub.reachable(self.sid, *sid) => connected.push(*sid),
+                _ => unconnected.push(addr),
+            }
+        }
+
+        (connected, unconnected)
+    }
+
+    fn peers_by_chain(&self, addrs: Vec<Address>) -> (Vec<PeerId>, Vec<Address>) {
+        let hub = self.network.hub.read();
+
+        let mut peers = Vec::new();
+        let mut unknown = Vec::new();
+        for addr in addrs {
+            match hub.by_chain.get(&addr) {
+                Some(sid) => peers.push(hub.nodes[sid].pid.clone()),
+                None => unknown.push(addr),
+            }
+        }
+
+        (peers, unknown)
+    }
+
+    fn all(&self) -> Vec<SessionId> {
+        let hub = self.network.hub.read();
+        let mut sids = hub
+            .nodes
+            .keys()
+            .filter(|sid| hub.reachable(self.sid, **sid))
+            .copied()
+            .collect::<Vec<_>>();
+
+        // Keep broadcast order stable between runs
+        sids.sort_by_key(|sid| sid.value());
+        sids
+    }
+
+    fn connected_addr(&self, sid: SessionId) -> Option<ConnectedAddr> {
+        let hub = self.network.hub.read();
+
+        if hub.nodes.contains_key(&sid) {
+            Some(ConnectedAddr {
+                host: MEMORY_HOST.to_owned(),
+                port: sid.value() as u16,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn pending_data_size(&self, _sid: SessionId) -> usize {
+        0
+    }
+
+    fn whitelist(&self) -> Vec<Address> {
+        Vec::new()
+    }
+}
+
+// There's no peer manager behind memory transport, trust feedback is
+// recorded on network hub instead, so that tests can inspect it.
+pub(crate) struct TrustRecorder {
+    sid:     SessionId,
+    network: MemoryNetwork,
+    mgr_rx:  UnboundedReceiver<PeerManagerEvent>,
+}
+
+impl Future for TrustRecorder {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let mgr_rx = &mut self.as_mut().mgr_rx;
+            pin_mut!(mgr_rx);
+
+            let event = crate::service_ready!("memory trust recorder", mgr_rx.poll_next(ctx));
+
+            if let PeerManagerEvent::TrustMetric { pid, feedback } = event {
+                let mut hub = self.network.hub.write();
+
+                let opt_addr = hub
+                    .by_peer
+                    .get(&pid)
+                    .and_then(|sid| hub.nodes.get(sid))
+                    .map(|n| n.chain_addr.clone());
+
+                if let Some(addr) = opt_addr {
+                    hub.feedbacks
+                        .entry(self.sid)
+                        .or_insert_with(Vec::new)
+                        .push((addr, feedback));
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures::{
+        channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        stream::StreamExt,
+    };
+    use futures_timer::Delay;
+    use protocol::{
+        traits::{Context, Gossip, MessageHandler, Priority, Rpc, TrustFeedback},
+        types::Address,
+        Bytes, ProtocolResult,
+    };
+    use tentacle::secio::SecioKeyPair;
+
+    use super::{LinkCondition, MemoryNetwork};
+    use crate::{NetworkConfig, NetworkService, NetworkServiceHandle};
+
+    const END_GOSSIP: &str = "/gossip/memory/news";
+    const END_RPC_CALL: &str = "/rpc_call/memory/echo";
+    const END_RPC_RESP: &str = "/rpc_resp/memory/echo";
+    const END_RPC_STREAM_CALL: &str = "/rpc_call/memory/count";
+    const END_RPC_STREAM_RESP: &str = "/rpc_resp/memory/count";
+
+    struct NewsReader(UnboundedSender<String>);
+
+    #[async_trait]
+    impl MessageHandler for NewsReader {
+        type Message = String;
+
+        async fn process(&self, _ctx: Context, msg: Self::Message) -> TrustFeedback {
+            self.0.unbounded_send(msg).expect("news reader");
+            TrustFeedback::Good
+        }
+    }
+
+    struct Echo(NetworkServiceHandle);
+
+    #[async_trait]
+    impl MessageHandler for Echo {
+        type Message = String;
+
+        async fn process(&self, ctx: Context, msg: Self::Message) -> TrustFeedback {
+            self.0
+                .response(ctx, END_RPC_RESP, Ok(msg), Priority::High)
+                .await
+                .expect("echo");
+            TrustFeedback::Neutral
+        }
+    }
+
+    struct Count(NetworkServiceHandle);
+
+    #[async_trait]
+    impl MessageHandler for Count {
+        type Message = u64;
+
+        async fn process(&self, ctx: Context, msg: Self::Message) -> TrustFeedback {
+            let nums = (0..msg).collect::<Vec<_>>();
+            self.0
+                .response_stream(ctx, END_RPC_STREAM_RESP, Ok(nums), Priority::High)
+                .await
+                .expect("count");
+            TrustFeedback::Neutral
+        }
+    }
+
+    fn node(
+        network: &MemoryNetwork,
+        seed: u8,
+    ) -> (NetworkServiceHandle, Address, UnboundedReceiver<String>) {
+        node_with_config(network, seed, NetworkConfig::new())
+    }
+
+    fn node_with_config(
+        network: &MemoryNetwork,
+        seed: u8,
+        config: NetworkConfig,
+    ) -> (NetworkServiceHandle, Address, UnboundedReceiver<String>) {
+        let seckey = Bytes::from(vec![seed; 32]);
+        let config = config
+            .secio_keypair(hex::encode(seckey))
+            .expect("keypair")
+            .rpc_timeout(Some(1))
+            .rpc_max_chunk_size(Some(16));
+
+        let pubkey = SecioKeyPair::secp256k1_raw_key(vec![seed; 32])
+            .expect("keypair")
+            .public_key();
+        let addr = Address::from_pubkey_bytes(Bytes::from(pubkey.inner())).expect("address");
+
+        let (news_tx, news_rx) = unbounded();
+        let mut service = NetworkService::in_memory(config, network).expect("memory service");
+        let handle = service.handle();
+
+        service
+            .register_endpoint_handler(END_GOSSIP, Box::new(NewsReader(news_tx)))
+            .expect("register news reader");
+        service
+            .register_endpoint_handler(END_RPC_CALL, Box::new(Echo(handle.clone())))
+            .expect("register echo");
+        service
+            .register_rpc_response::<String>(END_RPC_RESP)
+            .expect("register echo response");
+        service
+            .register_endpoint_handler(END_RPC_STREAM_CALL, Box::new(Count(handle.clone())))
+            .expect("register count");
+        service
+            .register_rpc_response::<u64>(END_RPC_STREAM_RESP)
+            .expect("register count response");
+
+        tokio::spawn(service);
+        (handle, addr, news_rx)
+    }
+
+    #[tokio::test]
+    async fn should_broadcast_to_all_nodes() {
+        let network = MemoryNetwork::new(0);
+        let (alice, _, _) = node(&network, 1);
+        let (_bob, _, mut bob_rx) = node(&network, 2);
+        let (_carol, _, mut carol_rx) = node(&network, 3);
+
+        let msg = "spike lee action started".to_owned();
+        alice
+            .broadcast(Context::new(), END_GOSSIP, msg.clone(), Priority::High)
+            .await
+            .expect("broadcast");
+
+        assert_eq!(bob_rx.next().await, Some(msg.clone()));
+        assert_eq!(carol_rx.next().await, Some(msg));
+    }
+
+    #[tokio::test]
+    async fn should_record_trust_feedback() {
+        let network = MemoryNetwork::new(0);
+        let (alice, alice_addr, _) = node(&network, 1);
+        let (_bob, bob_addr, mut bob_rx) = node(&network, 2);
+
+        alice
+            .users_cast(
+                Context::new(),
+                END_GOSSIP,
+                vec![bob_addr.clone()],
+                "hi".to_owned(),
+                Priority::High,
+            )
+            .await
+            .expect("users cast");
+        assert_eq!(bob_rx.next().await, Some("hi".to_owned()));
+        Delay::new(Duration::from_millis(100)).await;
+
+        let feedbacks = network.take_trust_feedback(&bob_addr);
+        assert_eq!(feedbacks.len(), 1);
+        assert_eq!(feedbacks[0].0, alice_addr);
+        match feedbacks[0].1 {
+            TrustFeedback::Good => (),
+            _ => panic!("should be good feedback"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_not_deliver_across_partition() {
+        let network = MemoryNetwork::new(0);
+        let (alice, alice_addr, _) = node(&network, 1);
+        let (_bob, bob_addr, mut bob_rx) = node(&network, 2);
+
+        network.partition(&[alice_addr], &[bob_addr.clone()]);
+
+        let ret = alice
+            .users_cast(
+                Context::new(),
+                END_GOSSIP,
+                vec![bob_addr.clone()],
+                "lost".to_owned(),
+                Priority::High,
+            )
+            .await;
+        assert!(ret.is_err(), "bob should be unconnected");
+
+        network.heal();
+        alice
+            .users_cast(
+                Context::new(),
+                END_GOSSIP,
+                vec![bob_addr],
+                "found".to_owned(),
+                Priority::High,
+            )
+            .await
+            .expect("users cast");
+
+        assert_eq!(bob_rx.next().await, Some("found".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn should_drop_all_messages_on_full_drop_rate() {
+        let network = MemoryNetwork::new(0);
+        let (alice, _, _) = node(&network, 1);
+        let (_bob, _, mut bob_rx) = node(&network, 2);
+
+        network.set_default_link(LinkCondition::default().drop_rate(1.0));
+        alice
+            .broadcast(
+                Context::new(),
+                END_GOSSIP,
+                "lost".to_owned(),
+                Priority::High,
+            )
+            .await
+            .expect("broadcast");
+
+        network.set_default_link(LinkCondition::default());
+        alice
+            .broadcast(
+                Context::new(),
+                END_GOSSIP,
+                "found".to_owned(),
+                Priority::High,
+            )
+            .await
+            .expect("broadcast");
+
+        assert_eq!(bob_rx.next().await, Some("found".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn should_call_rpc_with_latency() {
+        let network = MemoryNetwork::new(0);
+        let (alice, alice_addr, _) = node(&network, 1);
+        let (_bob, bob_addr, _) = node(&network, 2);
+
+        let cond = LinkCondition::default().latency(Duration::from_millis(50));
+        network.set_link(&alice_addr, &bob_addr, cond);
+
+        let bob_sid = network.hub.read().by_chain[&bob_addr];
+        let ctx = {
+            use crate::traits::NetworkContext;
+            Context::new().set_session_id(bob_sid)
+        };
+
+        let resp: String = alice
+            .call(ctx, END_RPC_CALL, "ping".to_owned(), Priority::High)
+            .await
+            .expect("rpc call");
+        assert_eq!(resp, "ping");
+    }
+    #[tokio::test]
+    async fn should_receive_stream_response_in_order() {
+        let network = MemoryNetwork::new(0);
+        let (alice, alice_addr, _) = node(&network, 1);
+        let (_bob, bob_addr, _) = node(&network, 2);
+
+        let cond = LinkCondition::default()
+            .latency(Duration::from_millis(5))
+            .jitter(Duration::from_millis(5));
+        network.set_link(&alice_addr, &bob_addr, cond);
+
+        let bob_sid = network.hub.read().by_chain[&bob_addr];
+        let ctx = {
+            use crate::traits::NetworkContext;
+            Context::new().set_session_id(bob_sid)
+        };
+
+        // 8 bytes each, two per chunk, far more chunks than stream window
+        let nums: Vec<u64> = alice
+            .call_stream(ctx, END_RPC_STREAM_CALL, 100u64, Priority::High)
+            .await
+            .expect("rpc stream call");
+        assert_eq!(nums, (0..100).collect::<Vec<u64>>());
+    }
+
+    #[tokio::test]
+    async fn should_reject_stream_response_over_max_items() {
+        let network = MemoryNetwork::new(0);
+        let config = NetworkConfig::new().rpc_stream_max_items(Some(10));
+        let (alice, _, _) = node_with_config(&network, 1, config);
+        let (_bob, bob_addr, _) = node(&network, 2);
+
+        let bob_sid = network.hub.read().by_chain[&bob_addr];
+        let ctx = {
+            use crate::traits::NetworkContext;
+            Context::new().set_session_id(bob_sid)
+        };
+
+        let ret: ProtocolResult<Vec<u64>> = alice
+            .call_stream(ctx.clone(), END_RPC_STREAM_CALL, 10u64, Priority::High)
+            .await;
+        assert_eq!(ret.expect("rpc stream call").len(), 10);
+
+        let ret: ProtocolResult<Vec<u64>> = alice
+            .call_stream(ctx, END_RPC_STREAM_CALL, 11u64, Priority::High)
+            .await;
+        assert!(ret.is_err(), "should exceed max items");
+    }
+}
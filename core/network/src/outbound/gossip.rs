@@ -10,6 +10,7 @@ use crate::{
     endpoint::Endpoint,
     error::NetworkError,
     message::{Headers, NetworkMessage},
+    relay::{RelayMessage, END_GOSSIP_RELAY},
     traits::{Compression, MessageSender},
 };
 
@@ -17,6 +18,9 @@ use crate::{
 pub struct NetworkGossip<S, C> {
     sender:      S,
     compression: C,
+
+    // Max hops to relay users cast message, zero means disabled
+    relay_hops: u8,
 }
 
 impl<S, C> NetworkGossip<S, C>
@@ -24,10 +28,12 @@ where
     S: MessageSender + Sync + Send + Clone,
     C: Compression + Sync + Send + Clone,
 {
-    pub fn new(sender: S, compression: C) -> Self {
+    pub fn new(sender: S, compression: C, relay_hops: u8) -> Self {
         NetworkGossip {
             sender,
             compression,
+
+            relay_hops,
         }
     }
 
@@ -51,7 +57,7 @@ where
         Ok(msg)
     }
 
-    fn send(
+    pub(crate) fn send(
         &self,
         _ctx: Context,
         tar: TargetSession,
@@ -70,6 +76,28 @@ where
     ) -> Result<(), NetworkError> {
         self.sender.users_send(users, msg, pri).await
     }
+
+    pub(crate) async fn relay(
+        &self,
+        ctx: Context,
+        tar: TargetSession,
+        users: Vec<Address>,
+        msg: Bytes,
+        hops: u8,
+    ) -> ProtocolResult<()> {
+        let relay_msg = RelayMessage {
+            targets: users,
+            hops,
+            msg: msg.to_vec(),
+        };
+
+        let msg = self
+            .package_message(ctx.clone(), END_GOSSIP_RELAY, relay_msg)
+            .await?;
+        self.send(ctx, tar, msg, Priority::High)?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -101,9 +129,27 @@ where
     {
         let msg = self.package_message(cx.clone(), end, msg).await?;
         let user_count = users.len();
-        self.users_send(cx, users, msg, p).await?;
+        let ret = self.users_send(cx.clone(), users, msg.clone(), p).await;
         common_apm::metrics::network::on_network_message_sent_multi_target(end, user_count as i64);
 
-        Ok(())
+        match ret {
+            Err(NetworkError::UserSend {
+                unconnected,
+                unknown,
+                other: None,
+            }) if self.relay_hops > 0 => {
+                // Users we cannot reach directly, for example validators
+                // behind sentry nodes, let connected peers relay it.
+                let users = unconnected
+                    .into_iter()
+                    .chain(unknown.into_iter())
+                    .flatten()
+                    .collect();
+
+                self.relay(cx, TargetSession::All, users, msg, self.relay_hops)
+                    .await
+            }
+            ret => Ok(ret?),
+        }
     }
 }
@@ -1,20 +1,24 @@
-use std::{sync::Arc, time::Instant};
+use std::{collections::BTreeMap, mem, sync::Arc, time::Instant};
 
 use async_trait::async_trait;
-use futures::future::{self, Either};
+use futures::{
+    future::{self, Either},
+    stream::StreamExt,
+};
 use futures_timer::Delay;
+use log::debug;
 use protocol::{
-    traits::{Context, MessageCodec, Priority, Rpc},
+    traits::{Context, MessageCodec, MessageHandler, Priority, Rpc, TrustFeedback},
     Bytes, ProtocolResult,
 };
 use tentacle::{service::TargetSession, SessionId};
 
 use crate::{
-    config::TimeoutConfig,
+    config::{RpcStreamConfig, TimeoutConfig},
     endpoint::Endpoint,
     error::{ErrorKind, NetworkError},
     message::{Headers, NetworkMessage},
-    rpc::{RpcErrorMessage, RpcResponse, RpcResponseCode},
+    rpc::{RpcChunk, RpcErrorMessage, RpcResponse, RpcResponseCode, RpcStreamAck, RPC_STREAM_ACK},
     rpc_map::RpcMap,
     traits::{Compression, MessageSender, NetworkContext},
 };
@@ -24,8 +28,11 @@ pub struct NetworkRpc<S, C> {
     sender:      S,
     compression: C,
     map:         Arc<RpcMap>,
+    // Acks for our stream responses, keyed by remote rpc id
+    acks:        Arc<RpcMap>,
 
     timeout: TimeoutConfig,
+    stream:  RpcStreamConfig,
 }
 
 impl<S, C> NetworkRpc<S, C>
@@ -33,13 +40,27 @@ where
     S: MessageSender + Sync + Clone,
     C: Compression + Sync + Clone,
 {
-    pub fn new(sender: S, compression: C, map: Arc<RpcMap>, timeout: TimeoutConfig) -> Self {
+    pub fn new(
+        sender: S,
+        compression: C,
+        map: Arc<RpcMap>,
+        timeout: TimeoutConfig,
+        stream: RpcStreamConfig,
+    ) -> Self {
         NetworkRpc {
             sender,
             compression,
             map,
+            acks: Arc::new(RpcMap::new()),
 
             timeout,
+            stream,
+        }
+    }
+
+    pub fn stream_ack_handler(&self) -> RpcStreamAckHandler {
+        RpcStreamAckHandler {
+            acks: Arc::clone(&self.acks),
         }
     }
 
@@ -49,6 +70,97 @@ where
 
         self.sender.send(target, compressed_msg, p)
     }
+
+    async fn encode_net_msg(
+        cx: &Context,
+        endpoint: Endpoint,
+        data: Bytes,
+    ) -> ProtocolResult<Bytes> {
+        let mut headers = Headers::default();
+        if let Some(state) = common_apm::muta_apm::MutaTracer::span_state(cx) {
+            headers.set_trace_id(state.trace_id());
+            headers.set_span_id(state.span_id());
+        }
+        common_apm::metrics::network::on_network_message_sent(endpoint.full_url());
+
+        let net_msg = NetworkMessage::new(endpoint, data, headers)
+            .encode()
+            .await?;
+
+        Ok(net_msg)
+    }
+}
+
+// Remove rpc entry once call is done, either success, fail or timeout.
+struct RpcGuard {
+    map: Arc<RpcMap>,
+    sid: SessionId,
+    rid: u64,
+}
+
+impl Drop for RpcGuard {
+    fn drop(&mut self) {
+        self.map.remove(self.sid, self.rid);
+    }
+}
+
+// Pack encoded messages into ordered chunks, each chunk's total size is
+// below max_chunk_size unless it contains only one oversize message.
+// There's always at least one chunk, the last one.
+fn pack_chunks(items: Vec<Bytes>, max_chunk_size: usize) -> Vec<RpcChunk> {
+    let mut chunks = Vec::new();
+    let mut chunk_items = Vec::new();
+    let mut chunk_size = 0;
+
+    for item in items {
+        if !chunk_items.is_empty() && chunk_size + item.len() > max_chunk_size {
+            chunks.push(RpcChunk {
+                seq:   chunks.len() as u64,
+                items: mem::take(&mut chunk_items),
+                last:  false,
+            });
+            chunk_size = 0;
+        }
+
+        chunk_size += item.len();
+        chunk_items.push(item);
+    }
+
+    chunks.push(RpcChunk {
+        seq:   chunks.len() as u64,
+        items: chunk_items,
+        last:  true,
+    });
+
+    chunks
+}
+
+pub struct RpcStreamAckHandler {
+    acks: Arc<RpcMap>,
+}
+
+#[async_trait]
+impl MessageHandler for RpcStreamAckHandler {
+    type Message = RpcStreamAck;
+
+    async fn process(&self, ctx: Context, ack: Self::Message) -> TrustFeedback {
+        let (sid, rid) = match (ctx.session_id(), ctx.rpc_id()) {
+            (Ok(sid), Ok(rid)) => (sid, rid),
+            _ => return TrustFeedback::Neutral,
+        };
+
+        // Stream may already finish or timeout
+        match self.acks.stream_sender::<u64>(sid, rid) {
+            Ok(ack_tx) => {
+                if ack_tx.unbounded_send(ack.seq).is_err() {
+                    debug!("network: rpc stream {} from {} dropped", rid, sid);
+                }
+            }
+            Err(e) => debug!("network: rpc stream ack {}", e),
+        }
+
+        TrustFeedback::Neutral
+    }
 }
 
 #[async_trait]
@@ -171,4 +283,241 @@ where
 
         Ok(())
     }
+
+    async fn call_stream<M, R>(
+        &self,
+        cx: Context,
+        end: &str,
+        mut msg: M,
+        p: Priority,
+    ) -> ProtocolResult<Vec<R>>
+    where
+        M: MessageCodec,
+        R: MessageCodec,
+    {
+        let endpoint = end.parse::<Endpoint>()?;
+        let sid = cx.session_id()?;
+        let rid = self.map.next_rpc_id();
+        let connected_addr = cx.remote_connected_addr();
+        let mut chunk_rx = self.map.insert_stream::<RpcResponse>(sid, rid);
+        let inst = Instant::now();
+
+        let _guard = RpcGuard {
+            map: Arc::clone(&self.map),
+            sid,
+            rid,
+        };
+
+        let data = msg.encode().await?;
+        let endpoint = endpoint.extend(&rid.to_string())?;
+        let net_msg = Self::encode_net_msg(&cx, endpoint, data).await?;
+        self.send(cx.clone(), sid, net_msg, p)?;
+
+        let ack_endpoint = RPC_STREAM_ACK
+            .parse::<Endpoint>()?
+            .extend(&rid.to_string())?;
+        let mut msgs = Vec::new();
+        let mut expect = 0u64;
+        // Chunks may be reordered by concurrent reactor, buffer them until
+        // expected one arrives. Responder's window bounds its size.
+        let mut pending = BTreeMap::new();
+
+        'stream: loop {
+            // Rpc timeout applies to each chunk, capped by whole stream deadline
+            let remain = self
+                .stream
+                .timeout
+                .checked_sub(inst.elapsed())
+                .unwrap_or_default();
+            let timeout = Delay::new(remain.min(self.timeout.rpc));
+            let resp = match future::select(chunk_rx.next(), timeout).await {
+                Either::Left((Some(resp), _timeout)) => resp,
+                Either::Left((None, _timeout)) => {
+                    return Err(NetworkError::from(ErrorKind::RpcDropped(connected_addr)).into());
+                }
+                Either::Right((_unresolved, _timeout)) => {
+                    common_apm::metrics::network::NETWORK_RPC_RESULT_COUNT_VEC_STATIC
+                        .timeout
+                        .inc();
+
+                    return Err(NetworkError::from(ErrorKind::RpcTimeout(connected_addr)).into());
+                }
+            };
+
+            let chunk = match resp {
+                RpcResponse::Chunk(chunk) => chunk,
+                RpcResponse::Error(e) => {
+                    return Err(NetworkError::RemoteResponse(Box::new(e)).into());
+                }
+                RpcResponse::Success(_) => {
+                    return Err(NetworkError::from(ErrorKind::UnexpectedRpcResponse).into());
+                }
+            };
+
+            // Responder never runs ahead of unacked window
+            let got = chunk.seq;
+            let window = self.stream.window as u64;
+            if got < expect
+                || got >= expect.saturating_add(window)
+                || pending.insert(got, chunk).is_some()
+            {
+                return Err(
+                    NetworkError::from(ErrorKind::UnexpectedRpcChunk { expect, got }).into(),
+                );
+            }
+
+            while let Some(chunk) = pending.remove(&expect) {
+                if chunk.items.len() > self.stream.max_items.saturating_sub(msgs.len()) {
+                    let max = self.stream.max_items;
+                    return Err(NetworkError::from(ErrorKind::TooManyRpcItems { max }).into());
+                }
+                for item in chunk.items {
+                    msgs.push(R::decode(item).await?);
+                }
+                if chunk.last {
+                    break 'stream;
+                }
+
+                let mut ack = RpcStreamAck { seq: chunk.seq };
+                let data = ack.encode().await?;
+                let net_msg = Self::encode_net_msg(&cx, ack_endpoint.clone(), data).await?;
+                self.send(cx.clone(), sid, net_msg, Priority::High)?;
+
+                expect += 1;
+            }
+        }
+
+        common_apm::metrics::network::NETWORK_RPC_RESULT_COUNT_VEC_STATIC
+            .success
+            .inc();
+        common_apm::metrics::network::NETWORK_PROTOCOL_TIME_HISTOGRAM_VEC_STATIC
+            .rpc
+            .observe(common_apm::metrics::duration_to_sec(inst.elapsed()));
+
+        Ok(msgs)
+    }
+
+    async fn response_stream<M>(
+        &self,
+        cx: Context,
+        end: &str,
+        ret: ProtocolResult<Vec<M>>,
+        p: Priority,
+    ) -> ProtocolResult<()>
+    where
+        M: MessageCodec,
+    {
+        let endpoint = end.parse::<Endpoint>()?;
+        let sid = cx.session_id()?;
+        let rid = cx.rpc_id()?;
+        let connected_addr = cx.remote_connected_addr();
+        let endpoint = endpoint.extend(&rid.to_string())?;
+
+        let msgs = match ret {
+            Ok(msgs) => msgs,
+            Err(err) => {
+                let mut resp = RpcResponse::Error(RpcErrorMessage {
+                    code: RpcResponseCode::ServerError,
+                    msg:  err.to_string(),
+                });
+
+                let data = resp.encode().await?;
+                let net_msg = Self::encode_net_msg(&cx, endpoint, data).await?;
+                self.send(cx, sid, net_msg, p)?;
+
+                return Ok(());
+            }
+        };
+
+        let mut items = Vec::with_capacity(msgs.len());
+        for mut msg in msgs {
+            items.push(msg.encode().await?);
+        }
+        let chunks = pack_chunks(items, self.stream.max_chunk_size);
+
+        // Register before first chunk is sent, ack may come back quickly
+        let mut ack_rx = self.acks.insert_stream::<u64>(sid, rid);
+        let _guard = RpcGuard {
+            map: Arc::clone(&self.acks),
+            sid,
+            rid,
+        };
+
+        let window = self.stream.window as u64;
+        let mut acked = 0u64;
+
+        for chunk in chunks {
+            let seq = chunk.seq;
+
+            // Wait for caller to catch up
+            while seq >= acked + window {
+                let timeout = Delay::new(self.timeout.rpc);
+                match future::select(ack_rx.next(), timeout).await {
+                    Either::Left((Some(ack_seq), _timeout)) => acked = acked.max(ack_seq + 1),
+                    Either::Left((None, _timeout)) => {
+                        return Err(
+                            NetworkError::from(ErrorKind::RpcDropped(connected_addr)).into()
+                        );
+                    }
+                    Either::Right((_unresolved, _timeout)) => {
+                        return Err(
+                            NetworkError::from(ErrorKind::RpcTimeout(connected_addr)).into()
+                        );
+                    }
+                }
+            }
+
+            let mut resp = RpcResponse::Chunk(chunk);
+            let data = resp.encode().await?;
+            let net_msg = Self::encode_net_msg(&cx, endpoint.clone(), data).await?;
+            self.send(cx.clone(), sid, net_msg, p)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::Bytes;
+
+    use super::pack_chunks;
+
+    #[test]
+    fn should_always_pack_last_chunk() {
+        let chunks = pack_chunks(vec![], 10);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].seq, 0);
+        assert!(chunks[0].last);
+        assert!(chunks[0].items.is_empty());
+    }
+
+    #[test]
+    fn should_pack_items_in_order_below_max_chunk_size() {
+        let items = (0..10u8)
+            .map(|i| Bytes::from(vec![i; 4]))
+            .collect::<Vec<_>>();
+        let chunks = pack_chunks(items.clone(), 10);
+
+        assert_eq!(chunks.len(), 5);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.seq, idx as u64);
+            assert_eq!(chunk.last, idx == 4);
+            assert!(chunk.items.iter().map(Bytes::len).sum::<usize>() <= 10);
+        }
+
+        let packed = chunks.into_iter().flat_map(|c| c.items).collect::<Vec<_>>();
+        assert_eq!(packed, items);
+    }
+
+    #[test]
+    fn should_pack_oversize_item_alone() {
+        let items = vec![Bytes::from(vec![0u8; 2]), Bytes::from(vec![1u8; 20])];
+        let chunks = pack_chunks(items, 10);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].items.len(), 1);
+        assert_eq!(chunks[1].items[0].len(), 20);
+    }
 }
@@ -16,7 +16,7 @@ pub mod diagnostic;
 use addr_set::PeerAddrSet;
 use peer::Peer;
 use retry::Retry;
-use save_restore::{NoPeerDatFile, PeerDatFile, SaveRestore};
+use save_restore::{NoPeerStore, PeerStoreDir, PeerStoreThread, SaveRestore};
 
 pub use disc::DiscoveryAddrManager;
 pub use ident::IdentifyCallback;
@@ -29,7 +29,7 @@ mod test_manager;
 
 use std::{
     borrow::Borrow,
-    cmp::PartialEq,
+    cmp::{PartialEq, Reverse},
     collections::{HashMap, HashSet},
     convert::{TryFrom, TryInto},
     future::Future,
@@ -41,7 +41,7 @@ use std::{
     sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     sync::Arc,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use derive_more::Display;
@@ -52,9 +52,9 @@ use futures::{
     task::AtomicWaker,
 };
 use log::{debug, error, info, warn};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use protocol::{traits::TrustFeedback, types::Address};
-use rand::seq::IteratorRandom;
+use rand::seq::{IteratorRandom, SliceRandom};
 use serde_derive::{Deserialize, Serialize};
 #[cfg(not(test))]
 use tentacle::context::SessionContext;
@@ -386,6 +386,7 @@ impl Deref for ArcSession {
 
 struct Inner {
     whitelist: RwLock<HashSet<ArcWhitelistedPeer>>,
+    private:   RwLock<HashSet<Address>>,
 
     sessions: RwLock<HashSet<ArcSession>>,
     peers:    RwLock<HashSet<ArcPeer>>,
@@ -398,6 +399,7 @@ impl Inner {
     pub fn new() -> Self {
         Inner {
             whitelist: Default::default(),
+            private:   Default::default(),
 
             sessions: Default::default(),
             peers:    Default::default(),
@@ -459,11 +461,21 @@ impl Inner {
                 && addition_filter(p)
         };
 
-        let mut rng = rand::thread_rng();
-        let book = self.peers.read();
-        let qualified_peers = book.iter().filter(connectable).map(ArcPeer::to_owned);
+        let mut qualified_peers = {
+            let book = self.peers.read();
+            book.iter()
+                .filter(connectable)
+                .map(ArcPeer::to_owned)
+                .collect::<Vec<_>>()
+        };
 
-        qualified_peers.choose_multiple(&mut rng, max)
+        // Best scored peers first, shuffle before stable sort so that peers
+        // with same score are still picked randomly.
+        qualified_peers.shuffle(&mut rand::thread_rng());
+        qualified_peers.sort_by_cached_key(|p| Reverse(p.trust_metric().map(|m| m.trust_score())));
+        qualified_peers.truncate(max);
+
+        qualified_peers
     }
 
     #[allow(dead_code)]
@@ -516,6 +528,17 @@ impl Inner {
         }
     }
 
+    pub fn add_private_peers_by_chain_addr(&self, chain_addrs: Vec<Address>) {
+        self.private.write().extend(chain_addrs);
+    }
+
+    pub fn private(&self, peer: &ArcPeer) -> bool {
+        match peer.owned_chain_addr() {
+            Some(ca) => self.private.read().contains(&ca),
+            None => false,
+        }
+    }
+
     #[cfg(test)]
     pub fn whitelist(&self) -> HashSet<ArcWhitelistedPeer> {
         self.whitelist.read().iter().cloned().collect()
@@ -565,6 +588,8 @@ pub struct PeerManagerConfig {
     pub whitelist_by_chain_addrs: Vec<Address>,
     /// Only allow peers in whitelist
     pub whitelist_peers_only:     bool,
+    /// Private peers by chain address, never shared through discovery
+    pub private_by_chain_addrs:   Vec<Address>,
 
     /// Trust metric config
     pub peer_trust_config: Arc<TrustMetricConfig>,
@@ -577,8 +602,8 @@ pub struct PeerManagerConfig {
     /// Routine job interval
     pub routine_interval: Duration,
 
-    /// Peer dat file path
-    pub peer_dat_file: PathBuf,
+    /// Peer store directory path
+    pub peer_store_dir: PathBuf,
 }
 
 #[derive(Clone)]
@@ -594,7 +619,10 @@ impl PeerManagerHandle {
     pub fn random_addrs(&self, max: usize) -> Vec<Multiaddr> {
         let mut rng = rand::thread_rng();
         let book = self.inner.peers.read();
-        let peers = book.iter().choose_multiple(&mut rng, max);
+        let peers = book
+            .iter()
+            .filter(|p| !self.inner.private(p))
+            .choose_multiple(&mut rng, max);
 
         // Should always include our self
         let our_self = self.listen_addrs();
@@ -636,8 +664,10 @@ pub struct PeerManager {
     heart_beat: Option<HeartBeat>,
     hb_waker:   Arc<AtomicWaker>,
 
-    // save restore
-    peer_dat_file: Box<dyn SaveRestore>,
+    // save restore, changed peers are saved during routine
+    peer_store:   Box<dyn SaveRestore>,
+    dirty_peers:  Mutex<HashSet<ArcPeer>>,
+    last_persist: Instant,
 }
 
 impl PeerManager {
@@ -652,9 +682,10 @@ impl PeerManager {
         let bootstraps = HashSet::from_iter(config.bootstraps.clone());
         let waker = Arc::new(AtomicWaker::new());
         let heart_beat = HeartBeat::new(Arc::clone(&waker), config.routine_interval);
-        let peer_dat_file = Box::new(NoPeerDatFile);
+        let peer_store = Box::new(NoPeerStore);
 
         inner.whitelist_never_expired_peers_by_chain_addr(config.whitelist_by_chain_addrs.clone());
+        inner.add_private_peers_by_chain_addr(config.private_by_chain_addrs.clone());
 
         PeerManager {
             inner,
@@ -670,7 +701,9 @@ impl PeerManager {
             heart_beat: Some(heart_beat),
             hb_waker: waker,
 
-            peer_dat_file,
+            peer_store,
+            dirty_peers: Default::default(),
+            last_persist: Instant::now(),
         }
     }
 
@@ -689,14 +722,23 @@ impl PeerManager {
         diagnostic::Diagnostic::new(Arc::clone(&self.inner))
     }
 
-    pub fn enable_save_restore(&mut self) {
-        let peer_dat_file = PeerDatFile::new(&self.config.peer_dat_file);
+    pub fn enable_save_restore(&mut self) -> Result<(), NetworkError> {
+        let peer_store = PeerStoreDir::new(
+            &self.config.peer_store_dir,
+            Arc::clone(&self.config.peer_trust_config),
+        );
 
-        self.peer_dat_file = Box::new(peer_dat_file);
+        self.peer_store = Box::new(PeerStoreThread::spawn(Arc::new(peer_store))?);
+        Ok(())
     }
 
+    // Restored peers keep their trust metric and ban, routine connecting dials
+    // connectable peers in trust score order, banned ones stay banned until
+    // expired.
     pub fn restore_peers(&self) -> Result<(), NetworkError> {
-        let peers = self.peer_dat_file.restore()?;
+        let peers = self.peer_store.restore()?;
+        info!("network: restore {} peers", peers.len());
+
         self.inner.restore(peers);
         Ok(())
     }
@@ -865,6 +907,7 @@ impl PeerManager {
 
         self.inner.sessions.write().insert(session);
         remote_peer.mark_connected(ctx.id);
+        self.mark_dirty(&remote_peer);
 
         match remote_peer.trust_metric() {
             Some(trust_metric) => trust_metric.start(),
@@ -888,6 +931,7 @@ impl PeerManager {
 
         info!("session closed {}", session.connected_addr);
         session.peer.mark_disconnected();
+        self.mark_dirty(&session.peer);
 
         match session.peer.trust_metric() {
             Some(trust_metric) => trust_metric.pause(),
@@ -949,6 +993,7 @@ impl PeerManager {
                 peer.set_connectedness(Connectedness::Unconnectable);
             }
         }
+        self.mark_dirty(&peer);
 
         if let Some(attempt) = self.connecting.take(&peer_id) {
             if attempt.peer.connectedness() == Connectedness::Unconnectable {
@@ -991,6 +1036,7 @@ impl PeerManager {
         // Ensure we disconnect this peer
         self.disconnect_session(sid);
         session.peer.mark_disconnected();
+        self.mark_dirty(&session.peer);
 
         match session.peer.trust_metric() {
             Some(trust_metric) => trust_metric.bad_events(1),
@@ -1063,6 +1109,7 @@ impl PeerManager {
 
         self.inner.remove_session(sid);
         peer.mark_disconnected();
+        self.mark_dirty(&peer);
         // Ensure we disconnect from this peer
         self.disconnect_session(sid);
 
@@ -1112,6 +1159,7 @@ impl PeerManager {
                     self.disconnect_session(session.id);
                 }
                 peer.mark_disconnected();
+                self.persist_peer_now(&peer);
             }
             Bad(_) | Worse(_) => {
                 match &feedback {
@@ -1137,10 +1185,16 @@ impl PeerManager {
                         self.disconnect_session(session.id);
                     }
                     peer.mark_disconnected();
+                    self.persist_peer_now(&peer);
+                } else {
+                    self.mark_dirty(&peer);
                 }
             }
             Neutral => (),
-            Good => peer_trust_metric.good_events(1),
+            Good => {
+                peer_trust_metric.good_events(1);
+                self.mark_dirty(&peer);
+            }
         }
     }
 
@@ -1169,6 +1223,28 @@ impl PeerManager {
         }
     }
 
+    fn mark_dirty(&self, peer: &ArcPeer) {
+        self.dirty_peers.lock().insert(peer.clone());
+    }
+
+    // Ban must survive restart, don't wait for routine
+    fn persist_peer_now(&self, peer: &ArcPeer) {
+        self.dirty_peers.lock().remove(peer);
+
+        if let Err(err) = self.peer_store.save(vec![peer.clone()]) {
+            error!("network: peer store: save peer {:?}: {}", peer.id, err);
+        }
+    }
+
+    fn persist_dirty_peers(&mut self) {
+        self.last_persist = Instant::now();
+
+        let peers = self.dirty_peers.lock().drain().collect::<Vec<_>>();
+        if let Err(err) = self.peer_store.save(peers) {
+            error!("network: peer store: {}", err);
+        }
+    }
+
     fn connect_peers_now(&mut self, peers: Vec<ArcPeer>) {
         let peer_addrs = peers.into_iter().map(|peer| {
             peer.set_connectedness(Connectedness::Connecting);
@@ -1334,8 +1410,8 @@ impl Drop for PeerManager {
     fn drop(&mut self) {
         let peers = self.inner.package_peers();
 
-        if let Err(err) = self.peer_dat_file.save(peers) {
-            error!("network: peer dat file: {}", err);
+        if let Err(err) = self.peer_store.save(peers) {
+            error!("network: peer store: {}", err);
         }
     }
 }
@@ -1364,6 +1440,11 @@ impl Future for PeerManager {
             self.process_event(event);
         }
 
+        // Save peers changed since last routine
+        if self.last_persist.elapsed() >= self.config.routine_interval {
+            self.persist_dirty_peers();
+        }
+
         // Check connecting count
         let connected_count = self.inner.connected();
         let connection_attempts = connected_count + self.connecting.len();
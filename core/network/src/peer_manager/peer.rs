@@ -197,6 +197,15 @@ impl Peer {
         self.alive.store(alive, Ordering::SeqCst);
     }
 
+    // Connected peer is seen right now, otherwise last time we saw it
+    // is when it got disconnected.
+    pub fn last_seen(&self) -> u64 {
+        match self.connectedness() {
+            Connectedness::Connected => time::now(),
+            _ => self.connected_at().max(self.disconnected_at()),
+        }
+    }
+
     pub(super) fn set_alive(&self, live: u64) {
         self.alive.store(live, Ordering::SeqCst);
     }
@@ -230,7 +239,6 @@ impl Peer {
             .store(expired_at.as_secs(), Ordering::SeqCst);
     }
 
-    #[cfg(test)]
     pub fn ban_expired_at(&self) -> u64 {
         self.ban_expired_at.load(Ordering::SeqCst)
     }
@@ -252,8 +260,7 @@ impl Peer {
         }
     }
 
-    #[cfg(test)]
-    fn set_ban_expired_at(&self, at: u64) {
+    pub(super) fn set_ban_expired_at(&self, at: u64) {
         self.ban_expired_at.store(at, Ordering::SeqCst);
     }
 
@@ -1,13 +1,21 @@
-use super::{ArcPeer, Connectedness, PeerMultiaddr};
+use super::trust_metric::TrustMetricSnapshot;
+use super::{ArcPeer, Connectedness, PeerMultiaddr, TrustMetric, TrustMetricConfig};
 
 use std::{
-    convert::TryFrom,
+    cmp::Reverse,
     fmt,
-    fs::File,
+    fs::{self, File},
     io::{BufReader, Read, Write},
     path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
 };
 
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    executor::block_on_stream,
+};
+use log::{error, info, warn};
 use serde::{de, ser};
 use serde_derive::{Deserialize, Serialize};
 use tentacle::{
@@ -17,6 +25,11 @@ use tentacle::{
 
 use crate::error::NetworkError;
 
+pub const PEER_FILE_EXT: &str = "peer";
+const PEER_TMP_FILE_EXT: &str = "tmp";
+// Peers used to be saved in a single "peers.dat" file next to store dir
+const LEGACY_PEER_DAT_FILE_EXT: &str = "dat";
+
 // TODO: remove skip tag on retry and next_attempt_at
 // TODO: save multiaddr failure count
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,6 +45,9 @@ struct SerdePeer {
     connected_at:    u64,
     disconnected_at: u64,
     alive:           u64,
+    ban_expired_at:  u64,
+    last_seen:       u64,
+    trust_metric:    Option<TrustMetricSnapshot>,
 }
 
 impl From<ArcPeer> for SerdePeer {
@@ -51,22 +67,58 @@ impl From<ArcPeer> for SerdePeer {
             connected_at:    peer.connected_at(),
             disconnected_at: peer.disconnected_at(),
             alive:           peer.alive(),
+            ban_expired_at:  peer.ban_expired_at(),
+            last_seen:       peer.last_seen(),
+            trust_metric:    peer.trust_metric().map(|m| m.snapshot()),
         }
     }
 }
 
-impl TryFrom<SerdePeer> for ArcPeer {
-    type Error = NetworkError;
+// Peer layout inside legacy single peer dat file
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacySerdePeer {
+    id:              SerdePeerId,
+    pubkey:          Option<SerdePubKey>,
+    multiaddrs:      Vec<PeerMultiaddr>,
+    connectedness:   usize,
+    #[serde(skip)]
+    retry:           u8,
+    #[serde(skip)]
+    next_attempt_at: u64,
+    connected_at:    u64,
+    disconnected_at: u64,
+    alive:           u64,
+}
 
-    fn try_from(serde_peer: SerdePeer) -> Result<Self, Self::Error> {
-        let peer_id = serde_peer.id.0;
+impl From<LegacySerdePeer> for SerdePeer {
+    fn from(legacy: LegacySerdePeer) -> SerdePeer {
+        SerdePeer {
+            id:              legacy.id,
+            pubkey:          legacy.pubkey,
+            multiaddrs:      legacy.multiaddrs,
+            connectedness:   legacy.connectedness,
+            retry:           legacy.retry,
+            next_attempt_at: legacy.next_attempt_at,
+            connected_at:    legacy.connected_at,
+            disconnected_at: legacy.disconnected_at,
+            alive:           legacy.alive,
+            ban_expired_at:  0,
+            last_seen:       legacy.disconnected_at,
+            trust_metric:    None,
+        }
+    }
+}
+
+impl SerdePeer {
+    fn into_peer(self, trust_config: &Arc<TrustMetricConfig>) -> Result<ArcPeer, NetworkError> {
+        let peer_id = self.id.0;
 
         let peer = ArcPeer::new(peer_id.clone());
-        if let Some(pubkey) = serde_peer.pubkey {
+        if let Some(pubkey) = self.pubkey {
             peer.set_pubkey(pubkey.0)?;
         }
 
-        let multiaddrs = serde_peer
+        let multiaddrs = self
             .multiaddrs
             .into_iter()
             .map(|ma| {
@@ -77,70 +129,223 @@ impl TryFrom<SerdePeer> for ArcPeer {
             .collect();
         peer.multiaddrs.set(multiaddrs);
 
-        peer.set_connectedness(Connectedness::from(serde_peer.connectedness));
-        peer.retry.set(serde_peer.retry);
-        peer.retry.set_next_attempt_at(serde_peer.next_attempt_at);
-        peer.set_connected_at(serde_peer.connected_at);
-        peer.set_disconnected_at(serde_peer.disconnected_at);
-        peer.set_alive(serde_peer.alive);
+        peer.set_connectedness(Connectedness::from(self.connectedness));
+        peer.retry.set(self.retry);
+        peer.retry.set_next_attempt_at(self.next_attempt_at);
+        peer.set_connected_at(self.connected_at);
+        // Peer may still be connected when we crashed, last seen is the
+        // closest time we have.
+        peer.set_disconnected_at(self.disconnected_at.max(self.last_seen));
+        peer.set_alive(self.alive);
+        peer.set_ban_expired_at(self.ban_expired_at);
+
+        if let Some(snapshot) = self.trust_metric {
+            let trust_metric = TrustMetric::restore(Arc::clone(trust_config), snapshot);
+            peer.set_trust_metric(trust_metric);
+        }
 
         Ok(peer)
     }
 }
 
-// TODO: Async support, right now, it's ok since we only restore/save data once.
 pub(super) trait SaveRestore: Send + Sync {
+    // Insert or update given peers, peers not given are untouched
     fn save(&self, peers: Vec<ArcPeer>) -> Result<(), NetworkError>;
+    // Restored peers are ordered by trust score, best first
     fn restore(&self) -> Result<Vec<ArcPeer>, NetworkError>;
 }
 
+// Every peer is saved in its own file under given directory, so that we
+// can update them incrementally when peer is connected, failed or banned.
 #[derive(Clone)]
-pub(super) struct PeerDatFile {
-    path: PathBuf,
+pub(super) struct PeerStoreDir {
+    path:         PathBuf,
+    trust_config: Arc<TrustMetricConfig>,
 }
 
-impl PeerDatFile {
-    pub fn new<P: AsRef<Path>>(path: P) -> Self {
-        PeerDatFile {
+impl PeerStoreDir {
+    pub fn new<P: AsRef<Path>>(path: P, trust_config: Arc<TrustMetricConfig>) -> Self {
+        PeerStoreDir {
             path: path.as_ref().to_owned(),
+            trust_config,
         }
     }
-}
 
-impl SaveRestore for PeerDatFile {
-    fn save(&self, peers: Vec<ArcPeer>) -> Result<(), NetworkError> {
-        let mut file = File::create(&self.path)?;
-        let peers_to_save = peers.into_iter().map(SerdePeer::from).collect::<Vec<_>>();
-        let data = bincode::serialize(&peers_to_save)?;
+    fn peer_path(&self, peer_id: &PeerId) -> PathBuf {
+        let mut path = self.path.join(peer_id.to_base58());
+        path.set_extension(PEER_FILE_EXT);
+        path
+    }
+
+    fn save_peer(&self, peer: ArcPeer) -> Result<(), NetworkError> {
+        let path = self.peer_path(&peer.id);
+        let data = bincode::serialize(&SerdePeer::from(peer))?;
+
+        // Write to temporary file first, rename is atomic, so we never
+        // leave a half written peer file behind.
+        let mut tmp_path = path.clone();
+        tmp_path.set_extension(PEER_TMP_FILE_EXT);
 
+        let mut file = File::create(&tmp_path)?;
         file.write_all(data.as_slice())?;
+        file.sync_all()?;
+
+        fs::rename(tmp_path, path)?;
         Ok(())
     }
 
-    // restore data only happen once during network service starting
-    fn restore(&self) -> Result<Vec<ArcPeer>, NetworkError> {
-        let file = File::open(&self.path)?;
+    fn legacy_dat_path(&self) -> PathBuf {
+        self.path.with_extension(LEGACY_PEER_DAT_FILE_EXT)
+    }
+
+    // Move peers from legacy dat file into store dir, then remove that file,
+    // so it's imported only once.
+    fn import_legacy_dat(&self) -> Result<(), NetworkError> {
+        let dat_path = self.legacy_dat_path();
+        if !dat_path.is_file() {
+            return Ok(());
+        }
+
+        let data = fs::read(&dat_path)?;
+        let legacy_peers: Vec<LegacySerdePeer> = bincode::deserialize(&data)?;
+        info!(
+            "network: import {} peers from {:?}",
+            legacy_peers.len(),
+            dat_path
+        );
+
+        fs::create_dir_all(&self.path)?;
+        for legacy_peer in legacy_peers {
+            let serde_peer = SerdePeer::from(legacy_peer);
+            match serde_peer.into_peer(&self.trust_config) {
+                Ok(peer) => self.save_peer(peer)?,
+                Err(err) => warn!("network: import peer from {:?}: {}", dat_path, err),
+            }
+        }
+
+        fs::remove_file(&dat_path)?;
+        Ok(())
+    }
+
+    fn restore_peer(&self, path: &Path) -> Result<ArcPeer, NetworkError> {
+        let file = File::open(path)?;
         let mut buf_reader = BufReader::new(file);
         let mut data = Vec::new();
 
         buf_reader.read_to_end(&mut data)?;
-        let peers_to_restore: Vec<SerdePeer> = bincode::deserialize(&data)?;
+        let serde_peer: SerdePeer = bincode::deserialize(&data)?;
+
+        serde_peer.into_peer(&self.trust_config)
+    }
+}
+
+impl SaveRestore for PeerStoreDir {
+    fn save(&self, peers: Vec<ArcPeer>) -> Result<(), NetworkError> {
+        if peers.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.path)?;
+        for peer in peers {
+            self.save_peer(peer)?;
+        }
+
+        Ok(())
+    }
+
+    // restore data only happen once during network service starting
+    fn restore(&self) -> Result<Vec<ArcPeer>, NetworkError> {
+        if let Err(err) = self.import_legacy_dat() {
+            warn!("network: import legacy peer dat file: {}", err);
+        }
+
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut peers = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(PEER_FILE_EXT) {
+                continue;
+            }
 
-        let mut peers = Vec::with_capacity(peers_to_restore.len());
-        for p in peers_to_restore {
-            if let Ok(p) = ArcPeer::try_from(p) {
-                peers.push(p);
+            match self.restore_peer(&path) {
+                Ok(peer) => peers.push(peer),
+                Err(err) => warn!("network: restore peer from {:?}: {}", path, err),
             }
         }
 
+        // Peer without trust metric has never connected
+        peers.sort_by_key(|p| Reverse(p.trust_metric().map(|m| m.trust_score())));
         Ok(peers)
     }
 }
 
+// Peers are saved on a dedicated thread, so file io never blocks peer
+// manager poll. Queued peers are flushed before the thread is joined on
+// drop.
+pub(super) struct PeerStoreThread {
+    store:  Arc<dyn SaveRestore>,
+    tx:     UnboundedSender<Vec<ArcPeer>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PeerStoreThread {
+    pub fn spawn(store: Arc<dyn SaveRestore>) -> Result<Self, NetworkError> {
+        let (tx, rx) = unbounded::<Vec<ArcPeer>>();
+        let thread_store = Arc::clone(&store);
+
+        let handle = thread::Builder::new()
+            .name("peer-store".to_owned())
+            .spawn(move || {
+                for peers in block_on_stream(rx) {
+                    if let Err(err) = thread_store.save(peers) {
+                        error!("network: peer store: {}", err);
+                    }
+                }
+            })?;
+
+        Ok(PeerStoreThread {
+            store,
+            tx,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl SaveRestore for PeerStoreThread {
+    fn save(&self, peers: Vec<ArcPeer>) -> Result<(), NetworkError> {
+        if peers.is_empty() {
+            return Ok(());
+        }
+
+        self.tx
+            .unbounded_send(peers)
+            .map_err(|_| NetworkError::Shutdown)
+    }
+
+    fn restore(&self) -> Result<Vec<ArcPeer>, NetworkError> {
+        self.store.restore()
+    }
+}
+
+impl Drop for PeerStoreThread {
+    fn drop(&mut self) {
+        self.tx.close_channel();
+
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("network: peer store thread panicked");
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
-pub(super) struct NoPeerDatFile;
+pub(super) struct NoPeerStore;
 
-impl SaveRestore for NoPeerDatFile {
+impl SaveRestore for NoPeerStore {
     fn save(&self, _peers: Vec<ArcPeer>) -> Result<(), NetworkError> {
         Ok(())
     }
@@ -251,3 +456,123 @@ impl<'de> de::Deserialize<'de> for SerdePeerId {
         deserializer.deserialize_bytes(Visitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LegacySerdePeer, PeerStoreDir, PeerStoreThread, SaveRestore, SerdePeerId, SerdePubKey,
+    };
+    use crate::peer_manager::{ArcPeer, TrustMetric, TrustMetricConfig};
+
+    use tentacle::secio::SecioKeyPair;
+
+    use std::{fs, sync::Arc, time::Duration};
+
+    fn make_peer(trust_config: &Arc<TrustMetricConfig>, bad_intervals: usize) -> ArcPeer {
+        let keypair = SecioKeyPair::secp256k1_generated();
+        let peer = ArcPeer::from_pubkey(keypair.public_key()).expect("make peer");
+
+        let trust_metric = TrustMetric::new(Arc::clone(trust_config));
+        for _ in 0..10 {
+            trust_metric.good_events(1);
+            trust_metric.enter_new_interval();
+        }
+        for _ in 0..bad_intervals {
+            trust_metric.bad_events(1);
+            trust_metric.enter_new_interval();
+        }
+        peer.set_trust_metric(trust_metric);
+
+        peer
+    }
+
+    #[test]
+    fn should_restore_ban_and_trust_metric_ordered_by_score() {
+        let trust_config = Arc::new(TrustMetricConfig::default());
+        let worse_peer = make_peer(&trust_config, 3);
+        let good_peer = make_peer(&trust_config, 0);
+        worse_peer.ban(Duration::from_secs(60));
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("peers-{}", good_peer.id.to_base58()));
+        let store = PeerStoreDir::new(&path, Arc::clone(&trust_config));
+
+        store
+            .save(vec![worse_peer.clone()])
+            .expect("save worse peer");
+        store.save(vec![good_peer.clone()]).expect("save good peer");
+
+        let peers = store.restore().expect("restore");
+        fs::remove_dir_all(&path).expect("clean up");
+
+        assert_eq!(peers, vec![good_peer.clone(), worse_peer.clone()]);
+
+        let restored_worse_peer = &peers[1];
+        assert!(restored_worse_peer.banned(), "should keep ban");
+        assert_eq!(
+            restored_worse_peer.ban_expired_at(),
+            worse_peer.ban_expired_at()
+        );
+
+        for (restored, origin) in peers.iter().zip(vec![good_peer, worse_peer]) {
+            let restored_metric = restored.trust_metric().expect("trust metric");
+            let origin_metric = origin.trust_metric().expect("trust metric");
+
+            assert_eq!(restored_metric.snapshot(), origin_metric.snapshot());
+            assert_eq!(restored_metric.trust_score(), origin_metric.trust_score());
+        }
+    }
+
+    #[test]
+    fn should_flush_queued_peers_on_store_thread_drop() {
+        let trust_config = Arc::new(TrustMetricConfig::default());
+        let peer = make_peer(&trust_config, 0);
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("peers-{}", peer.id.to_base58()));
+        let store = Arc::new(PeerStoreDir::new(&path, Arc::clone(&trust_config)));
+
+        let store_thread = PeerStoreThread::spawn(Arc::clone(&store)).expect("spawn");
+        store_thread.save(vec![peer.clone()]).expect("queue peer");
+        drop(store_thread);
+
+        let peers = store.restore().expect("restore");
+        fs::remove_dir_all(&path).expect("clean up");
+
+        assert_eq!(peers, vec![peer]);
+    }
+
+    #[test]
+    fn should_import_legacy_peer_dat_file_once() {
+        let trust_config = Arc::new(TrustMetricConfig::default());
+        let keypair = SecioKeyPair::secp256k1_generated();
+        let peer = ArcPeer::from_pubkey(keypair.public_key()).expect("make peer");
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("peers-{}", peer.id.to_base58()));
+        let store = PeerStoreDir::new(&path, Arc::clone(&trust_config));
+
+        let legacy_peer = LegacySerdePeer {
+            id:              SerdePeerId(peer.owned_id()),
+            pubkey:          peer.owned_pubkey().map(SerdePubKey),
+            multiaddrs:      vec![],
+            connectedness:   0,
+            retry:           0,
+            next_attempt_at: 0,
+            connected_at:    1,
+            disconnected_at: 2,
+            alive:           1,
+        };
+        let data = bincode::serialize(&vec![legacy_peer]).expect("serialize");
+        let dat_path = store.legacy_dat_path();
+        fs::write(&dat_path, data).expect("write legacy dat file");
+
+        let peers = store.restore().expect("restore");
+        let dat_removed = !dat_path.exists();
+        fs::remove_dir_all(&path).expect("clean up");
+
+        assert!(dat_removed, "legacy dat file should be removed");
+        assert_eq!(peers, vec![peer]);
+        assert_eq!(peers[0].disconnected_at(), 2);
+    }
+}
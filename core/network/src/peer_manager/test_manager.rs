@@ -129,8 +129,8 @@ fn make_manager(
     let manager_pubkey = make_pubkey();
     let manager_id = manager_pubkey.peer_id();
     let bootstraps = make_bootstraps(bootstrap_num);
-    let mut peer_dat_file = std::env::temp_dir();
-    peer_dat_file.push("peer.dat");
+    let mut peer_store_dir = std::env::temp_dir();
+    peer_store_dir.push("peers");
     let peer_trust_config = Arc::new(TrustMetricConfig::default());
     let peer_fatal_ban = Duration::from_secs(50);
     let peer_soft_ban = Duration::from_secs(10);
@@ -141,12 +141,13 @@ fn make_manager(
         bootstraps,
         whitelist_by_chain_addrs: Default::default(),
         whitelist_peers_only: false,
+        private_by_chain_addrs: Default::default(),
         peer_trust_config,
         peer_fatal_ban,
         peer_soft_ban,
         max_connections,
         routine_interval: Duration::from_secs(10),
-        peer_dat_file,
+        peer_store_dir,
     };
 
     let (conn_tx, conn_rx) = unbounded();
@@ -2127,6 +2128,45 @@ async fn should_always_include_our_listen_addrs_in_return_from_manager_handle_ra
     );
 }
 
+#[tokio::test]
+async fn should_exclude_private_peers_in_return_from_manager_handle_random_addrs() {
+    let (mgr, _conn_rx) = make_manager(0, 20);
+    let self_id = mgr.inner.peer_id.to_owned();
+
+    let inner = mgr.core_inner();
+    inner.add_listen(make_peer_multiaddr(9000, self_id));
+
+    let public_peer = make_peer(9001);
+    let private_peer = make_peer(9002);
+    let private_chain_addr = private_peer
+        .owned_chain_addr()
+        .expect("private peer chain addr");
+
+    inner.add_peer(public_peer.clone());
+    inner.add_peer(private_peer.clone());
+    inner.add_private_peers_by_chain_addr(vec![private_chain_addr]);
+
+    let handle = mgr.inner.handle();
+    let addrs = handle.random_addrs(100);
+
+    assert!(
+        public_peer
+            .multiaddrs
+            .all_raw()
+            .iter()
+            .all(|ma| addrs.contains(ma)),
+        "should include public peer addresses"
+    );
+    assert!(
+        !private_peer
+            .multiaddrs
+            .all_raw()
+            .iter()
+            .any(|ma| addrs.contains(ma)),
+        "should not include private peer addresses"
+    );
+}
+
 #[tokio::test]
 async fn should_whitelist_peer_chain_addrs_on_whitelist_peers_by_chain_addrs() {
     let (mut mgr, _conn_rx) = make_manager(0, 20);
@@ -2281,8 +2321,8 @@ async fn should_never_expire_peers_from_config_whitelist() {
     let manager_pubkey = make_pubkey();
     let manager_id = manager_pubkey.peer_id();
     let bootstraps = make_bootstraps(10);
-    let mut peer_dat_file = std::env::temp_dir();
-    peer_dat_file.push("peer.dat");
+    let mut peer_store_dir = std::env::temp_dir();
+    peer_store_dir.push("peers");
     let peer_trust_config = Arc::new(TrustMetricConfig::default());
     let peer_fatal_ban = Duration::from_secs(50);
     let peer_soft_ban = Duration::from_secs(10);
@@ -2298,12 +2338,13 @@ async fn should_never_expire_peers_from_config_whitelist() {
         bootstraps,
         whitelist_by_chain_addrs: vec![test_chain_addr.clone()],
         whitelist_peers_only: false,
+        private_by_chain_addrs: Default::default(),
         peer_trust_config,
         peer_fatal_ban,
         peer_soft_ban,
         max_connections: 10,
         routine_interval: Duration::from_secs(10),
-        peer_dat_file,
+        peer_store_dir,
     };
 
     let (conn_tx, _conn_rx) = unbounded();
@@ -2335,8 +2376,8 @@ async fn should_not_refresh_never_expired_peers_from_config_whitelist() {
     let manager_pubkey = make_pubkey();
     let manager_id = manager_pubkey.peer_id();
     let bootstraps = make_bootstraps(10);
-    let mut peer_dat_file = std::env::temp_dir();
-    peer_dat_file.push("peer.dat");
+    let mut peer_store_dir = std::env::temp_dir();
+    peer_store_dir.push("peers");
     let peer_trust_config = Arc::new(TrustMetricConfig::default());
     let peer_fatal_ban = Duration::from_secs(50);
     let peer_soft_ban = Duration::from_secs(10);
@@ -2352,12 +2393,13 @@ async fn should_not_refresh_never_expired_peers_from_config_whitelist() {
         bootstraps,
         whitelist_by_chain_addrs: vec![test_chain_addr.clone()],
         whitelist_peers_only: false,
+        private_by_chain_addrs: Default::default(),
         peer_trust_config,
         peer_fatal_ban,
         peer_soft_ban,
         max_connections: 10,
         routine_interval: Duration::from_secs(10),
-        peer_dat_file,
+        peer_store_dir,
     };
 
     let (conn_tx, _conn_rx) = unbounded();
@@ -2398,8 +2440,8 @@ async fn should_not_refresh_never_expired_peers_from_config_whitelist() {
 async fn should_only_connect_peers_in_whitelist_if_whitelist_only_enabled() {
     let manager_pubkey = make_pubkey();
     let manager_id = manager_pubkey.peer_id();
-    let mut peer_dat_file = std::env::temp_dir();
-    peer_dat_file.push("peer.dat");
+    let mut peer_store_dir = std::env::temp_dir();
+    peer_store_dir.push("peers");
     let peer_trust_config = Arc::new(TrustMetricConfig::default());
     let peer_fatal_ban = Duration::from_secs(50);
     let peer_soft_ban = Duration::from_secs(10);
@@ -2417,12 +2459,13 @@ async fn should_only_connect_peers_in_whitelist_if_whitelist_only_enabled() {
         bootstraps: Default::default(),
         whitelist_by_chain_addrs: vec![test_chain_addr.clone()],
         whitelist_peers_only: true,
+        private_by_chain_addrs: Default::default(),
         peer_trust_config,
         peer_fatal_ban,
         peer_soft_ban,
         max_connections: 10,
         routine_interval: Duration::from_secs(10),
-        peer_dat_file,
+        peer_store_dir,
     };
 
     let (conn_tx, mut conn_rx) = unbounded();
@@ -2463,8 +2506,8 @@ async fn should_only_connect_peers_in_whitelist_if_whitelist_only_enabled() {
 async fn should_only_allow_incoming_peers_in_whitelist_if_whitelist_only_enabled() {
     let manager_pubkey = make_pubkey();
     let manager_id = manager_pubkey.peer_id();
-    let mut peer_dat_file = std::env::temp_dir();
-    peer_dat_file.push("peer.dat");
+    let mut peer_store_dir = std::env::temp_dir();
+    peer_store_dir.push("peers");
     let peer_trust_config = Arc::new(TrustMetricConfig::default());
     let peer_fatal_ban = Duration::from_secs(50);
     let peer_soft_ban = Duration::from_secs(10);
@@ -2482,12 +2525,13 @@ async fn should_only_allow_incoming_peers_in_whitelist_if_whitelist_only_enabled
         bootstraps: Default::default(),
         whitelist_by_chain_addrs: vec![test_chain_addr.clone()],
         whitelist_peers_only: true,
+        private_by_chain_addrs: Default::default(),
         peer_trust_config,
         peer_fatal_ban,
         peer_soft_ban,
         max_connections: 10,
         routine_interval: Duration::from_secs(10),
-        peer_dat_file,
+        peer_store_dir,
     };
 
     let (conn_tx, _conn_rx) = unbounded();
@@ -2985,3 +3029,63 @@ async fn should_update_chain_book_on_new_session() {
     let peer_id = peer_by_chain.map(|p| p.owned_id());
     assert_eq!(peer_id, Some(remote_peer_id), "should be peer in session");
 }
+
+#[tokio::test]
+async fn should_connect_best_scored_peers_first_after_restore() {
+    let (mut mgr, mut conn_rx) = make_manager(0, 1);
+    let peer_trust_config = Arc::new(TrustMetricConfig::default());
+    let remain_count = 1 + MAX_CONNECTING_MARGIN;
+
+    let make_scored_peers = |num: usize, port: u16, bad_events: usize| -> Vec<ArcPeer> {
+        (0..num)
+            .map(|n| {
+                let peer = make_peer(port + n as u16);
+                let trust_metric = TrustMetric::new(Arc::clone(&peer_trust_config));
+                for _ in 0..30 {
+                    trust_metric.good_events(1);
+                    trust_metric.bad_events(bad_events);
+                    trust_metric.enter_new_interval();
+                }
+                peer.set_trust_metric(trust_metric);
+                peer
+            })
+            .collect()
+    };
+
+    let better_peers = make_scored_peers(remain_count, 5000, 1);
+    let worse_peers = make_scored_peers(remain_count * 2, 6000, 3);
+
+    let better_score = better_peers[0]
+        .trust_metric()
+        .expect("metric")
+        .trust_score();
+    let worse_score = worse_peers[0].trust_metric().expect("metric").trust_score();
+    assert!(better_score < GOOD_TRUST_SCORE, "should not be good peer");
+    assert!(better_score > worse_score, "should have better score");
+
+    // Worse peers come first, dialing should not depend on restore order
+    let inner = mgr.core_inner();
+    inner.restore(
+        worse_peers
+            .into_iter()
+            .chain(better_peers.clone())
+            .collect(),
+    );
+    mgr.poll().await;
+
+    let conn_event = conn_rx.next().await.expect("should have connect event");
+    let multiaddrs_in_event = match conn_event {
+        ConnectionEvent::Connect { addrs, .. } => addrs,
+        _ => panic!("should be connect event"),
+    };
+
+    let better_multiaddrs = better_peers
+        .iter()
+        .map(|p| p.multiaddrs.all_raw().pop().expect("get multiaddr"))
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        multiaddrs_in_event.into_iter().collect::<HashSet<_>>(),
+        better_multiaddrs,
+        "should dial best scored peers"
+    );
+}
@@ -4,6 +4,7 @@ use futures::{
 };
 use futures_timer::Delay;
 use parking_lot::RwLock;
+use serde_derive::{Deserialize, Serialize};
 
 use std::{
     future::Future,
@@ -105,6 +106,16 @@ impl FadedMemory {
     }
 }
 
+// Persisted part of trust metric. Weights sum and aggregate trust are
+// recalculated on restore, since they only depend on intervals and memorys.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrustMetricSnapshot {
+    intervals:   u64,
+    memorys:     Vec<f64>,
+    good_events: usize,
+    bad_events:  usize,
+}
+
 #[derive(Debug)]
 struct History {
     max_intervals:   u64,
@@ -127,7 +138,35 @@ impl History {
         }
     }
 
-    #[cfg(test)]
+    fn restore(max_intervals: u64, max_memorys: u64, intervals: u64, memorys: Vec<f64>) -> History {
+        let mut history = History::new(max_intervals, max_memorys);
+
+        // Config may be changed between restarts, drop what we can't hold
+        let expect_memorys = intervals.min(max_intervals).min(max_memorys) as usize;
+        history.memorys = memorys
+            .into_iter()
+            .take(expect_memorys)
+            .map(FadedMemory::new)
+            .collect();
+        history.intervals = if history.memorys.len() < expect_memorys {
+            history.memorys.len() as u64
+        } else {
+            intervals.min(max_intervals)
+        };
+
+        history.weights_sum = (1..=history.intervals)
+            .map(
+                |i| match HISTORY_TRUST_WEIGHTS.read().get(i as usize - 1).cloned() {
+                    Some(v) => v,
+                    None => OPTIMISTIC_HISTORY_WEIGHT.powf((i - 1) as f64),
+                },
+            )
+            .sum();
+        history.update_aggregate_trust();
+
+        history
+    }
+
     fn intervals(&self) -> u64 {
         self.intervals
     }
@@ -262,6 +301,18 @@ impl Inner {
         self.bad_events.store(0, SeqCst);
     }
 
+    pub fn snapshot(&self) -> TrustMetricSnapshot {
+        let (good_events, bad_events) = self.events();
+        let history = self.history.read();
+
+        TrustMetricSnapshot {
+            intervals: history.intervals(),
+            memorys: history.memorys.iter().map(|m| **m).collect(),
+            good_events,
+            bad_events,
+        }
+    }
+
     pub fn reset_history(&self) {
         let max_intervals = self.config.max_intervals;
         let max_memorys = self.config.max_faded_memorys;
@@ -383,6 +434,29 @@ impl TrustMetric {
         }
     }
 
+    pub fn restore(config: Arc<TrustMetricConfig>, snapshot: TrustMetricSnapshot) -> Self {
+        let history = History::restore(
+            config.max_intervals,
+            config.max_faded_memorys,
+            snapshot.intervals,
+            snapshot.memorys,
+        );
+        let good_events = snapshot.good_events.min(GOOD_INTERVAL_CAP);
+
+        let inner = Inner {
+            config,
+            history: RwLock::new(history),
+            good_events: AtomicUsize::new(good_events),
+            bad_events: AtomicUsize::new(snapshot.bad_events),
+        };
+
+        TrustMetric {
+            inner:     Arc::new(inner),
+            hb_handle: Arc::new(RwLock::new(None)),
+            pause:     Arc::new(RwLock::new(None)),
+        }
+    }
+
     pub fn start(&self) {
         if self.hb_handle.read().is_some() {
             // Already started
@@ -440,7 +514,7 @@ impl Deref for TrustMetric {
 
 #[cfg(test)]
 mod tests {
-    use super::{Inner, TrustMetricConfig, GOOD_INTERVAL_CAP};
+    use super::{Inner, TrustMetric, TrustMetricConfig, GOOD_INTERVAL_CAP};
 
     use std::sync::{atomic::Ordering::SeqCst, Arc};
 
@@ -509,4 +583,29 @@ mod tests {
         metric.good_events(20);
         assert_eq!(metric.good_events.load(SeqCst), GOOD_INTERVAL_CAP);
     }
+
+    #[test]
+    fn snapshot_restore_test() {
+        let config = Arc::new(TrustMetricConfig::default());
+        let metric = TrustMetric::new(Arc::clone(&config));
+
+        for _ in 0..10 {
+            metric.good_events(1);
+            metric.enter_new_interval();
+        }
+        for _ in 0..3 {
+            metric.bad_events(1);
+            metric.enter_new_interval();
+        }
+        metric.good_events(2);
+        metric.bad_events(1);
+
+        let snapshot = metric.snapshot();
+        let restored = TrustMetric::restore(config, snapshot.clone());
+
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(restored.intervals(), 13);
+        assert_eq!(restored.trust_score(), metric.trust_score());
+        assert!(!restored.is_started());
+    }
 }
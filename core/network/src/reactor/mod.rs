@@ -116,6 +116,18 @@ where
                         return Ok(());
                     }
 
+                    // Stream response, chunks are forwarded until caller is done
+                    if rpc_map.is_stream(sid, rpc_id) {
+                        let chunk_tx = rpc_map.stream_sender::<RpcResponse>(sid, rpc_id)?;
+                        if chunk_tx.unbounded_send(content).is_err() {
+                            let end = rpc_endpoint.endpoint().full_url();
+
+                            warn!("network: reactor: {} rpc stream dropped on {}", sid, end);
+                        }
+
+                        return Ok::<(), ProtocolError>(());
+                    }
+
                     let resp_tx =
                         rpc_map.take::<RpcResponse>(sid, rpc_endpoint.rpc_id().value())?;
                     if resp_tx.send(content).is_err() {
@@ -0,0 +1,357 @@
+use std::collections::{HashSet, VecDeque};
+
+use async_trait::async_trait;
+use futures::channel::mpsc::UnboundedSender;
+use log::{debug, warn};
+use parking_lot::Mutex;
+use protocol::{
+    traits::{Context, MessageHandler, Priority, TrustFeedback},
+    types::{Address, Hash},
+    Bytes,
+};
+use serde_derive::{Deserialize, Serialize};
+use tentacle::service::TargetSession;
+
+use crate::{
+    message::RawSessionMessage,
+    outbound::NetworkGossip,
+    traits::{Compression, MessageSender, NetworkContext, SessionBook},
+};
+
+pub const END_GOSSIP_RELAY: &str = "/gossip/network/relay";
+
+// Max number of relayed messages we remember, used to break relay loop
+const MAX_SEEN_RELAY: usize = 4096;
+
+// Message for users which we don't connect to directly. It's flooded to
+// connected peers until one of them, for example a sentry node, has a
+// session to target user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelayMessage {
+    pub targets: Vec<Address>,
+    pub hops:    u8,
+    // Compressed network message, same as we send to target directly
+    pub msg:     Vec<u8>,
+}
+
+#[derive(Default)]
+struct SeenRelay {
+    set:   HashSet<Hash>,
+    queue: VecDeque<Hash>,
+}
+
+impl SeenRelay {
+    // Return false if already seen
+    fn insert(&mut self, hash: Hash) -> bool {
+        if self.set.contains(&hash) {
+            return false;
+        }
+
+        if self.queue.len() >= MAX_SEEN_RELAY {
+            if let Some(oldest) = self.queue.pop_front() {
+                self.set.remove(&oldest);
+            }
+        }
+
+        self.set.insert(hash.clone());
+        self.queue.push_back(hash);
+        true
+    }
+}
+
+pub struct RelayHandler<S, C, B> {
+    our_chain_addr: Option<Address>,
+    gossip:         NetworkGossip<S, C>,
+    sessions:       B,
+    raw_msg_tx:     UnboundedSender<RawSessionMessage>,
+    seen:           Mutex<SeenRelay>,
+}
+
+impl<S, C, B> RelayHandler<S, C, B>
+where
+    S: MessageSender + Sync + Send + Clone,
+    C: Compression + Sync + Send + Clone,
+    B: SessionBook + Sync + Send,
+{
+    pub fn new(
+        our_chain_addr: Option<Address>,
+        gossip: NetworkGossip<S, C>,
+        sessions: B,
+        raw_msg_tx: UnboundedSender<RawSessionMessage>,
+    ) -> Self {
+        RelayHandler {
+            our_chain_addr,
+            gossip,
+            sessions,
+            raw_msg_tx,
+            seen: Mutex::new(SeenRelay::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl<S, C, B> MessageHandler for RelayHandler<S, C, B>
+where
+    S: MessageSender + Sync + Send + Clone + 'static,
+    C: Compression + Sync + Send + Clone + 'static,
+    B: SessionBook + Sync + Send + 'static,
+{
+    type Message = RelayMessage;
+
+    async fn process(&self, ctx: Context, relay: Self::Message) -> TrustFeedback {
+        let (sid, pid) = match (ctx.session_id(), ctx.remote_peer_id()) {
+            (Ok(sid), Ok(pid)) => (sid, pid),
+            _ => return TrustFeedback::Neutral,
+        };
+
+        let msg = Bytes::from(relay.msg);
+        if !self.seen.lock().insert(Hash::digest(msg.clone())) {
+            return TrustFeedback::Neutral;
+        }
+
+        let mut targets = relay.targets;
+        if let Some(our_addr) = self.our_chain_addr.as_ref() {
+            if targets.contains(our_addr) {
+                targets.retain(|t| t != our_addr);
+
+                // Treat it as a message from relayer
+                let raw_msg = RawSessionMessage::new(sid, pid, msg.clone());
+                if self.raw_msg_tx.unbounded_send(raw_msg).is_err() {
+                    warn!("network: relay: message router offline");
+                }
+            }
+        }
+
+        if targets.is_empty() {
+            return TrustFeedback::Neutral;
+        }
+
+        let (connected, unconnected) = self.sessions.by_chain(targets);
+        let connected = connected
+            .into_iter()
+            .filter(|s| *s != sid)
+            .collect::<Vec<_>>();
+        if !connected.is_empty() {
+            let tar = TargetSession::Multi(connected);
+            if let Err(e) = self
+                .gossip
+                .send(ctx.clone(), tar, msg.clone(), Priority::High)
+            {
+                debug!("network: relay: forward to connected targets {}", e);
+            }
+        }
+
+        if !unconnected.is_empty() && relay.hops > 1 {
+            let others = self
+                .sessions
+                .all_sendable()
+                .into_iter()
+                .filter(|s| *s != sid)
+                .collect::<Vec<_>>();
+
+            if others.is_empty() {
+                return TrustFeedback::Neutral;
+            }
+
+            let tar = TargetSession::Multi(others);
+            let hops = relay.hops - 1;
+            if let Err(e) = self.gossip.relay(ctx, tar, unconnected, msg, hops).await {
+                debug!("network: relay: forward relay message {}", e);
+            }
+        }
+
+        TrustFeedback::Neutral
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures::{
+        channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        stream::StreamExt,
+    };
+    use futures_timer::Delay;
+    use protocol::{
+        traits::{Context, Gossip, MessageHandler, Priority, TrustFeedback},
+        types::{Address, Hash},
+        Bytes,
+    };
+    use tentacle::{
+        multiaddr::Multiaddr,
+        secio::{PeerId, SecioKeyPair},
+        service::SessionType,
+        SessionId,
+    };
+    use tentacle_discovery::AddressManager;
+
+    use super::{SeenRelay, MAX_SEEN_RELAY};
+    use crate::{
+        event::PeerManagerEvent,
+        peer_manager::{DiscoveryAddrManager, PeerManager, PeerManagerConfig},
+        test::mock::SessionContext,
+        traits::MultiaddrExt,
+        MemoryNetwork, NetworkConfig, NetworkService, NetworkServiceHandle,
+    };
+
+    const END_GOSSIP: &str = "/gossip/relay/news";
+
+    struct NewsReader(UnboundedSender<String>);
+
+    #[async_trait]
+    impl MessageHandler for NewsReader {
+        type Message = String;
+
+        async fn process(&self, _ctx: Context, msg: Self::Message) -> TrustFeedback {
+            self.0.unbounded_send(msg).expect("news reader");
+            TrustFeedback::Good
+        }
+    }
+
+    fn config(seed: u8) -> NetworkConfig {
+        NetworkConfig::new()
+            .secio_keypair(hex::encode(vec![seed; 32]))
+            .expect("keypair")
+            .relay_max_hops(Some(2))
+    }
+
+    fn chain_addr(seed: u8) -> Address {
+        let pubkey = SecioKeyPair::secp256k1_raw_key(vec![seed; 32])
+            .expect("keypair")
+            .public_key();
+
+        Address::from_pubkey_bytes(Bytes::from(pubkey.inner())).expect("address")
+    }
+
+    fn node(
+        network: &MemoryNetwork,
+        seed: u8,
+    ) -> (NetworkServiceHandle, UnboundedReceiver<String>) {
+        let (news_tx, news_rx) = unbounded();
+        let mut service = NetworkService::in_memory(config(seed), network).expect("memory service");
+        let handle = service.handle();
+
+        service
+            .register_endpoint_handler(END_GOSSIP, Box::new(NewsReader(news_tx)))
+            .expect("register news reader");
+
+        tokio::spawn(service);
+        (handle, news_rx)
+    }
+
+    #[tokio::test]
+    async fn should_relay_users_cast_through_sentry_and_hide_validator() {
+        const VALIDATOR: u8 = 1;
+        const SENTRY: u8 = 2;
+        const PEER: u8 = 3;
+
+        let network = MemoryNetwork::new(0);
+        let (_validator, mut validator_rx) = node(&network, VALIDATOR);
+        let (_sentry, _) = node(&network, SENTRY);
+        let (peer, _) = node(&network, PEER);
+
+        // Validator only talks to its sentry
+        network.partition(&[chain_addr(VALIDATOR)], &[chain_addr(PEER)]);
+
+        peer.users_cast(
+            Context::new(),
+            END_GOSSIP,
+            vec![chain_addr(VALIDATOR)],
+            "proposal".to_owned(),
+            Priority::High,
+        )
+        .await
+        .expect("users cast");
+        assert_eq!(validator_rx.next().await, Some("proposal".to_owned()));
+
+        // Sentry knows both of them, but only shares peer through discovery
+        let validator_hex = chain_addr(VALIDATOR).as_hex();
+        let sentry_config = config(SENTRY)
+            .private_peers(vec![validator_hex])
+            .expect("private peers");
+
+        let (conn_tx, _conn_rx) = unbounded();
+        let (mgr_tx, mgr_rx) = unbounded();
+        let sentry_mgr = PeerManager::new(PeerManagerConfig::from(&sentry_config), mgr_rx, conn_tx);
+        let sentry_handle = sentry_mgr.handle();
+        tokio::spawn(sentry_mgr);
+
+        let listen = multiaddr(9000 + SENTRY as u16, None);
+        mgr_tx
+            .unbounded_send(PeerManagerEvent::AddNewListenAddr { addr: listen })
+            .expect("add listen");
+
+        let mut session_addrs = Vec::new();
+        for seed in [VALIDATOR, PEER].iter() {
+            let pubkey = SecioKeyPair::secp256k1_raw_key(vec![*seed; 32])
+                .expect("keypair")
+                .public_key();
+            let pid = pubkey.peer_id();
+            let addr = multiaddr(9000 + *seed as u16, Some(pid.clone()));
+
+            let ctx = SessionContext::make(
+                SessionId::new(*seed as usize),
+                addr.clone(),
+                SessionType::Outbound,
+                pubkey.clone(),
+            );
+            mgr_tx
+                .unbounded_send(PeerManagerEvent::NewSession {
+                    pid,
+                    pubkey,
+                    ctx: ctx.arced(),
+                })
+                .expect("new session");
+            session_addrs.push(addr);
+        }
+        Delay::new(Duration::from_millis(100)).await;
+
+        let mut disc = DiscoveryAddrManager::new(sentry_handle, mgr_tx);
+        let shared = disc.get_random(100);
+        assert!(
+            shared.contains(&session_addrs[1]),
+            "should share peer address"
+        );
+        assert!(
+            !shared.contains(&session_addrs[0]),
+            "should never share validator address"
+        );
+    }
+
+    fn multiaddr(port: u16, id: Option<PeerId>) -> Multiaddr {
+        let mut multiaddr = format!("/ip4/127.0.0.1/tcp/{}", port)
+            .parse::<Multiaddr>()
+            .expect("multiaddr");
+
+        if let Some(id) = id {
+            multiaddr.push_id(id);
+        }
+
+        multiaddr
+    }
+
+    #[test]
+    fn should_reject_seen_relay() {
+        let mut seen = SeenRelay::default();
+        let hash = Hash::digest(Bytes::from_static(b"spike lee"));
+
+        assert!(seen.insert(hash.clone()));
+        assert!(!seen.insert(hash));
+    }
+
+    #[test]
+    fn should_forget_oldest_seen_relay() {
+        let mut seen = SeenRelay::default();
+        let first = Hash::digest(Bytes::from(0usize.to_be_bytes().to_vec()));
+
+        for i in 0..=MAX_SEEN_RELAY {
+            let hash = Hash::digest(Bytes::from(i.to_be_bytes().to_vec()));
+            assert!(seen.insert(hash));
+        }
+
+        assert_eq!(seen.queue.len(), MAX_SEEN_RELAY);
+        assert!(seen.insert(first), "first one should be forgotten");
+    }
+}
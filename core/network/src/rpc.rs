@@ -19,8 +19,28 @@ pub struct RpcErrorMessage {
 
 impl std::error::Error for RpcErrorMessage {}
 
+// Acknowledge received chunks on a stream response, see `RpcStreamAck`
+pub const RPC_STREAM_ACK: &str = "/rpc_call/network/stream_ack";
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum RpcResponse {
     Success(Bytes),
     Error(RpcErrorMessage),
+    Chunk(RpcChunk),
+}
+
+// One of ordered chunks of a stream response, all share same rpc id.
+// Each item is an encoded message.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RpcChunk {
+    pub seq:   u64,
+    pub items: Vec<Bytes>,
+    pub last:  bool,
+}
+
+// Sent by caller after chunk `seq` is received, responder doesn't send
+// more than window size chunks ahead of last acked one.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RpcStreamAck {
+    pub seq: u64,
 }
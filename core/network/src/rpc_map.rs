@@ -6,7 +6,10 @@ use std::{
 };
 
 use derive_more::Constructor;
-use futures::channel::oneshot::{self, Receiver, Sender};
+use futures::channel::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    oneshot::{self, Receiver, Sender},
+};
 use parking_lot::RwLock;
 use tentacle::SessionId;
 
@@ -18,7 +21,11 @@ struct Key {
     rid: u64,
 }
 
-struct BackSender(Box<Arc<dyn Any + Send + Sync + 'static>>);
+enum BackSender {
+    Once(Box<Arc<dyn Any + Send + Sync + 'static>>),
+    // Stream response, keep sender until rpc is done
+    Stream(Box<dyn Any + Send + Sync + 'static>),
+}
 
 #[derive(Default)]
 pub struct RpcMap {
@@ -42,18 +49,64 @@ impl RpcMap {
         let key = Key::new(sid, rid);
 
         let (done_tx, done_rx) = oneshot::channel();
-        let sender = BackSender(Box::new(Arc::new(done_tx)));
+        let sender = BackSender::Once(Box::new(Arc::new(done_tx)));
 
         self.map.write().insert(key, sender);
 
         done_rx
     }
 
+    pub fn insert_stream<T: Send + 'static>(
+        &self,
+        sid: SessionId,
+        rid: u64,
+    ) -> UnboundedReceiver<T> {
+        let key = Key::new(sid, rid);
+
+        let (chunk_tx, chunk_rx) = mpsc::unbounded();
+        let sender = BackSender::Stream(Box::new(chunk_tx));
+
+        self.map.write().insert(key, sender);
+
+        chunk_rx
+    }
+
     pub fn contains(&self, sid: SessionId, rid: u64) -> bool {
         let key = Key::new(sid, rid);
         self.map.read().contains_key(&key)
     }
 
+    pub fn is_stream(&self, sid: SessionId, rid: u64) -> bool {
+        let key = Key::new(sid, rid);
+
+        match self.map.read().get(&key) {
+            Some(BackSender::Stream(_)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn remove(&self, sid: SessionId, rid: u64) {
+        let key = Key::new(sid, rid);
+        self.map.write().remove(&key);
+    }
+
+    pub fn stream_sender<T: Send + 'static>(
+        &self,
+        sid: SessionId,
+        rid: u64,
+    ) -> Result<UnboundedSender<T>, NetworkError> {
+        let key = Key::new(sid, rid);
+
+        match self.map.read().get(&key) {
+            Some(BackSender::Stream(boxed_any)) => boxed_any
+                .downcast_ref::<UnboundedSender<T>>()
+                .cloned()
+                .ok_or_else(|| ErrorKind::UnexpectedRpcSender.into()),
+            Some(BackSender::Once(_)) => Err(ErrorKind::UnexpectedRpcSender.into()),
+            None => Err(ErrorKind::UnknownRpc { sid, rid }.into()),
+        }
+    }
+
     pub fn take<T: Send + 'static>(
         &self,
         sid: SessionId,
@@ -65,9 +118,17 @@ impl RpcMap {
             return Err(ErrorKind::UnknownRpc { sid, rid }.into());
         }
 
-        let BackSender(boxed_any) = {
-            let opt_sender = self.map.write().remove(&key);
-            opt_sender.ok_or_else(|| ErrorKind::UnknownRpc { sid, rid })?
+        let boxed_any = {
+            let mut map = self.map.write();
+            match map.remove(&key) {
+                Some(BackSender::Once(boxed_any)) => boxed_any,
+                Some(stream) => {
+                    // Put it back, stream sender is removed by its caller
+                    map.insert(key, stream);
+                    return Err(ErrorKind::UnexpectedRpcSender.into());
+                }
+                None => return Err(ErrorKind::UnknownRpc { sid, rid }.into()),
+            }
         };
 
         let arc_sender: Arc<Sender<T>> = boxed_any
@@ -19,13 +19,14 @@ use protocol::{
         Context, Gossip, MessageCodec, MessageHandler, PeerTrust, Priority, Rpc, TrustFeedback,
     },
     types::Address,
-    ProtocolResult,
+    Bytes, ProtocolResult,
 };
+use tentacle::{secio::PeerId, service::TargetSession, SessionId};
 
 #[cfg(feature = "diagnostic")]
 use crate::peer_manager::diagnostic::Diagnostic;
 use crate::{
-    common::{socket_to_multi_addr, HeartBeat},
+    common::{socket_to_multi_addr, ConnectedAddr, HeartBeat},
     compression::Snappy,
     connection::{
         ConnectionConfig, ConnectionService, ConnectionServiceControl, ConnectionServiceKeeper,
@@ -33,6 +34,7 @@ use crate::{
     endpoint::{Endpoint, EndpointScheme},
     error::NetworkError,
     event::{ConnectionEvent, PeerManagerEvent},
+    memory::{MemoryNetwork, MemoryTransport, TrustRecorder},
     message::RawSessionMessage,
     metrics::Metrics,
     outbound::{NetworkGossip, NetworkRpc},
@@ -41,16 +43,101 @@ use crate::{
     },
     protocols::CoreProtocol,
     reactor::{MessageRouter, Reactor},
+    relay::{RelayHandler, RelayMessage, END_GOSSIP_RELAY},
+    rpc::{RpcStreamAck, RPC_STREAM_ACK},
     rpc_map::RpcMap,
     selfcheck::SelfCheck,
-    traits::NetworkContext,
+    traits::{MessageSender, NetworkContext, SessionBook},
     NetworkConfig,
 };
 
+// Messages are sent through either tentacle connections or in-process
+// memory transport, both share the same gossip, rpc and reactor code.
+#[derive(Clone)]
+pub(crate) enum Transport {
+    Tentacle(ConnectionServiceControl<CoreProtocol, SharedSessions>),
+    Memory(MemoryTransport),
+}
+
+#[async_trait]
+impl MessageSender for Transport {
+    fn send(&self, tar: TargetSession, msg: Bytes, pri: Priority) -> Result<(), NetworkError> {
+        match self {
+            Transport::Tentacle(ctrl) => ctrl.send(tar, msg, pri),
+            Transport::Memory(mem) => mem.send(tar, msg, pri),
+        }
+    }
+
+    async fn users_send(
+        &self,
+        users: Vec<Address>,
+        msg: Bytes,
+        pri: Priority,
+    ) -> Result<(), NetworkError> {
+        match self {
+            Transport::Tentacle(ctrl) => ctrl.users_send(users, msg, pri).await,
+            Transport::Memory(mem) => mem.users_send(users, msg, pri).await,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum Sessions {
+    Shared(SharedSessions),
+    Memory(MemoryTransport),
+}
+
+macro_rules! sessions_call {
+    ($sessions:expr, $method:ident $(, $arg:expr)*) => {
+        match $sessions {
+            Sessions::Shared(shared) => shared.$method($($arg),*),
+            Sessions::Memory(mem) => mem.$method($($arg),*),
+        }
+    };
+}
+
+impl SessionBook for Sessions {
+    fn all_sendable(&self) -> Vec<SessionId> {
+        sessions_call!(self, all_sendable)
+    }
+
+    fn all_blocked(&self) -> Vec<SessionId> {
+        sessions_call!(self, all_blocked)
+    }
+
+    fn refresh_blocked(&self) {
+        sessions_call!(self, refresh_blocked)
+    }
+
+    fn by_chain(&self, addrs: Vec<Address>) -> (Vec<SessionId>, Vec<Address>) {
+        sessions_call!(self, by_chain, addrs)
+    }
+
+    fn peers_by_chain(&self, addrs: Vec<Address>) -> (Vec<PeerId>, Vec<Address>) {
+        sessions_call!(self, peers_by_chain, addrs)
+    }
+
+    fn all(&self) -> Vec<SessionId> {
+        sessions_call!(self, all)
+    }
+
+    fn connected_addr(&self, sid: SessionId) -> Option<ConnectedAddr> {
+        sessions_call!(self, connected_addr, sid)
+    }
+
+    fn pending_data_size(&self, sid: SessionId) -> usize {
+        sessions_call!(self, pending_data_size, sid)
+    }
+
+    fn whitelist(&self) -> Vec<Address> {
+        sessions_call!(self, whitelist)
+    }
+}
+
 #[derive(Clone)]
 pub struct NetworkServiceHandle {
-    gossip:     NetworkGossip<ConnectionServiceControl<CoreProtocol, SharedSessions>, Snappy>,
-    rpc:        NetworkRpc<ConnectionServiceControl<CoreProtocol, SharedSessions>, Snappy>,
+    gossip:     NetworkGossip<Transport, Snappy>,
+    rpc:        NetworkRpc<Transport, Snappy>,
     peer_trust: UnboundedSender<PeerManagerEvent>,
 
     #[cfg(feature = "diagnostic")]
@@ -103,6 +190,33 @@ impl Rpc for NetworkServiceHandle {
     {
         self.rpc.response(cx, end, msg, p).await
     }
+
+    async fn call_stream<M, R>(
+        &self,
+        cx: Context,
+        end: &str,
+        msg: M,
+        p: Priority,
+    ) -> ProtocolResult<Vec<R>>
+    where
+        M: MessageCodec,
+        R: MessageCodec,
+    {
+        self.rpc.call_stream(cx, end, msg, p).await
+    }
+
+    async fn response_stream<M>(
+        &self,
+        cx: Context,
+        end: &str,
+        msgs: ProtocolResult<Vec<M>>,
+        p: Priority,
+    ) -> ProtocolResult<()>
+    where
+        M: MessageCodec,
+    {
+        self.rpc.response_stream(cx, end, msgs, p).await
+    }
 }
 
 impl PeerTrust for NetworkServiceHandle {
@@ -145,17 +259,23 @@ pub struct NetworkService {
     hb_waker:   Arc<AtomicWaker>,
 
     // Config backup
-    config: NetworkConfig,
+    config:    NetworkConfig,
+    in_memory: bool,
 
     // Public service components
-    gossip:  NetworkGossip<ConnectionServiceControl<CoreProtocol, SharedSessions>, Snappy>,
-    rpc:     NetworkRpc<ConnectionServiceControl<CoreProtocol, SharedSessions>, Snappy>,
+    gossip:  NetworkGossip<Transport, Snappy>,
+    rpc:     NetworkRpc<Transport, Snappy>,
     rpc_map: Arc<RpcMap>,
 
     // Core service
     net_conn_srv: Option<NetworkConnectionService>,
     peer_mgr:     Option<PeerManager>,
-    router:       Option<MessageRouter<Snappy, SharedSessions>>,
+    router:       Option<MessageRouter<Snappy, Sessions>>,
+    relay:        Option<Reactor<RelayMessage>>,
+    stream_ack:   Option<Reactor<RpcStreamAck>>,
+
+    // Memory transport only, record trust feedback on memory network
+    trust_recorder: Option<TrustRecorder>,
 
     // Metrics
     metrics: Option<Metrics<SharedSessions>>,
@@ -189,7 +309,12 @@ impl NetworkService {
         let diagnostic = peer_mgr.diagnostic();
 
         if config.enable_save_restore {
-            peer_mgr.enable_save_restore();
+            if let Err(err) = peer_mgr.enable_save_restore() {
+                error!(
+                    "network: peer manager: enable save restore failure: {}",
+                    err
+                );
+            }
         }
 
         if let Err(err) = peer_mgr.restore_peers() {
@@ -218,16 +343,28 @@ impl NetworkService {
 
         // Build public service components
         let rpc_map = Arc::new(RpcMap::new());
-        let gossip = NetworkGossip::new(conn_ctrl.clone(), Snappy);
+        let transport = Transport::Tentacle(conn_ctrl);
+        let gossip = NetworkGossip::new(transport.clone(), Snappy, config.relay_max_hops);
         let rpc_map_clone = Arc::clone(&rpc_map);
-        let rpc = NetworkRpc::new(conn_ctrl, Snappy, rpc_map_clone, (&config).into());
-        let router = MessageRouter::new(
-            raw_msg_rx,
-            mgr_tx.clone(),
+        let rpc = NetworkRpc::new(
+            transport,
             Snappy,
-            session_book.clone(),
-            sys_tx,
+            rpc_map_clone,
+            (&config).into(),
+            (&config).into(),
         );
+        let sessions = Sessions::Shared(session_book.clone());
+        let mut router =
+            MessageRouter::new(raw_msg_rx, mgr_tx.clone(), Snappy, sessions.clone(), sys_tx);
+        let relay = Self::relay_reactor(
+            &config,
+            gossip.clone(),
+            sessions,
+            raw_msg_tx.clone(),
+            &mut router,
+            Arc::clone(&rpc_map),
+        );
+        let stream_ack = Self::stream_ack_reactor(&rpc, &mut router, Arc::clone(&rpc_map));
 
         // Build metrics service
         let metrics = Metrics::new(session_book.clone());
@@ -245,6 +382,7 @@ impl NetworkService {
             heart_beat: Some(heart_beat),
 
             config,
+            in_memory: false,
 
             gossip,
             rpc,
@@ -253,6 +391,10 @@ impl NetworkService {
             net_conn_srv: Some(NetworkConnectionService::NoListen(conn_srv)),
             peer_mgr: Some(peer_mgr),
             router: Some(router),
+            relay: Some(relay),
+            stream_ack: Some(stream_ack),
+
+            trust_recorder: None,
 
             metrics: Some(metrics),
 
@@ -263,6 +405,126 @@ impl NetworkService {
         }
     }
 
+    /// Build a network service on top of in-process memory network instead
+    /// of tentacle. There's no connection service and peer manager, every
+    /// node joined the same memory network is connected to each other.
+    pub fn in_memory(config: NetworkConfig, network: &MemoryNetwork) -> ProtocolResult<Self> {
+        let (mgr_tx, mgr_rx) = unbounded();
+        let (conn_tx, _) = unbounded();
+        let (raw_msg_tx, raw_msg_rx) = unbounded();
+        let (sys_tx, sys_rx) = unbounded();
+
+        let hb_waker = Arc::new(AtomicWaker::new());
+        let heart_beat = HeartBeat::new(Arc::clone(&hb_waker), config.heart_beat_interval);
+
+        let pubkey = config.secio_keypair.public_key();
+        let mem_transport = network.join(&pubkey, raw_msg_tx.clone())?;
+        let trust_recorder = mem_transport.trust_recorder(mgr_rx);
+
+        #[cfg(feature = "diagnostic")]
+        let diagnostic = {
+            let mgr_config = PeerManagerConfig::from(&config);
+            let (_, dummy_rx) = unbounded();
+
+            PeerManager::new(mgr_config, dummy_rx, conn_tx.clone()).diagnostic()
+        };
+
+        // Build public service components
+        let rpc_map = Arc::new(RpcMap::new());
+        let transport = Transport::Memory(mem_transport.clone());
+        let gossip = NetworkGossip::new(transport.clone(), Snappy, config.relay_max_hops);
+        let rpc_map_clone = Arc::clone(&rpc_map);
+        let rpc = NetworkRpc::new(
+            transport,
+            Snappy,
+            rpc_map_clone,
+            (&config).into(),
+            (&config).into(),
+        );
+        let sessions = Sessions::Memory(mem_transport);
+        let mut router =
+            MessageRouter::new(raw_msg_rx, mgr_tx.clone(), Snappy, sessions.clone(), sys_tx);
+        let relay = Self::relay_reactor(
+            &config,
+            gossip.clone(),
+            sessions,
+            raw_msg_tx.clone(),
+            &mut router,
+            Arc::clone(&rpc_map),
+        );
+        let stream_ack = Self::stream_ack_reactor(&rpc, &mut router, Arc::clone(&rpc_map));
+
+        Ok(NetworkService {
+            sys_rx,
+            conn_tx,
+            mgr_tx,
+            raw_msg_tx,
+            hb_waker,
+
+            heart_beat: Some(heart_beat),
+
+            config,
+            in_memory: true,
+
+            gossip,
+            rpc,
+            rpc_map,
+
+            net_conn_srv: None,
+            peer_mgr: None,
+            router: Some(router),
+            relay: Some(relay),
+            stream_ack: Some(stream_ack),
+
+            trust_recorder: Some(trust_recorder),
+
+            metrics: None,
+
+            selfcheck: None,
+
+            #[cfg(feature = "diagnostic")]
+            diagnostic,
+        })
+    }
+
+    // Relay reactor is spawned along with other services, because we may
+    // not be inside runtime yet.
+    fn relay_reactor(
+        config: &NetworkConfig,
+        gossip: NetworkGossip<Transport, Snappy>,
+        sessions: Sessions,
+        raw_msg_tx: UnboundedSender<RawSessionMessage>,
+        router: &mut MessageRouter<Snappy, Sessions>,
+        rpc_map: Arc<RpcMap>,
+    ) -> Reactor<RelayMessage> {
+        let pubkey = config.secio_keypair.public_key();
+        let our_chain_addr = Address::from_pubkey_bytes(Bytes::from(pubkey.inner())).ok();
+        let handler = RelayHandler::new(our_chain_addr, gossip, sessions, raw_msg_tx);
+
+        let endpoint = END_GOSSIP_RELAY
+            .parse::<Endpoint>()
+            .expect("impossible, relay endpoint is valid");
+        let (msg_tx, msg_rx) = unbounded();
+        router.register_reactor(endpoint, msg_tx);
+
+        Reactor::new(msg_rx, Box::new(handler), rpc_map)
+    }
+
+    // Same as relay reactor, acks for our rpc stream responses
+    fn stream_ack_reactor(
+        rpc: &NetworkRpc<Transport, Snappy>,
+        router: &mut MessageRouter<Snappy, Sessions>,
+        rpc_map: Arc<RpcMap>,
+    ) -> Reactor<RpcStreamAck> {
+        let endpoint = RPC_STREAM_ACK
+            .parse::<Endpoint>()
+            .expect("impossible, stream ack endpoint is valid");
+        let (msg_tx, msg_rx) = unbounded();
+        router.register_reactor(endpoint, msg_tx);
+
+        Reactor::new(msg_rx, Box::new(rpc.stream_ack_handler()), rpc_map)
+    }
+
     pub fn register_endpoint_handler<M>(
         &mut self,
         end: &str,
@@ -393,6 +655,18 @@ impl Future for NetworkService {
             tokio::spawn(router);
         }
 
+        if let Some(relay) = self.relay.take() {
+            tokio::spawn(relay);
+        }
+
+        if let Some(stream_ack) = self.stream_ack.take() {
+            tokio::spawn(stream_ack);
+        }
+
+        if let Some(trust_recorder) = self.trust_recorder.take() {
+            tokio::spawn(trust_recorder);
+        }
+
         if let Some(metrics) = self.metrics.take() {
             tokio::spawn(metrics);
         }
@@ -414,7 +688,7 @@ impl Future for NetworkService {
         // let it go, let it go
         // you'll never see me cry
         // bla bla bal ~~~
-        if self.conn_tx.is_closed() {
+        if self.conn_tx.is_closed() && !self.in_memory {
             info!("network: connection service closed");
         }
 
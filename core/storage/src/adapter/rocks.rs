@@ -36,6 +36,7 @@ impl RocksAdapter {
             map_category(StorageCategory::SignedTransaction),
             map_category(StorageCategory::Wal),
             map_category(StorageCategory::HashHeight),
+            map_category(StorageCategory::Evidence),
         ];
 
         let db = DB::open_cf(&opts, path, categories.iter()).map_err(RocksAdapterError::from)?;
@@ -247,6 +248,7 @@ const C_SIGNED_TRANSACTIONS: &str = "c2";
 const C_RECEIPTS: &str = "c3";
 const C_WALS: &str = "c4";
 const C_HASH_HEIGHT_MAP: &str = "c5";
+const C_EVIDENCES: &str = "c6";
 
 fn map_category(c: StorageCategory) -> &'static str {
     match c {
@@ -255,6 +257,7 @@ fn map_category(c: StorageCategory) -> &'static str {
         StorageCategory::SignedTransaction => C_SIGNED_TRANSACTIONS,
         StorageCategory::Wal => C_WALS,
         StorageCategory::HashHeight => C_HASH_HEIGHT_MAP,
+        StorageCategory::Evidence => C_EVIDENCES,
     }
 }
 
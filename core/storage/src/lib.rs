@@ -24,7 +24,7 @@ use protocol::codec::ProtocolCodecSync;
 use protocol::traits::{
     Context, Storage, StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema,
 };
-use protocol::types::{Block, Hash, Proof, Receipt, SignedTransaction};
+use protocol::types::{Block, Evidence, Hash, Proof, Receipt, SignedTransaction};
 use protocol::Bytes;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
@@ -256,6 +256,7 @@ impl_storage_schema_for!(HashHeightSchema, Hash, u64, HashHeight);
 impl_storage_schema_for!(LatestBlockSchema, Hash, Block, Block);
 impl_storage_schema_for!(LatestProofSchema, Hash, Proof, Block);
 impl_storage_schema_for!(OverlordWalSchema, Hash, Bytes, Wal);
+impl_storage_schema_for!(EvidenceSchema, CommonHashKey, Evidence, Evidence);
 
 #[async_trait]
 impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
@@ -529,6 +530,35 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         let wal_info = ensure_get!(self, OVERLORD_WAL_KEY.clone(), OverlordWalSchema);
         Ok(wal_info)
     }
+
+    #[muta_apm::derive::tracing_span(kind = "storage")]
+    async fn insert_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        let key = CommonHashKey::new(evidence.height, evidence.hash());
+
+        self.adapter.insert::<EvidenceSchema>(key, evidence).await?;
+        Ok(())
+    }
+
+    #[muta_apm::derive::tracing_span(kind = "storage")]
+    async fn get_evidences(&self, ctx: Context, height: u64) -> ProtocolResult<Vec<Evidence>> {
+        let key_prefix = CommonPrefix::new(height);
+        let prepare_iter = self
+            .adapter
+            .prepare_iter::<EvidenceSchema, _>(&key_prefix)?;
+        let iter = prepare_iter.ref_to_iter();
+
+        let mut evidences = Vec::new();
+        for kv in iter {
+            let (key, evidence) = kv?;
+            if key.height() != height {
+                break;
+            }
+
+            evidences.push(evidence);
+        }
+
+        Ok(evidences)
+    }
 }
 
 #[derive(Debug, Display, From)]
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use test::Bencher;
 
 use protocol::traits::{Context, Storage};
-use protocol::types::Hash;
+use protocol::types::{Address, Evidence, EvidenceType, Hash};
 
 use crate::adapter::memory::MemoryAdapter;
 use crate::tests::{get_random_bytes, mock_block, mock_proof, mock_receipt, mock_signed_tx};
@@ -102,6 +102,43 @@ fn test_storage_wal_insert() {
     assert_eq!(info, info_2);
 }
 
+#[test]
+fn test_storage_evidence_insert() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let height = 100;
+    let offender = Address::from_hash(Hash::digest(get_random_bytes(10))).unwrap();
+    let evidences = vec![
+        EvidenceType::DuplicatePrevote,
+        EvidenceType::DuplicatePrecommit,
+    ]
+    .into_iter()
+    .map(|ty| {
+        Evidence::new(
+            ty,
+            height,
+            0,
+            offender.clone(),
+            get_random_bytes(64),
+            get_random_bytes(64),
+        )
+    })
+    .collect::<Vec<_>>();
+
+    for evidence in evidences.iter() {
+        exec!(storage.insert_evidence(Context::new(), evidence.clone()));
+    }
+    // Insert same evidence again should not duplicate it
+    exec!(storage.insert_evidence(Context::new(), evidences[0].clone()));
+
+    let mut stored = exec!(storage.get_evidences(Context::new(), height));
+    stored.sort_by_key(|e| u32::from(e.evidence_type));
+    assert_eq!(stored, evidences);
+
+    let stored = exec!(storage.get_evidences(Context::new(), height + 1));
+    assert!(stored.is_empty());
+}
+
 #[rustfmt::skip]
 /// Bench in Intel(R) Core(TM) i7-4770HQ CPU @ 2.20GHz (8 x 2200)
 /// test tests::storage::bench_insert_10000_receipts ... bench:  33,954,916 ns/iter (+/- 3,818,780)
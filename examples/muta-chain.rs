@@ -4,9 +4,13 @@ use derive_more::{Display, From};
 use metadata::MetadataService;
 use multi_signature::MultiSignatureService;
 use muta::MutaBuilder;
+use name::NameService;
 use protocol::traits::{Service, ServiceMapping, ServiceSDK};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+use rewards::RewardsService;
+use staking::StakingService;
 use util::UtilService;
+use wasm::WasmService;
 
 struct DefaultServiceMapping;
 
@@ -21,7 +25,11 @@ impl ServiceMapping for DefaultServiceMapping {
             "authorization" => Box::new(AuthorizationService::new(sdk)) as Box<dyn Service>,
             "metadata" => Box::new(MetadataService::new(sdk)) as Box<dyn Service>,
             "multi_signature" => Box::new(MultiSignatureService::new(sdk)) as Box<dyn Service>,
+            "name" => Box::new(NameService::new(sdk)) as Box<dyn Service>,
+            "rewards" => Box::new(RewardsService::new(sdk)) as Box<dyn Service>,
+            "staking" => Box::new(StakingService::new(sdk)) as Box<dyn Service>,
             "util" => Box::new(UtilService::new(sdk)) as Box<dyn Service>,
+            "wasm" => Box::new(WasmService::new(sdk)) as Box<dyn Service>,
             _ => {
                 return Err(MappingError::NotFoundService {
                     service: name.to_owned(),
@@ -39,7 +47,11 @@ impl ServiceMapping for DefaultServiceMapping {
             "authorization".to_owned(),
             "metadata".to_owned(),
             "multi_signature".to_owned(),
+            "name".to_owned(),
+            "rewards".to_owned(),
+            "staking".to_owned(),
             "util".to_owned(),
+            "wasm".to_owned(),
         ]
     }
 }
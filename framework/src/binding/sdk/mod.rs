@@ -11,13 +11,15 @@ use derive_more::{Display, From};
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
     ChainQuerier, Dispatcher, ServiceResponse, ServiceSDK, ServiceState, StoreArray, StoreBool,
-    StoreMap, StoreString, StoreUint64,
+    StoreDeque, StoreMap, StoreOrderedMap, StoreSet, StoreString, StoreUint128, StoreUint64,
 };
 use protocol::types::{Address, Block, Hash, Receipt, ServiceContext, SignedTransaction};
 use protocol::{ProtocolError, ProtocolErrorKind};
 
 use crate::binding::store::{
-    DefaultStoreArray, DefaultStoreBool, DefaultStoreMap, DefaultStoreString, DefaultStoreUint64,
+    DefaultStoreArray, DefaultStoreBool, DefaultStoreDeque, DefaultStoreMap,
+    DefaultStoreOrderedMap, DefaultStoreSet, DefaultStoreString, DefaultStoreUint128,
+    DefaultStoreUint64,
 };
 
 pub struct DefaultServiceSDK<S: ServiceState, C: ChainQuerier, D: Dispatcher> {
@@ -42,7 +44,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     // Alloc or recover a `Map` by` var_name`
     fn alloc_or_recover_map<
         K: 'static + Send + FixedCodec + Clone + PartialEq,
-        V: 'static + FixedCodec,
+        V: 'static + FixedCodec + Clone,
     >(
         &mut self,
         var_name: &str,
@@ -54,7 +56,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     }
 
     // Alloc or recover a `Array` by` var_name`
-    fn alloc_or_recover_array<E: 'static + FixedCodec>(
+    fn alloc_or_recover_array<E: 'static + FixedCodec + Clone>(
         &mut self,
         var_name: &str,
     ) -> Box<dyn StoreArray<E>> {
@@ -64,11 +66,52 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
         ))
     }
 
+    // Alloc or recover a `OrderedMap` by` var_name`
+    fn alloc_or_recover_ordered_map<
+        K: 'static + Send + FixedCodec + Clone + Ord,
+        V: 'static + FixedCodec + Clone,
+    >(
+        &mut self,
+        var_name: &str,
+    ) -> Box<dyn StoreOrderedMap<K, V>> {
+        Box::new(DefaultStoreOrderedMap::<S, K, V>::new(
+            Rc::clone(&self.state),
+            var_name,
+        ))
+    }
+
+    // Alloc or recover a `Set` by` var_name`
+    fn alloc_or_recover_set<E: 'static + Send + FixedCodec + Clone + PartialEq>(
+        &mut self,
+        var_name: &str,
+    ) -> Box<dyn StoreSet<E>> {
+        Box::new(DefaultStoreSet::<S, E>::new(
+            Rc::clone(&self.state),
+            var_name,
+        ))
+    }
+
+    // Alloc or recover a `Deque` by` var_name`
+    fn alloc_or_recover_deque<E: 'static + FixedCodec + Clone>(
+        &mut self,
+        var_name: &str,
+    ) -> Box<dyn StoreDeque<E>> {
+        Box::new(DefaultStoreDeque::<S, E>::new(
+            Rc::clone(&self.state),
+            var_name,
+        ))
+    }
+
     // Alloc or recover a `Uint64` by` var_name`
     fn alloc_or_recover_uint64(&mut self, var_name: &str) -> Box<dyn StoreUint64> {
         Box::new(DefaultStoreUint64::new(Rc::clone(&self.state), var_name))
     }
 
+    // Alloc or recover a `Uint128` by` var_name`
+    fn alloc_or_recover_uint128(&mut self, var_name: &str) -> Box<dyn StoreUint128> {
+        Box::new(DefaultStoreUint128::new(Rc::clone(&self.state), var_name))
+    }
+
     // Alloc or recover a `String` by` var_name`
     fn alloc_or_recover_string(&mut self, var_name: &str) -> Box<dyn StoreString> {
         Box::new(DefaultStoreString::new(Rc::clone(&self.state), var_name))
@@ -80,7 +123,10 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     }
 
     // Get a value from the service state by key
-    fn get_value<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> Option<Ret> {
+    fn get_value<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
+        &self,
+        key: &Key,
+    ) -> Option<Ret> {
         self.state
             .borrow()
             .get(key)
@@ -88,15 +134,23 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     }
 
     // Set a value to the service state by key
-    fn set_value<Key: FixedCodec, Val: FixedCodec>(&mut self, key: Key, val: Val) {
+    fn set_value<Key: FixedCodec, Val: 'static + FixedCodec>(&mut self, key: Key, val: Val) {
         self.state
             .borrow_mut()
             .insert(key, val)
             .unwrap_or_else(|e| panic!("service sdk set value failed: {}", e));
     }
 
+    // Remove a value from the service state by key
+    fn remove_value<Key: FixedCodec>(&mut self, key: &Key) {
+        self.state
+            .borrow_mut()
+            .remove(key)
+            .unwrap_or_else(|e| panic!("service sdk remove value failed: {}", e));
+    }
+
     // Get a value from the specified address by key
-    fn get_account_value<Key: FixedCodec, Ret: FixedCodec>(
+    fn get_account_value<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
         &self,
         address: &Address,
         key: &Key,
@@ -108,7 +162,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     }
 
     // Insert a pair of key / value to the specified address
-    fn set_account_value<Key: FixedCodec, Val: FixedCodec>(
+    fn set_account_value<Key: FixedCodec, Val: 'static + FixedCodec>(
         &mut self,
         address: &Address,
         key: Key,
@@ -120,6 +174,14 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
             .unwrap_or_else(|e| panic!("service sdk set account value failed: {}", e));
     }
 
+    // Remove a value from the specified address by key
+    fn remove_account_value<Key: FixedCodec>(&mut self, address: &Address, key: &Key) {
+        self.state
+            .borrow_mut()
+            .remove_account_value(address, key)
+            .unwrap_or_else(|e| panic!("service sdk remove account value failed: {}", e));
+    }
+
     // Get a signed transaction by `tx_hash`
     // if not found on the chain, return None
     fn get_transaction_by_hash(&self, tx_hash: &Hash) -> Option<SignedTransaction> {
@@ -147,7 +209,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
 
     // Call other read-only methods of `service` and return the results
     // synchronously NOTE: You can use recursive calls, but the maximum call
-    // stack is 1024
+    // stack is 64
     fn read(
         &self,
         ctx: &ServiceContext,
@@ -168,7 +230,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     }
 
     // Call other writable methods of `service` and return the results synchronously
-    // NOTE: You can use recursive calls, but the maximum call stack is 1024
+    // NOTE: You can use recursive calls, but the maximum call stack is 64
     fn write(
         &mut self,
         ctx: &ServiceContext,
@@ -4,7 +4,9 @@ mod trie_db;
 pub use trie::{MPTTrie, MPTTrieError};
 pub use trie_db::{RocksTrieDB, RocksTrieDBError};
 
-use std::collections::HashMap;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
 use bytes::Bytes;
 use cita_trie::DB as TrieDB;
@@ -17,10 +19,38 @@ use protocol::ProtocolResult;
 pub struct GeneralServiceState<DB: TrieDB> {
     trie: MPTTrie<DB>,
 
-    // TODO(@yejiayu): The value of HashMap should be changed to Box<dyn Any> to avoid multiple
-    // serializations.
-    cache_map: HashMap<Bytes, Bytes>,
-    stash_map: HashMap<Bytes, Bytes>,
+    // Values are kept decoded and only encoded on `commit`.
+    // A `None` value marks a removed key, it shadows the value in lower layers
+    // until it is committed.
+    cache_map:  CacheMap,
+    stash_map:  CacheMap,
+    // Layers on top of `cache_map`, one for each nested service call in
+    // progress. Writes always go to the last one.
+    savepoints: Vec<CacheMap>,
+    // Decoded values read from the trie, cleared on `commit` since the trie
+    // is only changed there.
+    read_cache: RefCell<CacheMap>,
+    // Keys read and stashed since `start_record`, used to find conflicts
+    // between transactions executed in parallel.
+    record:     RefCell<Option<AccessRecord>>,
+}
+
+/// Encoded keys accessed through `ServiceState`. Only stashed writes are
+/// recorded, writes reverted before `stash` never reach other transactions.
+/// `touched` keeps every inserted or removed key, reverted or not.
+#[derive(Default, Debug)]
+pub struct AccessRecord {
+    pub reads:   HashSet<Bytes>,
+    pub writes:  HashSet<Bytes>,
+    pub touched: HashSet<Bytes>,
+}
+
+impl AccessRecord {
+    pub fn merge(&mut self, other: AccessRecord) {
+        self.reads.extend(other.reads);
+        self.writes.extend(other.writes);
+        self.touched.extend(other.touched);
+    }
 }
 
 impl<DB: TrieDB> GeneralServiceState<DB> {
@@ -30,40 +60,155 @@ impl<DB: TrieDB> GeneralServiceState<DB> {
 
             cache_map: HashMap::new(),
             stash_map: HashMap::new(),
+            savepoints: Vec::new(),
+            read_cache: RefCell::new(HashMap::new()),
+            record: RefCell::new(None),
+        }
+    }
+
+    // Start a new write layer, data written after this can be dropped by
+    // `revert_savepoint` without touching the data written before.
+    pub fn savepoint(&mut self) {
+        self.savepoints.push(HashMap::new());
+    }
+
+    // Merge the last savepoint into its parent layer
+    pub fn commit_savepoint(&mut self) {
+        if let Some(savepoint) = self.savepoints.pop() {
+            self.top_layer().extend(savepoint);
+        }
+    }
+
+    // Drop all data written since the last savepoint
+    pub fn revert_savepoint(&mut self) {
+        self.savepoints.pop();
+    }
+
+    pub fn savepoint_depth(&self) -> usize {
+        self.savepoints.len()
+    }
+
+    pub fn root(&self) -> MerkleRoot {
+        self.trie.root()
+    }
+
+    pub fn start_record(&mut self) {
+        *self.record.get_mut() = Some(AccessRecord::default());
+    }
+
+    pub fn take_record(&mut self) -> Option<AccessRecord> {
+        self.record.get_mut().take()
+    }
+
+    pub fn restore_record(&mut self, record: Option<AccessRecord>) {
+        *self.record.get_mut() = record;
+    }
+
+    // Encoded values of all stashed keys, or only `keys` if given
+    pub fn export_stash(
+        &self,
+        keys: Option<&HashSet<Bytes>>,
+    ) -> ProtocolResult<HashMap<Bytes, Option<Bytes>>> {
+        let mut values = HashMap::new();
+
+        for (key, opt_value) in self.stash_map.iter() {
+            if keys.map_or(false, |keys| !keys.contains(key)) {
+                continue;
+            }
+
+            let opt_bytes = match opt_value {
+                Some(value) => Some(value.encode()?),
+                None => None,
+            };
+            values.insert(key.clone(), opt_bytes);
+        }
+
+        Ok(values)
+    }
+
+    // Stash encoded values as if they were written and stashed here
+    pub fn import_stash(&mut self, values: HashMap<Bytes, Option<Bytes>>) {
+        for (key, opt_bytes) in values.into_iter() {
+            let opt_value = opt_bytes.map(|bytes| Box::new(bytes) as Box<dyn CacheValue>);
+            self.stash_map.insert(key, opt_value);
+        }
+    }
+
+    // Drop all uncommitted data and start over from `values`
+    pub fn reset_stash(&mut self, values: HashMap<Bytes, Option<Bytes>>) {
+        self.savepoints.clear();
+        self.cache_map.clear();
+        self.stash_map.clear();
+        self.import_stash(values);
+    }
+
+    fn record_read(&self, encoded_key: &Bytes) {
+        if let Some(record) = self.record.borrow_mut().as_mut() {
+            record.reads.insert(encoded_key.clone());
         }
     }
+
+    fn record_touch(&mut self, encoded_key: &Bytes) {
+        if let Some(record) = self.record.get_mut().as_mut() {
+            record.touched.insert(encoded_key.clone());
+        }
+    }
+
+    fn top_layer(&mut self) -> &mut CacheMap {
+        match self.savepoints.last_mut() {
+            Some(savepoint) => savepoint,
+            None => &mut self.cache_map,
+        }
+    }
+
+    fn get_uncommitted(&self, encoded_key: &Bytes) -> Option<&Option<Box<dyn CacheValue>>> {
+        self.savepoints
+            .iter()
+            .rev()
+            .chain(vec![&self.cache_map, &self.stash_map])
+            .find_map(|layer| layer.get(encoded_key))
+    }
 }
 
 impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
-    fn get<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> ProtocolResult<Option<Ret>> {
+    fn get<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
+        &self,
+        key: &Key,
+    ) -> ProtocolResult<Option<Ret>> {
         let encoded_key = key.encode_fixed()?;
+        self.record_read(&encoded_key);
 
-        if let Some(value_bytes) = self.cache_map.get(&encoded_key) {
-            let inst = <_>::decode_fixed(value_bytes.clone())?;
-            return Ok(Some(inst));
+        if let Some(opt_value) = self.get_uncommitted(&encoded_key) {
+            return downcast_opt_value(opt_value);
         }
 
-        if let Some(value_bytes) = self.stash_map.get(&encoded_key) {
-            let inst = <_>::decode_fixed(value_bytes.clone())?;
-            return Ok(Some(inst));
+        if let Some(opt_value) = self.read_cache.borrow().get(&encoded_key) {
+            return downcast_opt_value(opt_value);
         }
 
-        if let Some(value_bytes) = self.trie.get(&encoded_key)? {
-            return Ok(Some(<_>::decode_fixed(value_bytes)?));
-        }
+        let opt_ret: Option<Ret> = match self.trie.get(&encoded_key)? {
+            Some(value_bytes) => Some(<_>::decode_fixed(value_bytes)?),
+            None => None,
+        };
+
+        let opt_value = opt_ret
+            .clone()
+            .map(|ret| Box::new(ret) as Box<dyn CacheValue>);
+        self.read_cache.borrow_mut().insert(encoded_key, opt_value);
 
-        Ok(None)
+        Ok(opt_ret)
     }
 
     fn contains<Key: FixedCodec>(&self, key: &Key) -> ProtocolResult<bool> {
         let encoded_key = key.encode_fixed()?;
+        self.record_read(&encoded_key);
 
-        if self.cache_map.contains_key(&encoded_key) {
-            return Ok(true);
+        if let Some(opt_value) = self.get_uncommitted(&encoded_key) {
+            return Ok(opt_value.is_some());
         };
 
-        if self.stash_map.contains_key(&encoded_key) {
-            return Ok(true);
+        if let Some(opt_value) = self.read_cache.borrow().get(&encoded_key) {
+            return Ok(opt_value.is_some());
         };
 
         self.trie.contains(&encoded_key)
@@ -72,17 +217,26 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
     // Insert a pair of key / value
     // Note: This key/value pair will go into the cache first
     // and will not be persisted to MPT until `commit` is called.
-    fn insert<Key: FixedCodec, Value: FixedCodec>(
+    fn insert<Key: FixedCodec, Value: 'static + FixedCodec>(
         &mut self,
         key: Key,
         value: Value,
     ) -> ProtocolResult<()> {
-        self.cache_map
-            .insert(key.encode_fixed()?, value.encode_fixed()?);
+        let encoded_key = key.encode_fixed()?;
+        self.record_touch(&encoded_key);
+        self.top_layer()
+            .insert(encoded_key, Some(Box::new(value) as Box<dyn CacheValue>));
+        Ok(())
+    }
+
+    fn remove<Key: FixedCodec>(&mut self, key: &Key) -> ProtocolResult<()> {
+        let encoded_key = key.encode_fixed()?;
+        self.record_touch(&encoded_key);
+        self.top_layer().insert(encoded_key, None);
         Ok(())
     }
 
-    fn get_account_value<Key: FixedCodec, Ret: FixedCodec>(
+    fn get_account_value<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
         &self,
         address: &Address,
         key: &Key,
@@ -91,7 +245,7 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
         self.get(&hash_key)
     }
 
-    fn set_account_value<Key: FixedCodec, Val: FixedCodec>(
+    fn set_account_value<Key: FixedCodec, Val: 'static + FixedCodec>(
         &mut self,
         address: &Address,
         key: Key,
@@ -101,15 +255,34 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
         self.insert(hash_key, val)
     }
 
-    // Roll back all data in the cache
+    fn remove_account_value<Key: FixedCodec>(
+        &mut self,
+        address: &Address,
+        key: &Key,
+    ) -> ProtocolResult<()> {
+        let hash_key = get_address_key(address, key)?;
+        self.remove(&hash_key)
+    }
+
+    // Roll back all data in the cache, including all savepoints
     fn revert_cache(&mut self) -> ProtocolResult<()> {
+        self.savepoints.clear();
         self.cache_map.clear();
         Ok(())
     }
 
-    // Move data from cache to stash
+    // Move data from cache to stash, savepoints left open are committed
+    // first
     fn stash(&mut self) -> ProtocolResult<()> {
+        while !self.savepoints.is_empty() {
+            self.commit_savepoint();
+        }
+
+        let record = self.record.get_mut();
         for (k, v) in self.cache_map.drain() {
+            if let Some(record) = record.as_mut() {
+                record.writes.insert(k.clone());
+            }
             self.stash_map.insert(k, v);
         }
 
@@ -118,15 +291,53 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
 
     // Persist data from stash into MPT
     fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
-        for (key, value) in self.stash_map.drain() {
-            self.trie.insert(key, value)?;
+        for (key, opt_value) in self.stash_map.drain() {
+            match opt_value {
+                Some(value) => self.trie.insert(key, value.encode()?)?,
+                None => self.trie.remove(&key)?,
+            }
         }
+        self.read_cache.get_mut().clear();
 
         let root = self.trie.commit()?;
         Ok(root)
     }
 }
 
+type CacheMap = HashMap<Bytes, Option<Box<dyn CacheValue>>>;
+
+trait CacheValue {
+    fn as_any(&self) -> &dyn Any;
+
+    fn encode(&self) -> ProtocolResult<Bytes>;
+}
+
+impl<T: 'static + FixedCodec> CacheValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        self.encode_fixed()
+    }
+}
+
+// The same key may be read as another type than the one it was written
+// with, fall back to a round trip through bytes in that case.
+fn downcast_opt_value<Ret: 'static + FixedCodec + Clone>(
+    opt_value: &Option<Box<dyn CacheValue>>,
+) -> ProtocolResult<Option<Ret>> {
+    let value = match opt_value {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    match value.as_any().downcast_ref::<Ret>() {
+        Some(ret) => Ok(Some(ret.clone())),
+        None => Ok(Some(<_>::decode_fixed(value.encode()?)?)),
+    }
+}
+
 fn get_address_key<Key: FixedCodec>(address: &Address, key: &Key) -> ProtocolResult<Hash> {
     let mut hash_bytes = address.as_bytes().to_vec();
     hash_bytes.extend_from_slice(key.encode_fixed()?.as_ref());
@@ -35,6 +35,10 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(Self { root, trie })
     }
 
+    pub fn root(&self) -> MerkleRoot {
+        self.root.clone()
+    }
+
     pub fn get(&self, key: &Bytes) -> ProtocolResult<Option<Bytes>> {
         Ok(self
             .trie
@@ -54,6 +58,11 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(())
     }
 
+    pub fn remove(&mut self, key: &Bytes) -> ProtocolResult<()> {
+        self.trie.remove(key).map_err(MPTTrieError::from)?;
+        Ok(())
+    }
+
     pub fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
         let root_bytes = self.trie.root().map_err(MPTTrieError::from)?;
         let root = MerkleRoot::from_bytes(Bytes::from(root_bytes))?;
@@ -18,7 +18,7 @@ pub struct DefaultStoreArray<S: ServiceState, E: FixedCodec> {
     phantom:  PhantomData<E>,
 }
 
-impl<S: ServiceState, E: FixedCodec> DefaultStoreArray<S, E> {
+impl<S: ServiceState, E: 'static + FixedCodec + Clone> DefaultStoreArray<S, E> {
     pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
         let var_name = Hash::digest(Bytes::from(name.to_owned() + "array"));
 
@@ -77,11 +77,16 @@ impl<S: ServiceState, E: FixedCodec> DefaultStoreArray<S, E> {
             .borrow_mut()
             .insert(self.var_name.clone(), self.keys.encode_fixed()?)?;
 
-        self.state.borrow_mut().insert(key, Bytes::new())
+        // Equal elements share one key, keep the value while any index still
+        // points to it.
+        if self.keys.inner.contains(&key) {
+            return Ok(());
+        }
+        self.state.borrow_mut().remove(&key)
     }
 }
 
-impl<S: ServiceState, E: FixedCodec> StoreArray<E> for DefaultStoreArray<S, E> {
+impl<S: ServiceState, E: 'static + FixedCodec + Clone> StoreArray<E> for DefaultStoreArray<S, E> {
     fn get(&self, index: u32) -> Option<E> {
         self.inner_get(index)
             .unwrap_or_else(|e| panic!("StoreArray get value failed: {}", e))
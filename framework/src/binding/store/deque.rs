@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use bytes::Bytes;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{ServiceState, StoreDeque};
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::binding::store::StoreError;
+
+// Both ends start from the middle so the deque can grow either way.
+const INITIAL_CURSOR: u64 = 1 << 63;
+
+/// Elements are stored by their absolute position, `head` is the position of
+/// the front and `tail` is one past the back, so every push or pop writes a
+/// fixed number of entries.
+pub struct DefaultStoreDeque<S: ServiceState, E: FixedCodec> {
+    state:    Rc<RefCell<S>>,
+    var_name: String,
+    head_key: Hash,
+    tail_key: Hash,
+    head:     u64,
+    tail:     u64,
+    phantom:  PhantomData<E>,
+}
+
+impl<S: ServiceState, E: 'static + FixedCodec + Clone> DefaultStoreDeque<S, E> {
+    pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
+        let head_key = Hash::digest(Bytes::from(name.to_owned() + "_deque_head"));
+        let tail_key = Hash::digest(Bytes::from(name.to_owned() + "_deque_tail"));
+
+        let (head, tail) = {
+            let state = state.borrow();
+            let head = state
+                .get(&head_key)
+                .expect("get deque head should not fail")
+                .unwrap_or(INITIAL_CURSOR);
+            let tail = state
+                .get(&tail_key)
+                .expect("get deque tail should not fail")
+                .unwrap_or(INITIAL_CURSOR);
+
+            (head, tail)
+        };
+
+        Self {
+            state,
+            var_name: name.to_owned(),
+            head_key,
+            tail_key,
+            head,
+            tail,
+            phantom: PhantomData,
+        }
+    }
+
+    fn inner_get(&self, index: u32) -> ProtocolResult<Option<E>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+
+        self.state
+            .borrow()
+            .get(&self.get_element_key(self.head + u64::from(index)))
+    }
+
+    fn inner_push_front(&mut self, elm: E) -> ProtocolResult<()> {
+        let head = self.head.checked_sub(1).ok_or(StoreError::Overflow)?;
+        self.state
+            .borrow_mut()
+            .insert(self.get_element_key(head), elm)?;
+
+        self.set_head(head)
+    }
+
+    fn inner_push_back(&mut self, elm: E) -> ProtocolResult<()> {
+        let tail = self.tail.checked_add(1).ok_or(StoreError::Overflow)?;
+        self.state
+            .borrow_mut()
+            .insert(self.get_element_key(self.tail), elm)?;
+
+        self.set_tail(tail)
+    }
+
+    fn inner_pop_front(&mut self) -> ProtocolResult<Option<E>> {
+        if self.head == self.tail {
+            return Ok(None);
+        }
+
+        let key = self.get_element_key(self.head);
+        let elm = self.state.borrow().get(&key)?;
+        self.state.borrow_mut().remove(&key)?;
+        self.set_head(self.head + 1)?;
+
+        Ok(elm)
+    }
+
+    fn inner_pop_back(&mut self) -> ProtocolResult<Option<E>> {
+        if self.head == self.tail {
+            return Ok(None);
+        }
+
+        let key = self.get_element_key(self.tail - 1);
+        let elm = self.state.borrow().get(&key)?;
+        self.state.borrow_mut().remove(&key)?;
+        self.set_tail(self.tail - 1)?;
+
+        Ok(elm)
+    }
+
+    fn set_head(&mut self, head: u64) -> ProtocolResult<()> {
+        self.head = head;
+        self.state.borrow_mut().insert(self.head_key.clone(), head)
+    }
+
+    fn set_tail(&mut self, tail: u64) -> ProtocolResult<()> {
+        self.tail = tail;
+        self.state.borrow_mut().insert(self.tail_key.clone(), tail)
+    }
+
+    fn get_element_key(&self, position: u64) -> Hash {
+        let mut bytes = (self.var_name.clone() + "_deque_").as_bytes().to_vec();
+        bytes.extend_from_slice(&position.to_be_bytes());
+
+        Hash::digest(Bytes::from(bytes))
+    }
+}
+
+impl<S: ServiceState, E: 'static + FixedCodec + Clone> StoreDeque<E> for DefaultStoreDeque<S, E> {
+    fn get(&self, index: u32) -> Option<E> {
+        self.inner_get(index)
+            .unwrap_or_else(|e| panic!("StoreDeque get value failed: {}", e))
+    }
+
+    fn front(&self) -> Option<E> {
+        self.get(0)
+    }
+
+    fn back(&self) -> Option<E> {
+        if self.is_empty() {
+            None
+        } else {
+            self.get(self.len() - 1)
+        }
+    }
+
+    fn push_front(&mut self, elm: E) {
+        self.inner_push_front(elm)
+            .unwrap_or_else(|e| panic!("StoreDeque push front failed: {}", e));
+    }
+
+    fn push_back(&mut self, elm: E) {
+        self.inner_push_back(elm)
+            .unwrap_or_else(|e| panic!("StoreDeque push back failed: {}", e));
+    }
+
+    fn pop_front(&mut self) -> Option<E> {
+        self.inner_pop_front()
+            .unwrap_or_else(|e| panic!("StoreDeque pop front failed: {}", e))
+    }
+
+    fn pop_back(&mut self) -> Option<E> {
+        self.inner_pop_back()
+            .unwrap_or_else(|e| panic!("StoreDeque pop back failed: {}", e))
+    }
+
+    fn len(&self) -> u32 {
+        (self.tail - self.head) as u32
+    }
+
+    fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (u32, E)> + 'a> {
+        Box::new((0..self.len()).map(move |index| {
+            let elm = self.get(index).expect("get element should not fail");
+            (index, elm)
+        }))
+    }
+}
@@ -4,195 +4,276 @@ use std::marker::PhantomData;
 use std::rc::Rc;
 
 use bytes::Bytes;
-use rayon::prelude::*;
 
-use protocol::fixed_codec::FixedCodec;
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
 use protocol::traits::{ServiceState, StoreMap};
-use protocol::types::Hash;
 use protocol::{ProtocolError, ProtocolResult};
 
-use crate::binding::store::{get_bucket_index, Bucket, FixedBuckets, StoreError};
-
+use crate::binding::store::{get_hashed_key, BucketStoreMap, StoreError};
+
+const LEN_TAG: u8 = 0;
+const HEAD_TAG: u8 = 1;
+const TAIL_TAG: u8 = 2;
+const NODE_TAG: u8 = 3;
+const VALUE_TAG: u8 = 4;
+const MIGRATED_TAG: u8 = 5;
+
+/// Keys are linked into a doubly linked list on the trie, every key has a
+/// node pointing to its neighbours, so insert and remove only write a fixed
+/// number of entries no matter how large the map is. Iteration follows the
+/// list from head, that is the insertion order.
+///
+/// Entries of a map written by `BucketStoreMap` stay in the buckets until
+/// `migrate` moves them, a batch at a time, to the tail of the list. Before
+/// that they are read and updated in place, removing one still rewrites its
+/// bucket. Once the buckets are drained, or the map is first written without
+/// any, a flag is recorded so that the buckets are never probed again.
 pub struct DefaultStoreMap<S: ServiceState, K: FixedCodec + PartialEq, V: FixedCodec> {
-    state:    Rc<RefCell<S>>,
-    var_name: String,
-    keys:     RefCell<FixedBuckets<K>>,
-    len_key:  Bytes,
-    len:      u32,
-    phantom:  PhantomData<V>,
+    state:        Rc<RefCell<S>>,
+    var_name:     String,
+    len_key:      Bytes,
+    len:          u32,
+    head_key:     Bytes,
+    tail_key:     Bytes,
+    migrated_key: Bytes,
+    migrated:     bool,
+    legacy:       Option<BucketStoreMap<S, K, V>>,
+    phantom:      PhantomData<V>,
 }
 
 impl<S, K, V> DefaultStoreMap<S, K, V>
 where
     S: 'static + ServiceState,
-    K: 'static + Send + FixedCodec + PartialEq,
-    V: 'static + FixedCodec,
+    K: 'static + Send + FixedCodec + Clone + PartialEq,
+    V: 'static + FixedCodec + Clone,
 {
     pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
-        let len_key = Bytes::from(name.to_string() + "_map_len");
+        let len_key = get_hashed_key(name, LEN_TAG, &[]);
         let len = state
             .borrow()
             .get(&len_key)
             .expect("Get len failed")
             .unwrap_or(0u32);
 
+        let migrated_key = get_hashed_key(name, MIGRATED_TAG, &[]);
+        let migrated = state
+            .borrow()
+            .get(&migrated_key)
+            .expect("Get migrated failed")
+            .unwrap_or(false);
+
+        let legacy = if migrated {
+            None
+        } else {
+            Some(BucketStoreMap::new(Rc::clone(&state), name)).filter(|legacy| !legacy.is_empty())
+        };
+
         DefaultStoreMap {
             state,
             len_key,
             len,
+            head_key: get_hashed_key(name, HEAD_TAG, &[]),
+            tail_key: get_hashed_key(name, TAIL_TAG, &[]),
+            migrated_key,
+            migrated,
+            legacy,
             var_name: name.to_string(),
-            keys: RefCell::new(FixedBuckets::new()),
             phantom: PhantomData,
         }
     }
 
     fn inner_insert(&mut self, key: K, value: V) -> ProtocolResult<()> {
-        let key_bytes = key.encode_fixed()?;
-        let mk = self.get_map_key(&key_bytes);
-        let bkt_idx = get_bucket_index(&key_bytes);
+        if let Some(legacy) = self.legacy.as_mut() {
+            if legacy.contains(&key) {
+                legacy.insert(key, value);
+                return Ok(());
+            }
+        }
 
-        if !self.inner_contains(bkt_idx, &key)? {
-            self.keys.borrow_mut().insert(bkt_idx, key);
+        if self.legacy.is_none() {
+            self.set_migrated()?;
+        }
 
-            self.state.borrow_mut().insert(
-                self.get_bucket_name(bkt_idx),
-                self.keys.borrow().get_bucket(bkt_idx).encode_fixed()?,
-            )?;
+        let key_bytes = key.encode_fixed()?;
+        if self.get_node(&key_bytes)?.is_none() {
+            self.link_node(key_bytes.clone())?;
             self.len_add_one()?;
         }
-        self.state.borrow_mut().insert(mk, value)
+
+        let vk = self.get_value_key(&key_bytes);
+        self.state.borrow_mut().insert(vk, value)
     }
 
     fn inner_get(&self, key: &K) -> ProtocolResult<Option<V>> {
         let key_bytes = key.encode_fixed()?;
-        let bkt_idx = get_bucket_index(&key_bytes);
-
-        if self.inner_contains(bkt_idx, &key)? {
-            self.state
-                .borrow()
-                .get(&self.get_map_key(&key_bytes))?
-                .map_or_else(
-                    || {
-                        Ok(Some(<_>::decode_fixed(Bytes::new()).map_err(|_| {
-                            ProtocolError::from(StoreError::DecodeError)
-                        })?))
-                    },
-                    |v| Ok(Some(v)),
-                )
-        } else {
-            Ok(None)
+        if self.get_node(&key_bytes)?.is_none() {
+            return Ok(self.legacy.as_ref().and_then(|legacy| legacy.get(key)));
         }
+
+        self.state
+            .borrow()
+            .get(&self.get_value_key(&key_bytes))?
+            .map_or_else(
+                || {
+                    Ok(Some(<_>::decode_fixed(Bytes::new()).map_err(|_| {
+                        ProtocolError::from(StoreError::DecodeError)
+                    })?))
+                },
+                |v| Ok(Some(v)),
+            )
     }
 
     fn inner_remove(&mut self, key: &K) -> ProtocolResult<Option<V>> {
         let key_bytes = key.encode_fixed()?;
-        let bkt_idx = get_bucket_index(&key_bytes);
-
-        if self.inner_contains(bkt_idx, &key)? {
-            let value = self.inner_get(key)?.expect("value should be existed");
-            let bkt_idx = get_bucket_index(&key_bytes);
-            let bkt_name = self.get_bucket_name(bkt_idx);
-
-            let _ = self.keys.borrow_mut().remove_item(bkt_idx, key)?;
-            self.state.borrow_mut().insert(
-                bkt_name,
-                self.keys.borrow().get_bucket(bkt_idx).encode_fixed()?,
-            )?;
+        let node = match self.get_node(&key_bytes)? {
+            Some(node) => node,
+            None => return Ok(self.legacy.as_mut().and_then(|legacy| legacy.remove(key))),
+        };
+
+        let value = self.inner_get(key)?.expect("value should be existed");
+        self.unlink_node(&key_bytes, node)?;
+        self.state
+            .borrow_mut()
+            .remove(&self.get_value_key(&key_bytes))?;
+        self.len_sub_one()?;
+
+        Ok(Some(value))
+    }
+
+    fn inner_contains(&self, key: &K) -> ProtocolResult<bool> {
+        if self.get_node(&key.encode_fixed()?)?.is_some() {
+            return Ok(true);
+        }
+
+        Ok(self
+            .legacy
+            .as_ref()
+            .map_or(false, |legacy| legacy.contains(key)))
+    }
+
+    fn inner_migrate(&mut self, limit: u32) -> ProtocolResult<bool> {
+        let entries = match self.legacy.as_mut() {
+            Some(legacy) => legacy.drain(limit)?,
+            None => {
+                self.set_migrated()?;
+                return Ok(true);
+            }
+        };
+
+        for (key, value) in entries {
+            let key_bytes = key.encode_fixed()?;
+            self.link_node(key_bytes.clone())?;
+            self.len_add_one()?;
             self.state
                 .borrow_mut()
-                .insert(self.get_map_key(&key_bytes), Bytes::new())?;
-            self.len_sub_one()?;
-            Ok(Some(value))
-        } else {
-            Ok(None)
+                .insert(self.get_value_key(&key_bytes), value)?;
         }
+
+        if self
+            .legacy
+            .as_ref()
+            .map_or(true, |legacy| legacy.is_empty())
+        {
+            self.legacy = None;
+            self.set_migrated()?;
+        }
+
+        Ok(self.legacy.is_none())
     }
 
-    #[inline(always)]
-    fn inner_contains(&self, bkt_idx: usize, key: &K) -> ProtocolResult<bool> {
-        if self.keys.borrow().is_bucket_recovered(bkt_idx) {
-            return Ok(self.keys.borrow().contains(bkt_idx, key));
+    fn set_migrated(&mut self) -> ProtocolResult<()> {
+        if self.migrated {
+            return Ok(());
         }
 
-        let bkt = if let Some(bytes) = self.state.borrow().get(&self.get_bucket_name(bkt_idx))? {
-            <_>::decode_fixed(bytes)?
+        self.migrated = true;
+        self.state
+            .borrow_mut()
+            .insert(self.migrated_key.clone(), true)
+    }
+
+    fn link_node(&mut self, key_bytes: Bytes) -> ProtocolResult<()> {
+        let tail = self.get_pointer(&self.tail_key)?;
+        if let Some(tail) = tail.as_ref() {
+            let mut tail_node = self.get_node(tail)?.ok_or(StoreError::GetNone)?;
+            tail_node.next = Some(key_bytes.clone());
+            self.set_node(tail, &tail_node)?;
         } else {
-            Bucket::new()
-        };
+            self.set_pointer(&self.head_key, Some(&key_bytes))?;
+        }
 
-        let ret = bkt.contains(key);
-        self.keys.borrow_mut().recover_bucket(bkt_idx, bkt);
-        Ok(ret)
+        self.set_node(&key_bytes, &MapNode {
+            prev: tail,
+            next: None,
+        })?;
+        self.set_pointer(&self.tail_key, Some(&key_bytes))
     }
 
-    fn get_map_key(&self, key_bytes: &Bytes) -> Bytes {
-        let mut name_bytes = self.var_name.as_bytes().to_vec();
-        name_bytes.extend_from_slice(key_bytes);
+    fn unlink_node(&mut self, key_bytes: &Bytes, node: MapNode) -> ProtocolResult<()> {
+        match node.prev.as_ref() {
+            Some(prev) => {
+                let mut prev_node = self.get_node(prev)?.ok_or(StoreError::GetNone)?;
+                prev_node.next = node.next.clone();
+                self.set_node(prev, &prev_node)?;
+            }
+            None => self.set_pointer(&self.head_key, node.next.as_ref())?,
+        }
 
-        if key_bytes.len() > 32 {
-            Hash::digest(Bytes::from(name_bytes)).as_bytes()
-        } else {
-            Bytes::from(name_bytes)
+        match node.next.as_ref() {
+            Some(next) => {
+                let mut next_node = self.get_node(next)?.ok_or(StoreError::GetNone)?;
+                next_node.prev = node.prev.clone();
+                self.set_node(next, &next_node)?;
+            }
+            None => self.set_pointer(&self.tail_key, node.prev.as_ref())?,
         }
+
+        self.state
+            .borrow_mut()
+            .remove(&self.get_node_key(key_bytes))
     }
 
-    fn get_bucket_name(&self, index: usize) -> Bytes {
-        let mut bytes = (self.var_name.clone() + "_bucket_").as_bytes().to_vec();
-        bytes.extend_from_slice(&index.to_le_bytes());
-        Bytes::from(bytes)
+    fn get_node(&self, key_bytes: &Bytes) -> ProtocolResult<Option<MapNode>> {
+        self.state.borrow().get(&self.get_node_key(key_bytes))
     }
 
-    fn len_add_one(&mut self) -> ProtocolResult<()> {
-        self.len += 1;
+    fn set_node(&self, key_bytes: &Bytes, node: &MapNode) -> ProtocolResult<()> {
         self.state
             .borrow_mut()
-            .insert(self.len_key.clone(), self.len.encode_fixed()?)
+            .insert(self.get_node_key(key_bytes), node.clone())
     }
 
-    fn len_sub_one(&mut self) -> ProtocolResult<()> {
-        self.len -= 1;
+    fn get_pointer(&self, pointer_key: &Bytes) -> ProtocolResult<Option<Bytes>> {
+        let pointer: Option<MapPointer> = self.state.borrow().get(pointer_key)?;
+        Ok(pointer.and_then(|p| p.0))
+    }
+
+    fn set_pointer(&self, pointer_key: &Bytes, key_bytes: Option<&Bytes>) -> ProtocolResult<()> {
         self.state
             .borrow_mut()
-            .insert(self.len_key.clone(), self.len.encode_fixed()?)
+            .insert(pointer_key.clone(), MapPointer(key_bytes.cloned()))
     }
 
-    fn recover_all_buckets(&self) {
-        let idxs = self
-            .keys
-            .borrow()
-            .is_recovered
-            .iter()
-            .enumerate()
-            .filter_map(|(i, &res)| if !res { Some(i) } else { None })
-            .collect::<Vec<_>>();
-
-        let opt_bytes = idxs
-            .iter()
-            .map(|idx| {
-                let name = self.get_bucket_name(*idx);
-                self.state.borrow().get(&name).unwrap()
-            })
-            .collect::<Vec<_>>();
+    fn get_value_key(&self, key_bytes: &Bytes) -> Bytes {
+        get_hashed_key(&self.var_name, VALUE_TAG, key_bytes)
+    }
 
-        let buckets = opt_bytes
-            .into_par_iter()
-            .map(|bytes| {
-                if let Some(bs) = bytes {
-                    <_>::decode_fixed(bs).expect("Decode bucket failed")
-                } else {
-                    Bucket::new()
-                }
-            })
-            .collect::<Vec<_>>();
+    fn get_node_key(&self, key_bytes: &Bytes) -> Bytes {
+        get_hashed_key(&self.var_name, NODE_TAG, key_bytes)
+    }
 
-        for (idx, bkt) in idxs.into_iter().zip(buckets.into_iter()) {
-            self.keys.borrow_mut().recover_bucket(idx, bkt);
-        }
+    fn len_add_one(&mut self) -> ProtocolResult<()> {
+        self.len += 1;
+        self.state
+            .borrow_mut()
+            .insert(self.len_key.clone(), self.len.encode_fixed()?)
     }
 
-    #[cfg(test)]
-    fn get_buckets(self) -> FixedBuckets<K> {
-        self.keys.into_inner()
+    fn len_sub_one(&mut self) -> ProtocolResult<()> {
+        self.len -= 1;
+        self.state
+            .borrow_mut()
+            .insert(self.len_key.clone(), self.len.encode_fixed()?)
     }
 }
 
@@ -200,7 +281,7 @@ impl<S, K, V> StoreMap<K, V> for DefaultStoreMap<S, K, V>
 where
     S: 'static + ServiceState,
     K: 'static + Send + FixedCodec + Clone + PartialEq,
-    V: 'static + FixedCodec,
+    V: 'static + FixedCodec + Clone,
 {
     fn get(&self, key: &K) -> Option<V> {
         self.inner_get(key)
@@ -218,81 +299,151 @@ where
     }
 
     fn contains(&self, key: &K) -> bool {
-        if let Ok(bytes) = key.encode_fixed() {
-            self.inner_contains(get_bucket_index(&bytes), &key)
-                .unwrap_or(false)
-        } else {
-            false
-        }
+        self.inner_contains(key).unwrap_or(false)
     }
 
     fn len(&self) -> u32 {
-        self.len
+        self.len + self.legacy.as_ref().map_or(0, |legacy| legacy.len())
     }
 
     fn is_empty(&self) -> bool {
-        self.len == 0
+        self.len() == 0
     }
 
     fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (K, V)> + 'a> {
-        self.recover_all_buckets();
-        Box::new(NewMapIter::<S, K, V>::new(0, self))
+        let head = self
+            .get_pointer(&self.head_key)
+            .unwrap_or_else(|e| panic!("StoreMap iter failed: {}", e));
+        let linked = LinkedMapIter::<S, K, V>::new(head, self);
+
+        match self.legacy.as_ref() {
+            Some(legacy) => Box::new(linked.chain(legacy.iter())),
+            None => Box::new(linked),
+        }
+    }
+
+    fn migrate(&mut self, limit: u32) -> bool {
+        self.inner_migrate(limit)
+            .unwrap_or_else(|e| panic!("StoreMap migrate failed: {}", e))
     }
 }
 
-pub struct NewMapIter<
+pub struct LinkedMapIter<
     'a,
     S: 'static + ServiceState,
     K: 'static + FixedCodec + PartialEq,
-    V: 'static + FixedCodec,
+    V: 'static + FixedCodec + Clone,
 > {
-    idx: u32,
-    map: &'a DefaultStoreMap<S, K, V>,
+    next: Option<Bytes>,
+    map:  &'a DefaultStoreMap<S, K, V>,
 }
 
-impl<'a, S, K, V> NewMapIter<'a, S, K, V>
+impl<'a, S, K, V> LinkedMapIter<'a, S, K, V>
 where
     S: 'static + ServiceState,
     K: 'static + FixedCodec + PartialEq,
-    V: 'static + FixedCodec,
+    V: 'static + FixedCodec + Clone,
 {
-    pub fn new(idx: u32, map: &'a DefaultStoreMap<S, K, V>) -> Self {
-        Self { idx, map }
+    pub fn new(head: Option<Bytes>, map: &'a DefaultStoreMap<S, K, V>) -> Self {
+        Self { next: head, map }
     }
 }
 
-impl<'a, S, K, V> Iterator for NewMapIter<'a, S, K, V>
+impl<'a, S, K, V> Iterator for LinkedMapIter<'a, S, K, V>
 where
     S: 'static + ServiceState,
     K: 'static + Send + FixedCodec + Clone + PartialEq,
-    V: 'static + FixedCodec,
+    V: 'static + FixedCodec + Clone,
 {
     type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let idx = self.idx;
-        if idx >= self.map.len {
-            return None;
-        }
+        let key_bytes = self.next.take()?;
+        let node = self
+            .map
+            .get_node(&key_bytes)
+            .expect("get node should not fail")
+            .expect("linked node should be existed");
+        self.next = node.next;
+
+        let key = K::decode_fixed(key_bytes).expect("decode key should not fail");
+        let value = self.map.get(&key).expect("get value should not fail");
+        Some((key, value))
+    }
+}
 
-        for i in 0..16 {
-            let (left, right) = self.map.keys.borrow().get_abs_index_interval(i);
-            if left <= idx && idx < right {
-                let index = idx - left;
-                let key = self.map.keys.borrow().keys_bucket[i]
-                    .0
-                    .get(index as usize)
-                    .cloned()
-                    .expect("get key should not fail");
-
-                self.idx += 1;
-                return Some((
-                    key.clone(),
-                    self.map.get(&key).expect("get value should not fail"),
-                ));
-            }
-        }
-        None
+/// Neighbours of a key in the linked list, both are encoded keys.
+#[derive(Clone)]
+struct MapNode {
+    prev: Option<Bytes>,
+    next: Option<Bytes>,
+}
+
+/// Head or tail of the linked list, none if the map is empty.
+#[derive(Clone)]
+struct MapPointer(Option<Bytes>);
+
+fn append_opt_bytes(s: &mut rlp::RlpStream, bytes: &Option<Bytes>) {
+    match bytes {
+        Some(bytes) => s.begin_list(1).append(&bytes.to_vec()),
+        None => s.begin_list(0),
+    };
+}
+
+fn decode_opt_bytes(r: &rlp::Rlp) -> Result<Option<Bytes>, rlp::DecoderError> {
+    if r.item_count()? == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(Bytes::from(r.val_at::<Vec<u8>>(0)?)))
+    }
+}
+
+impl rlp::Encodable for MapNode {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        append_opt_bytes(s, &self.prev);
+        append_opt_bytes(s, &self.next);
+    }
+}
+
+impl rlp::Decodable for MapNode {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(MapNode {
+            prev: decode_opt_bytes(&r.at(0)?)?,
+            next: decode_opt_bytes(&r.at(1)?)?,
+        })
+    }
+}
+
+impl FixedCodec for MapNode {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Encodable for MapPointer {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        append_opt_bytes(s, &self.0);
+    }
+}
+
+impl rlp::Decodable for MapPointer {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Ok(MapPointer(decode_opt_bytes(r)?))
+    }
+}
+
+impl FixedCodec for MapPointer {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
     }
 }
 
@@ -301,53 +452,127 @@ mod tests {
     use std::sync::Arc;
 
     use cita_trie::MemoryDB;
-    use rand::random;
 
     use crate::binding::state::{GeneralServiceState, MPTTrie};
-    use crate::binding::store::map::DefaultStoreMap;
 
     use super::*;
 
-    fn gen_bytes() -> Bytes {
-        Bytes::from((0..16).map(|_| random::<u8>()).collect::<Vec<_>>())
+    #[test]
+    fn test_link_and_unlink() {
+        let state = Rc::new(RefCell::new(GeneralServiceState::new(MPTTrie::new(
+            Arc::new(MemoryDB::new(false)),
+        ))));
+        let mut map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+
+        for i in 0..5u64 {
+            map.insert(i, i * 10);
+        }
+
+        // remove the head, a middle key and the tail
+        assert_eq!(map.remove(&0), Some(0));
+        assert_eq!(map.remove(&2), Some(20));
+        assert_eq!(map.remove(&4), Some(40));
+        assert_eq!(map.remove(&4), None);
+        map.insert(5, 50);
+
+        let map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![
+            (1, 10),
+            (3, 30),
+            (5, 50)
+        ]);
     }
 
     #[test]
-    fn test_map_and_bucket() {
+    fn test_key_namespaces() {
         let state = Rc::new(RefCell::new(GeneralServiceState::new(MPTTrie::new(
             Arc::new(MemoryDB::new(false)),
         ))));
         let mut map = DefaultStoreMap::<_, Bytes, Bytes>::new(Rc::clone(&state), "test");
-        let key_1 = gen_bytes();
-        let val_1 = gen_bytes();
-        let key_2 = gen_bytes();
-        let val_2 = gen_bytes();
-        let key_idx_1 = get_bucket_index(&key_1.encode_fixed().unwrap());
-        let key_idx_2 = get_bucket_index(&key_2.encode_fixed().unwrap());
-
-        map.insert(key_1, val_1);
-        map.insert(key_2, val_2);
-
-        assert_eq!(map.len(), 2);
-
-        let fbkt = map.get_buckets();
-        assert!(fbkt.is_recovered[key_idx_1]);
-        assert!(fbkt.is_recovered[key_idx_2]);
-        assert_eq!(fbkt.len(), 2);
-
-        let max = key_idx_1.max(key_idx_2);
-        let min = key_idx_1.min(key_idx_2);
-        let res = (0..17)
-            .map(|i| {
-                if i > max {
-                    2u32
-                } else if i > min {
-                    1u32
-                } else {
-                    0u32
-                }
-            })
+
+        // value key of "_node_a" used to be the node key of "a"
+        map.insert(Bytes::from("a"), Bytes::from("1"));
+        map.insert(Bytes::from("_node_a"), Bytes::from("2"));
+        map.insert(Bytes::from("_map_head"), Bytes::from("3"));
+
+        let map = DefaultStoreMap::<_, Bytes, Bytes>::new(Rc::clone(&state), "test");
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![
+            (Bytes::from("a"), Bytes::from("1")),
+            (Bytes::from("_node_a"), Bytes::from("2")),
+            (Bytes::from("_map_head"), Bytes::from("3")),
+        ]);
+    }
+
+    #[test]
+    fn test_migrate_bucket_map() {
+        let state = Rc::new(RefCell::new(GeneralServiceState::new(MPTTrie::new(
+            Arc::new(MemoryDB::new(false)),
+        ))));
+
+        let mut bucket_map = BucketStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        for i in 0..20u64 {
+            bucket_map.insert(i, i);
+        }
+
+        // writes before migration don't touch the legacy entries
+        let mut map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(map.legacy.is_some());
+        assert_eq!(map.get(&3), Some(3));
+        map.insert(3, 30);
+        map.insert(20, 20);
+        assert_eq!(map.remove(&4), Some(4));
+        assert_eq!(map.len(), 20);
+
+        let mut map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(!map.migrate(8));
+        assert_eq!(map.len(), 20);
+        assert_eq!(map.get(&3), Some(30));
+
+        let mut map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(!map.migrated);
+        assert!(map.migrate(100));
+        assert!(map.legacy.is_none());
+
+        let map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(map.migrated);
+        assert!(map.legacy.is_none());
+        assert_eq!(map.len(), 20);
+        assert_eq!(map.iter().next(), Some((20, 20)));
+
+        let mut entries = map.iter().collect::<Vec<_>>();
+        entries.sort();
+        let mut expected = (0..21u64)
+            .filter(|i| *i != 4)
+            .map(|i| (i, i))
             .collect::<Vec<_>>();
-        assert_eq!(fbkt.bucket_lens, res);
+        expected[3] = (3, 30);
+        assert_eq!(entries, expected);
+
+        let bucket_map = BucketStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(bucket_map.is_empty());
+        assert_eq!(bucket_map.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_skip_bucket_map_once_migrated() {
+        let state = Rc::new(RefCell::new(GeneralServiceState::new(MPTTrie::new(
+            Arc::new(MemoryDB::new(false)),
+        ))));
+
+        let mut map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(!map.migrated);
+        map.insert(1, 1);
+        assert!(map.migrated);
+
+        // buckets written after the flag are never probed
+        let mut bucket_map = BucketStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        bucket_map.insert(2, 2);
+
+        let map = DefaultStoreMap::<_, u64, u64>::new(Rc::clone(&state), "test");
+        assert!(map.legacy.is_none());
+        assert_eq!(map.get(&2), None);
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(1, 1)]);
     }
 }
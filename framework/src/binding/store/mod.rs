@@ -1,17 +1,29 @@
 mod array;
+mod bucket_map;
+mod deque;
 mod map;
+mod ordered_map;
 mod primitive;
+mod set;
 
 use bytes::Bytes;
 use derive_more::{Display, From};
 
 use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::Hash;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 pub use array::DefaultStoreArray;
+pub use bucket_map::BucketStoreMap;
+pub use deque::DefaultStoreDeque;
 pub use map::DefaultStoreMap;
-pub use primitive::{DefaultStoreBool, DefaultStoreString, DefaultStoreUint64};
+pub use ordered_map::DefaultStoreOrderedMap;
+pub use primitive::{
+    DefaultStoreBool, DefaultStoreString, DefaultStoreUint128, DefaultStoreUint64,
+};
+pub use set::DefaultStoreSet;
 
+#[derive(Clone)]
 pub struct FixedKeys<K: FixedCodec> {
     pub inner: Vec<K>,
 }
@@ -214,6 +226,18 @@ impl<K: FixedCodec + PartialEq> FixedCodec for Bucket<K> {
     }
 }
 
+/// State key of an entry of a store, the variable name, a one byte tag and
+/// the entry are hashed together, so entries of different kinds or of
+/// different variables never share a key.
+fn get_hashed_key(var_name: &str, tag: u8, entry: &[u8]) -> Bytes {
+    let mut bytes = (var_name.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(var_name.as_bytes());
+    bytes.push(tag);
+    bytes.extend_from_slice(entry);
+
+    Hash::digest(Bytes::from(bytes)).as_bytes()
+}
+
 #[inline(always)]
 fn get_bucket_index(bytes: &Bytes) -> usize {
     let len = bytes.len() - 1;
@@ -0,0 +1,485 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use bytes::Bytes;
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::traits::{ServiceState, StoreOrderedMap};
+use protocol::ProtocolResult;
+
+use crate::binding::store::{get_hashed_key, FixedKeys, StoreError};
+
+/// Maximum number of keys in a page, a full page is split into two halves.
+const PAGE_SIZE: usize = 64;
+
+const LEN_TAG: u8 = 0;
+const INDEX_TAG: u8 = 1;
+const PAGE_TAG: u8 = 2;
+const VALUE_TAG: u8 = 3;
+
+/// Sorted keys are split into pages of at most `PAGE_SIZE` keys, and an
+/// index records the first key of every page. An insert or remove rewrites
+/// one page, the index is only rewritten when a page is split, dropped or
+/// its first key changes. Empty pages are dropped, but pages are never
+/// merged.
+pub struct DefaultStoreOrderedMap<S: ServiceState, K: FixedCodec + Ord, V: FixedCodec> {
+    state:     Rc<RefCell<S>>,
+    var_name:  String,
+    index_key: Bytes,
+    // Loaded on first use
+    index:     RefCell<Option<OrderedIndex<K>>>,
+    len_key:   Bytes,
+    len:       u32,
+    phantom:   PhantomData<V>,
+}
+
+impl<S, K, V> DefaultStoreOrderedMap<S, K, V>
+where
+    S: 'static + ServiceState,
+    K: 'static + Send + FixedCodec + Clone + Ord,
+    V: 'static + FixedCodec + Clone,
+{
+    pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
+        let len_key = get_hashed_key(name, LEN_TAG, &[]);
+        let len = state
+            .borrow()
+            .get(&len_key)
+            .expect("Get len failed")
+            .unwrap_or(0u32);
+
+        Self {
+            state,
+            var_name: name.to_owned(),
+            index_key: get_hashed_key(name, INDEX_TAG, &[]),
+            index: RefCell::new(None),
+            len_key,
+            len,
+            phantom: PhantomData,
+        }
+    }
+
+    fn inner_get(&self, key: &K) -> ProtocolResult<Option<V>> {
+        self.state
+            .borrow()
+            .get(&self.get_value_key(&key.encode_fixed()?))
+    }
+
+    fn inner_contains(&self, key: &K) -> ProtocolResult<bool> {
+        self.state
+            .borrow()
+            .contains(&self.get_value_key(&key.encode_fixed()?))
+    }
+
+    fn inner_insert(&mut self, key: K, value: V) -> ProtocolResult<()> {
+        let value_key = self.get_value_key(&key.encode_fixed()?);
+
+        if !self.inner_contains(&key)? {
+            self.insert_key(key)?;
+            self.len += 1;
+            self.state
+                .borrow_mut()
+                .insert(self.len_key.clone(), self.len)?;
+        }
+
+        self.state.borrow_mut().insert(value_key, value)
+    }
+
+    fn inner_remove(&mut self, key: &K) -> ProtocolResult<Option<V>> {
+        let value = match self.inner_get(key)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        self.remove_key(key)?;
+        self.len -= 1;
+        self.state
+            .borrow_mut()
+            .insert(self.len_key.clone(), self.len)?;
+        self.state
+            .borrow_mut()
+            .remove(&self.get_value_key(&key.encode_fixed()?))?;
+
+        Ok(Some(value))
+    }
+
+    fn insert_key(&mut self, key: K) -> ProtocolResult<()> {
+        self.load_index()?;
+        let mut opt_index = self.index.borrow_mut();
+        let index = opt_index.as_mut().expect("index should be loaded");
+
+        if index.pages.is_empty() {
+            let id = index.alloc_page_id();
+            self.set_page(id, vec![key.clone()])?;
+            index.pages.push(PageRef { first: key, id });
+
+            return self.set_index(index);
+        }
+
+        let pos = index.find_page(&key);
+        let id = index.pages[pos].id;
+        let mut keys = self.get_page(id)?;
+        let at = match keys.binary_search(&key) {
+            Ok(_) => return Ok(()),
+            Err(at) => at,
+        };
+        keys.insert(at, key.clone());
+
+        let mut index_changed = false;
+        if at == 0 {
+            index.pages[pos].first = key;
+            index_changed = true;
+        }
+
+        if keys.len() > PAGE_SIZE {
+            let right = keys.split_off(keys.len() / 2);
+            let right_id = index.alloc_page_id();
+            index.pages.insert(pos + 1, PageRef {
+                first: right[0].clone(),
+                id:    right_id,
+            });
+            self.set_page(right_id, right)?;
+            index_changed = true;
+        }
+        self.set_page(id, keys)?;
+
+        if index_changed {
+            self.set_index(index)?;
+        }
+        Ok(())
+    }
+
+    fn remove_key(&mut self, key: &K) -> ProtocolResult<()> {
+        self.load_index()?;
+        let mut opt_index = self.index.borrow_mut();
+        let index = opt_index.as_mut().expect("index should be loaded");
+
+        if index.pages.is_empty() {
+            return Err(StoreError::GetNone.into());
+        }
+
+        let pos = index.find_page(key);
+        let id = index.pages[pos].id;
+        let mut keys = self.get_page(id)?;
+        let at = keys.binary_search(key).map_err(|_| StoreError::GetNone)?;
+        keys.remove(at);
+
+        if keys.is_empty() {
+            index.pages.remove(pos);
+            self.state.borrow_mut().remove(&self.get_page_key(id))?;
+            return self.set_index(index);
+        }
+
+        if at == 0 {
+            index.pages[pos].first = keys[0].clone();
+            self.set_index(index)?;
+        }
+        self.set_page(id, keys)
+    }
+
+    fn load_index(&self) -> ProtocolResult<()> {
+        if self.index.borrow().is_some() {
+            return Ok(());
+        }
+
+        let index = self
+            .state
+            .borrow()
+            .get(&self.index_key)?
+            .unwrap_or_else(|| OrderedIndex {
+                next_page_id: 0,
+                pages:        Vec::new(),
+            });
+        *self.index.borrow_mut() = Some(index);
+
+        Ok(())
+    }
+
+    fn set_index(&self, index: &OrderedIndex<K>) -> ProtocolResult<()> {
+        self.state
+            .borrow_mut()
+            .insert(self.index_key.clone(), index.clone())
+    }
+
+    // Page ids of the index in order, `from` skips pages which only hold
+    // smaller keys.
+    fn page_ids(&self, from: Option<&K>) -> ProtocolResult<Vec<u64>> {
+        self.load_index()?;
+        let opt_index = self.index.borrow();
+        let index = opt_index.as_ref().expect("index should be loaded");
+
+        let start = match from {
+            Some(from) if !index.pages.is_empty() => index.find_page(from),
+            _ => 0,
+        };
+
+        Ok(index.pages[start..].iter().map(|page| page.id).collect())
+    }
+
+    fn get_page(&self, id: u64) -> ProtocolResult<Vec<K>> {
+        let keys: Option<FixedKeys<K>> = self.state.borrow().get(&self.get_page_key(id))?;
+        Ok(keys.map(|keys| keys.inner).unwrap_or_default())
+    }
+
+    fn set_page(&self, id: u64, keys: Vec<K>) -> ProtocolResult<()> {
+        self.state
+            .borrow_mut()
+            .insert(self.get_page_key(id), FixedKeys { inner: keys })
+    }
+
+    fn get_page_key(&self, id: u64) -> Bytes {
+        get_hashed_key(&self.var_name, PAGE_TAG, &id.to_be_bytes())
+    }
+
+    fn get_value_key(&self, key_bytes: &Bytes) -> Bytes {
+        get_hashed_key(&self.var_name, VALUE_TAG, key_bytes)
+    }
+
+    fn entry(&self, key: K) -> (K, V) {
+        let value = self
+            .get(&key)
+            .expect("value of indexed key should not be none");
+        (key, value)
+    }
+}
+
+impl<S, K, V> StoreOrderedMap<K, V> for DefaultStoreOrderedMap<S, K, V>
+where
+    S: 'static + ServiceState,
+    K: 'static + Send + FixedCodec + Clone + Ord,
+    V: 'static + FixedCodec + Clone,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        self.inner_get(key)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap get failed: {}", e))
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.inner_contains(key).unwrap_or(false)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.inner_insert(key, value)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap insert failed: {}", e));
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.inner_remove(key)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap remove failed: {}", e))
+    }
+
+    fn len(&self) -> u32 {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn first(&self) -> Option<(K, V)> {
+        let ids = self
+            .page_ids(None)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap first failed: {}", e));
+        let id = ids.first()?;
+
+        let keys = self
+            .get_page(*id)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap first failed: {}", e));
+        keys.into_iter().next().map(|key| self.entry(key))
+    }
+
+    fn last(&self) -> Option<(K, V)> {
+        let ids = self
+            .page_ids(None)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap last failed: {}", e));
+        let id = ids.last()?;
+
+        let mut keys = self
+            .get_page(*id)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap last failed: {}", e));
+        keys.pop().map(|key| self.entry(key))
+    }
+
+    fn range<'a>(
+        &'a self,
+        from: Option<K>,
+        to: Option<K>,
+    ) -> Box<dyn Iterator<Item = (K, V)> + 'a> {
+        let page_ids = self
+            .page_ids(from.as_ref())
+            .unwrap_or_else(|e| panic!("StoreOrderedMap range failed: {}", e));
+
+        Box::new(OrderedMapIter {
+            map: self,
+            page_ids,
+            page_pos: 0,
+            keys: Vec::new().into_iter(),
+            from,
+            to,
+        })
+    }
+
+    fn page(&self, offset: u32, limit: u32) -> Vec<(K, V)> {
+        let page_ids = self
+            .page_ids(None)
+            .unwrap_or_else(|e| panic!("StoreOrderedMap page failed: {}", e));
+
+        let mut offset = offset as usize;
+        let mut entries = Vec::new();
+        for id in page_ids {
+            if entries.len() >= limit as usize {
+                break;
+            }
+
+            let keys = self
+                .get_page(id)
+                .unwrap_or_else(|e| panic!("StoreOrderedMap page failed: {}", e));
+            if offset >= keys.len() {
+                offset -= keys.len();
+                continue;
+            }
+
+            let take = limit as usize - entries.len();
+            entries.extend(
+                keys.into_iter()
+                    .skip(offset)
+                    .take(take)
+                    .map(|key| self.entry(key)),
+            );
+            offset = 0;
+        }
+
+        entries
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (K, V)> + 'a> {
+        self.range(None, None)
+    }
+}
+
+pub struct OrderedMapIter<
+    'a,
+    S: 'static + ServiceState,
+    K: 'static + FixedCodec + Ord,
+    V: 'static + FixedCodec + Clone,
+> {
+    map:      &'a DefaultStoreOrderedMap<S, K, V>,
+    page_ids: Vec<u64>,
+    page_pos: usize,
+    keys:     std::vec::IntoIter<K>,
+    from:     Option<K>,
+    to:       Option<K>,
+}
+
+impl<'a, S, K, V> Iterator for OrderedMapIter<'a, S, K, V>
+where
+    S: 'static + ServiceState,
+    K: 'static + Send + FixedCodec + Clone + Ord,
+    V: 'static + FixedCodec + Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(key) = self.keys.next() {
+                if self.from.as_ref().map_or(false, |from| &key < from) {
+                    continue;
+                }
+                if self.to.as_ref().map_or(false, |to| &key >= to) {
+                    self.page_pos = self.page_ids.len();
+                    self.keys = Vec::new().into_iter();
+                    return None;
+                }
+
+                return Some(self.map.entry(key));
+            }
+
+            let id = *self.page_ids.get(self.page_pos)?;
+            self.page_pos += 1;
+            self.keys = self
+                .map
+                .get_page(id)
+                .expect("get page should not fail")
+                .into_iter();
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PageRef<K> {
+    first: K,
+    id:    u64,
+}
+
+#[derive(Clone)]
+struct OrderedIndex<K> {
+    next_page_id: u64,
+    pages:        Vec<PageRef<K>>,
+}
+
+impl<K: Ord> OrderedIndex<K> {
+    fn alloc_page_id(&mut self) -> u64 {
+        let id = self.next_page_id;
+        self.next_page_id += 1;
+        id
+    }
+
+    // The last page whose first key is not greater than the key, or the first
+    // page if the key is smaller than all. Pages must not be empty.
+    fn find_page(&self, key: &K) -> usize {
+        match self.pages.binary_search_by(|page| page.first.cmp(key)) {
+            Ok(pos) => pos,
+            Err(0) => 0,
+            Err(pos) => pos - 1,
+        }
+    }
+}
+
+impl<K: FixedCodec> rlp::Encodable for OrderedIndex<K> {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2).append(&self.next_page_id);
+        s.begin_list(self.pages.len());
+
+        for page in self.pages.iter() {
+            let first = page
+                .first
+                .encode_fixed()
+                .expect("encode should not fail")
+                .to_vec();
+            s.begin_list(2).append(&first).append(&page.id);
+        }
+    }
+}
+
+impl<K: FixedCodec> rlp::Decodable for OrderedIndex<K> {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let next_page_id = r.val_at(0)?;
+
+        let mut pages = Vec::new();
+        for page in r.at(1)?.iter() {
+            let first: Vec<u8> = page.val_at(0)?;
+            let first = <_>::decode_fixed(Bytes::from(first))
+                .map_err(|_| rlp::DecoderError::Custom("decode K from bytes fail"))?;
+
+            pages.push(PageRef {
+                first,
+                id: page.val_at(1)?,
+            });
+        }
+
+        Ok(OrderedIndex {
+            next_page_id,
+            pages,
+        })
+    }
+}
+
+impl<K: FixedCodec> FixedCodec for OrderedIndex<K> {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
@@ -3,7 +3,7 @@ use std::rc::Rc;
 
 use bytes::Bytes;
 
-use protocol::traits::{ServiceState, StoreBool, StoreString, StoreUint64};
+use protocol::traits::{ServiceState, StoreBool, StoreString, StoreUint128, StoreUint64};
 use protocol::types::Hash;
 use protocol::ProtocolResult;
 
@@ -217,6 +217,173 @@ impl<S: ServiceState> StoreUint64 for DefaultStoreUint64<S> {
     }
 }
 
+pub struct DefaultStoreUint128<S: ServiceState> {
+    state: Rc<RefCell<S>>,
+    key:   Hash,
+}
+
+impl<S: ServiceState> DefaultStoreUint128<S> {
+    pub fn new(state: Rc<RefCell<S>>, var_name: &str) -> Self {
+        Self {
+            state,
+            key: Hash::digest(Bytes::from(var_name.to_owned() + "uint128")),
+        }
+    }
+
+    fn inner_get(&self) -> u128 {
+        let u: Option<u128> = self
+            .state
+            .borrow()
+            .get(&self.key)
+            .unwrap_or_else(|e| panic!("StoreUint128 get failed: {}", e));
+
+        match u {
+            Some(v) => v,
+            None => {
+                self.state
+                    .borrow_mut()
+                    .insert(self.key.clone(), 0u128)
+                    .unwrap_or_else(|e| panic!("StoreUint128 get failed: {}", e));
+                0
+            }
+        }
+    }
+
+    fn inner_set(&mut self, val: u128) {
+        self.state
+            .borrow_mut()
+            .insert(self.key.clone(), val)
+            .unwrap_or_else(|e| panic!("StoreUint128 set failed: {}", e));
+    }
+
+    // Add val with self
+    // And set the result back to self
+    fn inner_add(&mut self, val: u128) -> bool {
+        let sv = self.inner_get();
+
+        match val.overflowing_add(sv) {
+            (sum, false) => {
+                self.inner_set(sum);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    // Self minus val
+    // And set the result back to self
+    fn inner_sub(&mut self, val: u128) -> bool {
+        let sv = self.inner_get();
+
+        if sv >= val {
+            self.inner_set(sv - val);
+            false
+        } else {
+            true
+        }
+    }
+
+    // Multiply val with self
+    // And set the result back to self
+    fn inner_mul(&mut self, val: u128) -> bool {
+        let sv = self.inner_get();
+
+        match val.overflowing_mul(sv) {
+            (mul, false) => {
+                self.inner_set(mul);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    // Power of self
+    // And set the result back to self
+    fn inner_pow(&mut self, val: u32) -> bool {
+        let sv = self.inner_get();
+
+        match sv.overflowing_pow(val) {
+            (pow, false) => {
+                self.inner_set(pow);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    // Self divided by val
+    // And set the result back to self
+    fn inner_div(&mut self, val: u128) -> bool {
+        let sv = self.inner_get();
+
+        if let 0 = val {
+            true
+        } else {
+            self.inner_set(sv / val);
+            false
+        }
+    }
+
+    // Remainder of self
+    // And set the result back to self
+    fn inner_rem(&mut self, val: u128) -> bool {
+        let sv = self.inner_get();
+
+        if let 0 = val {
+            true
+        } else {
+            self.inner_set(sv % val);
+            false
+        }
+    }
+}
+
+impl<S: ServiceState> StoreUint128 for DefaultStoreUint128<S> {
+    fn get(&self) -> u128 {
+        self.inner_get()
+    }
+
+    fn set(&mut self, val: u128) {
+        self.inner_set(val);
+    }
+
+    // Add val with self
+    // And set the result back to self
+    fn safe_add(&mut self, val: u128) -> bool {
+        self.inner_add(val)
+    }
+
+    // Self minus val
+    // And set the result back to self
+    fn safe_sub(&mut self, val: u128) -> bool {
+        self.inner_sub(val)
+    }
+
+    // Multiply val with self
+    // And set the result back to self
+    fn safe_mul(&mut self, val: u128) -> bool {
+        self.inner_mul(val)
+    }
+
+    // Power of self
+    // And set the result back to self
+    fn safe_pow(&mut self, val: u32) -> bool {
+        self.inner_pow(val)
+    }
+
+    // Self divided by val
+    // And set the result back to self
+    fn safe_div(&mut self, val: u128) -> bool {
+        self.inner_div(val)
+    }
+
+    // Remainder of self
+    // And set the result back to self
+    fn safe_rem(&mut self, val: u128) -> bool {
+        self.inner_rem(val)
+    }
+}
+
 pub struct DefaultStoreString<S: ServiceState> {
     state: Rc<RefCell<S>>,
     key:   Hash,
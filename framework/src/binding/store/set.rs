@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{ServiceState, StoreMap, StoreSet};
+
+use crate::binding::store::DefaultStoreMap;
+
+/// Elements are keys of a `DefaultStoreMap`, so iteration follows the
+/// insertion order.
+pub struct DefaultStoreSet<S: ServiceState, E: FixedCodec + PartialEq> {
+    map: DefaultStoreMap<S, E, bool>,
+}
+
+impl<S, E> DefaultStoreSet<S, E>
+where
+    S: 'static + ServiceState,
+    E: 'static + Send + FixedCodec + Clone + PartialEq,
+{
+    pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
+        Self {
+            map: DefaultStoreMap::new(state, &(name.to_owned() + "_set")),
+        }
+    }
+}
+
+impl<S, E> StoreSet<E> for DefaultStoreSet<S, E>
+where
+    S: 'static + ServiceState,
+    E: 'static + Send + FixedCodec + Clone + PartialEq,
+{
+    fn contains(&self, element: &E) -> bool {
+        self.map.contains(element)
+    }
+
+    fn insert(&mut self, element: E) -> bool {
+        if self.map.contains(&element) {
+            return false;
+        }
+
+        self.map.insert(element, true);
+        true
+    }
+
+    fn remove(&mut self, element: &E) -> bool {
+        self.map.remove(element).is_some()
+    }
+
+    fn len(&self) -> u32 {
+        self.map.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = E> + 'a> {
+        Box::new(self.map.iter().map(|(element, _)| element))
+    }
+}
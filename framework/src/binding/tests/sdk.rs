@@ -8,7 +8,7 @@ use cita_trie::MemoryDB;
 
 use protocol::traits::{Context, NoopDispatcher, ServiceResponse, ServiceSDK, Storage};
 use protocol::types::{
-    Address, Block, BlockHeader, Event, Hash, MerkleRoot, Proof, RawTransaction, Receipt,
+    Address, Block, BlockHeader, Event, Evidence, Hash, MerkleRoot, Proof, RawTransaction, Receipt,
     ReceiptResponse, SignedTransaction, TransactionRequest, Validator,
 };
 use protocol::ProtocolResult;
@@ -43,6 +43,11 @@ fn test_service_sdk() {
     sdk_uint64.set(99);
     assert_eq!(sdk_uint64.get(), 99);
 
+    // test sdk store uint128
+    let mut sdk_uint128 = sdk.alloc_or_recover_uint128("test_uint128");
+    sdk_uint128.set(u128::max_value());
+    assert_eq!(sdk_uint128.get(), u128::max_value());
+
     // test sdk map
     let mut sdk_map = sdk.alloc_or_recover_map::<Hash, Bytes>("test_map");
     assert_eq!(sdk_map.is_empty(), true);
@@ -186,6 +191,14 @@ impl Storage for MockStorage {
     async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
         Err(StoreError::GetNone.into())
     }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        Ok(vec![])
+    }
 }
 
 // #####################
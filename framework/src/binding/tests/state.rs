@@ -1,8 +1,14 @@
+extern crate test;
+
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use bytes::Bytes;
 use cita_trie::MemoryDB;
+use test::Bencher;
 
+use asset::types::{Amount, AssetBalance};
+use protocol::fixed_codec::FixedCodec;
 use protocol::traits::ServiceState;
 use protocol::types::{Address, Hash, MerkleRoot};
 
@@ -56,6 +62,150 @@ fn test_state_account() {
     assert_eq!(val, value);
 }
 
+#[test]
+fn test_state_remove() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let mut state = new_state(Arc::clone(&memdb), None);
+
+    let key = Hash::digest(Bytes::from("key".to_owned()));
+    let value = Hash::digest(Bytes::from("value".to_owned()));
+    state.insert(key.clone(), value.clone()).unwrap();
+    state.stash().unwrap();
+    let root = state.commit().unwrap();
+
+    // test removal in cache shadows the committed value until reverted
+    state.remove(&key).unwrap();
+    assert_eq!(state.contains(&key).unwrap(), false);
+    assert_eq!(state.get::<_, Hash>(&key).unwrap(), None);
+
+    state.revert_cache().unwrap();
+    let val: Hash = state.get(&key).unwrap().unwrap();
+    assert_eq!(val, value);
+
+    // test removal in stash
+    state.remove(&key).unwrap();
+    state.stash().unwrap();
+    assert_eq!(state.contains(&key).unwrap(), false);
+
+    let new_root = state.commit().unwrap();
+    assert_ne!(new_root, root);
+    assert_eq!(new_root, Hash::from_empty());
+
+    let new_state = new_state(Arc::clone(&memdb), Some(new_root));
+    assert_eq!(new_state.contains(&key).unwrap(), false);
+    assert_eq!(new_state.get::<_, Hash>(&key).unwrap(), None);
+}
+
+#[test]
+fn test_state_savepoint() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let mut state = new_state(Arc::clone(&memdb), None);
+
+    let key_a = Hash::digest(Bytes::from("a".to_owned()));
+    let key_b = Hash::digest(Bytes::from("b".to_owned()));
+    let key_c = Hash::digest(Bytes::from("c".to_owned()));
+    let value = Hash::digest(Bytes::from("value".to_owned()));
+
+    state.insert(key_a.clone(), value.clone()).unwrap();
+    state.savepoint();
+    state.insert(key_b.clone(), value.clone()).unwrap();
+    state.remove(&key_a).unwrap();
+
+    // test reverting the inner savepoint keeps the outer one
+    state.savepoint();
+    state.insert(key_c.clone(), value.clone()).unwrap();
+    assert_eq!(state.savepoint_depth(), 2);
+    state.revert_savepoint();
+    assert_eq!(state.contains(&key_c).unwrap(), false);
+    assert_eq!(state.contains(&key_b).unwrap(), true);
+
+    state.commit_savepoint();
+    assert_eq!(state.savepoint_depth(), 0);
+    assert_eq!(state.contains(&key_a).unwrap(), false);
+    let val: Hash = state.get(&key_b).unwrap().unwrap();
+    assert_eq!(val, value);
+
+    // test stash commits savepoints left open
+    state.savepoint();
+    state.insert(key_c.clone(), value.clone()).unwrap();
+    state.stash().unwrap();
+    assert_eq!(state.savepoint_depth(), 0);
+    assert_eq!(state.contains(&key_c).unwrap(), true);
+
+    // test revert cache drops savepoints
+    state.savepoint();
+    state.remove(&key_c).unwrap();
+    state.revert_cache().unwrap();
+    assert_eq!(state.savepoint_depth(), 0);
+    assert_eq!(state.contains(&key_c).unwrap(), true);
+}
+
+const BENCH_ALLOWANCE_SIZE: u64 = 100;
+
+#[bench]
+fn bench_get_account_value(b: &mut Bencher) {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let mut state = new_state(Arc::clone(&memdb), None);
+
+    let (address, asset_id) = mock_balance_key();
+    state
+        .set_account_value(&address, asset_id.clone(), mock_balance())
+        .unwrap();
+    state.stash().unwrap();
+    state.commit().unwrap();
+
+    b.iter(|| {
+        let balance: AssetBalance = state
+            .get_account_value(&address, &asset_id)
+            .unwrap()
+            .unwrap();
+        test::black_box(balance)
+    });
+}
+
+// Decode on every read, which is what `get_account_value` did before values
+// were cached decoded.
+#[bench]
+fn bench_get_account_value_decode(b: &mut Bencher) {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let mut state = new_state(Arc::clone(&memdb), None);
+
+    let (address, asset_id) = mock_balance_key();
+    state
+        .set_account_value(&address, asset_id.clone(), mock_balance())
+        .unwrap();
+    state.stash().unwrap();
+    state.commit().unwrap();
+
+    b.iter(|| {
+        let bytes: Bytes = state
+            .get_account_value(&address, &asset_id)
+            .unwrap()
+            .unwrap();
+        test::black_box(AssetBalance::decode_fixed(bytes).unwrap())
+    });
+}
+
+fn mock_balance_key() -> (Address, Hash) {
+    let address = Address::from_hash(Hash::digest(Bytes::from("owner"))).unwrap();
+    let asset_id = Hash::digest(Bytes::from("asset"));
+
+    (address, asset_id)
+}
+
+fn mock_balance() -> AssetBalance {
+    let mut allowance = BTreeMap::new();
+    for i in 0..BENCH_ALLOWANCE_SIZE {
+        let spender = Address::from_hash(Hash::digest(Bytes::from(i.to_be_bytes().to_vec())));
+        allowance.insert(spender.unwrap(), Amount::from(i));
+    }
+
+    AssetBalance {
+        value: Amount(1_000_000),
+        allowance,
+    }
+}
+
 pub fn new_state(memdb: Arc<MemoryDB>, root: Option<MerkleRoot>) -> GeneralServiceState<MemoryDB> {
     let trie = match root {
         Some(root) => MPTTrie::from(root, memdb).unwrap(),
@@ -1,15 +1,23 @@
+extern crate test;
+
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use bytes::Bytes;
 use cita_trie::MemoryDB;
+use test::Bencher;
 
-use protocol::traits::{StoreArray, StoreBool, StoreMap, StoreString, StoreUint64};
+use protocol::traits::{
+    StoreArray, StoreBool, StoreDeque, StoreMap, StoreOrderedMap, StoreSet, StoreString,
+    StoreUint128, StoreUint64,
+};
 use protocol::types::Hash;
 
 use crate::binding::store::{
-    DefaultStoreArray, DefaultStoreBool, DefaultStoreMap, DefaultStoreString, DefaultStoreUint64,
+    BucketStoreMap, DefaultStoreArray, DefaultStoreBool, DefaultStoreDeque, DefaultStoreMap,
+    DefaultStoreOrderedMap, DefaultStoreSet, DefaultStoreString, DefaultStoreUint128,
+    DefaultStoreUint64,
 };
 use crate::binding::tests::state::new_state;
 
@@ -57,6 +65,41 @@ fn test_default_store_uint64() {
     assert_eq!(su.get(), 4u64);
 }
 
+#[test]
+fn test_default_store_uint128() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = new_state(Arc::clone(&memdb), None);
+
+    let mut su = DefaultStoreUint128::new(Rc::new(RefCell::new(state)), "test");
+
+    assert_eq!(su.get(), 0u128);
+    su.set(u128::from(u64::max_value()));
+    assert_eq!(su.get(), u128::from(u64::max_value()));
+
+    assert_eq!(su.safe_add(1u128), false);
+    assert_eq!(su.get(), 1u128 << 64);
+
+    assert_eq!(su.safe_mul(1u128 << 63), false);
+    assert_eq!(su.get(), 1u128 << 127);
+
+    assert_eq!(su.safe_mul(2u128), true);
+    assert_eq!(su.get(), 1u128 << 127);
+
+    assert_eq!(su.safe_div(1u128 << 124), false);
+    assert_eq!(su.get(), 8u128);
+
+    assert_eq!(su.safe_sub(10u128), true);
+    assert_eq!(su.safe_sub(6u128), false);
+    assert_eq!(su.get(), 2u128);
+
+    assert_eq!(su.safe_pow(100u32), false);
+    assert_eq!(su.get(), 1u128 << 100);
+
+    assert_eq!(su.safe_rem(0u128), true);
+    assert_eq!(su.safe_rem(3u128), false);
+    assert_eq!(su.get(), (1u128 << 100) % 3);
+}
+
 #[test]
 fn test_default_store_string() {
     let memdb = Arc::new(MemoryDB::new(false));
@@ -92,11 +135,11 @@ fn test_default_store_map() {
         let mut it = sm.iter();
         assert_eq!(
             it.next().unwrap(),
-            (Hash::digest(Bytes::from("key_2")), Bytes::from("val_2"))
+            (Hash::digest(Bytes::from("key_1")), Bytes::from("val_1"))
         );
         assert_eq!(
             it.next().unwrap(),
-            (Hash::digest(Bytes::from("key_1")), Bytes::from("val_1"))
+            (Hash::digest(Bytes::from("key_2")), Bytes::from("val_2"))
         );
         assert_eq!(it.next().is_none(), true);
     }
@@ -153,3 +196,188 @@ fn test_default_store_array() {
     assert_eq!(sa.len(), 1u32);
     assert_eq!(sa.get(0u32).unwrap(), Bytes::from("222"));
 }
+
+#[test]
+fn test_default_store_ordered_map() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = new_state(Arc::clone(&memdb), None);
+    let rs = Rc::new(RefCell::new(state));
+
+    let mut som = DefaultStoreOrderedMap::<_, u64, u64>::new(Rc::clone(&rs), "test");
+    assert_eq!(som.first().is_none(), true);
+
+    // Insert in reverse order so that pages split on the left
+    for i in (0..200u64).rev() {
+        som.insert(i * 2, i);
+    }
+    assert_eq!(som.len(), 200u32);
+    assert_eq!(som.first().unwrap(), (0, 0));
+    assert_eq!(som.last().unwrap(), (398, 199));
+
+    let keys = som.iter().map(|(k, _)| k).collect::<Vec<_>>();
+    assert_eq!(keys, (0..200u64).map(|i| i * 2).collect::<Vec<_>>());
+
+    let range = som.range(Some(99), Some(105)).collect::<Vec<_>>();
+    assert_eq!(range, vec![(100, 50), (102, 51), (104, 52)]);
+
+    assert_eq!(som.page(130, 3), vec![(260, 130), (262, 131), (264, 132)]);
+    assert_eq!(som.page(199, 10), vec![(398, 199)]);
+
+    assert_eq!(som.remove(&0).unwrap(), 0);
+    assert_eq!(som.remove(&0).is_none(), true);
+    for i in 1..=100u64 {
+        som.remove(&(i * 2));
+    }
+
+    let som = DefaultStoreOrderedMap::<_, u64, u64>::new(Rc::clone(&rs), "test");
+    assert_eq!(som.len(), 99u32);
+    assert_eq!(som.contains(&200), false);
+    assert_eq!(som.first().unwrap(), (202, 101));
+    assert_eq!(som.range(None, Some(206)).count(), 2);
+}
+
+#[test]
+fn test_default_store_ordered_map_key_namespaces() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = new_state(Arc::clone(&memdb), None);
+    let rs = Rc::new(RefCell::new(state));
+
+    // Keys named after the length and index entries of the map
+    let mut som = DefaultStoreOrderedMap::<_, Bytes, Bytes>::new(Rc::clone(&rs), "test");
+    som.insert(Bytes::from("len"), Bytes::from("1"));
+    som.insert(Bytes::from("index"), Bytes::from("2"));
+    som.insert(Bytes::from("key"), Bytes::from("3"));
+
+    let som = DefaultStoreOrderedMap::<_, Bytes, Bytes>::new(Rc::clone(&rs), "test");
+    assert_eq!(som.len(), 3u32);
+    assert_eq!(som.iter().collect::<Vec<_>>(), vec![
+        (Bytes::from("index"), Bytes::from("2")),
+        (Bytes::from("key"), Bytes::from("3")),
+        (Bytes::from("len"), Bytes::from("1")),
+    ]);
+}
+
+#[test]
+fn test_default_store_set() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = new_state(Arc::clone(&memdb), None);
+    let rs = Rc::new(RefCell::new(state));
+
+    let mut ss = DefaultStoreSet::<_, Bytes>::new(Rc::clone(&rs), "test");
+
+    assert_eq!(ss.insert(Bytes::from("111")), true);
+    assert_eq!(ss.insert(Bytes::from("222")), true);
+    assert_eq!(ss.insert(Bytes::from("111")), false);
+    assert_eq!(ss.len(), 2u32);
+    assert_eq!(ss.contains(&Bytes::from("222")), true);
+
+    assert_eq!(ss.remove(&Bytes::from("111")), true);
+    assert_eq!(ss.remove(&Bytes::from("111")), false);
+
+    let ss = DefaultStoreSet::<_, Bytes>::new(Rc::clone(&rs), "test");
+    assert_eq!(ss.iter().collect::<Vec<_>>(), vec![Bytes::from("222")]);
+}
+
+#[test]
+fn test_default_store_deque() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = new_state(Arc::clone(&memdb), None);
+    let rs = Rc::new(RefCell::new(state));
+
+    let mut sd = DefaultStoreDeque::<_, Bytes>::new(Rc::clone(&rs), "test");
+    assert_eq!(sd.pop_front().is_none(), true);
+    assert_eq!(sd.back().is_none(), true);
+
+    sd.push_back(Bytes::from("222"));
+    sd.push_front(Bytes::from("111"));
+    sd.push_back(Bytes::from("333"));
+
+    assert_eq!(sd.len(), 3u32);
+    assert_eq!(sd.front().unwrap(), Bytes::from("111"));
+    assert_eq!(sd.back().unwrap(), Bytes::from("333"));
+    assert_eq!(sd.get(1).unwrap(), Bytes::from("222"));
+    assert_eq!(sd.get(3).is_none(), true);
+
+    assert_eq!(sd.pop_front().unwrap(), Bytes::from("111"));
+    assert_eq!(sd.pop_back().unwrap(), Bytes::from("333"));
+
+    let sd = DefaultStoreDeque::<_, Bytes>::new(Rc::clone(&rs), "test");
+    assert_eq!(sd.iter().collect::<Vec<_>>(), vec![(
+        0u32,
+        Bytes::from("222")
+    )]);
+}
+
+const BENCH_MAP_SIZE: u64 = 10_000;
+
+#[bench]
+fn bench_bucket_store_map_insert(b: &mut Bencher) {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let rs = Rc::new(RefCell::new(new_state(memdb, None)));
+
+    let mut sm = BucketStoreMap::<_, Hash, u64>::new(Rc::clone(&rs), "bench");
+    for i in 0..BENCH_MAP_SIZE {
+        sm.insert(Hash::digest(Bytes::from(i.to_le_bytes().to_vec())), i);
+    }
+
+    let mut i = BENCH_MAP_SIZE;
+    b.iter(|| {
+        sm.insert(Hash::digest(Bytes::from(i.to_le_bytes().to_vec())), i);
+        i += 1;
+    });
+}
+
+#[bench]
+fn bench_default_store_map_insert(b: &mut Bencher) {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let rs = Rc::new(RefCell::new(new_state(memdb, None)));
+
+    let mut sm = DefaultStoreMap::<_, Hash, u64>::new(Rc::clone(&rs), "bench");
+    for i in 0..BENCH_MAP_SIZE {
+        sm.insert(Hash::digest(Bytes::from(i.to_le_bytes().to_vec())), i);
+    }
+
+    let mut i = BENCH_MAP_SIZE;
+    b.iter(|| {
+        sm.insert(Hash::digest(Bytes::from(i.to_le_bytes().to_vec())), i);
+        i += 1;
+    });
+}
+
+#[bench]
+fn bench_bucket_store_map_remove(b: &mut Bencher) {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let rs = Rc::new(RefCell::new(new_state(memdb, None)));
+
+    let mut sm = BucketStoreMap::<_, Hash, u64>::new(Rc::clone(&rs), "bench");
+    for i in 0..BENCH_MAP_SIZE {
+        sm.insert(Hash::digest(Bytes::from(i.to_le_bytes().to_vec())), i);
+    }
+
+    let mut i = 0u64;
+    b.iter(|| {
+        let key = Hash::digest(Bytes::from(i.to_le_bytes().to_vec()));
+        sm.remove(&key);
+        sm.insert(key, i);
+        i = (i + 1) % BENCH_MAP_SIZE;
+    });
+}
+
+#[bench]
+fn bench_default_store_map_remove(b: &mut Bencher) {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let rs = Rc::new(RefCell::new(new_state(memdb, None)));
+
+    let mut sm = DefaultStoreMap::<_, Hash, u64>::new(Rc::clone(&rs), "bench");
+    for i in 0..BENCH_MAP_SIZE {
+        sm.insert(Hash::digest(Bytes::from(i.to_le_bytes().to_vec())), i);
+    }
+
+    let mut i = 0u64;
+    b.iter(|| {
+        let key = Hash::digest(Bytes::from(i.to_le_bytes().to_vec()));
+        sm.remove(&key);
+        sm.insert(key, i);
+        i = (i + 1) % BENCH_MAP_SIZE;
+    });
+}
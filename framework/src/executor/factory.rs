@@ -20,4 +20,16 @@ impl<DB: 'static + cita_trie::DB, S: 'static + Storage, Mapping: 'static + Servi
         let executor = ServiceExecutor::with_root(root, db, storage, mapping)?;
         Ok(Box::new(executor))
     }
+
+    fn from_root_with_parallel(
+        root: MerkleRoot,
+        db: Arc<DB>,
+        storage: Arc<S>,
+        mapping: Arc<Mapping>,
+        parallel: bool,
+    ) -> ProtocolResult<Box<dyn Executor>> {
+        let mut executor = ServiceExecutor::with_root(root, db, storage, mapping)?;
+        executor.set_parallel(parallel);
+        Ok(Box::new(executor))
+    }
 }
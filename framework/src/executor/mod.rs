@@ -1,12 +1,14 @@
 mod factory;
+mod parallel;
 #[cfg(test)]
 mod tests;
+mod trace;
 
 pub use factory::ServiceExecutorFactory;
 
 use std::{
-    cell::RefCell,
-    collections::HashMap,
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
     panic::{self, AssertUnwindSafe},
     rc::Rc,
@@ -19,7 +21,7 @@ use derive_more::Display;
 use common_apm::muta_apm;
 use protocol::traits::{
     Context, Dispatcher, Executor, ExecutorParams, ExecutorResp, NoopDispatcher, Service,
-    ServiceMapping, ServiceResponse, ServiceState, Storage,
+    ServiceMapping, ServiceResponse, ServiceState, SimulateResp, Storage, TraceFrame,
 };
 use protocol::types::{
     Address, Hash, MerkleRoot, Receipt, ReceiptResponse, ServiceContext, ServiceContextParams,
@@ -28,11 +30,22 @@ use protocol::types::{
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
-use crate::binding::state::{GeneralServiceState, MPTTrie};
+use crate::binding::state::{AccessRecord, GeneralServiceState, MPTTrie};
+use crate::executor::parallel::{DirtyKeys, EncodedMap, Speculation, StateSnapshot};
+use crate::executor::trace::Tracer;
+
+// Blocks with fewer transactions are not worth spawning workers for
+const PARALLEL_EXEC_MIN_TXS: usize = 16;
+// Write hooks are traced as this method of the service defining them
+const WRITE_HOOK_METHOD: &str = "write_hook";
+// Nested calls recurse on the native stack, deeper ones are rejected before
+// they can overflow it, e.g. on a worker thread of parallel execution
+const MAX_CALL_DEPTH: usize = 64;
+const CALL_DEPTH_EXCEEDED_CODE: u64 = 4;
 
 trait TxHooks {
-    fn before(&mut self, _: Context, _: ServiceContext) -> ProtocolResult<()> {
-        Ok(())
+    fn before(&mut self, _: Context, _: ServiceContext) -> ProtocolResult<ServiceResponse<()>> {
+        Ok(ServiceResponse::<()>::from_succeed(()))
     }
 
     fn after(&mut self, _: Context, _: ServiceContext) -> ProtocolResult<()> {
@@ -91,6 +104,94 @@ impl<DB: TrieDB> ServiceStateMap<DB> {
 
         Ok(())
     }
+
+    // A called service may write to any service state through its own
+    // calls, so savepoints are always pushed and popped on all states.
+    fn savepoint(&self) {
+        for state in self.0.values() {
+            state.borrow_mut().savepoint();
+        }
+    }
+
+    fn commit_savepoint(&self) {
+        for state in self.0.values() {
+            state.borrow_mut().commit_savepoint();
+        }
+    }
+
+    fn revert_savepoint(&self) {
+        for state in self.0.values() {
+            state.borrow_mut().revert_savepoint();
+        }
+    }
+
+    fn snapshot(&self) -> ProtocolResult<StateSnapshot> {
+        let mut snapshot = HashMap::new();
+        for (name, state) in self.0.iter() {
+            let state = state.borrow();
+            snapshot.insert(name.to_owned(), (state.root(), state.export_stash(None)?));
+        }
+
+        Ok(StateSnapshot(snapshot))
+    }
+
+    fn reset(&self, snapshot: &StateSnapshot) {
+        for (name, state) in self.0.iter() {
+            let stash = match snapshot.0.get(name) {
+                Some((_, stash)) => stash.clone(),
+                None => HashMap::new(),
+            };
+            state.borrow_mut().reset_stash(stash);
+        }
+    }
+
+    fn start_record(&self) {
+        for state in self.0.values() {
+            state.borrow_mut().start_record();
+        }
+    }
+
+    fn take_records(&self) -> HashMap<String, AccessRecord> {
+        let mut records = HashMap::new();
+        for (name, state) in self.0.iter() {
+            if let Some(record) = state.borrow_mut().take_record() {
+                records.insert(name.to_owned(), record);
+            }
+        }
+
+        records
+    }
+
+    // Put back records taken by `take_records`, states without one stop
+    // recording.
+    fn restore_records(&self, mut records: HashMap<String, AccessRecord>) {
+        for (name, state) in self.0.iter() {
+            state.borrow_mut().restore_record(records.remove(name));
+        }
+    }
+
+    fn export_writes(
+        &self,
+        records: &HashMap<String, AccessRecord>,
+    ) -> ProtocolResult<HashMap<String, EncodedMap>> {
+        let mut writes = HashMap::new();
+        for (name, record) in records.iter() {
+            if let Some(state) = self.0.get(name) {
+                let values = state.borrow().export_stash(Some(&record.writes))?;
+                writes.insert(name.to_owned(), values);
+            }
+        }
+
+        Ok(writes)
+    }
+
+    fn import_writes(&self, writes: HashMap<String, EncodedMap>) {
+        for (name, values) in writes.into_iter() {
+            if let Some(state) = self.0.get(&name) {
+                state.borrow_mut().import_stash(values);
+            }
+        }
+    }
 }
 
 struct CommitHooks<DB: TrieDB> {
@@ -107,30 +208,54 @@ impl<DB: TrieDB> CommitHooks<DB> {
     }
 
     // bagua kan 101 :)
-    fn kan<H: FnOnce() -> R, R>(states: Rc<ServiceStateMap<DB>>, hook: H) -> ProtocolResult<()> {
+    // A panicked hook is ignored, a rejecting one is reverted and its response
+    // returned.
+    fn kan<H: FnOnce() -> ServiceResponse<()>>(
+        states: Rc<ServiceStateMap<DB>>,
+        hook: H,
+    ) -> ProtocolResult<ServiceResponse<()>> {
         match panic::catch_unwind(AssertUnwindSafe(hook)) {
-            Ok(_) => states.stash(),
-            Err(_) => states.revert_cache(),
+            Ok(resp) if resp.is_error() => {
+                states.revert_cache()?;
+                Ok(resp)
+            }
+            Ok(resp) => {
+                states.stash()?;
+                Ok(resp)
+            }
+            Err(_) => {
+                states.revert_cache()?;
+                Ok(ServiceResponse::<()>::from_succeed(()))
+            }
         }
     }
 }
 
 impl<DB: TrieDB> TxHooks for CommitHooks<DB> {
-    // TODO: support abort execution
-    fn before(&mut self, _context: Context, service_context: ServiceContext) -> ProtocolResult<()> {
+    // Hooks after the first rejecting one are not called
+    fn before(
+        &mut self,
+        _context: Context,
+        service_context: ServiceContext,
+    ) -> ProtocolResult<ServiceResponse<()>> {
         for hook in self.inner.iter_mut() {
-            Self::kan(Rc::clone(&self.states), || {
+            let resp = Self::kan(Rc::clone(&self.states), || {
                 hook.tx_hook_before_(service_context.clone())
             })?;
+
+            if resp.is_error() {
+                return Ok(resp);
+            }
         }
 
-        Ok(())
+        Ok(ServiceResponse::<()>::from_succeed(()))
     }
 
     fn after(&mut self, _context: Context, service_context: ServiceContext) -> ProtocolResult<()> {
         for hook in self.inner.iter_mut() {
             Self::kan(Rc::clone(&self.states), || {
-                hook.tx_hook_after_(service_context.clone())
+                hook.tx_hook_after_(service_context.clone());
+                ServiceResponse::<()>::from_succeed(())
             })?;
         }
 
@@ -143,6 +268,12 @@ pub struct ServiceExecutor<S: Storage, DB: TrieDB, Mapping: ServiceMapping> {
     querier:         Rc<DefaultChainQuerier<S>>,
     states:          Rc<ServiceStateMap<DB>>,
     root_state:      Rc<RefCell<GeneralServiceState<DB>>>,
+    trie_db:         Arc<DB>,
+    storage:         Arc<S>,
+    parallel:        bool,
+    tracer:          Option<Rc<RefCell<Tracer>>>,
+    // Shared with the dispatchers of nested calls
+    call_depth:      Rc<Cell<usize>>,
 }
 
 impl<S: Storage, DB: TrieDB, Mapping: ServiceMapping> Clone for ServiceExecutor<S, DB, Mapping> {
@@ -152,6 +283,11 @@ impl<S: Storage, DB: TrieDB, Mapping: ServiceMapping> Clone for ServiceExecutor<
             querier:         Rc::clone(&self.querier),
             states:          Rc::clone(&self.states),
             root_state:      Rc::clone(&self.root_state),
+            trie_db:         Arc::clone(&self.trie_db),
+            storage:         Arc::clone(&self.storage),
+            parallel:        self.parallel,
+            tracer:          self.tracer.as_ref().map(Rc::clone),
+            call_depth:      Rc::clone(&self.call_depth),
         }
     }
 }
@@ -224,9 +360,57 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
 
         Ok(Self {
             service_mapping,
-            querier: Rc::new(DefaultChainQuerier::new(storage)),
+            querier: Rc::new(DefaultChainQuerier::new(Arc::clone(&storage))),
             states: Rc::new(states),
             root_state: Rc::new(RefCell::new(root_state)),
+            trie_db,
+            storage,
+            parallel: false,
+            tracer: None,
+            call_depth: Rc::new(Cell::new(0)),
+        })
+    }
+
+    // Execute transactions optimistically in parallel, off by default. The
+    // result is always the same as executing them one by one.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    // A sequential executor for a worker thread, nothing is shared with the
+    // executor the snapshot was taken from.
+    fn from_snapshot(
+        snapshot: &StateSnapshot,
+        trie_db: Arc<DB>,
+        storage: Arc<S>,
+        service_mapping: Arc<Mapping>,
+    ) -> ProtocolResult<Self> {
+        let mut states = ServiceStateMap::new();
+        for (name, (root, stash)) in snapshot.0.iter() {
+            // A service trie which has never been committed is empty
+            let trie = if root == &Hash::from_empty() {
+                MPTTrie::new(Arc::clone(&trie_db))
+            } else {
+                MPTTrie::from(root.clone(), Arc::clone(&trie_db))?
+            };
+
+            let mut state = GeneralServiceState::new(trie);
+            state.reset_stash(stash.clone());
+            states.insert(name.to_owned(), Rc::new(RefCell::new(state)));
+        }
+
+        let root_state = GeneralServiceState::new(MPTTrie::new(Arc::clone(&trie_db)));
+
+        Ok(Self {
+            service_mapping,
+            querier: Rc::new(DefaultChainQuerier::new(Arc::clone(&storage))),
+            states: Rc::new(states),
+            root_state: Rc::new(RefCell::new(root_state)),
+            trie_db,
+            storage,
+            parallel: false,
+            tracer: None,
+            call_depth: Rc::new(Cell::new(0)),
         })
     }
 
@@ -361,13 +545,26 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
     ) -> ProtocolResult<ServiceResponse<String>> {
         let mut tx_hooks = self.get_tx_hooks(exec_type);
 
-        tx_hooks.before(context.clone(), service_context.clone())?;
+        let before = tx_hooks.before(context.clone(), service_context.clone())?;
+        if before.is_error() {
+            let ret = ServiceResponse::<String>::from_error(before.code, before.error_message);
+            tx_hooks.after(context, service_context)?;
+            return Ok(ret);
+        }
 
-        let ret = match panic::catch_unwind(AssertUnwindSafe(|| {
+        self.arm_tracer(true);
+        let call_ret = panic::catch_unwind(AssertUnwindSafe(|| {
             self.call(service_context.clone(), exec_type)
-        })) {
+        }));
+        self.arm_tracer(false);
+
+        let ret = match call_ret {
             Ok(r) => {
-                self.stash()?;
+                if r.is_error() {
+                    self.revert_cache()?;
+                } else {
+                    self.stash()?;
+                }
                 Ok(r)
             }
             Err(e) => {
@@ -382,7 +579,284 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         ret
     }
 
+    fn exec_tx(
+        &mut self,
+        ctx: Context,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+    ) -> ProtocolResult<Receipt> {
+        let service_context = self.get_context(
+            Some(stx.tx_hash.clone()),
+            Some(stx.raw.nonce.clone()),
+            &stx.raw.sender,
+            stx.raw.cycles_price,
+            stx.raw.cycles_limit,
+            params,
+            &stx.raw.request,
+        )?;
+
+        let exec_resp = self.catch_call(ctx, service_context.clone(), ExecType::Write)?;
+        let events = if exec_resp.is_error() {
+            Vec::new()
+        } else {
+            service_context.get_events()
+        };
+
+        Ok(Receipt {
+            state_root: MerkleRoot::from_empty(),
+            height: service_context.get_current_height(),
+            tx_hash: stx.tx_hash.clone(),
+            cycles_used: service_context.get_cycles_used(),
+            events,
+            response: ReceiptResponse {
+                service_name: service_context.get_service_name().to_owned(),
+                method:       service_context.get_service_method().to_owned(),
+                response:     exec_resp,
+            },
+        })
+    }
+
+    // Speculate all transactions against the state after the before hooks,
+    // then merge them in order. A transaction which read a key written by an
+    // earlier one is executed again on the merged state.
+    fn exec_parallel(
+        &mut self,
+        ctx: Context,
+        params: &ExecutorParams,
+        txs: &[SignedTransaction],
+    ) -> ProtocolResult<Vec<Receipt>> {
+        let snapshot = self.states.snapshot()?;
+        let speculations = parallel::speculate(
+            &snapshot,
+            &self.trie_db,
+            &self.storage,
+            &self.service_mapping,
+            params,
+            txs,
+        );
+
+        let mut dirty = DirtyKeys::new();
+        let mut receipts = Vec::with_capacity(txs.len());
+
+        for (stx, speculation) in txs.iter().zip(speculations.into_iter()) {
+            match speculation.filter(|s| !s.conflicts_with(&dirty)) {
+                Some(Speculation {
+                    receipt, writes, ..
+                }) => {
+                    for (name, values) in writes.iter() {
+                        let keys = dirty.entry(name.to_owned()).or_insert_with(HashSet::new);
+                        keys.extend(values.keys().cloned());
+                    }
+                    self.states.import_writes(writes);
+
+                    receipts.push(receipt?);
+                }
+                None => {
+                    self.states.start_record();
+                    let receipt = self.exec_tx(ctx.clone(), params, stx);
+
+                    for (name, record) in self.states.take_records().into_iter() {
+                        let keys = dirty.entry(name).or_insert_with(HashSet::new);
+                        keys.extend(record.writes);
+                    }
+
+                    receipts.push(receipt?);
+                }
+            }
+        }
+
+        Ok(receipts)
+    }
+
+    fn speculate_tx(
+        &mut self,
+        snapshot: &StateSnapshot,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+    ) -> Option<Speculation> {
+        self.states.reset(snapshot);
+        self.states.start_record();
+
+        let receipt = self.exec_tx(Context::new(), params, stx);
+        let records = self.states.take_records();
+        let writes = self.states.export_writes(&records).ok()?;
+
+        let reads = records
+            .into_iter()
+            .map(|(name, record)| (name, record.reads))
+            .collect();
+
+        Some(Speculation {
+            receipt,
+            reads,
+            writes,
+        })
+    }
+
+    fn arm_tracer(&self, armed: bool) {
+        if let Some(tracer) = self.tracer.as_ref() {
+            tracer.borrow_mut().armed = armed;
+        }
+    }
+
+    fn armed_tracer(&self) -> Option<&Rc<RefCell<Tracer>>> {
+        self.tracer.as_ref().filter(|tracer| tracer.borrow().armed)
+    }
+
     fn call(&self, context: ServiceContext, exec_type: ExecType) -> ServiceResponse<String> {
+        match self.armed_tracer() {
+            Some(tracer) => {
+                let (service, method) = (context.get_service_name(), context.get_service_method());
+                self.traced_call(tracer, &context, service, method, || {
+                    self.call_service(context.clone(), exec_type)
+                })
+            }
+            None => self.call_service(context, exec_type),
+        }
+    }
+
+    // Record the call as a frame of the trace. State records of the caller are
+    // put aside during the call and get the keys accessed by it afterwards.
+    fn traced_call<F: FnOnce() -> ServiceResponse<String>>(
+        &self,
+        tracer: &Rc<RefCell<Tracer>>,
+        context: &ServiceContext,
+        service: &str,
+        method: &str,
+        f: F,
+    ) -> ServiceResponse<String> {
+        let mut outer_records = self.states.take_records();
+        let cycles_before = context.get_cycles_used();
+        let events_before = context.get_events().len();
+
+        self.states.start_record();
+        tracer.borrow_mut().enter();
+
+        let ret = panic::catch_unwind(AssertUnwindSafe(f));
+
+        let records = self.states.take_records();
+        let (reads, writes) = trace::state_keys(&records);
+        for (name, record) in records.into_iter() {
+            if let Some(outer) = outer_records.get_mut(&name) {
+                outer.merge(record);
+            }
+        }
+        self.states.restore_records(outer_records);
+
+        let (response, panic_msg) = match &ret {
+            Ok(resp) => (Some(resp.clone()), None),
+            Err(e) => (None, Some(trace::panic_message(e.as_ref()))),
+        };
+
+        tracer.borrow_mut().exit(TraceFrame {
+            service: service.to_owned(),
+            method: method.to_owned(),
+            payload: context.get_payload().to_owned(),
+            cycles_used: context.get_cycles_used() - cycles_before,
+            reads,
+            writes,
+            events: context.get_events().split_off(events_before),
+            response,
+            panic: panic_msg,
+            calls: vec![],
+        });
+
+        match ret {
+            Ok(resp) => resp,
+            Err(e) => panic::resume_unwind(e),
+        }
+    }
+
+    // Every write, of a transaction or nested, is run by write hooks of all
+    // services first, e.g. to check method permissions of its caller, so a
+    // service writing on behalf of another one can't bypass them. Each hook is
+    // a nested call, the first rejecting one fails the write.
+    fn call_write_hooks(&self, context: &ServiceContext) -> ServiceResponse<String> {
+        for name in self.service_mapping.list_service_name().into_iter() {
+            let sdk = self
+                .get_sdk(&name)
+                .unwrap_or_else(|e| panic!("get write hook service sdk failed: {}", e));
+            let service = self
+                .service_mapping
+                .get_service(name.as_str(), sdk)
+                .unwrap_or_else(|e| panic!("get write hook service failed: {}", e));
+
+            if !service.has_write_hook_() {
+                continue;
+            }
+
+            let hook = || {
+                let resp = service.write_hook_(context.clone());
+                if resp.is_error() {
+                    ServiceResponse::<String>::from_error(resp.code, resp.error_message)
+                } else {
+                    ServiceResponse::<String>::from_succeed(String::new())
+                }
+            };
+            let resp = self.nested_call(|| match self.armed_tracer() {
+                Some(tracer) => self.traced_call(tracer, context, &name, WRITE_HOOK_METHOD, hook),
+                None => hook(),
+            });
+
+            if resp.is_error() {
+                return resp;
+            }
+        }
+
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+
+    // Run a call of the dispatcher one level deeper, it fails without being
+    // run past `MAX_CALL_DEPTH`.
+    fn nested_call<F: FnOnce() -> ServiceResponse<String>>(&self, f: F) -> ServiceResponse<String> {
+        let depth = self.call_depth.get();
+        if depth >= MAX_CALL_DEPTH {
+            return ServiceResponse::<String>::from_error(
+                CALL_DEPTH_EXCEEDED_CODE,
+                format!("exceeded max call depth {}", MAX_CALL_DEPTH),
+            );
+        }
+
+        self.call_depth.set(depth + 1);
+        let ret = panic::catch_unwind(AssertUnwindSafe(f));
+        self.call_depth.set(depth);
+
+        ret.unwrap_or_else(|e| panic::resume_unwind(e))
+    }
+
+    // Every nested write runs on its own savepoint, so a failed call only
+    // drops its own writes and the caller can carry on.
+    fn call_on_savepoint(&self, context: ServiceContext) -> ServiceResponse<String> {
+        self.states.savepoint();
+
+        match panic::catch_unwind(AssertUnwindSafe(|| self.call(context, ExecType::Write))) {
+            Ok(resp) => {
+                if resp.is_error() {
+                    self.states.revert_savepoint();
+                } else {
+                    self.states.commit_savepoint();
+                }
+                resp
+            }
+            Err(e) => {
+                self.states.revert_savepoint();
+                panic::resume_unwind(e)
+            }
+        }
+    }
+
+    fn call_service(
+        &self,
+        context: ServiceContext,
+        exec_type: ExecType,
+    ) -> ServiceResponse<String> {
+        if let ExecType::Write = exec_type {
+            let hooks = self.call_write_hooks(&context);
+            if hooks.is_error() {
+                return hooks;
+            }
+        }
+
         let sdk = self
             .get_sdk(context.get_service_name())
             .unwrap_or_else(|e| panic!("get target service sdk failed: {}", e));
@@ -411,41 +885,13 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
     ) -> ProtocolResult<ExecutorResp> {
         self.hook(ctx.clone(), HookType::Before, params)?;
 
-        let mut receipts = txs
-            .iter()
-            .map(|stx| {
-                let service_context = self.get_context(
-                    Some(stx.tx_hash.clone()),
-                    Some(stx.raw.nonce.clone()),
-                    &stx.raw.sender,
-                    stx.raw.cycles_price,
-                    stx.raw.cycles_limit,
-                    params,
-                    &stx.raw.request,
-                )?;
-
-                let exec_resp =
-                    self.catch_call(ctx.clone(), service_context.clone(), ExecType::Write)?;
-                let events = if exec_resp.is_error() {
-                    Vec::new()
-                } else {
-                    service_context.get_events()
-                };
-
-                Ok(Receipt {
-                    state_root: MerkleRoot::from_empty(),
-                    height: service_context.get_current_height(),
-                    tx_hash: stx.tx_hash.clone(),
-                    cycles_used: service_context.get_cycles_used(),
-                    events,
-                    response: ReceiptResponse {
-                        service_name: service_context.get_service_name().to_owned(),
-                        method:       service_context.get_service_method().to_owned(),
-                        response:     exec_resp,
-                    },
-                })
-            })
-            .collect::<Result<Vec<Receipt>, ProtocolError>>()?;
+        let mut receipts = if self.parallel && txs.len() >= PARALLEL_EXEC_MIN_TXS {
+            self.exec_parallel(ctx.clone(), params, txs)?
+        } else {
+            txs.iter()
+                .map(|stx| self.exec_tx(ctx.clone(), params, stx))
+                .collect::<Result<Vec<Receipt>, ProtocolError>>()?
+        };
 
         self.hook(ctx.clone(), HookType::After, params)?;
 
@@ -483,17 +929,85 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         panic::catch_unwind(AssertUnwindSafe(|| self.call(context, ExecType::Read)))
             .map_err(|e| ProtocolError::from(ExecutorError::QueryService(format!("{:?}", e))))
     }
+
+    fn simulate(
+        &mut self,
+        params: &ExecutorParams,
+        caller: &Address,
+        cycles_price: u64,
+        request: &TransactionRequest,
+        cycles_limit: u64,
+    ) -> ProtocolResult<SimulateResp> {
+        // A simulated request is not signed, it has no hash or nonce of its own
+        let context = self.get_context(
+            Some(Hash::from_empty()),
+            Some(Hash::from_empty()),
+            caller,
+            cycles_price,
+            cycles_limit,
+            params,
+            request,
+        )?;
+
+        let snapshot = self.states.snapshot()?;
+        let ret = self.catch_call(Context::new(), context.clone(), ExecType::Write);
+        self.states.reset(&snapshot);
+
+        let response = ret?;
+        let events = if response.is_error() {
+            Vec::new()
+        } else {
+            context.get_events()
+        };
+
+        Ok(SimulateResp {
+            cycles_used: context.get_cycles_used(),
+            events,
+            response,
+        })
+    }
+
+    fn trace(
+        &mut self,
+        ctx: Context,
+        params: &ExecutorParams,
+        txs: &[SignedTransaction],
+        tx_hash: &Hash,
+    ) -> ProtocolResult<Option<TraceFrame>> {
+        self.hook(ctx.clone(), HookType::Before, params)?;
+
+        for stx in txs.iter() {
+            if stx.tx_hash != *tx_hash {
+                self.exec_tx(ctx.clone(), params, stx)?;
+                continue;
+            }
+
+            let tracer = Rc::new(RefCell::new(Tracer::default()));
+            self.tracer = Some(Rc::clone(&tracer));
+            let ret = self.exec_tx(ctx, params, stx);
+            self.tracer = None;
+
+            // A panicked call is an error for `exec_tx`, but still traced
+            let root = tracer.borrow_mut().take_root();
+            return match root {
+                Some(root) => Ok(Some(root)),
+                None => ret.map(|_| None),
+            };
+        }
+
+        Ok(None)
+    }
 }
 
 impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMapping> Dispatcher
     for ServiceExecutor<S, DB, Mapping>
 {
     fn read(&self, context: ServiceContext) -> ServiceResponse<String> {
-        self.call(context, ExecType::Read)
+        self.nested_call(|| self.call(context, ExecType::Read))
     }
 
     fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
-        self.call(context, ExecType::Write)
+        self.nested_call(|| self.call_on_savepoint(context))
     }
 }
 
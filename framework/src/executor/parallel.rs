@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use cita_trie::DB as TrieDB;
+use rayon::prelude::*;
+
+use protocol::traits::{ExecutorParams, ServiceMapping, Storage};
+use protocol::types::{MerkleRoot, Receipt, SignedTransaction};
+use protocol::ProtocolResult;
+
+use crate::executor::ServiceExecutor;
+
+pub type EncodedMap = HashMap<Bytes, Option<Bytes>>;
+
+// Encoded keys written by the transactions merged so far, by service name
+pub type DirtyKeys = HashMap<String, HashSet<Bytes>>;
+
+/// Service trie roots and encoded stashed data, everything a worker needs to
+/// rebuild the state a block's transactions start from on its own thread.
+pub struct StateSnapshot(pub HashMap<String, (MerkleRoot, EncodedMap)>);
+
+/// A transaction executed against the snapshot. Its result is only the same
+/// as sequential execution if none of the keys it read was written by an
+/// earlier transaction in the block.
+pub struct Speculation {
+    pub receipt: ProtocolResult<Receipt>,
+    pub reads:   HashMap<String, HashSet<Bytes>>,
+    pub writes:  HashMap<String, EncodedMap>,
+}
+
+impl Speculation {
+    pub fn conflicts_with(&self, dirty: &DirtyKeys) -> bool {
+        self.reads
+            .iter()
+            .any(|(service, keys)| match dirty.get(service) {
+                Some(dirty_keys) => !dirty_keys.is_disjoint(keys),
+                None => false,
+            })
+    }
+}
+
+// Execute `txs` on the rayon pool, each worker takes a contiguous chunk and
+// resets its state to the snapshot before every transaction. `None` means the
+// transaction has to be executed again in order.
+//
+// Service states are `Rc<RefCell<_>>` all the way down to the SDKs, so rather
+// than making them `Send`, every worker rebuilds its own executor from the
+// snapshot, which is only trie roots and encoded stashes. The rebuild is paid
+// once per chunk, compare `bench_rebuild_from_snapshot` with
+// `bench_speculate_tx` in the executor tests.
+pub fn speculate<S, DB, Mapping>(
+    snapshot: &StateSnapshot,
+    trie_db: &Arc<DB>,
+    storage: &Arc<S>,
+    mapping: &Arc<Mapping>,
+    params: &ExecutorParams,
+    txs: &[SignedTransaction],
+) -> Vec<Option<Speculation>>
+where
+    S: 'static + Storage,
+    DB: 'static + TrieDB,
+    Mapping: 'static + ServiceMapping,
+{
+    let threads = rayon::current_num_threads().max(1);
+    let chunk_size = ((txs.len() + threads - 1) / threads).max(1);
+
+    txs.par_chunks(chunk_size)
+        .map(|chunk| {
+            let executor = ServiceExecutor::from_snapshot(
+                snapshot,
+                Arc::clone(trie_db),
+                Arc::clone(storage),
+                Arc::clone(mapping),
+            );
+
+            match executor {
+                Ok(mut executor) => chunk
+                    .iter()
+                    .map(|stx| executor.speculate_tx(snapshot, params, stx))
+                    .collect::<Vec<_>>(),
+                Err(e) => {
+                    log::warn!("[framework] create speculative executor failed: {}", e);
+                    chunk.iter().map(|_| None).collect::<Vec<_>>()
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flatten()
+        .collect()
+}
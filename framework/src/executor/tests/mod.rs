@@ -10,15 +10,17 @@ use bytes::{Bytes, BytesMut};
 use cita_trie::MemoryDB;
 use test::Bencher;
 
-use asset::types::{Asset, GetBalanceResponse};
+use asset::types::{Amount, Asset, GetBalanceResponse};
 use asset::AssetService;
+use binding_macro::{cycles, service, tx_hook_after};
 use metadata::MetadataService;
 use protocol::traits::{
-    Context, Executor, ExecutorParams, Service, ServiceMapping, ServiceSDK, Storage,
+    Context, Executor, ExecutorParams, ExecutorResp, Service, ServiceMapping, ServiceResponse,
+    ServiceSDK, Storage,
 };
 use protocol::types::{
-    Address, Block, Genesis, Hash, Proof, RawTransaction, Receipt, SignedTransaction,
-    TransactionRequest,
+    Address, Block, Evidence, Genesis, Hash, Proof, RawTransaction, Receipt, ServiceContext,
+    SignedTransaction, TransactionRequest,
 };
 use protocol::ProtocolResult;
 
@@ -67,7 +69,7 @@ fn test_create_genesis() {
     let res = executor.read(&params, &caller, 1, &request).unwrap();
     let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
 
-    assert_eq!(resp.balance, 320_000_011);
+    assert_eq!(resp.balance, Amount(320_000_011));
 }
 
 #[test]
@@ -110,7 +112,66 @@ fn test_exec() {
     let asset: Asset = serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
     assert_eq!(asset.name, "MutaToken2");
     assert_eq!(asset.symbol, "MT2");
-    assert_eq!(asset.supply, 320_000_011);
+    assert_eq!(asset.supply, Amount(320_000_011));
+}
+
+#[test]
+fn test_simulate() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        proposer:     Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let stx = mock_signed_tx();
+    let simulate = |executor: &mut ServiceExecutor<_, _, _>| {
+        executor
+            .simulate(
+                &params,
+                &stx.raw.sender,
+                stx.raw.cycles_price,
+                &stx.raw.request,
+                stx.raw.cycles_limit,
+            )
+            .unwrap()
+    };
+
+    // Nothing is left behind, the same request can be simulated again
+    let first = simulate(&mut executor);
+    let second = simulate(&mut executor);
+    assert_eq!(first.response.code, 0);
+    assert_eq!(first.response, second.response);
+    assert_eq!(first.cycles_used, second.cycles_used);
+
+    let executor_resp = executor.exec(Context::new(), &params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+
+    assert_eq!(first.cycles_used, receipt.cycles_used);
+    assert_eq!(first.events, receipt.events);
+    assert_eq!(first.response, receipt.response.response);
 }
 
 #[test]
@@ -415,6 +476,73 @@ fn test_tx_hook_before_panic() {
     assert_eq!(after.succeed_data, r#""after""#);
 }
 
+#[test]
+fn test_tx_hook_before_reject() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        proposer:     Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "tx_hook_before_reject".to_owned();
+    stx.raw.request.payload = r#""""#.to_owned();
+
+    let txs = vec![stx];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+
+    assert_eq!(receipt.response.response.code, 301);
+    assert_eq!(
+        receipt.response.response.error_message,
+        "tx hook before reject"
+    );
+    assert_eq!(receipt.cycles_used, 0);
+
+    // The rejecting hook and the target call are reverted, tx hooks after
+    // still run
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    for (key, value) in &[
+        ("before", ""),
+        ("tx_hook_before_reject", ""),
+        ("after", "after"),
+    ] {
+        let request = TransactionRequest {
+            service_name: "test".to_owned(),
+            method:       "test_read".to_owned(),
+            payload:      format!("{:?}", key),
+        };
+        let resp = executor
+            .read(&params, &caller, 1, &request)
+            .expect("read value");
+        assert_eq!(resp.succeed_data, format!("{:?}", value));
+    }
+}
+
 #[test]
 fn test_tx_hook_after_panic() {
     let toml_str = include_str!("./genesis_services.toml");
@@ -477,6 +605,145 @@ fn test_tx_hook_after_panic() {
     assert_eq!(before.succeed_data, r#""tx_hook_after_panic""#);
 }
 
+// Execute the same block one by one and in parallel, on the same genesis
+fn exec_sequential_and_parallel<Mapping: 'static + ServiceMapping>(
+    db: &Arc<MemoryDB>,
+    mapping: Arc<Mapping>,
+    txs: &[SignedTransaction],
+) -> (ExecutorResp, ExecutorResp) {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(db),
+        Arc::new(MockStorage {}),
+        Arc::clone(&mapping),
+    )
+    .unwrap();
+
+    let mut sequential = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(db),
+        Arc::new(MockStorage {}),
+        Arc::clone(&mapping),
+    )
+    .unwrap();
+
+    let mut parallel = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(db),
+        Arc::new(MockStorage {}),
+        mapping,
+    )
+    .unwrap();
+    parallel.set_parallel(true);
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        proposer:     Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let sequential_resp = sequential.exec(Context::new(), &params, txs).unwrap();
+    let parallel_resp = parallel.exec(Context::new(), &params, txs).unwrap();
+
+    (sequential_resp, parallel_resp)
+}
+
+fn mock_parallel_txs() -> Vec<SignedTransaction> {
+    // Transfers from the same sender conflict with each other, writes to
+    // distinct keys don't, blind writes to the same key must keep the order.
+    (0..48u64)
+        .map(|i| match i % 4 {
+            0 => {
+                let to = Address::from_hash(Hash::digest(Bytes::from(vec![(i % 3) as u8])));
+                let payload = format!(
+                    r#"{{ "asset_id": "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c", "to": "{}", "value": {} }}"#,
+                    to.unwrap().as_hex(),
+                    i + 1
+                );
+                mock_request_tx("asset", "transfer", &payload)
+            }
+            1 => {
+                let payload = format!(
+                    r#"{{ "key": "key_{}", "value": "{}", "extra": "" }}"#,
+                    i, i
+                );
+                mock_request_tx("test", "test_write", &payload)
+            }
+            2 => {
+                let payload = format!(r#"{{ "key": "shared", "value": "{}", "extra": "" }}"#, i);
+                mock_request_tx("test", "test_write", &payload)
+            }
+            _ => {
+                let payload = format!(
+                    r#"{{ "name": "Token{}", "symbol": "T{}", "supply": {} }}"#,
+                    i, i, i
+                );
+                mock_request_tx("asset", "create_asset", &payload)
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+#[test]
+fn test_parallel_exec_equals_sequential() {
+    let db = Arc::new(MemoryDB::new(false));
+    let (sequential_resp, parallel_resp) =
+        exec_sequential_and_parallel(&db, Arc::new(MockServiceMapping {}), &mock_parallel_txs());
+
+    assert_eq!(sequential_resp.state_root, parallel_resp.state_root);
+    assert_eq!(
+        sequential_resp.all_cycles_used,
+        parallel_resp.all_cycles_used
+    );
+    assert_eq!(sequential_resp.receipts, parallel_resp.receipts);
+    assert!(sequential_resp
+        .receipts
+        .iter()
+        .all(|receipt| receipt.response.response.code == 0));
+}
+
+#[test]
+fn test_parallel_exec_with_shared_tx_hook_write() {
+    let db = Arc::new(MemoryDB::new(false));
+    let (sequential_resp, parallel_resp) =
+        exec_sequential_and_parallel(&db, Arc::new(FeeServiceMapping {}), &mock_parallel_txs());
+
+    assert_eq!(sequential_resp.state_root, parallel_resp.state_root);
+    assert_eq!(sequential_resp.receipts, parallel_resp.receipts);
+
+    // Every transaction added its cycles to the same key
+    let executor = ServiceExecutor::with_root(
+        parallel_resp.state_root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(FeeServiceMapping {}),
+    )
+    .unwrap();
+    let params = ExecutorParams {
+        state_root:   parallel_resp.state_root,
+        height:       2,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        proposer:     Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "fee".to_owned(),
+        method:       "get_collected".to_owned(),
+        payload:      "".to_owned(),
+    };
+    let collected = executor.read(&params, &caller, 1, &request).unwrap();
+    assert_eq!(
+        collected.succeed_data,
+        parallel_resp.all_cycles_used.to_string()
+    );
+}
+
 #[bench]
 fn bench_execute(b: &mut Bencher) {
     let toml_str = include_str!("./genesis_services.toml");
@@ -515,6 +782,68 @@ fn bench_execute(b: &mut Bencher) {
     });
 }
 
+// Paid by every worker of parallel execution once per block
+#[bench]
+fn bench_rebuild_from_snapshot(b: &mut Bencher) {
+    let (executor, _) = new_bench_executor();
+    let snapshot = executor.states.snapshot().unwrap();
+
+    b.iter(|| {
+        ServiceExecutor::from_snapshot(
+            &snapshot,
+            Arc::clone(&executor.trie_db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockServiceMapping {}),
+        )
+        .unwrap()
+    });
+}
+
+// Paid by a worker for every transaction of its chunk
+#[bench]
+fn bench_speculate_tx(b: &mut Bencher) {
+    let (mut executor, params) = new_bench_executor();
+    let snapshot = executor.states.snapshot().unwrap();
+    let stx = mock_parallel_txs().remove(0);
+
+    b.iter(|| executor.speculate_tx(&snapshot, &params, &stx).unwrap());
+}
+
+fn new_bench_executor() -> (
+    ServiceExecutor<MockStorage, MemoryDB, MockServiceMapping>,
+    ExecutorParams,
+) {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let executor = ServiceExecutor::with_root(
+        root.clone(),
+        db,
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        proposer:     Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    (executor, params)
+}
+
 fn mock_signed_tx() -> SignedTransaction {
     let raw = RawTransaction {
         chain_id:     Hash::from_empty(),
@@ -540,6 +869,17 @@ fn mock_signed_tx() -> SignedTransaction {
     }
 }
 
+fn mock_request_tx(service: &str, method: &str, payload: &str) -> SignedTransaction {
+    let mut stx = mock_signed_tx();
+    stx.raw.request = TransactionRequest {
+        service_name: service.to_owned(),
+        method:       method.to_owned(),
+        payload:      payload.to_owned(),
+    };
+
+    stx
+}
+
 struct MockServiceMapping;
 
 impl ServiceMapping for MockServiceMapping {
@@ -637,4 +977,61 @@ impl Storage for MockStorage {
     async fn load_overlord_wal(&self, _ctx: Context) -> ProtocolResult<Bytes> {
         unimplemented!()
     }
+
+    async fn insert_evidence(&self, _ctx: Context, _: Evidence) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_evidences(&self, _ctx: Context, _: u64) -> ProtocolResult<Vec<Evidence>> {
+        unimplemented!()
+    }
+}
+
+const COLLECTED_KEY: &str = "collected";
+
+// Every transaction reads and writes the same key, like fee collection
+struct MockFeeService<SDK> {
+    sdk: SDK,
+}
+
+#[service]
+impl<SDK: ServiceSDK> MockFeeService<SDK> {
+    pub fn new(sdk: SDK) -> Self {
+        Self { sdk }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_collected(&self, _ctx: ServiceContext) -> ServiceResponse<u64> {
+        let collected: u64 = self.sdk.get_value(&COLLECTED_KEY.to_owned()).unwrap_or(0);
+        ServiceResponse::from_succeed(collected)
+    }
+
+    #[tx_hook_after]
+    fn collect(&mut self, ctx: ServiceContext) {
+        let collected: u64 = self.sdk.get_value(&COLLECTED_KEY.to_owned()).unwrap_or(0);
+        self.sdk
+            .set_value(COLLECTED_KEY.to_owned(), collected + ctx.get_cycles_used());
+    }
+}
+
+struct FeeServiceMapping;
+
+impl ServiceMapping for FeeServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        match name {
+            "fee" => Ok(Box::new(MockFeeService::new(sdk)) as Box<dyn Service>),
+            _ => MockServiceMapping.get_service(name, sdk),
+        }
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        let mut names = MockServiceMapping.list_service_name();
+        names.push("fee".to_owned());
+        names
+    }
 }
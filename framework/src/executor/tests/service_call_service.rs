@@ -2,22 +2,25 @@ use std::sync::Arc;
 
 use bytes::{Bytes, BytesMut};
 use cita_trie::MemoryDB;
+use serde::{Deserialize, Serialize};
 
-use asset::types::{Asset, CreateAssetPayload};
+use asset::types::{Amount, Asset, CreateAssetPayload};
 use asset::AssetService;
-use binding_macro::{cycles, service};
+use binding_macro::{cycles, service, write_hook};
 use metadata::MetadataService;
 
 use protocol::traits::{
-    Context, Executor, ExecutorParams, Service, ServiceMapping, ServiceResponse, ServiceSDK,
+    Context, Dispatcher, Executor, ExecutorParams, Service, ServiceMapping, ServiceResponse,
+    ServiceSDK,
 };
 use protocol::types::{
-    Address, Genesis, Hash, RawTransaction, ServiceContext, SignedTransaction, TransactionRequest,
+    Address, Genesis, Hash, RawTransaction, Receipt, ServiceContext, SignedTransaction,
+    TransactionRequest,
 };
 use protocol::ProtocolResult;
 
 use crate::executor::tests::{MockStorage, PUB_KEY_STR};
-use crate::executor::ServiceExecutor;
+use crate::executor::{ServiceExecutor, CALL_DEPTH_EXCEEDED_CODE, MAX_CALL_DEPTH};
 
 #[test]
 fn test_service_call_service() {
@@ -88,7 +91,326 @@ fn test_service_call_service() {
     let asset: Asset = serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
     assert_eq!(asset.name, "TestCallAsset");
     assert_eq!(asset.symbol, "TCA");
-    assert_eq!(asset.supply, 320_000_011);
+    assert_eq!(asset.supply, Amount(320_000_011));
+}
+
+#[test]
+fn test_commit_nested_calls() {
+    let (mut executor, params) = new_executor();
+
+    let payload = NestedSetPayload::new("a", false).call(NestedSetPayload::new("b", false));
+    let receipt = exec_nested_set(&mut executor, &params, payload);
+
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(read_value(&executor, &params, "a"), "a");
+    assert_eq!(read_value(&executor, &params, "b"), "b");
+}
+
+#[test]
+fn test_revert_failed_inner_call() {
+    let (mut executor, params) = new_executor();
+
+    // a -> b (failed) -> c, c is committed to b and then dropped with it
+    let payload = NestedSetPayload::new("a", false)
+        .call(NestedSetPayload::new("b", true).call(NestedSetPayload::new("c", false)));
+    let receipt = exec_nested_set(&mut executor, &params, payload);
+
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(read_value(&executor, &params, "a"), "a");
+    assert_eq!(read_value(&executor, &params, "b"), "");
+    assert_eq!(read_value(&executor, &params, "c"), "");
+}
+
+#[test]
+fn test_revert_succeed_inner_call_on_outer_error() {
+    let (mut executor, params) = new_executor();
+
+    let payload = NestedSetPayload::new("a", true).call(NestedSetPayload::new("b", false));
+    let receipt = exec_nested_set(&mut executor, &params, payload);
+
+    assert_eq!(receipt.response.response.code, 101);
+    assert_eq!(read_value(&executor, &params, "a"), "");
+    assert_eq!(read_value(&executor, &params, "b"), "");
+}
+
+#[test]
+fn test_revert_panic_inner_call() {
+    let (mut executor, params) = new_executor();
+
+    let mut inner = NestedSetPayload::new("b", false);
+    inner.panic = true;
+    let payload = NestedSetPayload::new("a", false).call(inner);
+
+    let stx = mock_signed_tx("nested_set", &serde_json::to_string(&payload).unwrap());
+    assert!(executor.exec(Context::new(), &params, &[stx]).is_err());
+
+    assert_eq!(read_value(&executor, &params, "a"), "");
+    assert_eq!(read_value(&executor, &params, "b"), "");
+    for state in executor.states.values() {
+        assert_eq!(state.borrow().savepoint_depth(), 0);
+    }
+}
+
+#[test]
+fn test_write_hook_rejects_nested_calls() {
+    let (mut executor, params) = new_executor_with(AuthorizedServiceMapping);
+
+    let payload = NestedSetPayload::new("a", false).call(NestedSetPayload::new("b", false));
+    let receipt = exec_nested_set(&mut executor, &params, payload);
+
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(read_value(&executor, &params, "a"), "a");
+    assert_eq!(read_value(&executor, &params, "b"), "");
+}
+
+#[test]
+fn test_write_hook_rejects_transactions() {
+    let (mut executor, params) = new_executor_with(AuthorizedServiceMapping);
+
+    let receipt = exec_nested_set(&mut executor, &params, NestedSetPayload::new("b", false));
+
+    assert_eq!(receipt.response.response.code, 105);
+    assert_eq!(read_value(&executor, &params, "b"), "");
+}
+
+#[test]
+fn test_trace_write_hooks() {
+    let (mut executor, params) = new_executor_with(AuthorizedServiceMapping);
+
+    let payload = NestedSetPayload::new("a", false).call(NestedSetPayload::new("b", false));
+    let stx = mock_signed_tx("nested_set", &serde_json::to_string(&payload).unwrap());
+
+    let trace = executor
+        .trace(Context::new(), &params, &[stx.clone()], &stx.tx_hash)
+        .unwrap()
+        .unwrap();
+
+    // Hook of the transaction runs first, then the nested write of `b`
+    assert_eq!(trace.calls.len(), 2);
+    let hook = &trace.calls[0];
+    assert_eq!(
+        (hook.service.as_str(), hook.method.as_str()),
+        ("authorization", "write_hook")
+    );
+    assert_eq!(hook.response.as_ref().unwrap().code, 0);
+
+    let inner = &trace.calls[1];
+    assert_eq!(inner.response.as_ref().unwrap().code, 105);
+    assert_eq!(inner.calls.len(), 1);
+    assert_eq!(inner.calls[0].response.as_ref().unwrap().code, 105);
+    assert!(inner.calls[0].calls.is_empty());
+}
+
+#[test]
+fn test_reject_calls_past_max_call_depth() {
+    let (mut executor, params) = new_executor();
+
+    // The transaction itself is not a nested call, `k0` calls `k1` at depth 1
+    let depth = MAX_CALL_DEPTH + 1;
+    let payload = (0..depth).rev().fold(
+        NestedSetPayload::new(&format!("k{}", depth), false),
+        |inner, i| NestedSetPayload::new(&format!("k{}", i), false).call(inner),
+    );
+    let receipt = exec_nested_set(&mut executor, &params, payload);
+
+    assert_eq!(receipt.response.response.code, 0);
+    let last = format!("k{}", MAX_CALL_DEPTH);
+    assert_eq!(read_value(&executor, &params, &last), last);
+    assert_eq!(read_value(&executor, &params, &format!("k{}", depth)), "");
+
+    let request = TransactionRequest {
+        service_name: "mock".to_owned(),
+        method:       "get_value".to_owned(),
+        payload:      serde_json::to_string("k0").unwrap(),
+    };
+    let context = executor
+        .get_context(
+            None,
+            None,
+            &params.proposer,
+            1,
+            std::u64::MAX,
+            &params,
+            &request,
+        )
+        .unwrap();
+    executor.call_depth.set(MAX_CALL_DEPTH);
+
+    let resp = Dispatcher::read(&executor, context);
+    assert_eq!(resp.code, CALL_DEPTH_EXCEEDED_CODE);
+}
+
+#[test]
+fn test_trace_nested_calls() {
+    let (mut executor, params) = new_executor();
+
+    let payload = NestedSetPayload::new("a", false)
+        .call(NestedSetPayload::new("b", true).call(NestedSetPayload::new("c", false)));
+    let stx = mock_signed_tx("nested_set", &serde_json::to_string(&payload).unwrap());
+
+    let trace = executor
+        .trace(Context::new(), &params, &[stx.clone()], &stx.tx_hash)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        (trace.service.as_str(), trace.method.as_str()),
+        ("mock", "nested_set")
+    );
+    assert_eq!(trace.payload, serde_json::to_string(&payload).unwrap());
+    assert_eq!(trace.response.unwrap().code, 0);
+    assert_eq!(trace.writes.len(), 3);
+    assert_eq!(trace.calls.len(), 1);
+
+    let inner = &trace.calls[0];
+    assert_eq!(inner.response.as_ref().unwrap().code, 101);
+    assert!(inner.cycles_used < trace.cycles_used);
+    assert_eq!(inner.writes.len(), 2);
+    assert_eq!(inner.calls.len(), 1);
+
+    let innermost = &inner.calls[0];
+    assert_eq!(innermost.response.as_ref().unwrap().code, 0);
+    assert_eq!(innermost.writes.len(), 1);
+    assert!(innermost.calls.is_empty());
+}
+
+#[test]
+fn test_trace_panic_call() {
+    let (mut executor, params) = new_executor();
+
+    let mut inner = NestedSetPayload::new("b", false);
+    inner.panic = true;
+    let payload = NestedSetPayload::new("a", false).call(inner);
+    let stx = mock_signed_tx("nested_set", &serde_json::to_string(&payload).unwrap());
+
+    let trace = executor
+        .trace(Context::new(), &params, &[stx.clone()], &stx.tx_hash)
+        .unwrap()
+        .unwrap();
+
+    assert!(trace.response.is_none());
+    assert_eq!(trace.panic.as_deref(), Some("nested set panic"));
+    assert_eq!(trace.calls[0].panic.as_deref(), Some("nested set panic"));
+}
+
+fn new_executor() -> (
+    ServiceExecutor<MockStorage, MemoryDB, MockServiceMapping>,
+    ExecutorParams,
+) {
+    new_executor_with(MockServiceMapping)
+}
+
+fn new_executor_with<M: 'static + ServiceMapping>(
+    mapping: M,
+) -> (ServiceExecutor<MockStorage, MemoryDB, M>, ExecutorParams) {
+    let mapping = Arc::new(mapping);
+    let memdb = Arc::new(MemoryDB::new(false));
+
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&memdb),
+        Arc::new(MockStorage {}),
+        Arc::clone(&mapping),
+    )
+    .unwrap();
+
+    let executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&memdb),
+        Arc::new(MockStorage {}),
+        mapping,
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        proposer:     Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    (executor, params)
+}
+
+fn mock_signed_tx(method: &str, payload: &str) -> SignedTransaction {
+    let raw = RawTransaction {
+        chain_id:     Hash::from_empty(),
+        nonce:        Hash::from_empty(),
+        timeout:      0,
+        cycles_price: 1,
+        cycles_limit: 1_000_000,
+        request:      TransactionRequest {
+            service_name: "mock".to_owned(),
+            method:       method.to_owned(),
+            payload:      payload.to_owned(),
+        },
+        sender:       Address::from_pubkey_bytes(Bytes::from(hex::decode(PUB_KEY_STR).unwrap()))
+            .unwrap(),
+    };
+
+    SignedTransaction {
+        raw,
+        tx_hash: Hash::from_empty(),
+        pubkey: Bytes::from(hex::decode(PUB_KEY_STR).unwrap()),
+        signature: BytesMut::from("").freeze(),
+    }
+}
+
+fn exec_nested_set<M: 'static + ServiceMapping>(
+    executor: &mut ServiceExecutor<MockStorage, MemoryDB, M>,
+    params: &ExecutorParams,
+    payload: NestedSetPayload,
+) -> Receipt {
+    let stx = mock_signed_tx("nested_set", &serde_json::to_string(&payload).unwrap());
+    let mut resp = executor.exec(Context::new(), params, &[stx]).unwrap();
+
+    resp.receipts.remove(0)
+}
+
+fn read_value<M: 'static + ServiceMapping>(
+    executor: &ServiceExecutor<MockStorage, MemoryDB, M>,
+    params: &ExecutorParams,
+    key: &str,
+) -> String {
+    let request = TransactionRequest {
+        service_name: "mock".to_owned(),
+        method:       "get_value".to_owned(),
+        payload:      serde_json::to_string(key).unwrap(),
+    };
+    let resp = executor
+        .read(params, &params.proposer, 1, &request)
+        .unwrap();
+
+    serde_json::from_str(&resp.succeed_data).unwrap()
+}
+
+// Set `key` to itself, then call `inner` and ignore its result
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NestedSetPayload {
+    pub key:   String,
+    pub fail:  bool,
+    pub panic: bool,
+    pub inner: Option<Box<NestedSetPayload>>,
+}
+
+impl NestedSetPayload {
+    fn new(key: &str, fail: bool) -> Self {
+        Self {
+            key: key.to_owned(),
+            fail,
+            panic: false,
+            inner: None,
+        }
+    }
+
+    fn call(mut self, inner: NestedSetPayload) -> Self {
+        self.inner = Some(Box::new(inner));
+        self
+    }
 }
 
 pub struct MockService<SDK> {
@@ -119,6 +441,39 @@ impl<SDK: ServiceSDK> MockService<SDK> {
         ctx.emit_event("call create asset succeed".to_owned());
         ServiceResponse::<Asset>::from_succeed(asset)
     }
+
+    #[cycles(100_00)]
+    #[read]
+    fn get_value(&self, ctx: ServiceContext, payload: String) -> ServiceResponse<String> {
+        let value: String = self.sdk.get_value(&payload).unwrap_or_default();
+        ServiceResponse::<String>::from_succeed(value)
+    }
+
+    #[cycles(100_00)]
+    #[write]
+    fn nested_set(
+        &mut self,
+        ctx: ServiceContext,
+        payload: NestedSetPayload,
+    ) -> ServiceResponse<()> {
+        self.sdk.set_value(payload.key.clone(), payload.key.clone());
+
+        if let Some(inner) = payload.inner {
+            let payload_str = serde_json::to_string(&inner).unwrap();
+            self.sdk
+                .write(&ctx, None, "mock", "nested_set", &payload_str);
+        }
+
+        if payload.panic {
+            panic!("nested set panic");
+        }
+
+        if payload.fail {
+            return ServiceResponse::<()>::from_error(101, "nested set failed".to_owned());
+        }
+
+        ServiceResponse::<()>::from_succeed(())
+    }
 }
 
 pub struct MockServiceMapping;
@@ -143,3 +498,51 @@ impl ServiceMapping for MockServiceMapping {
         vec!["asset".to_owned(), "mock".to_owned(), "metadata".to_owned()]
     }
 }
+
+// Nobody has the role to set key `b` through `mock.nested_set`
+pub struct MockAuthorizationService<SDK> {
+    _sdk: SDK,
+}
+
+#[service]
+impl<SDK: ServiceSDK> MockAuthorizationService<SDK> {
+    pub fn new(_sdk: SDK) -> Self {
+        Self { _sdk }
+    }
+
+    #[cycles(100_00)]
+    #[write_hook]
+    fn check_write_permission(&self, ctx: ServiceContext) -> ServiceResponse<()> {
+        if ctx.get_service_name() != "mock" || ctx.get_service_method() != "nested_set" {
+            return ServiceResponse::<()>::from_succeed(());
+        }
+
+        match serde_json::from_str::<NestedSetPayload>(ctx.get_payload()) {
+            Ok(payload) if payload.key == "b" => {
+                ServiceResponse::<()>::from_error(105, "caller has no role".to_owned())
+            }
+            _ => ServiceResponse::<()>::from_succeed(()),
+        }
+    }
+}
+
+pub struct AuthorizedServiceMapping;
+
+impl ServiceMapping for AuthorizedServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        match name {
+            "authorization" => Ok(Box::new(MockAuthorizationService::new(sdk)) as Box<dyn Service>),
+            _ => MockServiceMapping.get_service(name, sdk),
+        }
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        let mut names = MockServiceMapping.list_service_name();
+        names.push("authorization".to_owned());
+        names
+    }
+}
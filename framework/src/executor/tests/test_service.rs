@@ -110,8 +110,22 @@ impl<SDK: ServiceSDK> TestService<SDK> {
         ServiceResponse::from_succeed(())
     }
 
+    #[cycles(210_00)]
+    #[write]
+    fn tx_hook_before_reject(
+        &mut self,
+        ctx: ServiceContext,
+        _payload: String,
+    ) -> ServiceResponse<()> {
+        self.sdk.set_value(
+            "tx_hook_before_reject".to_owned(),
+            "tx_hook_before_reject".to_owned(),
+        );
+        ServiceResponse::from_succeed(())
+    }
+
     #[tx_hook_before]
-    fn test_tx_hook_before(&mut self, ctx: ServiceContext) {
+    fn test_tx_hook_before(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
         if ctx.get_service_name() == "test"
             && ctx.get_payload().to_owned().contains("test_hook_before")
         {
@@ -123,6 +137,12 @@ impl<SDK: ServiceSDK> TestService<SDK> {
         }
 
         self.sdk.set_value("before".to_owned(), "before".to_owned());
+
+        if ctx.get_service_method() == "tx_hook_before_reject" {
+            return ServiceResponse::from_error(301, "tx hook before reject".to_owned());
+        }
+
+        ServiceResponse::from_succeed(())
     }
 
     #[tx_hook_after]
@@ -0,0 +1,69 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use protocol::traits::{TraceFrame, TraceStateKey};
+
+use crate::binding::state::AccessRecord;
+
+/// Builds the call tree of a traced transaction. Only calls made while it is
+/// armed are traced, so calls from tx hooks are left out.
+#[derive(Default)]
+pub struct Tracer {
+    pub armed: bool,
+    // Finished child frames of every call in progress
+    stack:     Vec<Vec<TraceFrame>>,
+    root:      Option<TraceFrame>,
+}
+
+impl Tracer {
+    pub fn enter(&mut self) {
+        self.stack.push(Vec::new());
+    }
+
+    pub fn exit(&mut self, mut frame: TraceFrame) {
+        frame.calls = self.stack.pop().unwrap_or_default();
+
+        match self.stack.last_mut() {
+            Some(calls) => calls.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+
+    pub fn take_root(&mut self) -> Option<TraceFrame> {
+        self.root.take()
+    }
+}
+
+// Reads and inserted or removed keys of every service, sorted so traces of the
+// same transaction always look the same.
+pub fn state_keys(
+    records: &HashMap<String, AccessRecord>,
+) -> (Vec<TraceStateKey>, Vec<TraceStateKey>) {
+    let mut reads = vec![];
+    let mut writes = vec![];
+
+    for (service, record) in records.iter() {
+        reads.extend(record.reads.iter().map(|key| TraceStateKey {
+            service: service.to_owned(),
+            key:     key.clone(),
+        }));
+        writes.extend(record.touched.iter().map(|key| TraceStateKey {
+            service: service.to_owned(),
+            key:     key.clone(),
+        }));
+    }
+
+    reads.sort();
+    writes.sort();
+    (reads, writes)
+}
+
+pub fn panic_message(e: &(dyn Any + Send)) -> String {
+    if let Some(msg) = e.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = e.downcast_ref::<String>() {
+        msg.to_owned()
+    } else {
+        format!("{:?}", e)
+    }
+}
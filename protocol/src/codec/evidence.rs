@@ -0,0 +1,88 @@
+use std::convert::TryFrom;
+
+use bytes::Bytes;
+use prost::Message;
+
+use crate::{
+    codec::{primitive::Address, CodecError, ProtocolCodecSync},
+    field, impl_default_bytes_codec_for,
+    types::primitive as protocol_primitive,
+    ProtocolError, ProtocolResult,
+};
+
+// #####################
+// Protobuf
+// #####################
+
+#[derive(Clone, Message)]
+pub struct Evidence {
+    #[prost(uint32, tag = "1")]
+    pub evidence_type: u32,
+
+    #[prost(uint64, tag = "2")]
+    pub height: u64,
+
+    #[prost(uint64, tag = "3")]
+    pub round: u64,
+
+    #[prost(message, tag = "4")]
+    pub offender: Option<Address>,
+
+    #[prost(bytes, tag = "5")]
+    pub first: Vec<u8>,
+
+    #[prost(bytes, tag = "6")]
+    pub second: Vec<u8>,
+}
+
+// #################
+// Conversion
+// #################
+
+// Evidence
+
+impl From<evidence::Evidence> for Evidence {
+    fn from(evidence: evidence::Evidence) -> Evidence {
+        let offender = Some(Address::from(evidence.offender));
+
+        Evidence {
+            evidence_type: u32::from(evidence.evidence_type),
+            height: evidence.height,
+            round: evidence.round,
+            offender,
+            first: evidence.first.to_vec(),
+            second: evidence.second.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<Evidence> for evidence::Evidence {
+    type Error = ProtocolError;
+
+    fn try_from(evidence: Evidence) -> Result<evidence::Evidence, Self::Error> {
+        let evidence_type = match evidence.evidence_type {
+            0 => evidence::EvidenceType::DuplicateProposal,
+            1 => evidence::EvidenceType::DuplicatePrevote,
+            2 => evidence::EvidenceType::DuplicatePrecommit,
+            ty => return Err(CodecError::InvalidEvidenceType(ty).into()),
+        };
+        let offender = field!(evidence.offender, "Evidence", "offender")?;
+
+        let evidence = evidence::Evidence {
+            evidence_type,
+            height: evidence.height,
+            round: evidence.round,
+            offender: protocol_primitive::Address::try_from(offender)?,
+            first: Bytes::from(evidence.first),
+            second: Bytes::from(evidence.second),
+        };
+
+        Ok(evidence)
+    }
+}
+
+// #################
+// Codec
+// #################
+
+impl_default_bytes_codec_for!(evidence, [Evidence]);
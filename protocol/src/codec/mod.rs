@@ -4,6 +4,7 @@
 #[macro_use]
 mod r#macro;
 pub mod block;
+pub mod evidence;
 pub mod primitive;
 pub mod receipt;
 #[cfg(test)]
@@ -78,6 +79,9 @@ pub enum CodecError {
     #[display(fmt = "invalid contract type {}", _0)]
     InvalidContractType(i32),
 
+    #[display(fmt = "invalid evidence type {}", _0)]
+    InvalidEvidenceType(u32),
+
     #[display(fmt = "wrong bytes length: {{ expect: {}, got: {} }}", expect, real)]
     WrongBytesLength { expect: usize, real: usize },
 
@@ -39,6 +39,8 @@ fn test_codec() {
     test!(block, BlockHeader, mock_block_header);
     test!(block, Block, mock_block, 100);
     test!(block, Pill, mock_pill, 100, 200);
+
+    test!(evidence, Evidence, mock_evidence);
 }
 
 #[test]
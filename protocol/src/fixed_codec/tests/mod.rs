@@ -5,6 +5,7 @@ use rand::random;
 
 use crate::traits::ServiceResponse;
 use crate::types::block::{Block, BlockHeader, Pill, Proof, Validator};
+use crate::types::evidence::{Evidence, EvidenceType};
 use crate::types::primitive::{Address, Hash, MerkleRoot};
 use crate::types::receipt::{Event, Receipt, ReceiptResponse};
 use crate::types::transaction::{RawTransaction, SignedTransaction, TransactionRequest};
@@ -154,6 +155,21 @@ pub fn mock_pill(order_size: usize, propose_size: usize) -> Pill {
     }
 }
 
+// #####################
+// Mock Evidence
+// #####################
+
+pub fn mock_evidence() -> Evidence {
+    Evidence::new(
+        EvidenceType::DuplicatePrecommit,
+        42,
+        1,
+        mock_address(),
+        get_random_bytes(64),
+        get_random_bytes(64),
+    )
+}
+
 pub fn get_random_bytes(len: usize) -> Bytes {
     let vec: Vec<u8> = (0..len).map(|_| random::<u8>()).collect();
     Bytes::from(vec)
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 
-use crate::traits::{Context, ServiceResponse};
-use crate::types::{Address, Block, Hash, Receipt, SignedTransaction};
+use crate::traits::{Context, ServiceResponse, SimulateResp, TraceFrame};
+use crate::types::{Address, Block, Evidence, Hash, Receipt, SignedTransaction};
 use crate::ProtocolResult;
 
 #[async_trait]
@@ -34,4 +34,22 @@ pub trait APIAdapter: Send + Sync {
         method: String,
         payload: String,
     ) -> ProtocolResult<ServiceResponse<String>>;
+
+    // `cycles_limit` is capped at the cycles limit of a block
+    async fn simulate_transaction(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+        cycles_price: u64,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+    ) -> ProtocolResult<SimulateResp>;
+
+    async fn get_evidences(&self, ctx: Context, height: u64) -> ProtocolResult<Vec<Evidence>>;
+
+    // Execute the transaction again at the state its block started from
+    async fn trace_transaction(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<TraceFrame>;
 }
@@ -23,32 +23,46 @@ pub trait ServiceMapping: Send + Sync {
 // Each `service` will have a separate` ServiceState`, so their states are
 // isolated from each other.
 pub trait ServiceState {
-    fn get<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> ProtocolResult<Option<Ret>>;
+    fn get<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
+        &self,
+        key: &Key,
+    ) -> ProtocolResult<Option<Ret>>;
 
     fn contains<Key: FixedCodec>(&self, key: &Key) -> ProtocolResult<bool>;
 
     // Insert a pair of key / value
     // Note: This key/value pair will go into the cache first
     // and will not be persisted to MPT until `commit` is called.
-    fn insert<Key: FixedCodec, Value: FixedCodec>(
+    fn insert<Key: FixedCodec, Value: 'static + FixedCodec>(
         &mut self,
         key: Key,
         value: Value,
     ) -> ProtocolResult<()>;
 
-    fn get_account_value<Key: FixedCodec, Ret: FixedCodec>(
+    // Remove a key
+    // Note: Like `insert`, the removal goes into the cache first and the key
+    // will not be deleted from MPT until `commit` is called.
+    fn remove<Key: FixedCodec>(&mut self, key: &Key) -> ProtocolResult<()>;
+
+    fn get_account_value<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
         &self,
         address: &Address,
         key: &Key,
     ) -> ProtocolResult<Option<Ret>>;
 
-    fn set_account_value<Key: FixedCodec, Val: FixedCodec>(
+    fn set_account_value<Key: FixedCodec, Val: 'static + FixedCodec>(
         &mut self,
         address: &Address,
         key: Key,
         val: Val,
     ) -> ProtocolResult<()>;
 
+    fn remove_account_value<Key: FixedCodec>(
+        &mut self,
+        address: &Address,
+        key: &Key,
+    ) -> ProtocolResult<()>;
+
     // Roll back all data in the cache
     fn revert_cache(&mut self) -> ProtocolResult<()>;
 
@@ -91,11 +105,25 @@ pub trait Service {
     // Called after block execution
     fn hook_after_(&mut self, _params: &ExecutorParams) {}
 
-    // Called before tx execution
-    fn tx_hook_before_(&mut self, _ctx: ServiceContext) {}
+    // Called before tx execution, an error response rejects the tx
+    fn tx_hook_before_(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
+        ServiceResponse::<()>::from_succeed(())
+    }
+
     // Called after tx execution
     fn tx_hook_after_(&mut self, _ctx: ServiceContext) {}
 
+    // Called before every write of any service, by a tx or nested, with the
+    // context of that write. An error response rejects the write.
+    fn write_hook_(&self, _ctx: ServiceContext) -> ServiceResponse<()> {
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    // Whether the service has a write hook, executor skips those without one
+    fn has_write_hook_(&self) -> bool {
+        false
+    }
+
     fn write_(&mut self, ctx: ServiceContext) -> ServiceResponse<String>;
 
     fn read_(&self, ctx: ServiceContext) -> ServiceResponse<String>;
@@ -121,21 +149,45 @@ pub trait ServiceSDK {
     // Alloc or recover a `Map` by` var_name`
     fn alloc_or_recover_map<
         Key: 'static + Send + FixedCodec + Clone + PartialEq,
-        Val: 'static + FixedCodec,
+        Val: 'static + FixedCodec + Clone,
     >(
         &mut self,
         var_name: &str,
     ) -> Box<dyn StoreMap<Key, Val>>;
 
     // Alloc or recover a `Array` by` var_name`
-    fn alloc_or_recover_array<Elm: 'static + FixedCodec>(
+    fn alloc_or_recover_array<Elm: 'static + FixedCodec + Clone>(
         &mut self,
         var_name: &str,
     ) -> Box<dyn StoreArray<Elm>>;
 
+    // Alloc or recover a `OrderedMap` by` var_name`, entries are sorted by key
+    fn alloc_or_recover_ordered_map<
+        Key: 'static + Send + FixedCodec + Clone + Ord,
+        Val: 'static + FixedCodec + Clone,
+    >(
+        &mut self,
+        var_name: &str,
+    ) -> Box<dyn StoreOrderedMap<Key, Val>>;
+
+    // Alloc or recover a `Set` by` var_name`
+    fn alloc_or_recover_set<Elm: 'static + Send + FixedCodec + Clone + PartialEq>(
+        &mut self,
+        var_name: &str,
+    ) -> Box<dyn StoreSet<Elm>>;
+
+    // Alloc or recover a `Deque` by` var_name`
+    fn alloc_or_recover_deque<Elm: 'static + FixedCodec + Clone>(
+        &mut self,
+        var_name: &str,
+    ) -> Box<dyn StoreDeque<Elm>>;
+
     // Alloc or recover a `Uint64` by` var_name`
     fn alloc_or_recover_uint64(&mut self, var_name: &str) -> Box<dyn StoreUint64>;
 
+    // Alloc or recover a `Uint128` by` var_name`
+    fn alloc_or_recover_uint128(&mut self, var_name: &str) -> Box<dyn StoreUint128>;
+
     // Alloc or recover a `String` by` var_name`
     fn alloc_or_recover_string(&mut self, var_name: &str) -> Box<dyn StoreString>;
 
@@ -143,26 +195,35 @@ pub trait ServiceSDK {
     fn alloc_or_recover_bool(&mut self, var_name: &str) -> Box<dyn StoreBool>;
 
     // Get a value from the service state by key
-    fn get_value<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> Option<Ret>;
+    fn get_value<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
+        &self,
+        key: &Key,
+    ) -> Option<Ret>;
 
     // Set a value to the service state by key
-    fn set_value<Key: FixedCodec, Val: FixedCodec>(&mut self, key: Key, val: Val);
+    fn set_value<Key: FixedCodec, Val: 'static + FixedCodec>(&mut self, key: Key, val: Val);
+
+    // Remove a value from the service state by key
+    fn remove_value<Key: FixedCodec>(&mut self, key: &Key);
 
     // Get a value from the specified address by key
-    fn get_account_value<Key: FixedCodec, Ret: FixedCodec>(
+    fn get_account_value<Key: FixedCodec, Ret: 'static + FixedCodec + Clone>(
         &self,
         address: &Address,
         key: &Key,
     ) -> Option<Ret>;
 
     // Insert a pair of key / value to the specified address
-    fn set_account_value<Key: FixedCodec, Val: FixedCodec>(
+    fn set_account_value<Key: FixedCodec, Val: 'static + FixedCodec>(
         &mut self,
         address: &Address,
         key: Key,
         val: Val,
     );
 
+    // Remove a value from the specified address by key
+    fn remove_account_value<Key: FixedCodec>(&mut self, address: &Address, key: &Key);
+
     // Get a signed transaction by `tx_hash`
     // if not found on the chain, return None
     fn get_transaction_by_hash(&self, tx_hash: &Hash) -> Option<SignedTransaction>;
@@ -178,7 +239,7 @@ pub trait ServiceSDK {
 
     // Call other read-only methods of `service` and return the results
     // synchronously NOTE: You can use recursive calls, but the maximum call
-    // stack is 1024
+    // stack is 64
     fn read(
         &self,
         ctx: &ServiceContext,
@@ -189,7 +250,7 @@ pub trait ServiceSDK {
     ) -> ServiceResponse<String>;
 
     // Call other writable methods of `service` and return the results synchronously
-    // NOTE: You can use recursive calls, but the maximum call stack is 1024
+    // NOTE: You can use recursive calls, but the maximum call stack is 64
     fn write(
         &mut self,
         ctx: &ServiceContext,
@@ -214,6 +275,80 @@ pub trait StoreMap<K: FixedCodec + PartialEq, V: FixedCodec> {
     fn is_empty(&self) -> bool;
 
     fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (K, V)> + 'a>;
+
+    // Move at most `limit` entries written in an older layout to the current
+    // one, returns true once nothing is left to migrate. Meant for block
+    // hooks, reads and writes work on a map in the middle of a migration.
+    fn migrate(&mut self, limit: u32) -> bool;
+}
+
+pub trait StoreOrderedMap<K: FixedCodec + Ord, V: FixedCodec> {
+    fn get(&self, key: &K) -> Option<V>;
+
+    fn contains(&self, key: &K) -> bool;
+
+    fn insert(&mut self, key: K, value: V);
+
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    fn len(&self) -> u32;
+
+    fn is_empty(&self) -> bool;
+
+    // Entry with the smallest key
+    fn first(&self) -> Option<(K, V)>;
+
+    // Entry with the largest key
+    fn last(&self) -> Option<(K, V)>;
+
+    // Entries with `from <= key < to` in ascending order, `None` means
+    // unbounded
+    fn range<'a>(&'a self, from: Option<K>, to: Option<K>)
+        -> Box<dyn Iterator<Item = (K, V)> + 'a>;
+
+    // At most `limit` entries in ascending order, skipping the first `offset`
+    fn page(&self, offset: u32, limit: u32) -> Vec<(K, V)>;
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (K, V)> + 'a>;
+}
+
+pub trait StoreSet<E: FixedCodec + PartialEq> {
+    fn contains(&self, element: &E) -> bool;
+
+    // Return false if the element is already in the set
+    fn insert(&mut self, element: E) -> bool;
+
+    // Return false if the element is not in the set
+    fn remove(&mut self, element: &E) -> bool;
+
+    fn len(&self) -> u32;
+
+    fn is_empty(&self) -> bool;
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = E> + 'a>;
+}
+
+pub trait StoreDeque<E: FixedCodec> {
+    // Index 0 is the front
+    fn get(&self, index: u32) -> Option<E>;
+
+    fn front(&self) -> Option<E>;
+
+    fn back(&self) -> Option<E>;
+
+    fn push_front(&mut self, element: E);
+
+    fn push_back(&mut self, element: E);
+
+    fn pop_front(&mut self) -> Option<E>;
+
+    fn pop_back(&mut self) -> Option<E>;
+
+    fn len(&self) -> u32;
+
+    fn is_empty(&self) -> bool;
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (u32, E)> + 'a>;
 }
 
 pub trait StoreArray<E: FixedCodec> {
@@ -260,6 +395,36 @@ pub trait StoreUint64 {
     fn safe_rem(&mut self, val: u64) -> bool;
 }
 
+pub trait StoreUint128 {
+    fn get(&self) -> u128;
+
+    fn set(&mut self, val: u128);
+
+    // Add val with self
+    // And set the result back to self
+    fn safe_add(&mut self, val: u128) -> bool;
+
+    // Self minus val
+    // And set the result back to self
+    fn safe_sub(&mut self, val: u128) -> bool;
+
+    // Multiply val with self
+    // And set the result back to self
+    fn safe_mul(&mut self, val: u128) -> bool;
+
+    // Power of self
+    // And set the result back to self
+    fn safe_pow(&mut self, val: u32) -> bool;
+
+    // Self divided by val
+    // And set the result back to self
+    fn safe_div(&mut self, val: u128) -> bool;
+
+    // Remainder of self
+    // And set the result back to self
+    fn safe_rem(&mut self, val: u128) -> bool;
+}
+
 pub trait StoreString {
     fn get(&self) -> String;
 
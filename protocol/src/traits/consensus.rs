@@ -5,8 +5,8 @@ use creep::Context;
 
 use crate::traits::{ExecutorParams, ExecutorResp, TrustFeedback};
 use crate::types::{
-    Address, Block, Bytes, Hash, Hex, MerkleRoot, Metadata, Proof, Receipt, SignedTransaction,
-    Validator,
+    Address, Block, Bytes, Evidence, Hash, Hex, MerkleRoot, Metadata, Proof, Receipt,
+    SignedTransaction, Validator,
 };
 use crate::{traits::mempool::MixedTxHashes, ProtocolResult};
 
@@ -35,6 +35,9 @@ pub trait Consensus: Send + Sync {
 
     /// Network set a received signed choke to consensus.
     async fn set_choke(&self, ctx: Context, choke: Vec<u8>) -> ProtocolResult<()>;
+
+    /// Network set a received equivocation evidence to consensus.
+    async fn set_evidence(&self, ctx: Context, evidence: Vec<u8>) -> ProtocolResult<()>;
 }
 
 #[async_trait]
@@ -220,5 +223,8 @@ pub trait ConsensusAdapter: CommonConsensusAdapter + Send + Sync {
     /// Load latest overlord wal info.
     async fn load_overlord_wal(&self, ctx: Context) -> ProtocolResult<Bytes>;
 
+    /// Save an equivocation evidence.
+    async fn save_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
+
     async fn verify_txs(&self, ctx: Context, height: u64, txs: Vec<Hash>) -> ProtocolResult<()>;
 }
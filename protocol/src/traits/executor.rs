@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
+use bytes::Bytes;
 use creep::Context;
 
 use crate::traits::{ServiceMapping, Storage};
 use crate::types::{
-    Address, MerkleRoot, Receipt, ServiceContext, SignedTransaction, TransactionRequest,
+    Address, Event, Hash, MerkleRoot, Receipt, ServiceContext, SignedTransaction,
+    TransactionRequest,
 };
 use crate::ProtocolResult;
 
@@ -15,6 +17,14 @@ pub struct ExecutorResp {
     pub state_root:      MerkleRoot,
 }
 
+/// Result of a transaction request executed without being committed
+#[derive(Debug, Clone)]
+pub struct SimulateResp {
+    pub cycles_used: u64,
+    pub events:      Vec<Event>,
+    pub response:    ServiceResponse<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecutorParams {
     pub state_root:   MerkleRoot,
@@ -24,6 +34,28 @@ pub struct ExecutorParams {
     pub proposer:     Address,
 }
 
+/// One service call made while executing a traced transaction. Cycles, state
+/// keys and events include the ones of nested calls.
+#[derive(Debug, Clone, Default)]
+pub struct TraceFrame {
+    pub service:     String,
+    pub method:      String,
+    pub payload:     String,
+    pub cycles_used: u64,
+    pub reads:       Vec<TraceStateKey>,
+    pub writes:      Vec<TraceStateKey>,
+    pub events:      Vec<Event>,
+    pub response:    Option<ServiceResponse<String>>,
+    pub panic:       Option<String>,
+    pub calls:       Vec<TraceFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TraceStateKey {
+    pub service: String,
+    pub key:     Bytes,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ServiceResponse<T: Default> {
     pub code:          u64,
@@ -72,6 +104,16 @@ pub trait ExecutorFactory<DB: cita_trie::DB, S: Storage, Mapping: ServiceMapping
         storage: Arc<S>,
         mapping: Arc<Mapping>,
     ) -> ProtocolResult<Box<dyn Executor>>;
+
+    // Same as `from_root`, but transactions of a block are executed
+    // optimistically in parallel if `parallel` is true.
+    fn from_root_with_parallel(
+        root: MerkleRoot,
+        db: Arc<DB>,
+        storage: Arc<S>,
+        mapping: Arc<Mapping>,
+        parallel: bool,
+    ) -> ProtocolResult<Box<dyn Executor>>;
 }
 
 pub trait Executor {
@@ -89,6 +131,27 @@ pub trait Executor {
         cycles_price: u64,
         request: &TransactionRequest,
     ) -> ProtocolResult<ServiceResponse<String>>;
+
+    // Run `request` as a write with tx hooks, all state changes are dropped
+    // afterwards.
+    fn simulate(
+        &mut self,
+        params: &ExecutorParams,
+        caller: &Address,
+        cycles_price: u64,
+        request: &TransactionRequest,
+        cycles_limit: u64,
+    ) -> ProtocolResult<SimulateResp>;
+
+    // Execute `txs` without committing and return the call tree of the one
+    // with `tx_hash`, `None` if it is not in `txs`.
+    fn trace(
+        &mut self,
+        ctx: Context,
+        params: &ExecutorParams,
+        txs: &[SignedTransaction],
+        tx_hash: &Hash,
+    ) -> ProtocolResult<Option<TraceFrame>>;
 }
 
 // `Dispatcher` provides ability to send a call message to other services
@@ -9,7 +9,8 @@ mod storage;
 pub use api::APIAdapter;
 pub use binding::{
     AdmissionControl, ChainQuerier, Service, ServiceMapping, ServiceSDK, ServiceState, StoreArray,
-    StoreBool, StoreMap, StoreString, StoreUint64,
+    StoreBool, StoreDeque, StoreMap, StoreOrderedMap, StoreSet, StoreString, StoreUint128,
+    StoreUint64,
 };
 pub use consensus::{
     CommonConsensusAdapter, Consensus, ConsensusAdapter, MessageTarget, NodeInfo, Synchronization,
@@ -17,7 +18,7 @@ pub use consensus::{
 };
 pub use executor::{
     Dispatcher, Executor, ExecutorFactory, ExecutorParams, ExecutorResp, NoopDispatcher,
-    ServiceResponse,
+    ServiceResponse, SimulateResp, TraceFrame, TraceStateKey,
 };
 pub use mempool::{MemPool, MemPoolAdapter, MixedTxHashes};
 pub use network::{Gossip, MessageCodec, MessageHandler, PeerTrust, Priority, Rpc, TrustFeedback};
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{traits::Context, types::Address, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Priority {
     High,
     Normal,
@@ -96,6 +96,29 @@ pub trait Rpc: Send + Sync {
     ) -> ProtocolResult<()>
     where
         M: MessageCodec;
+
+    // Same as call, but response is delivered in ordered chunks, so it isn't
+    // limited by max frame length. Responder should use `response_stream`.
+    async fn call_stream<M, R>(
+        &self,
+        ctx: Context,
+        end: &str,
+        msg: M,
+        pri: Priority,
+    ) -> ProtocolResult<Vec<R>>
+    where
+        M: MessageCodec,
+        R: MessageCodec;
+
+    async fn response_stream<M>(
+        &self,
+        cx: Context,
+        end: &str,
+        ret: ProtocolResult<Vec<M>>,
+        p: Priority,
+    ) -> ProtocolResult<()>
+    where
+        M: MessageCodec;
 }
 
 pub trait PeerTrust: Send + Sync {
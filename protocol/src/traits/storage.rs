@@ -5,7 +5,7 @@ use crate::codec::ProtocolCodec;
 use crate::traits::Context;
 use crate::types::block::{Block, Proof};
 use crate::types::receipt::Receipt;
-use crate::types::{Hash, SignedTransaction};
+use crate::types::{Evidence, Hash, SignedTransaction};
 use crate::{Bytes, ProtocolResult};
 
 #[derive(Debug, Copy, Clone, Display)]
@@ -15,6 +15,7 @@ pub enum StorageCategory {
     SignedTransaction,
     Wal,
     HashHeight,
+    Evidence,
 }
 
 pub type StorageIterator<'a, S> = Box<
@@ -88,6 +89,10 @@ pub trait Storage: Send + Sync {
     async fn update_overlord_wal(&self, ctx: Context, info: Bytes) -> ProtocolResult<()>;
 
     async fn load_overlord_wal(&self, ctx: Context) -> ProtocolResult<Bytes>;
+
+    async fn insert_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
+
+    async fn get_evidences(&self, ctx: Context, height: u64) -> ProtocolResult<Vec<Evidence>>;
 }
 
 pub enum StorageBatchModify<S: StorageSchema> {
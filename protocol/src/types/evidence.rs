@@ -0,0 +1,84 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Address, Hash};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum EvidenceType {
+    #[display(fmt = "duplicate proposal")]
+    DuplicateProposal,
+
+    #[display(fmt = "duplicate prevote")]
+    DuplicatePrevote,
+
+    #[display(fmt = "duplicate precommit")]
+    DuplicatePrecommit,
+}
+
+impl From<EvidenceType> for u32 {
+    fn from(ty: EvidenceType) -> u32 {
+        match ty {
+            EvidenceType::DuplicateProposal => 0,
+            EvidenceType::DuplicatePrevote => 1,
+            EvidenceType::DuplicatePrecommit => 2,
+        }
+    }
+}
+
+/// A validator signed two different messages of the same type at the same
+/// height and round. Both messages are kept in overlord rlp encoding, so
+/// anyone can verify them against offender's bls public key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Evidence {
+    pub evidence_type: EvidenceType,
+    pub height:        u64,
+    pub round:         u64,
+    pub offender:      Address,
+    pub first:         Bytes,
+    pub second:        Bytes,
+}
+
+impl Evidence {
+    pub fn new(
+        evidence_type: EvidenceType,
+        height: u64,
+        round: u64,
+        offender: Address,
+        msg_a: Bytes,
+        msg_b: Bytes,
+    ) -> Self {
+        // Sort messages, so that same conflict reported by different nodes
+        // results in same evidence.
+        let (first, second) = if msg_a <= msg_b {
+            (msg_a, msg_b)
+        } else {
+            (msg_b, msg_a)
+        };
+
+        Evidence {
+            evidence_type,
+            height,
+            round,
+            offender,
+            first,
+            second,
+        }
+    }
+
+    pub fn hash(&self) -> Hash {
+        let offender = self.offender.as_bytes();
+        let mut buf = BytesMut::with_capacity(
+            4 + 8 + 8 + offender.len() + self.first.len() + self.second.len(),
+        );
+
+        buf.put_u32(u32::from(self.evidence_type));
+        buf.put_u64(self.height);
+        buf.put_u64(self.round);
+        buf.put_slice(&offender);
+        buf.put_slice(&self.first);
+        buf.put_slice(&self.second);
+
+        Hash::digest(buf.freeze())
+    }
+}
@@ -1,4 +1,5 @@
 pub(crate) mod block;
+pub(crate) mod evidence;
 pub(crate) mod genesis;
 pub(crate) mod primitive;
 pub(crate) mod receipt;
@@ -13,10 +14,11 @@ use crate::{ProtocolError, ProtocolErrorKind};
 
 pub use block::{Block, BlockHeader, Pill, Proof, Validator};
 pub use bytes::{Bytes, BytesMut};
+pub use evidence::{Evidence, EvidenceType};
 pub use genesis::{Genesis, ServiceParam};
 pub use primitive::{
-    Address, Hash, Hex, JsonString, MerkleRoot, Metadata, ValidatorExtend, GENESIS_HEIGHT,
-    METADATA_KEY,
+    Address, Hash, Hex, JsonString, MerkleRoot, Metadata, ResolveNamePayload, ResolveNameResponse,
+    ValidatorExtend, GENESIS_HEIGHT, METADATA_KEY,
 };
 pub use receipt::{Event, Receipt, ReceiptResponse};
 pub use service_context::{ServiceContext, ServiceContextError, ServiceContextParams};
@@ -250,6 +250,16 @@ impl Address {
         Self::from_bytes(hash_val)
     }
 
+    /// Address owned by a service itself, used as caller when a service
+    /// acts on its own behalf, e.g. holding staked assets. No private key
+    /// maps to it.
+    pub fn from_service_name(service_name: &str) -> Self {
+        let mut hash_val = Hash::digest(Bytes::from(service_name.to_owned())).as_bytes();
+        hash_val.truncate(ADDRESS_LEN);
+
+        Self(hash_val)
+    }
+
     pub fn from_bytes(bytes: Bytes) -> ProtocolResult<Self> {
         ensure_len(bytes.len(), ADDRESS_LEN)?;
 
@@ -324,6 +334,18 @@ impl fmt::Debug for ValidatorExtend {
     }
 }
 
+/// Payload of `resolve` of a name service, API resolves names in address
+/// inputs with it.
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolveNamePayload {
+    pub name: String,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolveNameResponse {
+    pub address: Address,
+}
+
 fn clean_0x(s: &str) -> ProtocolResult<&str> {
     if s.starts_with("0x") || s.starts_with("0X") {
         Ok(&s[2..])
@@ -365,6 +387,15 @@ mod tests {
         assert_eq!(addr.as_hex(), expect_addr);
     }
 
+    #[test]
+    fn test_from_service_name() {
+        let addr = Address::from_service_name("staking");
+
+        assert_eq!(addr, Address::from_service_name("staking"));
+        assert_ne!(addr, Address::from_service_name("asset"));
+        assert_eq!(addr.as_bytes().len(), 20);
+    }
+
     #[test]
     fn test_address() {
         let add_str = "CAB8EEA4799C21379C20EF5BAA2CC8AF1BEC475B";
@@ -84,6 +84,14 @@ impl ServiceContext {
         }
     }
 
+    /// Same context but called by another address, e.g. a service
+    /// dispatching a call on behalf of an account it controls.
+    pub fn with_caller(context: &ServiceContext, caller: Address) -> Self {
+        let mut ctx = context.clone();
+        ctx.caller = caller;
+        ctx
+    }
+
     pub fn get_tx_hash(&self) -> Option<Hash> {
         self.tx_hash.clone()
     }
@@ -208,5 +216,13 @@ mod tests {
         assert_eq!(ctx.get_service_name(), "service_name");
         assert_eq!(ctx.get_service_method(), "service_method");
         assert_eq!(ctx.get_payload(), "service_payload");
+
+        let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+        let sub_ctx = ServiceContext::with_caller(&ctx, caller.clone());
+        assert_eq!(sub_ctx.get_caller(), caller);
+
+        // Cycles are shared
+        sub_ctx.sub_cycles(2);
+        assert_eq!(ctx.get_cycles_used(), 20);
     }
 }
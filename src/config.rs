@@ -18,6 +18,7 @@ pub struct ConfigGraphQL {
     pub maxconn:           usize,
     #[serde(default)]
     pub max_payload_size:  usize,
+    pub name_service:      Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +26,9 @@ pub struct ConfigNetwork {
     pub bootstraps:                 Option<Vec<ConfigNetworkBootstrap>>,
     pub whitelist:                  Option<Vec<String>>,
     pub whitelist_peers_only:       Option<bool>,
+    pub enable_save_restore:        Option<bool>,
+    pub private_peers:              Option<Vec<String>>,
+    pub relay_max_hops:             Option<u8>,
     pub trust_interval_duration:    Option<u64>,
     pub trust_max_history_duration: Option<u64>,
     pub fatal_ban_duration:         Option<u64>,
@@ -32,6 +36,10 @@ pub struct ConfigNetwork {
     pub max_connected_peers:        Option<usize>,
     pub listening_address:          SocketAddr,
     pub rpc_timeout:                Option<u64>,
+    pub rpc_max_chunk_size:         Option<usize>,
+    pub rpc_stream_window:          Option<usize>,
+    pub rpc_stream_timeout:         Option<u64>,
+    pub rpc_stream_max_items:       Option<usize>,
     pub selfcheck_interval:         Option<u64>,
     pub send_buffer_size:           Option<usize>,
     pub write_timeout:              Option<u64>,
@@ -47,19 +55,6 @@ pub struct ConfigNetworkBootstrap {
     pub address: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ConfigConsensus {
-    pub sync_txs_chunk_size: usize,
-}
-
-impl Default for ConfigConsensus {
-    fn default() -> Self {
-        Self {
-            sync_txs_chunk_size: 5000,
-        }
-    }
-}
-
 fn default_broadcast_txs_size() -> usize {
     DEFAULT_BROADCAST_TXS_SIZE
 }
@@ -80,7 +75,10 @@ pub struct ConfigMempool {
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigExecutor {
-    pub light: bool,
+    pub light:    bool,
+    // Execute transactions of a block optimistically in parallel
+    #[serde(default)]
+    pub parallel: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -134,17 +132,15 @@ pub struct Config {
     // db config
     pub data_path: PathBuf,
 
-    pub graphql:   ConfigGraphQL,
-    pub network:   ConfigNetwork,
-    pub mempool:   ConfigMempool,
-    pub executor:  ConfigExecutor,
-    #[serde(default)]
-    pub consensus: ConfigConsensus,
+    pub graphql:  ConfigGraphQL,
+    pub network:  ConfigNetwork,
+    pub mempool:  ConfigMempool,
+    pub executor: ConfigExecutor,
     #[serde(default)]
-    pub logger:    ConfigLogger,
+    pub logger:   ConfigLogger,
     #[serde(default)]
-    pub rocksdb:   ConfigRocksDB,
-    pub apm:       Option<ConfigAPM>,
+    pub rocksdb:  ConfigRocksDB,
+    pub apm:      Option<ConfigAPM>,
 }
 
 impl Config {
@@ -15,14 +15,15 @@ use common_crypto::{
 };
 use core_api::adapter::DefaultAPIAdapter;
 use core_api::config::GraphQLConfig;
-use core_consensus::fixed_types::{FixedBlock, FixedProof, FixedSignedTxs};
+use core_consensus::fixed_types::{BlockPart, FixedBlock, FixedProof, FixedSignedTxs};
 use core_consensus::message::{
-    ChokeMessageHandler, ProposalMessageHandler, PullBlockRpcHandler, PullProofRpcHandler,
-    PullTxsRpcHandler, QCMessageHandler, RemoteHeightMessageHandler, VoteMessageHandler,
-    BROADCAST_HEIGHT, END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_CHOKE,
-    END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE, RPC_RESP_SYNC_PULL_BLOCK,
-    RPC_RESP_SYNC_PULL_PROOF, RPC_RESP_SYNC_PULL_TXS, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF,
-    RPC_SYNC_PULL_TXS,
+    ChokeMessageHandler, EvidenceMessageHandler, ProposalMessageHandler, PullBlockRpcHandler,
+    PullProofRpcHandler, PullTxsRpcHandler, QCMessageHandler, RemoteHeightMessageHandler,
+    VoteMessageHandler, BROADCAST_HEIGHT, END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_EVIDENCE,
+    END_GOSSIP_SIGNED_CHOKE, END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE,
+    RPC_RESP_SYNC_PULL_BLOCK, RPC_RESP_SYNC_PULL_BLOCK_STREAM, RPC_RESP_SYNC_PULL_PROOF,
+    RPC_RESP_SYNC_PULL_TXS, RPC_RESP_SYNC_PULL_TXS_STREAM, RPC_SYNC_PULL_BLOCK,
+    RPC_SYNC_PULL_BLOCK_STREAM, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS, RPC_SYNC_PULL_TXS_STREAM,
 };
 use core_consensus::status::{CurrentConsensusStatus, StatusAgent};
 use core_consensus::util::OverlordCrypto;
@@ -32,14 +33,17 @@ use core_consensus::{
 };
 use core_mempool::{
     DefaultMemPoolAdapter, HashMemPool, MsgPushTxs, NewTxsHandler, PullTxsHandler,
-    END_GOSSIP_NEW_TXS, RPC_PULL_TXS, RPC_RESP_PULL_TXS, RPC_RESP_PULL_TXS_SYNC,
+    END_GOSSIP_NEW_TXS, RPC_PULL_TXS, RPC_PULL_TXS_STREAM, RPC_RESP_PULL_TXS,
+    RPC_RESP_PULL_TXS_STREAM, RPC_RESP_PULL_TXS_SYNC,
 };
 use core_network::{NetworkConfig, NetworkService};
 use core_storage::{adapter::rocks::RocksAdapter, ImplStorage, StorageError};
 use framework::binding::state::RocksTrieDB;
 use framework::executor::{ServiceExecutor, ServiceExecutorFactory};
 use protocol::traits::{APIAdapter, Context, MemPool, NodeInfo, ServiceMapping, Storage};
-use protocol::types::{Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, Validator};
+use protocol::types::{
+    Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, SignedTransaction, Validator,
+};
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
 use crate::config::Config;
@@ -161,6 +165,8 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     let network_config = NetworkConfig::new()
         .max_connections(config.network.max_connected_peers)
         .whitelist_peers_only(config.network.whitelist_peers_only)
+        .enable_save_restore(config.network.enable_save_restore)
+        .relay_max_hops(config.network.relay_max_hops)
         .peer_trust_metric(
             config.network.trust_interval_duration,
             config.network.trust_max_history_duration,
@@ -168,13 +174,18 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
         .peer_soft_ban(config.network.soft_ban_duration)
         .peer_fatal_ban(config.network.fatal_ban_duration)
         .rpc_timeout(config.network.rpc_timeout)
+        .rpc_max_chunk_size(config.network.rpc_max_chunk_size)
+        .rpc_stream_window(config.network.rpc_stream_window)
+        .rpc_stream_timeout(config.network.rpc_stream_timeout)
+        .rpc_stream_max_items(config.network.rpc_stream_max_items)
         .ping_interval(config.network.ping_interval)
         .selfcheck_interval(config.network.selfcheck_interval)
         .max_wait_streams(config.network.max_wait_streams)
         .max_frame_length(config.network.max_frame_length)
         .send_buffer_size(config.network.send_buffer_size)
         .write_timeout(config.network.write_timeout)
-        .recv_buffer_size(config.network.recv_buffer_size);
+        .recv_buffer_size(config.network.recv_buffer_size)
+        .peer_store_dir(&config.data_path);
 
     let network_privkey = config.privkey.as_string_trim0x();
 
@@ -189,10 +200,12 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     }
 
     let whitelist = config.network.whitelist.clone().unwrap_or_default();
+    let private_peers = config.network.private_peers.clone().unwrap_or_default();
 
     let network_config = network_config
         .bootstraps(bootstrap_pairs)?
         .whitelist(whitelist)?
+        .private_peers(private_peers)?
         .secio_keypair(network_privkey)?;
     let mut network_service = NetworkService::new(network_config);
     network_service
@@ -289,7 +302,15 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&mempool),
         )),
     )?;
+    network_service.register_endpoint_handler(
+        RPC_PULL_TXS_STREAM,
+        Box::new(PullTxsHandler::streamed(
+            Arc::new(network_service.handle()),
+            Arc::clone(&mempool),
+        )),
+    )?;
     network_service.register_rpc_response::<MsgPushTxs>(RPC_RESP_PULL_TXS)?;
+    network_service.register_rpc_response::<SignedTransaction>(RPC_RESP_PULL_TXS_STREAM)?;
 
     network_service.register_rpc_response::<MsgPushTxs>(RPC_RESP_PULL_TXS_SYNC)?;
 
@@ -375,6 +396,7 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&service_mapping),
             status_agent.clone(),
             Arc::clone(&crypto),
+            config.executor.parallel,
         )?;
 
     let exec_demon = consensus_adapter.take_exec_demon();
@@ -394,7 +416,6 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     consensus_adapter.set_overlord_handler(overlord_consensus.get_overlord_handler());
 
     let synchronization = Arc::new(OverlordSynchronization::<_>::new(
-        config.consensus.sync_txs_chunk_size,
         consensus_adapter,
         status_agent.clone(),
         crypto,
@@ -445,6 +466,10 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
         END_GOSSIP_SIGNED_CHOKE,
         Box::new(ChokeMessageHandler::new(Arc::clone(&overlord_consensus))),
     )?;
+    network_service.register_endpoint_handler(
+        END_GOSSIP_EVIDENCE,
+        Box::new(EvidenceMessageHandler::new(Arc::clone(&overlord_consensus))),
+    )?;
     network_service.register_endpoint_handler(
         BROADCAST_HEIGHT,
         Box::new(RemoteHeightMessageHandler::new(Arc::clone(
@@ -458,6 +483,13 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&storage),
         )),
     )?;
+    network_service.register_endpoint_handler(
+        RPC_SYNC_PULL_BLOCK_STREAM,
+        Box::new(PullBlockRpcHandler::streamed(
+            Arc::new(network_service.handle()),
+            Arc::clone(&storage),
+        )),
+    )?;
 
     network_service.register_endpoint_handler(
         RPC_SYNC_PULL_PROOF,
@@ -474,9 +506,18 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&storage),
         )),
     )?;
+    network_service.register_endpoint_handler(
+        RPC_SYNC_PULL_TXS_STREAM,
+        Box::new(PullTxsRpcHandler::streamed(
+            Arc::new(network_service.handle()),
+            Arc::clone(&storage),
+        )),
+    )?;
     network_service.register_rpc_response::<FixedBlock>(RPC_RESP_SYNC_PULL_BLOCK)?;
     network_service.register_rpc_response::<FixedProof>(RPC_RESP_SYNC_PULL_PROOF)?;
     network_service.register_rpc_response::<FixedSignedTxs>(RPC_RESP_SYNC_PULL_TXS)?;
+    network_service.register_rpc_response::<BlockPart>(RPC_RESP_SYNC_PULL_BLOCK_STREAM)?;
+    network_service.register_rpc_response::<SignedTransaction>(RPC_RESP_SYNC_PULL_TXS_STREAM)?;
 
     // Run network
     tokio::spawn(network_service);
@@ -531,6 +572,7 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     if config.graphql.max_payload_size != 0 {
         graphql_config.max_payload_size = config.graphql.max_payload_size;
     }
+    graphql_config.name_service = config.graphql.name_service.clone();
 
     tokio::task::spawn_local(async move {
         let local = tokio::task::LocalSet::new();
@@ -52,10 +52,11 @@ describe("API test via muta-sdk-js", () => {
       asset_id: asset_id,
     })!;
 
-    const c1 = from_balance_before.succeedData.balance as number;
-    expect(from_balance_after.succeedData.balance).toBe(c1 - 1);
-    const c2 = to_balance_before.succeedData.balance as number;
-    expect(to_balance_after.succeedData.balance).toBe(c2 + 1);
+    // Balances are decimal strings
+    const c1 = BigInt(from_balance_before.succeedData.balance);
+    expect(BigInt(from_balance_after.succeedData.balance)).toBe(c1 - BigInt(1));
+    const c2 = BigInt(to_balance_before.succeedData.balance);
+    expect(BigInt(to_balance_after.succeedData.balance)).toBe(c2 + BigInt(1));
   });
 
   test('multisig', async () => {
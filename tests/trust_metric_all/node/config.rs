@@ -34,19 +34,6 @@ pub struct ConfigNetworkBootstrap {
     pub address: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ConfigConsensus {
-    pub sync_txs_chunk_size: usize,
-}
-
-impl Default for ConfigConsensus {
-    fn default() -> Self {
-        Self {
-            sync_txs_chunk_size: 5000,
-        }
-    }
-}
-
 fn default_broadcast_txs_size() -> usize {
     DEFAULT_BROADCAST_TXS_SIZE
 }
@@ -67,7 +54,9 @@ pub struct ConfigMempool {
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigExecutor {
-    pub light: bool,
+    pub light:    bool,
+    #[serde(default)]
+    pub parallel: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,11 +90,9 @@ pub struct Config {
     // crypto
     pub privkey: Hex,
 
-    pub network:   ConfigNetwork,
-    pub mempool:   ConfigMempool,
-    pub executor:  ConfigExecutor,
-    #[serde(default)]
-    pub consensus: ConfigConsensus,
+    pub network:  ConfigNetwork,
+    pub mempool:  ConfigMempool,
+    pub executor: ConfigExecutor,
     #[serde(default)]
-    pub logger:    ConfigLogger,
+    pub logger:   ConfigLogger,
 }
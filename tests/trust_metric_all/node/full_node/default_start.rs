@@ -17,14 +17,15 @@ use common_crypto::{
     ToPublicKey,
 };
 use core_api::adapter::DefaultAPIAdapter;
-use core_consensus::fixed_types::{FixedBlock, FixedProof, FixedSignedTxs};
+use core_consensus::fixed_types::{BlockPart, FixedBlock, FixedProof, FixedSignedTxs};
 use core_consensus::message::{
-    ChokeMessageHandler, ProposalMessageHandler, PullBlockRpcHandler, PullProofRpcHandler,
-    PullTxsRpcHandler, QCMessageHandler, RemoteHeightMessageHandler, VoteMessageHandler,
-    BROADCAST_HEIGHT, END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_CHOKE,
-    END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE, RPC_RESP_SYNC_PULL_BLOCK,
-    RPC_RESP_SYNC_PULL_PROOF, RPC_RESP_SYNC_PULL_TXS, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF,
-    RPC_SYNC_PULL_TXS,
+    ChokeMessageHandler, EvidenceMessageHandler, ProposalMessageHandler, PullBlockRpcHandler,
+    PullProofRpcHandler, PullTxsRpcHandler, QCMessageHandler, RemoteHeightMessageHandler,
+    VoteMessageHandler, BROADCAST_HEIGHT, END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_EVIDENCE,
+    END_GOSSIP_SIGNED_CHOKE, END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE,
+    RPC_RESP_SYNC_PULL_BLOCK, RPC_RESP_SYNC_PULL_BLOCK_STREAM, RPC_RESP_SYNC_PULL_PROOF,
+    RPC_RESP_SYNC_PULL_TXS, RPC_RESP_SYNC_PULL_TXS_STREAM, RPC_SYNC_PULL_BLOCK,
+    RPC_SYNC_PULL_BLOCK_STREAM, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS, RPC_SYNC_PULL_TXS_STREAM,
 };
 use core_consensus::status::{CurrentConsensusStatus, StatusAgent};
 use core_consensus::util::OverlordCrypto;
@@ -34,13 +35,16 @@ use core_consensus::{
 };
 use core_mempool::{
     DefaultMemPoolAdapter, HashMemPool, MsgPushTxs, NewTxsHandler, PullTxsHandler,
-    END_GOSSIP_NEW_TXS, RPC_PULL_TXS, RPC_RESP_PULL_TXS,
+    END_GOSSIP_NEW_TXS, RPC_PULL_TXS, RPC_PULL_TXS_STREAM, RPC_RESP_PULL_TXS,
+    RPC_RESP_PULL_TXS_STREAM,
 };
 use core_network::{NetworkConfig, NetworkService};
 use core_storage::{ImplStorage, StorageError};
 use framework::executor::{ServiceExecutor, ServiceExecutorFactory};
 use protocol::traits::{APIAdapter, Context, MemPool, NodeInfo, ServiceMapping, Storage};
-use protocol::types::{Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, Validator};
+use protocol::types::{
+    Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, SignedTransaction, Validator,
+};
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
 pub async fn create_genesis<Mapping: 'static + ServiceMapping>(
@@ -264,7 +268,15 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&mempool),
         )),
     )?;
+    network_service.register_endpoint_handler(
+        RPC_PULL_TXS_STREAM,
+        Box::new(PullTxsHandler::streamed(
+            Arc::new(network_service.handle()),
+            Arc::clone(&mempool),
+        )),
+    )?;
     network_service.register_rpc_response::<MsgPushTxs>(RPC_RESP_PULL_TXS)?;
+    network_service.register_rpc_response::<SignedTransaction>(RPC_RESP_PULL_TXS_STREAM)?;
 
     // Init Consensus
     let validators: Vec<Validator> = metadata
@@ -343,6 +355,7 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&service_mapping),
             status_agent.clone(),
             Arc::clone(&crypto),
+            config.executor.parallel,
         )?;
 
     let exec_demon = consensus_adapter.take_exec_demon();
@@ -362,7 +375,6 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     consensus_adapter.set_overlord_handler(overlord_consensus.get_overlord_handler());
 
     let synchronization = Arc::new(OverlordSynchronization::<_>::new(
-        config.consensus.sync_txs_chunk_size,
         consensus_adapter,
         status_agent.clone(),
         crypto,
@@ -413,6 +425,10 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
         END_GOSSIP_SIGNED_CHOKE,
         Box::new(ChokeMessageHandler::new(Arc::clone(&overlord_consensus))),
     )?;
+    network_service.register_endpoint_handler(
+        END_GOSSIP_EVIDENCE,
+        Box::new(EvidenceMessageHandler::new(Arc::clone(&overlord_consensus))),
+    )?;
     network_service.register_endpoint_handler(
         BROADCAST_HEIGHT,
         Box::new(RemoteHeightMessageHandler::new(Arc::clone(
@@ -426,6 +442,13 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&storage),
         )),
     )?;
+    network_service.register_endpoint_handler(
+        RPC_SYNC_PULL_BLOCK_STREAM,
+        Box::new(PullBlockRpcHandler::streamed(
+            Arc::new(network_service.handle()),
+            Arc::clone(&storage),
+        )),
+    )?;
 
     network_service.register_endpoint_handler(
         RPC_SYNC_PULL_PROOF,
@@ -442,9 +465,18 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&storage),
         )),
     )?;
+    network_service.register_endpoint_handler(
+        RPC_SYNC_PULL_TXS_STREAM,
+        Box::new(PullTxsRpcHandler::streamed(
+            Arc::new(network_service.handle()),
+            Arc::clone(&storage),
+        )),
+    )?;
     network_service.register_rpc_response::<FixedBlock>(RPC_RESP_SYNC_PULL_BLOCK)?;
     network_service.register_rpc_response::<FixedProof>(RPC_RESP_SYNC_PULL_PROOF)?;
     network_service.register_rpc_response::<FixedSignedTxs>(RPC_RESP_SYNC_PULL_TXS)?;
+    network_service.register_rpc_response::<BlockPart>(RPC_RESP_SYNC_PULL_BLOCK_STREAM)?;
+    network_service.register_rpc_response::<SignedTransaction>(RPC_RESP_SYNC_PULL_TXS_STREAM)?;
 
     // Run network
     tokio::spawn(network_service);